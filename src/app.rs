@@ -3,40 +3,316 @@
 //! The binary application delegates directly to this module, which is responsible for parsing
 //! command-line options and determining what features to launch.
 
+use crate::ast::Ctx;
+#[cfg(feature = "serde")]
+use crate::cmd::Report;
+use crate::cmd::{Cmd, Format, Settings, Status};
+use crate::err::Severity;
+use crate::ide::Session;
 use crate::ide::IDE;
-use clap::{AppSettings, Parser};
-use std::io::Result;
+use crate::log;
+use crate::pmt::Prompt;
+use crate::thm::Coloring;
+use crate::wch::Watch;
+use crate::xpt::{self, Markup};
+use clap::{AppSettings, CommandFactory, Parser, Subcommand};
+use exitcode::ExitCode;
+use std::io::{stderr, stdin, stdout, Error, IsTerminal, Result, Write};
+use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Parser)]
 #[clap(about, long_about = None, version)]
-#[clap(global_setting(AppSettings::ArgRequiredElseHelp))]
 #[clap(global_setting(AppSettings::ColorAuto))]
 #[clap(global_setting(AppSettings::DeriveDisplayOrder))]
 #[clap(global_setting(AppSettings::DisableColoredHelp))]
 #[clap(global_setting(AppSettings::DisableVersion))]
 struct Args {
+    #[clap(subcommand)]
+    cmd: Option<Command>,
+
     /// Open an interactive development environment.
     #[clap(short, long)]
     interactive: bool,
 
+    /// Read commands line by line, without taking over the terminal (as for dumb terminals).
+    #[clap(long)]
+    plain: bool,
+
+    /// Evaluate this single command, print its output and exit.
+    #[clap(short, long, value_name = "COMMAND")]
+    eval: Option<String>,
+
+    /// Load the commands in this file into the context before evaluating the command given with
+    /// --eval.
+    #[clap(long, value_name = "FILE", requires = "eval")]
+    load: Option<PathBuf>,
+
+    /// Write the output of commands that are run, evaluated, piped in or checked in this format.
+    #[clap(long, value_enum, value_name = "FORMAT")]
+    format: Option<Format>,
+
+    /// Permit at most this many steps for each reduction.
+    #[clap(long, value_name = "STEPS")]
+    fuel: Option<usize>,
+
+    /// Give up on the reductions of each command after this many seconds, failing the command.
+    #[clap(long, value_name = "SECONDS")]
+    timeout: Option<u64>,
+
+    /// Give up on each reduction that grows an expression beyond this many nodes, failing the
+    /// command.
+    #[clap(long, value_name = "N")]
+    max_term_size: Option<usize>,
+
+    /// Log the decisions of the parser (given once), and of the type checker and the reducer as
+    /// well (given twice).
+    #[clap(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Append the log to this file, rather than to standard error (or, in the interactive
+    /// development environment, to a file in the state directory).
+    #[clap(long, value_name = "FILE")]
+    log_file: Option<PathBuf>,
+
+    /// Color output always, never, or (by default) only when it is written to a terminal and
+    /// NO_COLOR is not set.
+    #[clap(long, value_enum, value_name = "WHEN", default_value_t, global = true)]
+    color: Coloring,
+
     /// Print version information and exit.
-    #[clap(short, long)]
+    #[clap(short = 'V', long)]
     version: bool,
 }
 
-/// Run the application, parsing arguments supplied to the binary during invocation.
-pub fn run() -> Result<()> {
+#[derive(Subcommand)]
+enum Command {
+    /// Read commands line by line, without taking over the terminal (the same as --plain).
+    Repl,
+    /// Evaluate the commands in a file in order, printing their output, and stop at the first
+    /// that fails.
+    Run {
+        /// Path of the file of commands.
+        #[clap(value_name = "FILE")]
+        path: PathBuf,
+    },
+    /// Type-check the commands in one or more files (or standard input, given none or `-`),
+    /// printing each error as `FILE:LINE:COLUMN: error: MESSAGE` for editors and CI pipelines,
+    /// followed by the offending line with the offending part underlined, and likewise each
+    /// warning (unless turned off with `set warnings off`), which does not fail the check.
+    Check {
+        /// Paths of the files of commands.
+        #[clap(value_name = "FILE")]
+        paths: Vec<PathBuf>,
+    },
+    /// Type-check the commands in one or more files whenever any of them is saved, printing
+    /// whether each passes, along with its first error.
+    Watch {
+        /// Paths of the files of commands.
+        #[clap(value_name = "FILE", required = true)]
+        paths: Vec<PathBuf>,
+    },
+    /// Evaluate the commands in a file in order, and print the judgements they make (such as the
+    /// type of each definition) as markup, for inclusion in papers and notes.
+    Export {
+        /// Markup to print.
+        #[clap(long, value_enum, value_name = "FORMAT", default_value_t)]
+        format: Markup,
+        /// Path of the file of commands.
+        #[clap(value_name = "FILE")]
+        path: PathBuf,
+    },
+}
+
+/// Run the application, parsing arguments supplied to the binary during invocation, and return
+/// the code that the process exits with, which is [DATAERR][exitcode::DATAERR] if a command in a
+/// file that is run (or checked), a command given to evaluate, or one piped in, fails.
+pub fn run() -> Result<ExitCode> {
     let args = Args::parse();
 
     if args.version {
         println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
         println!("{}", env!("CARGO_PKG_DESCRIPTION"));
-        return Ok(());
+        return Ok(exitcode::OK);
+    }
+
+    // the interactive development environment takes over the terminal, so it never logs there
+    let ide = args.interactive && args.cmd.is_none() && args.eval.is_none() && !args.plain;
+    let ide = ide && stdin().is_terminal();
+    match &args.log_file {
+        Some(path) => log::init(args.verbose, Some(path))?,
+        None if !ide => log::init(args.verbose, None)?,
+        None if args.verbose > 0 => log::init(args.verbose, log::default_path().as_deref())?,
+        None => {}
     }
 
-    if args.interactive {
-        IDE::run()?;
+    args.color.apply(stdout().is_terminal());
+
+    let fmt = args.format.unwrap_or_default();
+    let mut ctx = Ctx::new();
+    if let Some(fuel) = args.fuel {
+        ctx.set_fuel(fuel);
     }
+    let mut set = Settings {
+        asc: !Prompt::probe_unicode(),
+        tmo: args.timeout.map(Duration::from_secs),
+        max: args.max_term_size,
+        ..Settings::default()
+    };
 
-    Ok(())
+    if let Some(Command::Run { path }) = &args.cmd {
+        let src = std::fs::read_to_string(path)
+            .map_err(|e| Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+        let path = path.display().to_string();
+        let ok = Session::new(&ctx)
+            .with_settings(set)
+            .with_format(fmt)
+            .script(&mut ctx, &src, &path, stdout(), stderr())?;
+        return Ok(if ok { exitcode::OK } else { exitcode::DATAERR });
+    }
+
+    if let Some(src) = &args.eval {
+        if let Some(path) = &args.load {
+            let cmd = Cmd::Load(path.clone());
+            let out = cmd.eval(&mut ctx, &mut set);
+            if let Status::Failure = out.status {
+                let msg = out.msg.unwrap_or_default();
+                eprint!("{}", Prompt::show_plain(&msg, out.status, set.asc));
+                return Ok(exitcode::DATAERR);
+            }
+        }
+        let ok = Session::new(&ctx)
+            .with_settings(set)
+            .with_format(fmt)
+            .once(&mut ctx, src, stdout(), stderr())?;
+        return Ok(if ok { exitcode::OK } else { exitcode::DATAERR });
+    }
+
+    if let Some(Command::Export { format, path }) = &args.cmd {
+        let src = std::fs::read_to_string(path)
+            .map_err(|e| Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+        return match xpt::export(&src, &mut ctx, &mut set, *format) {
+            Ok(doc) => {
+                print!("{}", doc);
+                Ok(exitcode::OK)
+            }
+            Err(diag) => {
+                let msg = format!("{}:{}", path.display(), diag);
+                eprint!("{}", Prompt::show_plain(&msg, &Status::Failure, set.asc));
+                Ok(exitcode::DATAERR)
+            }
+        };
+    }
+
+    if let Some(Command::Watch { paths }) = &args.cmd {
+        let mut wch = Watch::new(paths);
+        loop {
+            for path in paths {
+                let (msg, status) = match std::fs::read_to_string(path) {
+                    Ok(src) => match &(Cmd::check_source(&src, &mut ctx.clone(), &mut set.clone())
+                        .into_iter())
+                    .filter(|diag| diag.severity == Severity::Error)
+                    .collect::<Vec<_>>()[..]
+                    {
+                        [] => (format!("{}: ok", path.display()), Status::Success),
+                        [diag] => (format!("{}:{}", path.display(), diag), Status::Failure),
+                        [diag, rest @ ..] => {
+                            let more = format!("(+{} more)", rest.len());
+                            let msg = format!("{}:{} {}", path.display(), diag, more);
+                            (msg, Status::Failure)
+                        }
+                    },
+                    Err(e) => (format!("{}: {}", path.display(), e), Status::Failure),
+                };
+                print!("{}", Prompt::show_plain(&msg, &status, set.asc));
+            }
+            let msg = format!("watching {} file(s) for changes", paths.len());
+            print!("{}", Prompt::show_plain(&msg, &Status::Content, set.asc));
+            stdout().flush()?;
+            wch.wait();
+        }
+    }
+
+    if let Some(Command::Check { paths }) = &args.cmd {
+        let stdin_path = PathBuf::from("-");
+        let paths = match paths.is_empty() {
+            true => std::slice::from_ref(&stdin_path),
+            false => &paths[..],
+        };
+        let (mut errs, mut bad, mut warns) = (0, 0, 0);
+        for path in paths {
+            let (src, name) = if *path == stdin_path {
+                (std::io::read_to_string(stdin())?, "<stdin>".to_string())
+            } else {
+                let src = std::fs::read_to_string(path)
+                    .map_err(|e| Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+                (src, path.display().to_string())
+            };
+            let diags = Cmd::check_source(&src, &mut ctx.clone(), &mut set.clone());
+            for diag in &diags {
+                match fmt {
+                    Format::Text => println!("{}", diag.render(&src, Some(&name))),
+                    #[cfg(feature = "serde")]
+                    Format::Json => Report {
+                        file: Some(&name),
+                        command: None,
+                        status: match diag.severity {
+                            Severity::Error => &Status::Failure,
+                            Severity::Warning => &Status::Warning,
+                        },
+                        text: &diag.msg,
+                        error: Some(diag.clone()),
+                    }
+                    .to_writer(stdout())?,
+                }
+            }
+            let (errors, warnings): (Vec<_>, Vec<_>) =
+                (diags.iter()).partition(|diag| diag.severity == Severity::Error);
+            errs += errors.len();
+            bad += usize::from(!errors.is_empty());
+            warns += warnings.len();
+        }
+        eprintln!(
+            "checked {} file(s): {} error(s) in {} file(s), {} warning(s)",
+            paths.len(),
+            errs,
+            bad,
+            warns
+        );
+        return Ok(if errs == 0 {
+            exitcode::OK
+        } else {
+            exitcode::DATAERR
+        });
+    }
+
+    if args.plain || matches!(args.cmd, Some(Command::Repl)) {
+        (Session::new(&ctx).with_settings(set)).run(ctx, stdin().lock(), stdout())?;
+    } else if !stdin().is_terminal() {
+        // commands are piped in, so there is no terminal to take over (or prompt the user on)
+        let ok = Session::new(&ctx)
+            .with_settings(set)
+            .with_format(fmt)
+            .batch(&mut ctx, stdin().lock(), stdout(), stderr())?;
+        return Ok(if ok { exitcode::OK } else { exitcode::DATAERR });
+    } else if args.interactive {
+        IDE::run(ctx, set)?;
+    } else {
+        Args::command().print_help()?;
+    }
+
+    Ok(exitcode::OK)
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_app_0001() {
+        // the JSON format is available in default builds
+        let args = Args::try_parse_from(["fluxo", "--format", "json", "--eval", "show *"]).unwrap();
+        assert_eq!(args.format, Some(Format::Json));
+        assert!(Args::try_parse_from(["fluxo", "--format", "yaml"]).is_err());
+    }
 }
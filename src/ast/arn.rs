@@ -0,0 +1,137 @@
+//! Interned representation of expressions and related structures.
+
+use super::{Exp, Idx, VarIdx};
+use std::collections::HashMap;
+
+/// Identifier of an [expression][Exp] interned in an [arena][Arena], such that expressions
+/// interned in the same arena have the same identifier if and only if they are structurally equal,
+/// which may therefore be checked in constant time.
+#[derive(Debug, Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ExpId(usize);
+
+/// Node of an interned [expression][Exp], which holds the shape of the expression (that is, the
+/// expression with each of its immediate sub-expressions replaced by a [hole][Exp::Hole]) along
+/// with the identifiers of those sub-expressions, in order.
+#[derive(Debug, Clone, Eq, Hash, PartialEq)]
+pub struct Node {
+    /// Shape of the expression, whose sub-expressions are left as holes.
+    pub shape: Exp,
+    /// Identifiers of the sub-expressions, from left to right.
+    pub kids: Vec<ExpId>,
+}
+
+/// Arena that holds interned [expressions][Exp], storing each distinct sub-expression only once,
+/// however many times (and in however many expressions) it occurs.
+#[derive(Debug, Clone, Default)]
+pub struct Arena {
+    nodes: Vec<Node>,
+    ids: HashMap<Node, ExpId>,
+}
+
+impl Node {
+    /// Check whether this node has the same shape as the other, up to the names of the variables
+    /// bound by either (which are not otherwise significant, given their de Bruijn indices).
+    fn is_like(&self, other: &Node) -> bool {
+        match (&self.shape, &other.shape) {
+            (Exp::Abs(..), Exp::Abs(..))
+            | (Exp::For(..), Exp::For(..))
+            | (Exp::Sig(..), Exp::Sig(..)) => true,
+            (shape, other) => shape == other,
+        }
+    }
+}
+
+impl Arena {
+    /// Create a new instance of an empty arena.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Intern the given expression (and all of its sub-expressions), returning its identifier,
+    /// which is the same as that of any structurally equal expression interned before.
+    pub fn intern(&mut self, exp: Exp) -> ExpId {
+        let mut kids = vec![];
+        let shape = exp.map_subterms(|sub, _| {
+            kids.push(self.intern(sub));
+            Exp::Hole
+        });
+        let node = Node { shape, kids };
+        if let Some(id) = self.ids.get(&node) {
+            return *id;
+        }
+        let id = ExpId(self.nodes.len());
+        self.nodes.push(node.clone());
+        self.ids.insert(node, id);
+        id
+    }
+
+    /// Fetch the node of the interned expression with the given identifier.
+    ///
+    /// Panics if the identifier was not issued by this arena.
+    pub fn node(&self, id: ExpId) -> &Node {
+        &self.nodes[id.0]
+    }
+
+    /// Rebuild the interned expression with the given identifier.
+    ///
+    /// Panics if the identifier was not issued by this arena.
+    pub fn get(&self, id: ExpId) -> Exp {
+        let node = self.node(id);
+        let mut kids = node.kids.iter();
+        node.shape
+            .clone()
+            .map_subterms(|_, _| self.get(*kids.next().unwrap())) // one identifier per hole
+    }
+
+    /// Get the number of distinct expressions interned in this arena.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Check whether no expressions have been interned in this arena.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Check whether the interned expressions with the given identifiers are equal up to the names
+    /// of bound variables, which holds at once for any sub-expressions they share.
+    ///
+    /// Variables bound by an enclosing binder are compared by index, except where one side refers
+    /// to the binder by name (as the types of nested binders do), in which case the names are
+    /// compared instead.
+    pub fn alpha_eq(&self, fst: ExpId, snd: ExpId) -> bool {
+        if fst == snd {
+            return true;
+        }
+        let (fst, snd) = (self.node(fst), self.node(snd));
+        match (&fst.shape, &snd.shape) {
+            (Exp::Var(VarIdx::Idx(Idx(i, _))), Exp::Var(VarIdx::Idx(Idx(j, _)))) => i == j,
+            (Exp::Var(v), Exp::Var(w)) => v.get_var() == w.get_var(),
+            _ => {
+                fst.is_like(snd)
+                    && fst.kids.len() == snd.kids.len()
+                    && fst
+                        .kids
+                        .iter()
+                        .zip(&snd.kids)
+                        .all(|(e, f)| self.alpha_eq(*e, *f))
+            }
+        }
+    }
+
+    /// Check whether the interned type with the first identifier is contained in that with the
+    /// second, by virtue of the universes being cumulative (`Type i` is contained in `Type j` for
+    /// `i ≤ j`, including in the codomain of a Π type).
+    pub fn is_subtype(&self, fst: ExpId, snd: ExpId) -> bool {
+        let (fst_node, snd_node) = (self.node(fst), self.node(snd));
+        match (&fst_node.shape, &snd_node.shape) {
+            (Exp::Sort(i), Exp::Sort(j)) => i <= j,
+            (Exp::For(..), Exp::For(..)) => {
+                let (t, e) = (fst_node.kids[0], fst_node.kids[1]);
+                let (u, f) = (snd_node.kids[0], snd_node.kids[1]);
+                self.alpha_eq(t, u) && self.is_subtype(e, f)
+            }
+            _ => self.alpha_eq(fst, snd),
+        }
+    }
+}
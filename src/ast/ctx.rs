@@ -1,55 +1,776 @@
 //! Typing context and related utilities.
 
-use super::{Exp, Var};
-use crate::err::{TypeRedeclErr, TypeUnknownErr};
-use std::collections::HashMap;
+use super::{
+    unify, Arena, Data, Exp, Idx, Limits, MetaId, Metas, Notation, Strategy, Var, DEFAULT_FUEL,
+};
+use crate::err::{TypeDupDataErr, TypeRedeclErr, TypeUnknownErr, TypingErr};
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 
 /// Typing context, usually represented with the symbol 'Γ'.
-#[derive(Debug, Default, Clone)]
+///
+/// The context is a telescope, that is, an ordered sequence of variables along with their types,
+/// each of which may only refer to the variables before it. A variable may be bound more than once,
+/// in which case the latest binding shadows the earlier ones.
+///
+/// The telescope is persistent, so a context that is [extended][Ctx::extend] shares all of its
+/// bindings with the original (as do the data types and notation, until either is changed), which
+/// makes extending a context a constant-time operation however many bindings it holds.
+///
+/// Besides the types of variables, the context holds the values of variables that are defined
+/// (rather than merely declared), which are unfolded during reduction, the inductive [data types]
+/// [Data], whose eliminators compute by recursion on their constructors, and the [notation]
+/// [Notation] declared during the session, which governs how expressions are parsed and rendered,
+/// along with the reduction [strategy][Strategy] used to show expressions and the budget of steps
+/// permitted for each reduction.
+///
+/// Variables may be [qualified][Var::qualify] by a namespace, and the namespaces that are [opened]
+/// [Ctx::open] in the context are searched when [resolving][Ctx::resolve] an unqualified name.
+///
+/// The context also holds the [metavariables][Metas] created during elaboration, which are shared
+/// with every context extended from it, so that they may be solved while type-checking the
+/// bodies of binders.
+///
+/// Reductions may be [limited][Limits] further as well, by whoever evaluates the commands in the
+/// context, though the limits are not serialized.
+///
+/// A context that is deserialized (such as one saved by an earlier session) is [validated]
+/// [Ctx::validate] as it is loaded, so that it cannot smuggle in ill-typed bindings.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "Flat", try_from = "Flat"))]
 pub struct Ctx {
-    map: HashMap<Var, Exp>,
+    tel: Tel,
+    dat: Arc<Vec<Data>>,
+    ntn: Arc<Vec<Notation>>,
+    opn: Arc<Vec<Var>>,
+    stg: Strategy,
+    gas: usize,
+    met: Arc<Mutex<Metas>>,
+    arn: Arc<Mutex<Arena>>,
+    lim: Limits,
+}
+
+/// Persistent telescope of [entries][Entry], held as a chain of links from the latest entry back
+/// to the first, whose tails are shared between the contexts extended from one another.
+#[derive(Debug, Clone, Default)]
+struct Tel(Option<Arc<Link>>);
+
+/// Link in a [telescope][Tel], which holds an entry along with the telescope that precedes it.
+#[derive(Debug)]
+struct Link {
+    ent: Entry,
+    pre: Tel,
+}
+
+/// Entry of a [typing context][Ctx], which binds a variable to its type, along with its value if
+/// the variable is defined (rather than merely declared).
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Entry {
+    var: Var,
+    typ: Exp,
+    val: Option<Exp>,
+}
+
+/// Difference between two [typing contexts][Ctx], in terms of the (latest) bindings of the
+/// variables in each, as calculated by [Ctx::diff].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diff {
+    /// Variables bound only in the other context, along with their types.
+    pub added: Vec<(Var, Exp)>,
+    /// Variables bound only in this context, along with their types.
+    pub removed: Vec<(Var, Exp)>,
+    /// Variables bound in both contexts to different types, along with the type in this context
+    /// and then the type in the other context.
+    pub changed: Vec<(Var, Exp, Exp)>,
+}
+
+/// Degree to which the type of a variable matches the type searched for by [Ctx::search], from
+/// the closest to the loosest.
+#[derive(Debug, Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Match {
+    /// The type is the one searched for (up to reduction and the names of bound variables).
+    Exact,
+    /// The type unifies with the one searched for, once the variables it binds implicitly (and the
+    /// holes in the type searched for) are instantiated, as a more general type does.
+    Instance,
+    /// The type is that of a function whose result (after the given number of its explicit
+    /// arguments) unifies with the type searched for.
+    Result(usize),
+}
+
+/// Typing context flattened into the form in which it is serialized, with its telescope listed in
+/// declaration order, which is [validated][Ctx::validate] as it is deserialized.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Flat {
+    tel: Vec<Entry>,
+    dat: Vec<Data>,
+    ntn: Vec<Notation>,
+    #[serde(default)]
+    opn: Vec<Var>,
+    stg: Strategy,
+    gas: usize,
+}
+
+#[cfg(feature = "serde")]
+impl From<Ctx> for Flat {
+    fn from(ctx: Ctx) -> Self {
+        Flat {
+            tel: ctx.tel.entries().into_iter().cloned().collect(),
+            dat: ctx.dat.as_ref().clone(),
+            ntn: ctx.ntn.as_ref().clone(),
+            opn: ctx.opn.as_ref().clone(),
+            stg: ctx.stg,
+            gas: ctx.gas,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<Flat> for Ctx {
+    type Error = TypingErr;
+
+    fn try_from(raw: Flat) -> Result<Self, Self::Error> {
+        let ctx = Ctx {
+            tel: raw
+                .tel
+                .into_iter()
+                .fold(Tel::default(), |tel, ent| tel.push(ent)),
+            dat: Arc::new(raw.dat),
+            ntn: Arc::new(raw.ntn),
+            opn: Arc::new(raw.opn),
+            stg: raw.stg,
+            gas: raw.gas,
+            met: Default::default(),
+            arn: Default::default(),
+            lim: Limits::default(),
+        };
+        ctx.validate()?;
+        Ok(ctx)
+    }
+}
+
+impl Tel {
+    /// Create a new telescope with the given entry appended to this one.
+    fn push(&self, ent: Entry) -> Tel {
+        Tel(Some(Arc::new(Link {
+            ent,
+            pre: self.clone(),
+        })))
+    }
+
+    /// Iterate over the links of this telescope, from the latest back to the first.
+    fn links(&self) -> impl Iterator<Item = &Link> {
+        std::iter::successors(self.0.as_deref(), |link| link.pre.0.as_deref())
+    }
+
+    /// Fetch the latest link for a variable in this telescope.
+    fn find(&self, var: &Var) -> Option<&Link> {
+        self.links().find(|link| &link.ent.var == var)
+    }
+
+    /// Fetch the entries of this telescope, in declaration order.
+    fn entries(&self) -> Vec<&Entry> {
+        let mut ents: Vec<_> = self.links().map(|link| &link.ent).collect();
+        ents.reverse();
+        ents
+    }
+
+    /// Create a new telescope without the latest entry for a variable, re-linking only the entries
+    /// after it, unless there is no entry for the variable.
+    fn remove(&self, var: &Var) -> Option<Tel> {
+        let mut post = vec![];
+        for link in self.links() {
+            if &link.ent.var == var {
+                return Some(
+                    post.into_iter()
+                        .rev()
+                        .fold(link.pre.clone(), |tel, ent: &Entry| tel.push(ent.clone())),
+                );
+            }
+            post.push(&link.ent);
+        }
+        None
+    }
+
+    /// Create a new telescope with only the entries needed to bind the given variables, that is,
+    /// their latest entries along with (transitively) the entries of the variables that occur in
+    /// the types and values of those entries.
+    fn restrict(&self, mut vars: HashSet<Var>) -> Tel {
+        let mut kept = vec![];
+        for link in self.links() {
+            if vars.remove(&link.ent.var) {
+                vars.extend(link.ent.typ.free_vars());
+                vars.extend(link.ent.val.iter().flat_map(Exp::free_vars));
+                kept.push(&link.ent);
+            }
+        }
+        kept.into_iter()
+            .rev()
+            .fold(Tel::default(), |tel, ent| tel.push(ent.clone()))
+    }
+}
+
+impl Drop for Tel {
+    /// Drop the links that are not shared with any other telescope one at a time, rather than
+    /// recursively, which would overflow the stack for long telescopes.
+    fn drop(&mut self) {
+        let mut next = self.0.take();
+        while let Some(link) = next {
+            next = match Arc::try_unwrap(link) {
+                Ok(mut link) => link.pre.0.take(),
+                Err(_) => None, // shared, so the rest of the chain is still in use
+            };
+        }
+    }
+}
+
+impl Diff {
+    /// Check whether the two contexts compared have the same bindings.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Retain only the bindings of the variables that satisfy the given predicate.
+    pub fn retain(&mut self, f: impl Fn(&Var) -> bool) {
+        self.added.retain(|(var, _)| f(var));
+        self.removed.retain(|(var, _)| f(var));
+        self.changed.retain(|(var, _, _)| f(var));
+    }
+}
+
+/// Render the difference with one binding on each line, marked with `+` if it is added, `-` if it
+/// is removed and `~` if it is changed.
+impl Display for Diff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (var, typ) in &self.added {
+            writeln!(f, "+ {} : {}", var, typ)?;
+        }
+        for (var, typ) in &self.removed {
+            writeln!(f, "- {} : {}", var, typ)?;
+        }
+        for (var, typ, upd) in &self.changed {
+            writeln!(f, "~ {} : {} ≠ {}", var, typ, upd)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Match {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Exact => write!(f, "exact"),
+            Self::Instance => write!(f, "instance"),
+            Self::Result(_) => write!(f, "result"),
+        }
+    }
+}
+
+impl Default for Ctx {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Ctx {
     pub fn new() -> Self {
         Ctx {
-            map: HashMap::new(),
+            tel: Tel::default(),
+            dat: Default::default(),
+            ntn: Default::default(),
+            opn: Default::default(),
+            stg: Strategy::default(),
+            gas: DEFAULT_FUEL,
+            met: Default::default(),
+            arn: Default::default(),
+            lim: Limits::default(),
         }
     }
 }
 
 impl Ctx {
     /// Register a variable and its associated type in this typing context.
+    ///
+    /// The variable is merely declared, so any definition of the same name is shadowed.
     pub fn put(&mut self, var: &Var, typ: &Exp) -> Result<(), TypeRedeclErr> {
-        let old = self.map.get(var);
-        if let Some(old) = old {
-            if old != typ {
-                return Err(TypeRedeclErr::new(var, old, typ));
+        self.bind(var, typ, None)
+    }
+
+    /// Register a variable along with its type (and value, if it is defined) in this typing
+    /// context, replacing any earlier binding of the variable to the same type, which is moved to
+    /// the end of the context, since its value may refer to the variables bound after it.
+    fn bind(&mut self, var: &Var, typ: &Exp, val: Option<Exp>) -> Result<(), TypeRedeclErr> {
+        let ent = Entry {
+            var: var.clone(),
+            typ: typ.clone(),
+            val,
+        };
+        match self.tel.find(var) {
+            Some(link) if &link.ent.typ != typ => Err(TypeRedeclErr::new(var, &link.ent.typ, typ)),
+            Some(_) => {
+                self.tel = self.tel.remove(var).unwrap_or_default().push(ent);
+                Ok(())
+            }
+            None => {
+                self.tel = self.tel.push(ent);
+                Ok(())
+            }
+        }
+    }
+
+    /// Register a variable along with its type in this typing context, after checking that the type
+    /// is well-formed, so that the variable stands for a constant (or axiom) of that type, which has
+    /// no value and therefore does not reduce.
+    pub fn assume(&mut self, var: &Var, typ: &Exp) -> Result<(), TypingErr> {
+        typ.calculate_level(self)?;
+        Ok(self.put(var, typ)?)
+    }
+
+    /// Register a variable along with its value and type in this typing context, after
+    /// [elaborating][Exp::elaborate_as] the value and checking that it has that type.
+    pub fn define(&mut self, var: &Var, val: &Exp, typ: &Exp) -> Result<(), TypingErr> {
+        typ.calculate_level(self)?;
+        let val = val.elaborate_as(typ, self);
+        val.check(typ, self)?;
+        let val = val.zonk(self);
+        Ok(self.bind(var, typ, Some(val))?)
+    }
+
+    /// Register a variable along with its value and type in this typing context, replacing any
+    /// earlier binding of the variable whatever its type (unlike [define][Self::define]).
+    ///
+    /// The value is not checked, so it should already have been found to have that type, as for
+    /// the result of a command.
+    pub fn rebind(&mut self, var: &Var, val: &Exp, typ: &Exp) {
+        if let Some(tel) = self.tel.remove(var) {
+            self.tel = tel;
+        }
+        self.tel = self.tel.push(Entry {
+            var: var.clone(),
+            typ: typ.clone(),
+            val: Some(val.clone()),
+        });
+    }
+
+    /// Check that this context is well-formed, that is, that the type of each variable is itself
+    /// well-typed (as a type) in the prefix of the context that precedes it, and that the value of
+    /// each defined variable has its type in that prefix.
+    pub fn validate(&self) -> Result<(), TypingErr> {
+        for link in self.tel.links() {
+            let pre = self.with(link.pre.clone());
+            link.ent
+                .typ
+                .calculate_level(&pre)
+                .map_err(|err| self.explain(&pre, err))?;
+            if let Some(val) = &link.ent.val {
+                val.check(&link.ent.typ, &pre)
+                    .map_err(|err| self.explain(&pre, err))?;
             }
         }
-        self.map.insert(var.clone(), typ.clone());
         Ok(())
     }
 
-    /// Extend this context with a variable and return the context, without modifying the original.
-    pub fn extend(&self, var: &Var, typ: &Exp) -> Result<Ctx, TypeRedeclErr> {
+    /// Calculate the difference between this context and another, that is, the variables that are
+    /// bound only in the other context (added), those that are bound only in this context
+    /// (removed), and those that are bound in both but to different types (changed).
+    ///
+    /// Only the latest binding of each variable is compared, and the variables are listed in the
+    /// order in which they are bound in the context they are taken from.
+    pub fn diff(&self, other: &Ctx) -> Diff {
+        let latest = |ctx: &Ctx| {
+            let mut seen = HashSet::new();
+            let mut ents: Vec<_> = (ctx.tel.links())
+                .filter(|link| seen.insert(&link.ent.var))
+                .map(|link| (link.ent.var.clone(), link.ent.typ.clone()))
+                .collect();
+            ents.reverse();
+            ents
+        };
+        let mut dif = Diff::default();
+        for (var, typ) in latest(self) {
+            match other.get(&var) {
+                Ok(upd) if upd == &typ => {}
+                Ok(upd) => dif.changed.push((var, typ, upd.clone())),
+                Err(_) => dif.removed.push((var, typ)),
+            }
+        }
+        for (var, typ) in latest(other) {
+            if self.get(&var).is_err() {
+                dif.added.push((var, typ));
+            }
+        }
+        dif
+    }
+
+    /// Explain an error that occurred in the given scope (such as a [prefix][Ctx::prefix_before]
+    /// of this context) by how the scope differs from this context with respect to the variable
+    /// reported by the error, such as one that is only bound later in this context.
+    pub(super) fn explain(&self, scope: &Ctx, err: TypingErr) -> TypingErr {
+        let only = |var: &Var| {
+            let mut dif = scope.diff(self);
+            dif.retain(|v| v == var);
+            dif
+        };
+        match err {
+            TypingErr::TypeUnknownErr(e) => {
+                let dif = only(&e.var);
+                TypingErr::from(e.with_diff(dif))
+            }
+            TypingErr::TypeRedeclErr(e) => {
+                let dif = only(&e.var);
+                TypingErr::from(e.with_diff(dif))
+            }
+            TypingErr::TrailErr(mut e) => {
+                e.err = Box::new(self.explain(scope, *e.err));
+                TypingErr::from(e)
+            }
+            err => err,
+        }
+    }
+
+    /// Write this context to the given writer (as JSON), so that it may be restored with
+    /// [from_reader][Ctx::from_reader], such as in a later session.
+    ///
+    /// Metavariables are not written, so any that remain unsolved are lost.
+    #[cfg(feature = "serde")]
+    pub fn to_writer(&self, writer: impl std::io::Write) -> std::io::Result<()> {
+        Ok(serde_json::to_writer(writer, self)?)
+    }
+
+    /// Read a context written with [to_writer][Ctx::to_writer] from the given reader, after
+    /// [validating][Ctx::validate] it.
+    #[cfg(feature = "serde")]
+    pub fn from_reader(reader: impl std::io::Read) -> std::io::Result<Ctx> {
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// Declare an inductive data type in this typing context, along with its constructors and its
+    /// eliminator, after checking that the declaration is well-formed.
+    ///
+    /// The context is left unchanged if the declaration is rejected.
+    pub fn declare(&mut self, dat: &Data) -> Result<(), TypingErr> {
+        for var in dat.vars() {
+            if self.get(&var).is_ok() || dat.vars().iter().filter(|v| *v == &var).count() > 1 {
+                return Err(TypingErr::from(TypeDupDataErr::new(&dat.var, &var)));
+            }
+        }
         let mut can = self.clone();
-        can.put(var, typ)?;
-        Ok(can)
+        can.put(&dat.var, &Exp::get_type_meta())?;
+        dat.validate(&can)?;
+        for (var, typ) in dat.vars().iter().zip(dat.types(self)) {
+            can.put(var, &typ)?;
+        }
+        Arc::make_mut(&mut can.dat).push(dat.clone());
+        *self = can;
+        Ok(())
+    }
+
+    /// Fetch the inductive data type denoted by a variable in this typing context, if any.
+    pub fn get_data(&self, var: &Var) -> Option<&Data> {
+        self.dat.iter().find(|dat| &dat.var == var)
+    }
+
+    /// Fetch the inductive data type whose eliminator is denoted by a variable in this typing
+    /// context, if any.
+    pub(super) fn get_elim(&self, var: &Var) -> Option<&Data> {
+        self.dat.iter().find(|dat| &dat.rec() == var)
+    }
+
+    /// Fetch the value associated with a variable in this typing context, if it is defined.
+    pub fn get_def(&self, var: &Var) -> Option<&Exp> {
+        self.tel.find(var)?.ent.val.as_ref()
+    }
+
+    /// Extend this context with a variable and return the context, without modifying the original.
+    ///
+    /// The variable is bound by a binder, so it shadows any variable of the same name (and its
+    /// definition), rather than conflicting with it. A variable bound implicitly has the same
+    /// type as one bound explicitly.
+    pub fn extend(&self, var: &Var, typ: &Exp) -> Ctx {
+        let ent = Entry {
+            var: var.clone(),
+            typ: typ.explicit().clone(),
+            val: None,
+        };
+        self.with(self.tel.push(ent))
     }
 
-    /// Return a new context without the given variable, without modifying the original.
+    /// Return a new context without the (latest binding of the) given variable, without modifying
+    /// the original.
+    ///
+    /// Only the bindings after the variable are copied, so removing the latest binding takes
+    /// constant time. The bindings after the variable are kept, so the result is only well-formed
+    /// if none of them refer to it; use [prefix_before][Ctx::prefix_before] for the context in
+    /// which the variable was bound.
     pub fn subtract(&self, var: &Var) -> Result<Ctx, TypeUnknownErr> {
-        let mut can = self.clone();
-        can.map
-            .remove(var)
-            .map_or_else(|| Err(TypeUnknownErr::new(var)), |_| Ok(can))
+        match self.tel.remove(var) {
+            Some(tel) => Ok(self.with(tel)),
+            None => Err(TypeUnknownErr::new(var)),
+        }
+    }
+
+    /// Return the prefix of this context that precedes the (latest binding of the) given variable,
+    /// without modifying the original, which is the context `Γ` in which its type is formed, as in
+    /// the premise of the VAR rule.
+    ///
+    /// The prefix is shared with this context, so it is returned in constant time once the
+    /// binding is found.
+    pub fn prefix_before(&self, var: &Var) -> Result<Ctx, TypeUnknownErr> {
+        match self.tel.find(var) {
+            Some(link) => Ok(self.with(link.pre.clone())),
+            None => Err(TypeUnknownErr::new(var)),
+        }
+    }
+
+    /// Return a new context with only the bindings of the given variables, along with those of the
+    /// variables their types and values refer to, without modifying the original.
+    ///
+    /// Each dropped binding is one that the WEAK rule could have introduced, so an expression whose
+    /// free variables are among the given variables has the same type in both contexts. The data
+    /// types, notation and metavariables are kept as they are.
+    pub fn restrict<'a>(
+        &self,
+        vars: impl IntoIterator<Item = &'a Var>,
+    ) -> Result<Ctx, TypeUnknownErr> {
+        let vars: HashSet<Var> = vars.into_iter().cloned().collect();
+        if let Some(var) = vars.iter().find(|var| self.tel.find(var).is_none()) {
+            return Err(TypeUnknownErr::new(var));
+        }
+        Ok(self.with(self.tel.restrict(vars)))
+    }
+
+    /// Return a copy of this context with the given telescope in place of its own.
+    fn with(&self, tel: Tel) -> Ctx {
+        Ctx {
+            tel,
+            ..self.clone()
+        }
     }
 
-    /// Fetch the type associated with a variable in this typing context.
+    /// Fetch the type associated with (the latest binding of) a variable in this typing context.
     pub fn get(&self, var: &Var) -> Result<&Exp, TypeUnknownErr> {
-        self.map
-            .get(var)
-            .map_or_else(|| Err(TypeUnknownErr::new(var)), Ok)
+        self.tel
+            .find(var)
+            .map_or_else(|| Err(TypeUnknownErr::new(var)), |link| Ok(&link.ent.typ))
+    }
+
+    /// Fetch the type associated with (the latest binding of) a variable in this typing context,
+    /// after checking that it is a type in the [prefix][Ctx::prefix_before] that precedes the
+    /// binding, as in the premise of the VAR rule.
+    pub(super) fn get_formed(&self, var: &Var) -> Result<&Exp, TypingErr> {
+        let typ = self.get(var)?;
+        let pre = self.prefix_before(var)?;
+        typ.calculate_level(&pre)
+            .map_err(|err| self.explain(&pre, err))?;
+        Ok(typ)
+    }
+
+    /// Iterate over the variables bound in this context along with their types, in the order in
+    /// which they were bound, including those that are shadowed by later bindings.
+    pub fn iter(&self) -> impl Iterator<Item = (&Var, &Exp)> {
+        self.tel
+            .entries()
+            .into_iter()
+            .map(|ent| (&ent.var, &ent.typ))
+    }
+
+    /// Open a namespace in this context, so that the variables within it may be referred to by
+    /// their unqualified names, which take precedence over those of namespaces opened earlier.
+    pub fn open(&mut self, ns: &Var) -> Result<(), TypeUnknownErr> {
+        if !self.tel.links().any(|link| link.ent.var.is_within(ns)) {
+            return Err(TypeUnknownErr::new(ns));
+        }
+        let all = Arc::make_mut(&mut self.opn);
+        all.retain(|n| n != ns);
+        all.push(ns.clone());
+        Ok(())
+    }
+
+    /// Resolve a name to the variable it refers to in this context, that is, the variable itself
+    /// if it is bound, or else the variable qualified by the latest namespace opened in which it is
+    /// bound, if any.
+    ///
+    /// A variable that is bound (such as by a binder) therefore shadows the variables of the same
+    /// name in any namespace that is opened.
+    pub fn resolve(&self, var: &Var) -> Option<Var> {
+        if self.tel.find(var).is_some() {
+            return Some(var.clone());
+        }
+        (self.opn.iter().rev())
+            .map(|ns| var.qualify(ns))
+            .find(|var| self.tel.find(var).is_some())
+    }
+
+    /// Find the variables in the namespaces opened in this context that can no longer be referred
+    /// to by their names within the namespace, since those names [resolve][Ctx::resolve] to
+    /// other variables, along with those names.
+    pub fn unreachable(&self) -> Vec<(Var, Var)> {
+        let mut vars = vec![];
+        for ns in self.opn.iter() {
+            for (var, _) in self.iter() {
+                let Some(name) = var.0.strip_prefix(&format!("{}.", ns)).map(Var::new) else {
+                    continue;
+                };
+                if self.resolve(&name).as_ref() != Some(var) && !vars.iter().any(|(v, _)| v == var)
+                {
+                    vars.push((var.clone(), name));
+                }
+            }
+        }
+        vars
+    }
+
+    /// Search this context for the variables whose types match the given type, in which each
+    /// [hole][Exp::Hole] stands for any type, returning the (latest) bindings that match, ranked
+    /// from the closest [match][Match] to the loosest (and then by name).
+    ///
+    /// Each variable is matched in isolation, so that the metavariables solved along the way are
+    /// discarded afterwards rather than solved in this context.
+    pub fn search(&self, typ: &Exp) -> Vec<(Match, &Var, &Exp)> {
+        let mut seen = HashSet::new();
+        let mut found: Vec<_> = (self.tel.links())
+            .filter(|link| seen.insert(&link.ent.var)) // shadowed by a later binding otherwise
+            .map(|link| (&link.ent.var, &link.ent.typ))
+            .filter_map(|(var, can)| Some((self.isolated().fit(typ, can)?, var, can)))
+            .collect();
+        found.sort_by(|(m, v, _), (n, w, _)| m.cmp(n).then_with(|| v.0.cmp(&w.0)));
+        found
+    }
+
+    /// Determine how closely the given candidate type matches the given type, in this context, if
+    /// at all, solving metavariables along the way.
+    fn fit(&self, typ: &Exp, can: &Exp) -> Option<Match> {
+        let typ = self.fill(typ.elaborate(self));
+        if let (Ok(one), Ok(two)) = (typ.clone().reduce(self), can.clone().reduce(self)) {
+            if !typ.has_metas() && one.diverge(&two).is_none() {
+                return Some(Match::Exact);
+            }
+        }
+        let mut can = can.clone();
+        while let Exp::For(var, dom, cod) = &can {
+            if let Exp::Imp(dom) = dom.as_ref() {
+                can = cod.clone().subst(&Idx::new(var), &self.fresh_meta(dom));
+            } else {
+                break;
+            }
+        } // instantiate the variables bound implicitly
+        if unify(&typ, &can, self).is_ok() {
+            return Some(Match::Instance);
+        }
+        let mut args = 0;
+        while let Exp::For(var, dom, cod) = can {
+            args += usize::from(!dom.is_implicit());
+            can = cod.subst(&Idx::new(&var), &self.fresh_meta(dom.explicit()));
+            if unify(&typ, &can, self).is_ok() {
+                return Some(Match::Result(args));
+            }
+        } // supply the arguments of the function one at a time
+        None
+    }
+
+    /// Replace each hole in the given type with a fresh metavariable (which stands for a type).
+    fn fill(&self, typ: Exp) -> Exp {
+        match typ {
+            Exp::Hole => self.fresh_meta(&Exp::get_type_meta()),
+            _ => typ.map_subterms(|exp, _| self.fill(exp)),
+        }
+    }
+
+    /// Fetch a copy of this context whose metavariables are held apart from those of this context,
+    /// so that solving them leaves this context unchanged.
+    pub fn isolated(&self) -> Ctx {
+        let mut ctx = self.clone();
+        ctx.met = Arc::new(Mutex::new(self.metas().clone()));
+        ctx
+    }
+
+    /// Declare notation in this context, replacing any earlier declaration of the same operator.
+    pub fn put_notation(&mut self, ntn: Notation) {
+        let all = Arc::make_mut(&mut self.ntn);
+        all.retain(|n| n.opr != ntn.opr);
+        all.push(ntn);
+    }
+
+    /// Fetch the notation declared for an operator in this context.
+    pub fn get_notation(&self, opr: &str) -> Option<&Notation> {
+        self.ntn.iter().find(|n| n.opr == opr)
+    }
+
+    /// Fetch the notation (most recently) declared for a variable in this context, which is used
+    /// to render applications of that variable.
+    pub fn get_notation_for(&self, var: &Var) -> Option<&Notation> {
+        self.ntn.iter().rev().find(|n| &n.var == var)
+    }
+
+    /// Fetch the reduction strategy used to show expressions in this context.
+    pub fn get_strategy(&self) -> Strategy {
+        self.stg
+    }
+
+    /// Set the reduction strategy used to show expressions in this context.
+    pub fn set_strategy(&mut self, stg: Strategy) {
+        self.stg = stg;
+    }
+
+    /// Fetch the number of steps permitted for each reduction in this context.
+    pub fn get_fuel(&self) -> usize {
+        self.gas
+    }
+
+    /// Set the number of steps permitted for each reduction in this context.
+    pub fn set_fuel(&mut self, fuel: usize) {
+        self.gas = fuel;
+    }
+
+    /// Fetch the [limits][Limits] on the reductions performed in this context.
+    pub fn get_limits(&self) -> &Limits {
+        &self.lim
+    }
+
+    /// Set the [limits][Limits] on the reductions performed in this context, as whoever evaluates
+    /// a command does before evaluating it (since the time permitted starts with the limits).
+    pub fn set_limits(&mut self, lim: Limits) {
+        self.lim = lim;
+    }
+
+    /// Create a new (unsolved) metavariable of the given type in this context.
+    pub fn fresh_meta(&self, typ: &Exp) -> Exp {
+        Exp::Meta(self.metas().fresh(typ))
+    }
+
+    /// Fetch the type of a metavariable in this context, if it exists.
+    pub fn get_meta_type(&self, id: MetaId) -> Option<Exp> {
+        self.metas().get_type(id).cloned()
+    }
+
+    /// Fetch the solution of a metavariable in this context, if it exists and is solved.
+    pub fn get_meta(&self, id: MetaId) -> Option<Exp> {
+        self.metas().get_solution(id).cloned()
+    }
+
+    /// Fetch the metavariables in this context that are yet to be solved, in order of creation.
+    pub fn get_unsolved(&self) -> Vec<MetaId> {
+        self.metas().unsolved()
+    }
+
+    /// Record the solution of a metavariable in this context.
+    pub(super) fn solve_meta(&self, id: MetaId, sol: Exp) {
+        self.metas().solve(id, sol);
+    }
+
+    /// Lock the metavariables of this context, which it shares with the contexts extended from it
+    /// (even across threads), recovering them if a thread panicked while holding them.
+    fn metas(&self) -> MutexGuard<'_, Metas> {
+        self.met.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Lock the [arena][Arena] in which this context interns the types it compares, which it
+    /// shares with the contexts extended from it (and with those [isolated][Ctx::isolated] from
+    /// it, since interning leaves the meaning of an expression unchanged).
+    pub(super) fn arena(&self) -> MutexGuard<'_, Arena> {
+        self.arn.lock().unwrap_or_else(PoisonError::into_inner)
     }
 }
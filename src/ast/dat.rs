@@ -0,0 +1,309 @@
+//! Inductive data types and related structures.
+
+use super::{positivity, Ctx, Exp, Idx, Polarity, Var, VarIdx};
+use crate::err::{TypePosErr, TypingErr};
+use std::fmt::{Display, Formatter};
+
+/// Declaration of an inductive data type, which is given by its constructors:
+///
+/// ```text
+/// data Peano := zero | succ Peano
+/// ```
+///
+/// ...which declares the type `Peano : *`, the constructors `zero : Peano` and
+/// `succ : Peano → Peano`, and the eliminator `Peano_rec`, which performs recursion over the
+/// values of the type:
+///
+/// ```text
+/// Peano_rec : ΠP : Peano → □ . P zero → (Πa : Peano . P a → P (succ a)) → Πx : Peano . P x
+/// ```
+///
+/// An eliminator applied to a motive, a method for each constructor, and a constructor applied
+/// to its arguments reduces to the corresponding method applied to those arguments along with
+/// the results of recursion on the arguments of the data type itself (iota-reduction).
+///
+/// The data type may only occur in the arguments of its constructors in strictly positive
+/// positions, that is, either as the argument itself or as the codomain of a function that it
+/// returns (as in `node (Nat → Tree)`), which ensures that recursion always terminates.
+#[derive(Debug, Clone, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Data {
+    /// Variable that denotes the data type.
+    pub var: Var,
+    /// Constructors of the data type.
+    pub cns: Vec<Ctor>,
+}
+
+/// Constructor of an inductive [data type][Data], which is a function of the given arguments.
+#[derive(Debug, Clone, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ctor {
+    /// Variable that denotes the constructor.
+    pub var: Var,
+    /// Types of the arguments of the constructor.
+    pub args: Vec<Exp>,
+}
+
+impl Data {
+    /// Create a new instance of a data type declaration.
+    pub fn new(var: Var, cns: Vec<Ctor>) -> Self {
+        Data { var, cns }
+    }
+
+    /// Get the variable that denotes the eliminator of this data type.
+    pub fn rec(&self) -> Var {
+        Var(format!("{}_rec", self.var))
+    }
+
+    /// Get the variables introduced by this declaration, namely the data type itself, followed by
+    /// its constructors and its eliminator.
+    pub fn vars(&self) -> Vec<Var> {
+        let mut vars = vec![self.var.clone()];
+        vars.extend(self.cns.iter().map(|cn| cn.var.clone()));
+        vars.push(self.rec());
+        vars
+    }
+
+    /// Check that the constructors of this data type are well-formed in the given context, which
+    /// already holds the data type itself.
+    pub(super) fn validate(&self, ctx: &Ctx) -> Result<(), TypingErr> {
+        for cn in &self.cns {
+            for typ in &cn.args {
+                if !self.is_positive(typ) {
+                    return Err(TypingErr::from(TypePosErr::new(&self.var, &cn.var, typ)));
+                }
+                typ.validate_type(&[&Exp::get_type_meta()], ctx)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check whether the data type occurs only strictly positively in the given argument type.
+    fn is_positive(&self, typ: &Exp) -> bool {
+        positivity(&self.var, typ) == Polarity::Absent || self.rec_binders(typ).is_some()
+    }
+
+    /// Fetch the binders of the given argument type, if it is recursive, that is, if it is the
+    /// data type itself, or a function (of arguments that do not involve the data type) that
+    /// returns the data type.
+    fn rec_binders<'a>(&self, mut typ: &'a Exp) -> Option<Vec<(&'a Var, &'a Exp)>> {
+        let mut bnd = vec![];
+        while let Exp::For(var, dom, cod) = typ {
+            if dom.has_name(&self.var) {
+                return None;
+            }
+            bnd.push((var, dom.as_ref()));
+            typ = cod;
+        }
+        match typ {
+            Exp::Var(VarIdx::Var(var)) if var == &self.var => Some(bnd),
+            _ => None,
+        }
+    }
+
+    /// Calculate the types of the variables introduced by this declaration, in the same order as
+    /// [Data::vars], choosing names for bound variables that do not clash with the given context.
+    pub(super) fn types(&self, ctx: &Ctx) -> Vec<Exp> {
+        let mut types = vec![Exp::get_type_meta()];
+        types.extend(self.cns.iter().map(|cn| {
+            cn.args
+                .iter()
+                .rev()
+                .fold(Exp::new_var(self.var.clone()), |typ, arg| {
+                    Exp::new_arr(arg.clone(), typ)
+                })
+        }));
+        let mut used = vec![];
+        let p = self.fresh("P", &used, ctx);
+        used.push(p.clone());
+        let x = self.fresh("x", &used, ctx);
+        used.push(x.clone());
+        let dat = Exp::new_var(self.var.clone());
+        let app = Exp::new_app(Exp::new_var(p.clone()), Exp::new_var(x.clone()));
+        let rec = self
+            .cns
+            .iter()
+            .rev()
+            .fold(Exp::new_for(x, dat.clone(), app), |typ, cn| {
+                Exp::new_arr(self.method(cn, &p, &used, ctx), typ)
+            });
+        types.push(Exp::new_for(
+            p,
+            Exp::new_arr(dat, Exp::get_kind_meta()),
+            rec,
+        ));
+        types
+    }
+
+    /// Calculate the type of the method that the eliminator expects for the given constructor,
+    /// which is the motive applied to the constructor, given its arguments and the results of
+    /// recursion on them.
+    fn method(&self, cn: &Ctor, p: &Var, used: &[Var], ctx: &Ctx) -> Exp {
+        let mut used = used.to_vec();
+        let mut vars = vec![];
+        for i in 0..cn.args.len() {
+            let var = self.fresh(&Ctor::arg_name(i), &used, ctx);
+            used.push(var.clone());
+            vars.push(var);
+        }
+        let tgt = vars.iter().fold(Exp::new_var(cn.var.clone()), |exp, var| {
+            Exp::new_app(exp, Exp::new_var(var.clone()))
+        });
+        let ihs: Vec<Exp> = vars
+            .iter()
+            .zip(&cn.args)
+            .filter_map(|(var, typ)| {
+                let bnd = self.named_binders(typ, &used, ctx)?;
+                let arg = Exp::new_var(var.clone());
+                Some(Data::under(&bnd, Exp::For, |args| {
+                    Exp::new_app(Exp::new_var(p.clone()), Data::apply(arg, args))
+                }))
+            })
+            .collect();
+        let typ = ihs
+            .into_iter()
+            .rev()
+            .fold(Exp::new_app(Exp::new_var(p.clone()), tgt), |typ, ih| {
+                Exp::new_arr(ih, typ)
+            });
+        vars.into_iter()
+            .zip(&cn.args)
+            .rev()
+            .fold(typ, |typ, (var, arg)| Exp::new_for(var, arg.clone(), typ))
+    }
+
+    /// Perform iota-reduction on the eliminator of this data type applied to the given arguments,
+    /// if the eliminator is applied to (at least) a motive, the methods, and a constructor that is
+    /// fully applied to its own arguments.
+    pub(super) fn eliminate(&self, rec: &Exp, args: &[&Exp], ctx: &Ctx) -> Option<Exp> {
+        let k = self.cns.len();
+        let (head, vals) = args.get(k + 1)?.spine();
+        let (i, cn) = match head {
+            Exp::Var(VarIdx::Var(var)) => {
+                self.cns.iter().enumerate().find(|(_, c)| &c.var == var)?
+            }
+            _ => return None,
+        };
+        if vals.len() != cn.args.len() {
+            return None;
+        }
+        let mut exp = Data::apply(args[i + 1].clone(), vals.iter().copied().cloned().collect());
+        for (val, typ) in vals.iter().zip(&cn.args) {
+            if let Some(bnd) = self.named_binders(typ, &[], ctx) {
+                let shift = |exp: &Exp| exp.clone().shift(bnd.len(), 0); // moved under the binders
+                let ih = Data::under(&bnd, Exp::Abs, |vars| {
+                    let rec =
+                        Data::apply(rec.clone(), args[..=k].iter().map(|e| shift(e)).collect());
+                    Exp::new_app(rec, Data::apply(shift(val), vars))
+                });
+                exp = Exp::new_app(exp, ih);
+            }
+        }
+        Some(Data::apply(
+            exp,
+            args[k + 2..].iter().copied().cloned().collect(),
+        ))
+    }
+
+    /// Fetch the binders of the given argument type, if it is recursive (as [Data::rec_binders]
+    /// does), naming the anonymous binders of arrows so that they can be rendered as variables.
+    fn named_binders<'a>(
+        &self,
+        typ: &'a Exp,
+        used: &[Var],
+        ctx: &Ctx,
+    ) -> Option<Vec<(Var, &'a Exp)>> {
+        let mut used = used.to_vec();
+        let bnd = self.rec_binders(typ)?;
+        Some(
+            bnd.into_iter()
+                .map(|(var, typ)| {
+                    let var = if var.0.starts_with('_') {
+                        self.fresh("y", &used, ctx) // anonymous binder of an arrow
+                    } else {
+                        var.clone()
+                    };
+                    used.push(var.clone());
+                    (var, typ)
+                })
+                .collect(),
+        )
+    }
+
+    /// Wrap the expression produced from the variables of the given binders in binders of the same
+    /// kind (λ abstractions or Π types).
+    fn under(
+        bnd: &[(Var, &Exp)],
+        kind: fn(Var, Box<Exp>, Box<Exp>) -> Exp,
+        exp: impl FnOnce(Vec<Exp>) -> Exp,
+    ) -> Exp {
+        let vars = bnd
+            .iter()
+            .enumerate()
+            .map(|(i, (var, _))| Exp::Var(VarIdx::Idx(Idx(bnd.len() - 1 - i, var.clone()))))
+            .collect();
+        bnd.iter().rev().fold(exp(vars), |exp, (var, typ)| {
+            kind(var.clone(), Box::new((*typ).clone()), Box::new(exp))
+        })
+    }
+
+    /// Apply an expression to the given arguments, in order.
+    fn apply(exp: Exp, args: Vec<Exp>) -> Exp {
+        args.into_iter().fold(exp, Exp::new_app)
+    }
+
+    /// Choose a name based on the given one that does not occur in this declaration, among the
+    /// given names or in the given context.
+    fn fresh(&self, name: &str, used: &[Var], ctx: &Ctx) -> Var {
+        let mut var = Var::new(name);
+        while var == self.var
+            || used.contains(&var)
+            || ctx.get(&var).is_ok()
+            || self
+                .cns
+                .iter()
+                .any(|cn| cn.var == var || cn.args.iter().any(|typ| typ.has_name(&var)))
+        {
+            var.0.push('\'');
+        }
+        var
+    }
+}
+
+impl Ctor {
+    /// Create a new instance of a constructor declaration.
+    pub fn new(var: Var, args: Vec<Exp>) -> Self {
+        Ctor { var, args }
+    }
+
+    /// Get the name for the argument of a constructor at the given position.
+    fn arg_name(i: usize) -> String {
+        match u8::try_from(i) {
+            Ok(i) if i < 26 => ((b'a' + i) as char).to_string(),
+            _ => format!("a{}", i),
+        }
+    }
+}
+
+/// Render the data type as the declaration that introduces it.
+impl Display for Data {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "data {} :=", self.var)?;
+        for (i, cn) in self.cns.iter().enumerate() {
+            write!(f, "{} {}", if i == 0 { "" } else { " |" }, cn)?;
+        }
+        Ok(())
+    }
+}
+
+/// Render the constructor as its name followed by the types of its arguments.
+impl Display for Ctor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let exp = Data::apply(Exp::new_var(self.var.clone()), self.args.clone());
+        if f.alternate() {
+            write!(f, "{:#}", exp)
+        } else {
+            write!(f, "{}", exp)
+        }
+    }
+}
@@ -1,12 +1,25 @@
 //! Top-level expression in the core fluxo language and related logic.
 
-use super::{Ctx, Idx, Var, VarIdx};
-use crate::err::{TypeCompatErr, TypeUndefErr, TypingErr};
+use super::{
+    unify, Arena, Ctx, Idx, MetaId, Notation, Pos, PrintOptions, Stats, Strategy, Var, VarIdx,
+};
+use crate::err::{
+    InterruptErr, ReductionBudgetErr, ReductionOverflowErr, ReductionSizeErr, ReductionTimeoutErr,
+    SyntaxErr, TypeCompatErr, TypeDupFieldErr, TypeFieldErr, TypeHoleErr, TypeSortErr,
+    TypeUndefErr, TypingErr,
+};
+use crate::par::{self, Span};
 use std::cmp::Ordering;
 use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Number of reduction steps performed by [Exp::reduce] before giving up on reaching normal form,
+/// unless another budget is [set][Ctx::set_fuel] in the context.
+pub const DEFAULT_FUEL: usize = 100_000;
 
 /// Top-level expression in the core fluxo language.
 #[derive(Debug, Clone, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Exp {
     /// Variable expression, internally holding either a symbol or a bound (and indexed) variable.
     Var(VarIdx),
@@ -16,27 +29,68 @@ pub enum Exp {
     For(Var, Box<Exp>, Box<Exp>),
     /// Application of an abstraction to an expression.
     App(Box<Exp>, Box<Exp>),
-    /// The type of all types, denoted by `*`.
-    TypeMeta,
-    /// The type of all kinds, denoted by `□`.
-    KindMeta,
-}
-
-/// Structure that indicates the current state of which branch of the application tree we're
-/// exclusively in.
-///
-/// Within the core fluxo language, the [Exp::App] type drives the notion of 'left sub-tree' and
-/// 'right sub-tree'. As the tree is traversed, you can be in one of the following states:
-///
-/// * Left sub-tree only
-/// * Right sub-tree only
-/// * Neither sub-tree exclusively (default state)
-#[derive(Copy, Clone, Debug, Default)]
-struct Branch {
-    /// Left sub-tree.
-    ltree: bool,
-    /// Right sub-tree.
-    rtree: bool,
+    /// Σ type, which denotes the type of a dependent pair.
+    Sig(Var, Box<Exp>, Box<Exp>),
+    /// Dependent pair of expressions, annotated with its [Σ type][Exp::Sig].
+    Pair(Box<Exp>, Box<Exp>, Box<Exp>),
+    /// First projection of a dependent pair.
+    Fst(Box<Exp>),
+    /// Second projection of a dependent pair.
+    Snd(Box<Exp>),
+    /// Unit type, denoted by `⊤`, whose only element is [`()`][Exp::Triv].
+    Unit,
+    /// Only element of the [unit type][Exp::Unit], denoted by `()`.
+    Triv,
+    /// Empty type, denoted by `⊥`, which has no elements.
+    Void,
+    /// Elimination of the [empty type][Exp::Void] into the given type, denoted by `absurd A M`.
+    Absurd(Box<Exp>, Box<Exp>),
+    /// Binary sum of two types, denoted by `Sum A B`.
+    Sum(Box<Exp>, Box<Exp>),
+    /// Left injection into a [sum type][Exp::Sum], denoted by `inl B M`, which is annotated with
+    /// the right summand, since it cannot be determined from the injected expression.
+    Inl(Box<Exp>, Box<Exp>),
+    /// Right injection into a [sum type][Exp::Sum], denoted by `inr A M`, which is annotated with
+    /// the left summand, since it cannot be determined from the injected expression.
+    Inr(Box<Exp>, Box<Exp>),
+    /// Case analysis on an expression of a [sum type][Exp::Sum], denoted by `case P F G M`, which
+    /// applies `F` or `G` to the injected expression (according to its injection), producing an
+    /// expression whose type is given by the motive `P`.
+    Case(Box<Exp>, Box<Exp>, Box<Exp>, Box<Exp>),
+    /// Type of the built-in natural numbers, denoted by `ℕ`, which are represented by machine
+    /// integers during reduction.
+    Nat,
+    /// Literal of the [natural numbers][Exp::Nat], denoted by `42n` (as opposed to `42`, which
+    /// denotes a [Church numeral][Exp::new_num]).
+    Lit(usize),
+    /// Successor of a [natural number][Exp::Nat], denoted by `suc M`.
+    Suc(Box<Exp>),
+    /// Sum of two [natural numbers][Exp::Nat], denoted by `add M N`.
+    Add(Box<Exp>, Box<Exp>),
+    /// Product of two [natural numbers][Exp::Nat], denoted by `mul M N`.
+    Mul(Box<Exp>, Box<Exp>),
+    /// Induction on a [natural number][Exp::Nat], denoted by `Nat_rec P Z S M`, which applies
+    /// `S` to the predecessor of `M` and the result of induction on it (or else yields `Z` for
+    /// zero), producing an expression whose type is given by the motive `P`.
+    NatRec(Box<Exp>, Box<Exp>, Box<Exp>, Box<Exp>),
+    /// Record type with the given (distinctly labelled) fields, denoted by `{x : A, y : B}`, whose
+    /// types may not depend on one another.
+    Rec(Vec<(Var, Exp)>),
+    /// Record with the given fields, denoted by `{x = M, y = N}`, whose type is the [record type]
+    /// [Exp::Rec] with the types of its fields.
+    Struct(Vec<(Var, Exp)>),
+    /// Projection of a field out of a [record][Exp::Struct], denoted by `M.x`.
+    Proj(Box<Exp>, Var),
+    /// Universe at the given level, denoted by `Type n`, which is the type of all types at lower
+    /// levels. The universes at levels 0 and 1 are also denoted by `*` and `□` respectively.
+    Sort(usize),
+    /// Placeholder for an expression that is yet to be written, denoted by `_`.
+    Hole,
+    /// Metavariable, which stands for an expression that is yet to be solved by unification.
+    Meta(MetaId),
+    /// Type of a variable bound implicitly, denoted by `{x : A}` in a λ abstraction or Π type,
+    /// whose argument is inserted by [elaboration][Exp::elaborate] rather than written out.
+    Imp(Box<Exp>),
 }
 
 impl Exp {
@@ -57,55 +111,346 @@ impl Exp {
         Self::For(var, Box::new(typ), Box::new(exp))
     }
 
+    /// Create a new instance of an [expression][Exp] that denotes a non-dependent [Π type][Exp::For]
+    /// (written `A → B`), binding a fresh variable that does not occur in the codomain.
+    pub fn new_arr(typ: Exp, exp: Exp) -> Self {
+        let var = (0..)
+            .map(|i| match i {
+                0 => Var::new("_"),
+                _ => Var(format!("_{}", i)),
+            })
+            .find(|var| !exp.has_name(var))
+            .unwrap(); // the sequence of candidates is infinite
+        Self::For(var, Box::new(typ), Box::new(exp))
+    }
+
+    /// Create a new instance of an [expression][Exp] that denotes the Church numeral for `n`, which
+    /// is the polymorphic function that applies a successor function `n` times to zero:
+    ///
+    /// ```text
+    /// λnat : * . λs : nat → nat . λz : nat . s (s (... (s z)))
+    /// ```
+    pub fn new_num(n: usize) -> Self {
+        let nat = Var::new("nat");
+        let typ = || Exp::new_var(nat.clone());
+        let exp = (0..n).fold(Exp::new_var(Var::new("z")), |exp, _| {
+            Exp::new_app(Exp::new_var(Var::new("s")), exp)
+        });
+        let exp = Exp::new_abs(Var::new("z"), typ(), exp);
+        let exp = Exp::new_abs(Var::new("s"), Exp::new_arr(typ(), typ()), exp);
+        Exp::new_abs(nat, Exp::get_type_meta(), exp)
+    }
+
     /// Create a new instance of an [expression][Exp] that denotes an [application][Exp::App].
     pub fn new_app(fst: Exp, snd: Exp) -> Self {
         Self::App(Box::new(fst), Box::new(snd))
     }
 
-    /// Get the type of types (represented by `*`).
+    /// Create a new instance of an [expression][Exp] that denotes a [Σ type][Exp::Sig].
+    pub fn new_sig(var: Var, typ: Exp, mut exp: Exp) -> Self {
+        exp.index(&Idx::new(&var)); // set up de Bruijn indices
+        Self::Sig(var, Box::new(typ), Box::new(exp))
+    }
+
+    /// Create a new instance of an [expression][Exp] that denotes a [dependent pair][Exp::Pair]
+    /// of the given type.
+    pub fn new_pair(fst: Exp, snd: Exp, typ: Exp) -> Self {
+        Self::Pair(Box::new(fst), Box::new(snd), Box::new(typ))
+    }
+
+    /// Create a new instance of an [expression][Exp] that denotes the [first projection][Exp::Fst]
+    /// of a dependent pair.
+    pub fn new_fst(exp: Exp) -> Self {
+        Self::Fst(Box::new(exp))
+    }
+
+    /// Create a new instance of an [expression][Exp] that denotes the [second projection]
+    /// [Exp::Snd] of a dependent pair.
+    pub fn new_snd(exp: Exp) -> Self {
+        Self::Snd(Box::new(exp))
+    }
+
+    /// Create a new instance of an [expression][Exp] that denotes the [elimination][Exp::Absurd]
+    /// of the empty type into the given type.
+    pub fn new_absurd(typ: Exp, exp: Exp) -> Self {
+        Self::Absurd(Box::new(typ), Box::new(exp))
+    }
+
+    /// Create a new instance of an [expression][Exp] that denotes a [sum type][Exp::Sum].
+    pub fn new_sum(fst: Exp, snd: Exp) -> Self {
+        Self::Sum(Box::new(fst), Box::new(snd))
+    }
+
+    /// Create a new instance of an [expression][Exp] that denotes the [left injection][Exp::Inl]
+    /// of an expression, given the right summand.
+    pub fn new_inl(typ: Exp, exp: Exp) -> Self {
+        Self::Inl(Box::new(typ), Box::new(exp))
+    }
+
+    /// Create a new instance of an [expression][Exp] that denotes the [right injection][Exp::Inr]
+    /// of an expression, given the left summand.
+    pub fn new_inr(typ: Exp, exp: Exp) -> Self {
+        Self::Inr(Box::new(typ), Box::new(exp))
+    }
+
+    /// Create a new instance of an [expression][Exp] that denotes a [case analysis][Exp::Case]
+    /// with the given motive and cases.
+    pub fn new_case(mot: Exp, fst: Exp, snd: Exp, exp: Exp) -> Self {
+        Self::Case(Box::new(mot), Box::new(fst), Box::new(snd), Box::new(exp))
+    }
+
+    /// Create a new instance of an [expression][Exp] that denotes the [successor][Exp::Suc] of a
+    /// natural number.
+    pub fn new_suc(exp: Exp) -> Self {
+        Self::Suc(Box::new(exp))
+    }
+
+    /// Create a new instance of an [expression][Exp] that denotes the [sum][Exp::Add] of two
+    /// natural numbers.
+    pub fn new_add(fst: Exp, snd: Exp) -> Self {
+        Self::Add(Box::new(fst), Box::new(snd))
+    }
+
+    /// Create a new instance of an [expression][Exp] that denotes the [product][Exp::Mul] of two
+    /// natural numbers.
+    pub fn new_mul(fst: Exp, snd: Exp) -> Self {
+        Self::Mul(Box::new(fst), Box::new(snd))
+    }
+
+    /// Create a new instance of an [expression][Exp] that denotes [induction][Exp::NatRec] on a
+    /// natural number with the given motive and cases.
+    pub fn new_nat_rec(mot: Exp, zer: Exp, suc: Exp, exp: Exp) -> Self {
+        Self::NatRec(Box::new(mot), Box::new(zer), Box::new(suc), Box::new(exp))
+    }
+
+    /// Create a new instance of an [expression][Exp] that denotes a [record type][Exp::Rec] with
+    /// the given fields.
+    pub fn new_rec(fields: Vec<(Var, Exp)>) -> Self {
+        Self::Rec(fields)
+    }
+
+    /// Create a new instance of an [expression][Exp] that denotes a [record][Exp::Struct] with the
+    /// given fields.
+    pub fn new_struct(fields: Vec<(Var, Exp)>) -> Self {
+        Self::Struct(fields)
+    }
+
+    /// Create a new instance of an [expression][Exp] that denotes the [projection][Exp::Proj] of
+    /// a field out of a record.
+    pub fn new_proj(exp: Exp, var: Var) -> Self {
+        Self::Proj(Box::new(exp), var)
+    }
+
+    /// Parse an expression from the given source text, along with the [span][Span] of source text
+    /// that it (and each of its sub-expressions) was parsed from.
+    pub fn parse_spanned(src: &str) -> Result<(Self, Span), SyntaxErr> {
+        par::parse_spanned(src, &Ctx::new())
+    }
+
+    /// Get the type of types (represented by `*`, the universe at level 0).
     pub fn get_type_meta() -> Self {
-        Self::TypeMeta
+        Self::Sort(0)
     }
 
-    /// Get the type of kinds (aka, universe, represented by `□`).
+    /// Get the type of kinds (represented by `□`, the universe at level 1).
     pub fn get_kind_meta() -> Self {
-        Self::KindMeta
+        Self::Sort(1)
+    }
+
+    /// Create a new instance of an [expression][Exp] that denotes the [universe][Exp::Sort] at the
+    /// given level.
+    pub fn new_sort(lvl: usize) -> Self {
+        Self::Sort(lvl)
+    }
+
+    /// Get a placeholder for an expression that is yet to be written (represented by `_`).
+    pub fn get_hole() -> Self {
+        Self::Hole
     }
 
     /// Index an expression, converting bound variables into respective de Bruijn indices.
     pub fn index(&mut self, idx: &Idx) {
-        if let Exp::Var(varidx) = self {
-            if let VarIdx::Var(var) = varidx {
-                if var == &idx.1 {
-                    *varidx = VarIdx::new_idx(idx.clone())
+        *self = std::mem::take(self).indexed(idx);
+    }
+
+    /// Convert the variables bound by the binder for the given index into de Bruijn indices,
+    /// leaving the types of nested binders (which refer to variables by name) as they are.
+    fn indexed(self, idx: &Idx) -> Self {
+        match self {
+            Exp::Var(VarIdx::Var(var)) if var == idx.1 => Exp::Var(VarIdx::new_idx(idx.clone())),
+            _ => self.map_subterms(|exp, pos| match pos {
+                Pos::Arg => exp.indexed(idx),
+                Pos::Type(_) => exp,
+                Pos::Body(var) if var != &idx.1 => exp.indexed(&idx.inc()),
+                Pos::Body(_) => exp, // short-circuit if binding variable is shadowed
+            }),
+        }
+    }
+
+    /// Check whether this expression depends on the binder for the given variable, which is located
+    /// at the given de Bruijn index, either through that index or (within the types of nested
+    /// binders, which are not indexed) through the name of the variable.
+    pub(super) fn depends_on(&self, var: &Var, lvl: usize) -> bool {
+        match self {
+            Exp::Var(VarIdx::Idx(Idx(i, _))) => *i == lvl,
+            Exp::Var(VarIdx::Var(v)) => v == var,
+            _ => self.any_subterm(|exp, pos| match pos {
+                Pos::Body(_) => exp.depends_on(var, lvl + 1),
+                _ => exp.depends_on(var, lvl),
+            }),
+        }
+    }
+
+    /// Check whether the given name occurs anywhere in this expression, as a variable or binder.
+    pub(super) fn has_name(&self, var: &Var) -> bool {
+        match self {
+            Exp::Var(varidx) => varidx.get_var() == var,
+            _ => self.any_subterm(|exp, pos| {
+                pos == Pos::Type(var) || exp.has_name(var) // as the binder, or within it
+            }),
+        }
+    }
+
+    /// Fetch the number denoted by this expression, if it is a Church numeral (with any names for
+    /// its bound variables).
+    pub(super) fn as_num(&self) -> Option<usize> {
+        if let Exp::Abs(nat, knd, exp) = self {
+            if let Exp::Abs(_, sty, exp) = exp.as_ref() {
+                if let Exp::Abs(_, zty, exp) = exp.as_ref() {
+                    let typ = Exp::new_var(nat.clone());
+                    let arr = matches!(sty.as_ref(), Exp::For(var, dom, cod)
+                        if **dom == typ && **cod == typ && !cod.depends_on(var, 0));
+                    if **knd == Exp::Sort(0) && arr && **zty == typ {
+                        let mut exp = exp.as_ref();
+                        let mut n = 0;
+                        while let Exp::App(fst, snd) = exp {
+                            match fst.as_ref() {
+                                Exp::Var(VarIdx::Idx(Idx(1, _))) => (n, exp) = (n + 1, snd),
+                                _ => return None,
+                            }
+                        }
+                        if let Exp::Var(VarIdx::Idx(Idx(0, _))) = exp {
+                            return Some(n);
+                        }
+                    }
                 }
-            } // update if binding variable matches
-        } else if let Exp::Abs(var, _, exp) = self {
-            if var != &idx.1 {
-                exp.index(&idx.inc());
-            } // short-circuit if binding variable is shadowed
-        } else if let Exp::For(var, _, exp) = self {
-            if var != &idx.1 {
-                exp.index(&idx.inc());
-            } // short-circuit if binding variable is shadowed
-        } else if let Exp::App(fst, snd) = self {
-            fst.index(idx);
-            snd.index(idx);
-        }
-    }
-
-    /// Reduce this expression to beta-normal form, or until the expression remains unchanged upon reduction.
+            }
+        }
+        None
+    }
+
+    /// Check whether this expression is a non-dependent Π type, rendered as an arrow.
+    pub(super) fn is_arrow(&self) -> bool {
+        matches!(self, Exp::For(var, typ, exp) if !typ.is_implicit() && !exp.depends_on(var, 0))
+    }
+
+    /// Check whether this expression is the type of a variable bound implicitly.
+    pub fn is_implicit(&self) -> bool {
+        matches!(self, Exp::Imp(_))
+    }
+
+    /// Fetch the type of a variable bound by a binder, whether it is bound implicitly or not.
+    pub fn explicit(&self) -> &Exp {
+        match self {
+            Exp::Imp(typ) => typ,
+            _ => self,
+        }
+    }
+
+    /// Reduce this expression to normal form (unfolding definitions), performing at most as many
+    /// reduction steps as the [budget][Ctx::get_fuel] of the context permits.
     pub fn reduce(self, ctx: &Ctx) -> Result<Self, TypingErr> {
-        let p = self.clone();
-        let q = self.reduce_once(ctx)?;
-        if p == q {
-            Ok(q)
+        self.reduce_with(ctx, Strategy::Normal, ctx.get_fuel())
+    }
+
+    /// Reduce this expression (unfolding definitions) using the given [strategy][Strategy], by
+    /// repeated reduction until the expression remains unchanged, performing at most the given
+    /// number of reduction steps (each of which contracts a single redex).
+    ///
+    /// Reduction fails if the steps are used up before the expression remains unchanged, as
+    /// happens for expressions that cannot be reduced to normal form (or not within the budget),
+    /// and likewise if the expression grows beyond the size or takes longer than the time permitted
+    /// by the [limits][Ctx::get_limits] of the context.
+    pub fn reduce_with(self, ctx: &Ctx, stg: Strategy, fuel: usize) -> Result<Self, TypingErr> {
+        let mut rem = fuel;
+        let exp = self
+            .clone()
+            .normalize(ctx, stg, &mut rem)
+            .map_err(|e| self.grown(e))?;
+        tracing::trace!(exp = %self, red = %exp, ?stg, steps = fuel - rem, "reduced");
+        if exp.clone().reduce_once(ctx, stg, &mut 1)? != exp {
+            Err(TypingErr::from(ReductionBudgetErr::new(&self, fuel))) // another step remains
         } else {
-            q.reduce_once(ctx)
+            Ok(exp)
         }
     }
 
-    /// Calculate the normalized type of this expression.
+    /// Reduce this expression in the same way as [Exp::reduce_with] does, and gather the
+    /// [statistics][Stats] of the reduction along the way.
+    pub fn reduce_stats(
+        self,
+        ctx: &Ctx,
+        stg: Strategy,
+        fuel: usize,
+    ) -> Result<(Self, Stats), TypingErr> {
+        let mut rem = fuel;
+        let mut exp = self.clone();
+        let mut peak = exp.size();
+        loop {
+            let red = exp
+                .clone()
+                .reduce_once(ctx, stg, &mut rem)
+                .map_err(|e| self.grown(e))?;
+            if red == exp {
+                break; // reached a fixed point (or ran out of fuel)
+            }
+            peak = peak.max(red.size());
+            if let Some(max) = ctx.get_limits().max.filter(|max| peak > *max) {
+                return Err(TypingErr::from(ReductionSizeErr::new(&self, peak, max)));
+            }
+            exp = red;
+        }
+        if exp.clone().reduce_once(ctx, stg, &mut 1)? != exp {
+            return Err(TypingErr::from(ReductionBudgetErr::new(&self, fuel))); // another step
+        }
+        let steps = fuel - rem;
+        Ok((exp, Stats { steps, peak }))
+    }
+
+    /// Report the given error of the reduction of this expression against the expression as a
+    /// whole, if it is about the size that a part of the expression grew to.
+    fn grown(&self, e: TypingErr) -> TypingErr {
+        match e {
+            TypingErr::ReductionSizeErr(e) => ReductionSizeErr::new(self, e.size, e.max).into(),
+            e => e,
+        }
+    }
+
+    /// Reduce this expression using the given strategy, consuming the given fuel with every step.
+    pub(super) fn normalize(
+        self,
+        ctx: &Ctx,
+        stg: Strategy,
+        fuel: &mut usize,
+    ) -> Result<Self, TypingErr> {
+        let mut exp = self;
+        loop {
+            let red = exp.clone().reduce_once(ctx, stg, fuel)?;
+            if red == exp {
+                return Ok(red); // reached a fixed point (or ran out of fuel)
+            }
+            if let Some(max) = ctx.get_limits().max {
+                let size = red.size();
+                if size > max {
+                    return Err(TypingErr::from(ReductionSizeErr::new(&exp, size, max)));
+                }
+            }
+            exp = red;
+        }
+    }
+
+    /// Infer the normalized type of this expression from the expression alone, as opposed to
+    /// [checking][Exp::check] it against a type that is known in advance.
     ///
     /// Types are calculated and checked using the following typing rules:
     ///
@@ -114,9 +459,11 @@ impl Exp {
     /// ```text
     ///
     /// ─────────────────────────────────────────────────────────────
-    ///                         ϕ ⊢ * : □
+    ///                    ϕ ⊢ Type i : Type (i+1)
     /// ```
     ///
+    /// ...where `Type 0` is also written `*`, and `Type 1` is also written `□`.
+    ///
     /// ## VAR RULE
     ///
     /// ```text
@@ -125,7 +472,7 @@ impl Exp {
     ///                      Γ, x : A ⊢ x : A
     /// ```
     ///
-    /// ...where `s ∈ {*, □}`.
+    /// ...where `s` is a universe `Type i`.
     ///
     /// ## WEAK RULE
     ///
@@ -135,17 +482,18 @@ impl Exp {
     ///                      Γ, x : C ⊢ A : B
     /// ```
     ///
-    /// ...where `s ∈ {*, □}`.
+    /// ...where `s` is a universe `Type i`.
     ///
     /// ## FORM RULE
     ///
     /// ```text
-    ///              Γ ⊢ A : s1          Γ, x : A ⊢ B : s2
+    ///           Γ ⊢ A : Type i          Γ, x : A ⊢ B : Type j
     /// ─────────────────────────────────────────────────────────────
-    ///                     Γ ⊢ Πx : A . B : s2
+    ///                Γ ⊢ Πx : A . B : Type (max i j)
     /// ```
     ///
-    /// ...where `s1, s2 ∈ {*, □}`.
+    /// ...so that the hierarchy of universes is predicative: a type that quantifies over a
+    /// universe lives in the universe above it.
     ///
     /// ## APPL RULE
     ///
@@ -163,203 +511,1091 @@ impl Exp {
     ///                 Γ ⊢ λx : A . M : Πx : A . B
     /// ```
     ///
-    /// ...where `s ∈ {*, □}`.
+    /// ...where `s` is a universe `Type i`.
     ///
     /// ## CONV RULE
     ///
     /// ```text
     ///                Γ ⊢ A : B          Γ ⊢ B' : s
-    /// ─────────────────────────────────────────────────────────────    if B =ᵦ B'
+    /// ─────────────────────────────────────────────────────────────    if B =ᵦδ B'
     ///                         Γ ⊢ A : B'
     /// ```
     ///
-    /// ...where `s ∈ {*, □}`.
+    /// ...where `s` is a universe `Type i`, and types are compared after unfolding the variables
+    /// that are [defined][Ctx::define] in the context (delta-reduction).
     ///
-    pub fn calculate_type(&self, ctx: &Ctx) -> Result<Exp, TypingErr> {
+    /// ## CUMUL RULE
+    ///
+    /// ```text
+    ///                Γ ⊢ A : Type i
+    /// ─────────────────────────────────────────────────────────────    if i ≤ j
+    ///                Γ ⊢ A : Type j
+    /// ```
+    ///
+    /// ...which also applies to the codomain of a Π type, so that `Πx : A . Type i` is contained
+    /// in `Πx : A . Type j`.
+    ///
+    /// ## SIGMA RULE
+    ///
+    /// ```text
+    ///           Γ ⊢ A : Type i          Γ, x : A ⊢ B : Type j
+    /// ─────────────────────────────────────────────────────────────
+    ///                Γ ⊢ Σx : A . B : Type (max i j)
+    /// ```
+    ///
+    /// ## PAIR RULE
+    ///
+    /// ```text
+    ///   Γ ⊢ Σx : A . B : s       Γ ⊢ M : A       Γ ⊢ N : B [x := M]
+    /// ─────────────────────────────────────────────────────────────
+    ///              Γ ⊢ (M, N : Σx : A . B) : Σx : A . B
+    /// ```
+    ///
+    /// ## PROJ RULES
+    ///
+    /// ```text
+    ///                    Γ ⊢ M : Σx : A . B
+    /// ─────────────────────────────────────────────────────────────
+    ///                      Γ ⊢ fst M : A
+    /// ```
+    ///
+    /// ```text
+    ///                    Γ ⊢ M : Σx : A . B
+    /// ─────────────────────────────────────────────────────────────
+    ///                Γ ⊢ snd M : B [x := fst M]
+    /// ```
+    ///
+    /// ## UNIT RULES
+    ///
+    /// ```text
+    ///
+    /// ─────────────────────────────────────────────────────────────
+    ///                  Γ ⊢ ⊤ : *          Γ ⊢ () : ⊤
+    /// ```
+    ///
+    /// ## VOID RULES
+    ///
+    /// ```text
+    ///
+    /// ─────────────────────────────────────────────────────────────
+    ///                          Γ ⊢ ⊥ : *
+    /// ```
+    ///
+    /// ```text
+    ///                Γ ⊢ A : Type i          Γ ⊢ M : ⊥
+    /// ─────────────────────────────────────────────────────────────
+    ///                       Γ ⊢ absurd A M : A
+    /// ```
+    ///
+    /// ## SUM RULES
+    ///
+    /// ```text
+    ///                Γ ⊢ A : Type i          Γ ⊢ B : Type j
+    /// ─────────────────────────────────────────────────────────────
+    ///                  Γ ⊢ Sum A B : Type (max i j)
+    /// ```
+    ///
+    /// ```text
+    ///                Γ ⊢ M : A          Γ ⊢ B : Type j
+    /// ─────────────────────────────────────────────────────────────
+    ///                    Γ ⊢ inl B M : Sum A B
+    /// ```
+    ///
+    /// ```text
+    ///                Γ ⊢ A : Type i          Γ ⊢ M : B
+    /// ─────────────────────────────────────────────────────────────
+    ///                    Γ ⊢ inr A M : Sum A B
+    /// ```
+    ///
+    /// ```text
+    ///   Γ ⊢ M : Sum A B    Γ ⊢ P : Sum A B → Type i    Γ ⊢ F : Πx : A . P (inl B x)
+    ///                      Γ ⊢ G : Πy : B . P (inr A y)
+    /// ─────────────────────────────────────────────────────────────
+    ///                    Γ ⊢ case P F G M : P M
+    /// ```
+    ///
+    /// ## NAT RULES
+    ///
+    /// ```text
+    ///
+    /// ─────────────────────────────────────────────────────────────
+    ///                  Γ ⊢ ℕ : *          Γ ⊢ n : ℕ
+    /// ```
+    ///
+    /// ...where `n` is a literal, such as `42n`.
+    ///
+    /// ```text
+    ///                Γ ⊢ M : ℕ          Γ ⊢ N : ℕ
+    /// ─────────────────────────────────────────────────────────────
+    ///     Γ ⊢ suc M : ℕ          Γ ⊢ add M N : ℕ          Γ ⊢ mul M N : ℕ
+    /// ```
+    ///
+    /// ```text
+    ///   Γ ⊢ M : ℕ    Γ ⊢ P : ℕ → Type i    Γ ⊢ Z : P 0n    Γ ⊢ S : Πx : ℕ . P x → P (suc x)
+    /// ─────────────────────────────────────────────────────────────
+    ///                    Γ ⊢ Nat_rec P Z S M : P M
+    /// ```
+    ///
+    /// ## RECORD RULES
+    ///
+    /// ```text
+    ///          Γ ⊢ A₁ : Type i₁          …          Γ ⊢ Aₙ : Type iₙ
+    /// ─────────────────────────────────────────────────────────────    if x₁ … xₙ are distinct
+    ///          Γ ⊢ {x₁ : A₁, …, xₙ : Aₙ} : Type (max i₁ … iₙ)
+    /// ```
+    ///
+    /// ```text
+    ///                Γ ⊢ M₁ : A₁          …          Γ ⊢ Mₙ : Aₙ
+    /// ─────────────────────────────────────────────────────────────    if x₁ … xₙ are distinct
+    ///          Γ ⊢ {x₁ = M₁, …, xₙ = Mₙ} : {x₁ : A₁, …, xₙ : Aₙ}
+    /// ```
+    ///
+    /// ```text
+    ///                Γ ⊢ M : {…, x : A, …}
+    /// ─────────────────────────────────────────────────────────────
+    ///                      Γ ⊢ M.x : A
+    /// ```
+    ///
+    /// ## IMPLICIT BINDERS
+    ///
+    /// A variable bound implicitly (`{x : A}`) has type `A`, as though it were bound explicitly,
+    /// so that `λ{x : A} . M` has type `Π{x : A} . B`, and its arguments are checked in the same
+    /// way; they are merely inserted by [elaboration][Exp::elaborate] rather than written out.
+    ///
+    /// ## HOLES
+    ///
+    /// A [hole][Exp::Hole] has no type of its own, so type inference fails upon encountering
+    /// one, reporting the type(s) that an expression filling the hole is expected to have (where
+    /// this is known from the surrounding expression). The same holds for a λ abstraction whose
+    /// bound variable is not annotated, which can only be checked.
+    ///
+    pub fn infer(&self, ctx: &Ctx) -> Result<Exp, TypingErr> {
+        tracing::trace!(exp = %self, "inferring type");
+        // each rule is inferred by a function of its own, so that the frame of this function (of
+        // which there is one for every level of a nested expression) stays small
+        match self {
+            Exp::Var(varidx) => Exp::infer_var(varidx.get_var(), ctx), // VAR RULE
+            Exp::Abs(var, typ, exp) => self.infer_abs(var, typ, exp, ctx), // ABST RULE
+            Exp::For(var, typ, exp) | Exp::Sig(var, typ, exp) => {
+                self.infer_form(var, typ, exp, ctx)
+            } // FORM RULE, SIGMA RULE
+            Exp::App(fst, snd) => self.infer_app(fst, snd, ctx),       // APPL RULE
+            Exp::Pair(fst, snd, typ) => self.infer_pair(fst, snd, typ, ctx), // PAIR RULE
+            Exp::Fst(exp) | Exp::Snd(exp) => self.infer_proj(exp, ctx), // PROJ RULE
+            Exp::Sort(lvl) => match lvl.checked_add(1) {
+                Some(lvl) => Ok(Exp::Sort(lvl)),
+                None => Err(TypingErr::from(TypeUndefErr::new(self))), // no universe above
+            }, // SORT RULE
+            Exp::Hole => Err(TypingErr::from(TypeHoleErr::new(&[]))),  // expected type unknown
+            Exp::Meta(id) => match (ctx.get_meta(*id), ctx.get_meta_type(*id)) {
+                (Some(sol), _) => sol.infer(ctx),
+                (None, Some(typ)) => Ok(typ),
+                (None, None) => Err(TypingErr::from(TypeUndefErr::new(self))),
+            }, // META RULE
+            Exp::Imp(typ) => typ.infer(ctx),
+            Exp::Unit | Exp::Void => Ok(Exp::get_type_meta()), // UNIT RULE, VOID RULE
+            Exp::Triv => Ok(Exp::Unit),                        // UNIT RULE
+            Exp::Absurd(typ, exp) => self.infer_absurd(typ, exp, ctx), // VOID RULE
+            Exp::Sum(..) | Exp::Inl(..) | Exp::Inr(..) => self.infer_sum(ctx), // SUM RULE
+            Exp::Case(mot, fst, snd, exp) => self.infer_case(mot, fst, snd, exp, ctx), // SUM RULE
+            Exp::Nat => Ok(Exp::get_type_meta()),              // NAT RULE
+            Exp::Lit(_) => Ok(Exp::Nat),                       // NAT RULE
+            Exp::Suc(..) | Exp::Add(..) | Exp::Mul(..) => self.infer_arith(ctx), // NAT RULE
+            Exp::NatRec(mot, zer, suc, exp) => self.infer_nat_rec(mot, zer, suc, exp, ctx), // NAT RULE
+            Exp::Rec(..) | Exp::Struct(..) | Exp::Proj(..) => self.infer_rec(ctx), // RECORD RULE
+        }
+    }
+
+    /// Infer the type of a [variable][Exp::Var], which is the type it is bound to in the context.
+    fn infer_var(var: &Var, ctx: &Ctx) -> Result<Exp, TypingErr> {
+        let typ = ctx.get_formed(var)?.clone();
+        typ.reduce(ctx)
+    }
+
+    /// Infer the type of this [λ abstraction][Exp::Abs] from the type of its body.
+    fn infer_abs(&self, var: &Var, typ: &Exp, exp: &Exp, ctx: &Ctx) -> Result<Exp, TypingErr> {
+        typ.check_hole(&[&Exp::Sort(0), &Exp::Sort(1)])?;
+        let cod =
+            (exp.infer(&ctx.extend(var, typ))).map_err(|e| e.within("inferring the body", self))?;
+        let can = Exp::For(var.clone(), Box::new(typ.clone()), Box::new(cod));
+        can.calculate_level(ctx)?;
+        Ok(can)
+    }
+
+    /// Infer the universe of this [Π type][Exp::For] (or [Σ type][Exp::Sig]) from the levels of
+    /// its domain and codomain.
+    fn infer_form(&self, var: &Var, typ: &Exp, exp: &Exp, ctx: &Ctx) -> Result<Exp, TypingErr> {
+        typ.check_hole(&[&Exp::Sort(0), &Exp::Sort(1)])?;
+        let lvl = (exp.calculate_level(&ctx.extend(var, typ)))
+            .map_err(|e| e.within("checking the codomain", self))?;
+        let dom = (typ.calculate_level(ctx)).map_err(|e| e.within("checking the domain", self))?;
+        Ok(Exp::Sort(dom.max(lvl)))
+    }
+
+    /// Infer the type of this [application][Exp::App] from the type of the function, against
+    /// whose domain the argument is checked.
+    fn infer_app(&self, fst: &Exp, snd: &Exp, ctx: &Ctx) -> Result<Exp, TypingErr> {
+        let fty = (fst.infer(ctx)).map_err(|e| e.within("inferring the function", self))?;
+        if let Exp::For(var, typ, exp) = &fty {
+            (snd.check(typ.explicit(), ctx))
+                .map_err(|e| e.within("checking the argument", self))?;
+            let cod = exp.as_ref().clone().subst(&Idx::new(var), snd);
+            Ok((cod.reduce(ctx)).map_err(|e| e.within("normalizing the codomain", &fty))?)
+        } else {
+            let sty = (snd.infer(ctx)).map_err(|e| e.within("inferring the argument", self))?;
+            Err(TypingErr::from(TypeCompatErr::new(snd, &sty, &[])))
+        }
+    }
+
+    /// Infer the type of this [dependent pair][Exp::Pair], which is the Σ type it is annotated
+    /// with, against which its components are checked.
+    fn infer_pair(&self, fst: &Exp, snd: &Exp, typ: &Exp, ctx: &Ctx) -> Result<Exp, TypingErr> {
+        typ.check_hole(&[&Exp::Sort(0), &Exp::Sort(1)])?;
+        let knd = (typ.infer(ctx)).map_err(|e| e.within("inferring the type", self))?;
+        if let Exp::Sig(var, dom, cod) = typ {
+            (fst.check(dom, ctx)).map_err(|e| e.within("checking the first component", self))?;
+            let cod = (cod.as_ref().clone().subst(&Idx::new(var), fst).reduce(ctx))
+                .map_err(|e| e.within("normalizing the codomain", typ))?;
+            (snd.check(&cod, ctx)).map_err(|e| e.within("checking the second component", self))?;
+            Ok(typ.clone())
+        } else {
+            Err(TypingErr::from(TypeCompatErr::new(typ, &knd, &[])))
+        }
+    }
+
+    /// Infer the type of this [projection][Exp::Fst] (or [second projection][Exp::Snd]) of the
+    /// given pair from the Σ type of the pair.
+    fn infer_proj(&self, exp: &Exp, ctx: &Ctx) -> Result<Exp, TypingErr> {
+        let act = (exp.infer(ctx)).map_err(|e| e.within("inferring the pair", self))?;
+        match (self, &act) {
+            (Exp::Fst(_), Exp::Sig(_, dom, _)) => Ok(*dom.clone()),
+            (Exp::Snd(_), Exp::Sig(var, _, cod)) => {
+                let fst = Exp::Fst(Box::new(exp.clone()));
+                let cod = cod.as_ref().clone().subst(&Idx::new(var), &fst);
+                Ok((cod.reduce(ctx)).map_err(|e| e.within("normalizing the codomain", &act))?)
+            }
+            _ => Err(TypingErr::from(TypeCompatErr::new(exp, &act, &[]))),
+        }
+    }
+
+    /// Infer the type of this [elimination][Exp::Absurd] of the empty type, which is the type that
+    /// it is annotated with.
+    fn infer_absurd(&self, typ: &Exp, exp: &Exp, ctx: &Ctx) -> Result<Exp, TypingErr> {
+        (typ.calculate_level(ctx)).map_err(|e| e.within("checking the type", self))?;
+        (exp.check(&Exp::Void, ctx)).map_err(|e| e.within("checking the operand", self))?;
+        typ.clone().reduce(ctx)
+    }
+
+    /// Infer the type of this [sum type][Exp::Sum] or [injection][Exp::Inl] into one.
+    fn infer_sum(&self, ctx: &Ctx) -> Result<Exp, TypingErr> {
+        let level = |typ: &Exp, step| typ.calculate_level(ctx).map_err(|e| e.within(step, self));
+        let infer =
+            |exp: &Exp| (exp.infer(ctx)).map_err(|e| e.within("inferring the operand", self));
+        match self {
+            Exp::Sum(fst, snd) => {
+                let lft = level(fst, "checking the left summand")?;
+                let rgt = level(snd, "checking the right summand")?;
+                Ok(Exp::Sort(lft.max(rgt)))
+            }
+            Exp::Inl(typ, exp) => {
+                level(typ, "checking the other summand")?;
+                Ok(Exp::new_sum(infer(exp)?, typ.as_ref().clone()).reduce(ctx)?)
+            }
+            Exp::Inr(typ, exp) => {
+                level(typ, "checking the other summand")?;
+                Ok(Exp::new_sum(typ.as_ref().clone(), infer(exp)?).reduce(ctx)?)
+            }
+            _ => unreachable!("not a sum type or injection"),
+        }
+    }
+
+    /// Infer the type of this [case analysis][Exp::Case], which is given by its motive, against
+    /// which both of its cases are checked.
+    fn infer_case(
+        &self,
+        mot: &Exp,
+        fst: &Exp,
+        snd: &Exp,
+        exp: &Exp,
+        ctx: &Ctx,
+    ) -> Result<Exp, TypingErr> {
+        let sum = (exp.infer(ctx)).map_err(|e| e.within("inferring the operand", self))?;
+        let (dom, cod) = match &sum {
+            Exp::Sum(dom, cod) => (dom.as_ref(), cod.as_ref()),
+            _ => return Err(TypingErr::from(TypeCompatErr::new(exp, &sum, &[]))),
+        };
+        let knd = (mot.infer(ctx)).map_err(|e| e.within("inferring the motive", self))?;
+        match &knd {
+            Exp::For(_, arg, srt)
+                if Exp::conv(arg, &sum, ctx) && matches!(srt.as_ref(), Exp::Sort(_)) => {}
+            _ => return Err(TypingErr::from(TypeCompatErr::new(mot, &knd, &[]))),
+        }
+        (fst.check(&Exp::branch(mot, dom, cod, Exp::new_inl), ctx))
+            .map_err(|e| e.within("checking the left case", self))?;
+        (snd.check(&Exp::branch(mot, cod, dom, Exp::new_inr), ctx))
+            .map_err(|e| e.within("checking the right case", self))?;
+        Exp::new_app(mot.clone(), exp.clone()).reduce(ctx)
+    }
+
+    /// Infer the type of this [successor][Exp::Suc], [sum][Exp::Add] or [product][Exp::Mul] of
+    /// natural numbers, whose operands are checked to be natural numbers.
+    fn infer_arith(&self, ctx: &Ctx) -> Result<Exp, TypingErr> {
+        let check = |exp: &Exp, step| (exp.check(&Exp::Nat, ctx)).map_err(|e| e.within(step, self));
+        match self {
+            Exp::Suc(exp) => check(exp, "checking the operand")?,
+            Exp::Add(fst, snd) | Exp::Mul(fst, snd) => {
+                check(fst, "checking the left operand")?;
+                check(snd, "checking the right operand")?;
+            }
+            _ => unreachable!("not an arithmetic operation"),
+        }
+        Ok(Exp::Nat)
+    }
+
+    /// Infer the type of this [induction][Exp::NatRec] on a natural number, which is given by its
+    /// motive, against which its base case and inductive step are checked.
+    fn infer_nat_rec(
+        &self,
+        mot: &Exp,
+        zer: &Exp,
+        suc: &Exp,
+        exp: &Exp,
+        ctx: &Ctx,
+    ) -> Result<Exp, TypingErr> {
+        (exp.check(&Exp::Nat, ctx)).map_err(|e| e.within("checking the operand", self))?;
+        let knd = (mot.infer(ctx)).map_err(|e| e.within("inferring the motive", self))?;
+        match &knd {
+            Exp::For(_, arg, srt)
+                if Exp::conv(arg, &Exp::Nat, ctx) && matches!(srt.as_ref(), Exp::Sort(_)) => {}
+            _ => return Err(TypingErr::from(TypeCompatErr::new(mot, &knd, &[]))),
+        }
+        let typ = Exp::new_app(mot.clone(), Exp::Lit(0));
+        (zer.check(&typ, ctx)).map_err(|e| e.within("checking the base case", self))?;
+        (suc.check(&Exp::step(mot), ctx))
+            .map_err(|e| e.within("checking the inductive step", self))?;
+        Exp::new_app(mot.clone(), exp.clone()).reduce(ctx)
+    }
+
+    /// Infer the type of this [record type][Exp::Rec], [record][Exp::Struct] or [projection]
+    /// [Exp::Proj] of a field out of a record.
+    fn infer_rec(&self, ctx: &Ctx) -> Result<Exp, TypingErr> {
         match self {
-            Exp::Var(varidx) => {
-                let var = varidx.get_var();
-                let typ = ctx.get(var)?.clone();
-                typ.validate_type(&[&Exp::TypeMeta, &Exp::KindMeta], &ctx.subtract(var)?)?;
-                Ok(typ.reduce(ctx)?)
-            } // VAR RULE
-            Exp::Abs(var, typ, exp) => {
-                let can = Exp::For(
-                    var.clone(),
-                    Box::new(*typ.clone()),
-                    Box::new(exp.calculate_type(&ctx.extend(var, typ)?)?),
-                );
-                can.validate_type(&[&Exp::TypeMeta, &Exp::KindMeta], ctx)?;
-                Ok(can)
-            } // ABST RULE
-            Exp::For(var, typ, exp) => {
-                let can = exp.calculate_type(&ctx.extend(var, typ)?)?;
-                typ.validate_type(&[&Exp::TypeMeta, &Exp::KindMeta], ctx)?;
-                Ok(can)
-            } // FORM RULE
-            Exp::App(fst, snd) => {
-                let fty = fst.calculate_type(ctx)?;
-                let sty = snd.calculate_type(ctx)?;
-                if let Exp::For(var, typ, exp) = fty {
-                    snd.validate_type(&[&typ], ctx)?;
-                    Ok(exp.subst(&Idx::new(&var), snd).reduce(ctx)?)
-                } else {
-                    Err(TypingErr::from(TypeCompatErr::new(snd, &sty, &[])))
+            Exp::Rec(fields) => {
+                Exp::check_fields(self, fields)?;
+                let mut lvl = 0;
+                for (_, typ) in fields {
+                    let fld = (typ.calculate_level(ctx))
+                        .map_err(|e| e.within("checking the type of a field", self))?;
+                    lvl = lvl.max(fld);
+                }
+                Ok(Exp::Sort(lvl))
+            }
+            Exp::Struct(fields) => {
+                Exp::check_fields(self, fields)?;
+                let typ = fields
+                    .iter()
+                    .map(|(var, exp)| {
+                        let typ =
+                            (exp.infer(ctx)).map_err(|e| e.within("inferring a field", self))?;
+                        Ok((var.clone(), typ))
+                    })
+                    .collect::<Result<_, TypingErr>>()?;
+                Ok(Exp::Rec(typ))
+            }
+            Exp::Proj(exp, var) => {
+                let act = (exp.infer(ctx)).map_err(|e| e.within("inferring the record", self))?;
+                match &act {
+                    Exp::Rec(fields) => match fields.iter().find(|(v, _)| v == var) {
+                        Some((_, typ)) => Ok(typ.clone()),
+                        None => Err(TypingErr::from(TypeFieldErr::new(exp, &act, var))),
+                    },
+                    _ => Err(TypingErr::from(TypeCompatErr::new(exp, &act, &[]))),
                 }
-            } // APPL RULE
-            Exp::TypeMeta => Ok(Exp::KindMeta), // SORT RULE
-            Exp::KindMeta => Err(TypingErr::from(TypeUndefErr::new(self))), // not permitted
+            }
+            _ => unreachable!("not a record type, record or projection"),
         }
     }
 
-    /// Check that the type of this expression matches the given type.
-    fn validate_type(&self, typ: &[&Exp], ctx: &Ctx) -> Result<(), TypingErr> {
-        let act = &self.calculate_type(ctx)?;
+    /// Check that the fields of a record (or record type) are labelled distinctly.
+    fn check_fields(&self, fields: &[(Var, Exp)]) -> Result<(), TypingErr> {
+        for (i, (var, val)) in fields.iter().enumerate() {
+            if fields[..i].iter().any(|(v, _)| v == var) {
+                return Err(TypingErr::from(TypeDupFieldErr::new(self, var, val)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Build the type `Πx : A . P (c B x)` of a case in a [case analysis][Exp::Case] with the
+    /// motive `P`, for the summand `A` that is injected by `c` (given the other summand `B`).
+    fn branch(mot: &Exp, typ: &Exp, oth: &Exp, inj: fn(Exp, Exp) -> Exp) -> Exp {
+        let var = std::iter::successors(Some(Var::new("x")), |var| Some(Var(format!("{}'", var))))
+            .find(|var| !mot.mentions(var, 0) && !oth.mentions(var, 0))
+            .unwrap(); // the sequence of candidates is infinite
+        let exp = Exp::new_app(mot.clone(), inj(oth.clone(), Exp::new_var(var.clone())));
+        Exp::new_for(var, typ.clone(), exp)
+    }
+
+    /// Build the type `Πx : ℕ . P x → P (suc x)` of the inductive step in [induction]
+    /// [Exp::NatRec] on a natural number with the motive `P`.
+    fn step(mot: &Exp) -> Exp {
+        let var = std::iter::successors(Some(Var::new("x")), |var| Some(Var(format!("{}'", var))))
+            .find(|var| !mot.mentions(var, 0))
+            .unwrap(); // the sequence of candidates is infinite
+        let app = |exp: Exp| Exp::new_app(mot.clone(), exp);
+        let exp = Exp::new_var(var.clone());
+        let arr = Exp::new_arr(app(exp.clone()), app(Exp::new_suc(exp)));
+        Exp::new_for(var, Exp::Nat, arr)
+    }
+
+    /// Check that this expression has the given type, which is known in advance rather than
+    /// [inferred][Exp::infer], so that mismatches are reported for the sub-expression in which
+    /// they occur.
+    ///
+    /// A λ abstraction is checked against a Π type by checking its body against the codomain,
+    /// and a bound variable that is not annotated (`λx . M`, which leaves a [hole][Exp::Hole] in
+    /// place of its type) takes the domain as its type:
+    ///
+    /// ## ABST RULE (CHECKED)
+    ///
+    /// ```text
+    ///                      Γ, x : A ⊢ M ⇐ B
+    /// ─────────────────────────────────────────────────────────────
+    ///                    Γ ⊢ λx . M ⇐ Πx : A . B
+    /// ```
+    ///
+    /// The type of any other expression is inferred, and must then match the given type, up to
+    /// the unfolding of definitions, beta-reduction and cumulativity of universes.
+    pub fn check(&self, typ: &Exp, ctx: &Ctx) -> Result<(), TypingErr> {
+        tracing::trace!(exp = %self, %typ, "checking type");
+        self.check_hole(&[typ])?;
+        if let Exp::Abs(var, dom, exp) = self {
+            if let Exp::For(arg, exp_dom, cod) = typ.clone().reduce(ctx)? {
+                let fits = match dom.as_ref() {
+                    Exp::Hole => true,
+                    dom => {
+                        dom.is_implicit() == exp_dom.is_implicit()
+                            && Exp::conv(dom.explicit(), exp_dom.explicit(), ctx)
+                    }
+                };
+                if fits {
+                    let cod = cod.subst(&Idx::new(&arg), &Exp::new_var(var.clone()));
+                    return (exp.check(&cod, &ctx.extend(var, &exp_dom)))
+                        .map_err(|e| e.within("checking the body", self)); // ABST RULE (CHECKED)
+                }
+            }
+        }
+        self.validate_type(&[typ], ctx)
+    }
+
+    /// Check that this expression is not a hole, which would be expected to have the given type.
+    fn check_hole(&self, typ: &[&Exp]) -> Result<(), TypingErr> {
+        if let Exp::Hole = self {
+            Err(TypingErr::from(TypeHoleErr::new(typ)))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check whether the first type is contained in the second, up to the unfolding of
+    /// definitions and beta-reduction (and the names of bound variables).
+    fn conv(fst: &Exp, snd: &Exp, ctx: &Ctx) -> bool {
+        match (fst.clone().reduce(ctx), snd.clone().reduce(ctx)) {
+            (Ok(fst), Ok(snd)) => {
+                let mut arn = ctx.arena(); // shared with the rest of the session
+                let (fst, snd) = (arn.intern(fst), arn.intern(snd));
+                arn.is_subtype(fst, snd)
+            }
+            _ => false,
+        }
+    }
+
+    /// Check whether two expressions are equal up to the names of bound variables, as decided by
+    /// [interning][Arena] both, so that any sub-expressions they share are compared at once.
+    fn alpha_eq(&self, other: &Exp) -> bool {
+        let mut arn = Arena::new();
+        let (fst, snd) = (arn.intern(self.clone()), arn.intern(other.clone()));
+        arn.alpha_eq(fst, snd)
+    }
+
+    /// Find the first pair of corresponding sub-expressions (from left to right) at which this
+    /// expression differs from the other, up to the names of bound variables and eta-conversion
+    /// (so that `λx : A . f x` is taken to be the same as `f`), or nothing if they are the same.
+    ///
+    /// Both expressions are expected to be reduced already, since they are compared as they are.
+    pub fn diverge(&self, other: &Exp) -> Option<(Exp, Exp)> {
+        let eta = |var: &Var, exp: &Exp| {
+            let arg = Exp::Var(VarIdx::Idx(Idx(0, var.clone())));
+            Exp::new_app(exp.clone().shift(1, 0), arg)
+        }; // expand the expression by applying it to the variable bound by the abstraction
+        match (self, other) {
+            (Exp::Abs(var, _, exp), oth) if !matches!(oth, Exp::Abs(..)) => {
+                exp.diverge(&eta(var, oth))
+            }
+            (oth, Exp::Abs(var, _, exp)) if !matches!(oth, Exp::Abs(..)) => {
+                eta(var, oth).diverge(exp)
+            }
+            _ if self.alpha_eq(other) => None,
+            (Exp::Proj(_, v), Exp::Proj(_, w)) if v != w => Some((self.clone(), other.clone())),
+            (Exp::Rec(fs), Exp::Rec(gs)) | (Exp::Struct(fs), Exp::Struct(gs))
+                if fs.iter().map(|(v, _)| v).ne(gs.iter().map(|(w, _)| w)) =>
+            {
+                Some((self.clone(), other.clone()))
+            }
+            _ if std::mem::discriminant(self) == std::mem::discriminant(other) => {
+                let (subs, oths) = (self.subterms(), other.subterms());
+                let div = (subs.len() == oths.len())
+                    .then(|| {
+                        subs.iter()
+                            .zip(oths)
+                            .find_map(|(sub, oth)| sub.diverge(oth))
+                    })
+                    .flatten();
+                div.or_else(|| Some((self.clone(), other.clone()))) // differ in the node itself
+            }
+            _ => Some((self.clone(), other.clone())),
+        }
+    }
+
+    /// Calculate the universe level of this expression, which must be a type (that is, its own
+    /// type must be a universe).
+    pub(super) fn calculate_level(&self, ctx: &Ctx) -> Result<usize, TypingErr> {
+        match self.infer(ctx)? {
+            Exp::Sort(lvl) => Ok(lvl),
+            act => match act.clone().reduce(ctx)? {
+                Exp::Sort(lvl) => Ok(lvl),
+                _ => Err(TypingErr::from(TypeSortErr::new(self, &act))),
+            },
+        }
+    }
+
+    /// Check whether this type is contained in the other, so that every expression of this type
+    /// also has the other type, by virtue of the universes being cumulative (`Type i` is contained
+    /// in `Type j` for `i ≤ j`, including in the codomain of a Π type).
+    fn is_subtype(&self, other: &Exp) -> bool {
+        let mut arn = Arena::new();
+        let (fst, snd) = (arn.intern(self.clone()), arn.intern(other.clone()));
+        arn.is_subtype(fst, snd)
+    }
+
+    /// Check that the type of this expression matches (one of) the given type(s), up to the
+    /// unfolding of definitions, beta-reduction and cumulativity of universes.
+    pub(super) fn validate_type(&self, typ: &[&Exp], ctx: &Ctx) -> Result<(), TypingErr> {
+        let act = &self.infer(ctx)?;
         for t in typ {
-            if act == *t {
+            if act.is_subtype(t) || Exp::conv(act, t, ctx) {
                 return Ok(());
             }
         }
+        for t in typ {
+            if act.has_metas() || t.has_metas() {
+                match unify(act, t, ctx) {
+                    Ok(()) => return Ok(()),
+                    Err(err @ TypingErr::TypeOccursErr(_)) => return Err(err), // never solvable
+                    Err(_) => {}
+                }
+            }
+        }
         Err(TypingErr::from(TypeCompatErr::new(self, act, typ)))
     }
 
-    /// Perform a one-step beta-reduction on this expression.
-    fn reduce_once(self, ctx: &Ctx) -> Result<Self, TypingErr> {
-        self.calculate_type(ctx)?;
-        if let Exp::Abs(var, typ, exp) = self {
-            return Ok(Exp::Abs(
-                var,
-                Box::new(typ.reduce(ctx)?),
-                Box::new(exp.reduce(ctx)?),
-            ));
+    /// Perform a one-step beta-reduction on this expression using the given strategy (reducing its
+    /// sub-expressions as the strategy requires if it is not a redex itself), unfolding defined
+    /// variables (delta-reduction) and applying the eliminators of [data types][super::Data] to
+    /// their constructors (iota-reduction) along the way, and consuming a unit of the given fuel
+    /// for every redex contracted.
+    fn reduce_once(self, ctx: &Ctx, stg: Strategy, fuel: &mut usize) -> Result<Self, TypingErr> {
+        self.infer(ctx)?;
+        if *fuel == 0 {
+            return Ok(self); // out of fuel, so no further redexes may be contracted
+        }
+        let lim = ctx.get_limits();
+        if lim.is_interrupted() {
+            return Err(TypingErr::from(InterruptErr::new(&self)));
         }
-        if let Exp::For(var, typ, exp) = self {
-            return Ok(Exp::For(
-                var,
-                Box::new(typ.reduce(ctx)?),
-                Box::new(exp.reduce(ctx)?),
-            ));
+        if lim.is_timed_out() {
+            let time = lim.tmo.unwrap_or_default();
+            return Err(TypingErr::from(ReductionTimeoutErr::new(&self, time)));
         }
-        if let Exp::App(fst, snd) = self {
-            if let Exp::Abs(var, _, exp) = *fst {
-                return Ok(exp.subst(&Idx::new(&var), &snd));
-            } else {
-                return Ok(Exp::App(
-                    Box::new(fst.reduce(ctx)?),
-                    Box::new(snd.reduce(ctx)?),
-                ));
+        // each form is reduced by a function of its own, so that the frame of this function (of
+        // which there is one for every level of a nested expression) stays small
+        match self {
+            Exp::Var(VarIdx::Var(ref var)) if ctx.get_def(var).is_some() => {
+                *fuel -= 1;
+                Ok(ctx.get_def(var).cloned().unwrap_or(self))
+            }
+            Exp::Meta(id) if ctx.get_meta(id).is_some() => {
+                *fuel -= 1;
+                Ok(ctx.get_meta(id).unwrap_or(self))
+            }
+            Exp::Abs(..) | Exp::For(..) | Exp::Sig(..) if stg != Strategy::Normal => {
+                Ok(self) // only normal order reduces under binders
             }
+            Exp::Abs(..) | Exp::For(..) | Exp::Sig(..) => self.reduce_binder(ctx, stg, fuel),
+            Exp::Var(_) | Exp::App(..) => self.reduce_app(ctx, stg, fuel),
+            Exp::Pair(..) | Exp::Fst(..) | Exp::Snd(..) => self.reduce_pair(ctx, stg, fuel),
+            Exp::Case(..) => self.reduce_case(ctx, stg, fuel),
+            Exp::NatRec(..) | Exp::Suc(..) | Exp::Add(..) | Exp::Mul(..) => {
+                self.reduce_nat(ctx, stg, fuel)
+            }
+            Exp::Proj(exp, var) => {
+                let exp = exp.normalize(ctx, stg, fuel)?; // expose the record
+                if let (Exp::Struct(fields), true) = (&exp, *fuel > 0) {
+                    if let Some((_, val)) = fields.iter().find(|(v, _)| v == &var) {
+                        *fuel -= 1;
+                        return Ok(val.clone());
+                    }
+                }
+                Ok(Exp::new_proj(exp, var))
+            } // projection of a field out of a record
+            _ => self.reduce_parts(ctx, stg, fuel),
+        }
+    }
+
+    /// Reduce the type and body of this [λ abstraction][Exp::Abs], [Π type][Exp::For] or [Σ type]
+    /// [Exp::Sig], with the variable bound while reducing the body.
+    fn reduce_binder(self, ctx: &Ctx, stg: Strategy, fuel: &mut usize) -> Result<Self, TypingErr> {
+        let (var, typ, exp, new): (_, _, _, fn(_, _, _) -> _) = match self {
+            Exp::Abs(var, typ, exp) => (var, typ, exp, Exp::Abs),
+            Exp::For(var, typ, exp) => (var, typ, exp, Exp::For),
+            Exp::Sig(var, typ, exp) => (var, typ, exp, Exp::Sig),
+            exp => return Ok(exp),
+        };
+        let inner = ctx.extend(&var, &typ); // reduce the body with the variable bound
+        Ok(new(
+            var,
+            Box::new(typ.normalize(ctx, stg, fuel)?),
+            Box::new(exp.normalize(&inner, stg, fuel)?),
+        ))
+    }
+
+    /// Reduce this [application][Exp::App] (or variable), by applying an eliminator of a [data
+    /// type][super::Data] to a constructor or an abstraction to its argument if it is a redex, or
+    /// else reducing the function and argument as the strategy requires.
+    fn reduce_app(self, ctx: &Ctx, stg: Strategy, fuel: &mut usize) -> Result<Self, TypingErr> {
+        if let (rec @ Exp::Var(VarIdx::Var(var)), args) = self.spine() {
+            if let Some(dat) = ctx.get_elim(var) {
+                let mut args: Vec<Exp> = args.into_iter().cloned().collect();
+                if let (Strategy::Head, Some(arg)) = (stg, args.get_mut(dat.cns.len() + 1)) {
+                    *arg = arg.clone().normalize(ctx, stg, fuel)?; // expose the constructor
+                }
+                let red = args.iter().cloned().fold(rec.clone(), Exp::new_app);
+                match dat.eliminate(rec, &args.iter().collect::<Vec<_>>(), ctx) {
+                    Some(exp) if *fuel > 0 => {
+                        *fuel -= 1;
+                        return Ok(exp);
+                    }
+                    _ if stg == Strategy::Head => return Ok(red), // the arguments are not reduced
+                    _ => {}
+                }
+            }
+        } // iota-reduction of an eliminator applied to a constructor
+        let Exp::App(fst, snd) = self else {
+            return Ok(self);
+        };
+        let snd = match stg {
+            Strategy::Value => snd.normalize(ctx, stg, fuel)?, // arguments are reduced first
+            _ => *snd,
+        };
+        if let (Exp::Abs(var, _, exp), true) = (fst.as_ref(), *fuel > 0) {
+            *fuel -= 1;
+            return Ok(exp.clone().subst(&Idx::new(var), &snd));
+        }
+        let fst = fst.normalize(ctx, stg, fuel)?;
+        let snd = match stg {
+            Strategy::Normal => snd.normalize(ctx, stg, fuel)?,
+            _ => snd, // already reduced, or not to be reduced
+        };
+        Ok(Exp::App(Box::new(fst), Box::new(snd)))
+    }
+
+    /// Reduce this [dependent pair][Exp::Pair] or [projection][Exp::Fst] out of one, projecting
+    /// the component out of the pair if it is a redex.
+    fn reduce_pair(self, ctx: &Ctx, stg: Strategy, fuel: &mut usize) -> Result<Self, TypingErr> {
+        match self {
+            Exp::Pair(fst, snd, typ) if stg == Strategy::Head => Ok(Exp::Pair(fst, snd, typ)),
+            Exp::Pair(fst, snd, typ) => Ok(Exp::Pair(
+                Box::new(fst.normalize(ctx, stg, fuel)?),
+                Box::new(snd.normalize(ctx, stg, fuel)?),
+                Box::new(typ.normalize(ctx, stg, fuel)?),
+            )),
+            Exp::Fst(exp) => match *exp {
+                Exp::Pair(fst, _, _) => {
+                    *fuel -= 1;
+                    Ok(*fst)
+                }
+                exp => Ok(Exp::Fst(Box::new(exp.normalize(ctx, stg, fuel)?))),
+            },
+            Exp::Snd(exp) => match *exp {
+                Exp::Pair(_, snd, _) => {
+                    *fuel -= 1;
+                    Ok(*snd)
+                }
+                exp => Ok(Exp::Snd(Box::new(exp.normalize(ctx, stg, fuel)?))),
+            },
+            exp => Ok(exp),
+        }
+    }
+
+    /// Reduce this [case analysis][Exp::Case], applying the case for the injection of the operand
+    /// to the injected expression if it is a redex.
+    fn reduce_case(self, ctx: &Ctx, stg: Strategy, fuel: &mut usize) -> Result<Self, TypingErr> {
+        let Exp::Case(mot, fst, snd, exp) = self else {
+            return Ok(self);
+        };
+        let exp = exp.normalize(ctx, stg, fuel)?; // expose the injection
+        match exp {
+            Exp::Inl(_, exp) if *fuel > 0 => {
+                *fuel -= 1;
+                Ok(Exp::new_app(*fst, *exp))
+            }
+            Exp::Inr(_, exp) if *fuel > 0 => {
+                *fuel -= 1;
+                Ok(Exp::new_app(*snd, *exp))
+            }
+            _ if stg == Strategy::Head => Ok(Exp::Case(mot, fst, snd, Box::new(exp))),
+            _ => Ok(Exp::new_case(
+                mot.normalize(ctx, stg, fuel)?,
+                fst.normalize(ctx, stg, fuel)?,
+                snd.normalize(ctx, stg, fuel)?,
+                exp,
+            )),
+        }
+    }
+
+    /// Reduce this [induction][Exp::NatRec] on, [successor][Exp::Suc] of, or [sum][Exp::Add] or
+    /// [product][Exp::Mul] of natural numbers, computing on machine integers where the operands
+    /// are literals.
+    fn reduce_nat(self, ctx: &Ctx, stg: Strategy, fuel: &mut usize) -> Result<Self, TypingErr> {
+        match self {
+            Exp::NatRec(mot, zer, suc, exp) => {
+                let exp = exp.normalize(ctx, stg, fuel)?; // expose the literal or successor
+                let prd = match &exp {
+                    Exp::Lit(n) => n.checked_sub(1).map(Exp::Lit),
+                    Exp::Suc(exp) => Some(*exp.clone()),
+                    _ => None,
+                };
+                match (exp, prd) {
+                    (Exp::Lit(0), _) if *fuel > 0 => {
+                        *fuel -= 1;
+                        Ok(*zer)
+                    }
+                    (_, Some(prd)) if *fuel > 0 => {
+                        *fuel -= 1;
+                        let rec = Exp::NatRec(mot, zer, suc.clone(), Box::new(prd.clone()));
+                        Ok(Exp::new_app(Exp::new_app(*suc, prd), rec))
+                    }
+                    (exp, _) if stg == Strategy::Head => {
+                        Ok(Exp::NatRec(mot, zer, suc, Box::new(exp)))
+                    }
+                    (exp, _) => Ok(Exp::new_nat_rec(
+                        mot.normalize(ctx, stg, fuel)?,
+                        zer.normalize(ctx, stg, fuel)?,
+                        suc.normalize(ctx, stg, fuel)?,
+                        exp,
+                    )),
+                }
+            } // induction on a literal or successor
+            Exp::Suc(exp) => match exp.normalize(ctx, stg, fuel)? {
+                Exp::Lit(n) if *fuel > 0 => match n.checked_add(1) {
+                    Some(val) => {
+                        *fuel -= 1;
+                        Ok(Exp::Lit(val))
+                    }
+                    None => Err(TypingErr::from(ReductionOverflowErr::new(&Exp::new_suc(
+                        Exp::Lit(n),
+                    )))),
+                },
+                exp => Ok(Exp::new_suc(exp)),
+            },
+            Exp::Add(fst, snd) => {
+                Exp::arith(*fst, *snd, ctx, stg, fuel, usize::checked_add, Exp::new_add)
+            }
+            Exp::Mul(fst, snd) => {
+                Exp::arith(*fst, *snd, ctx, stg, fuel, usize::checked_mul, Exp::new_mul)
+            }
+            exp => Ok(exp),
+        }
+    }
+
+    /// Reduce the parts of this expression, which is not a redex itself, as the strategy requires.
+    fn reduce_parts(self, ctx: &Ctx, stg: Strategy, fuel: &mut usize) -> Result<Self, TypingErr> {
+        match self {
+            Exp::Imp(typ) => Ok(Exp::Imp(Box::new(typ.normalize(ctx, stg, fuel)?))),
+            Exp::Absurd(..)
+            | Exp::Sum(..)
+            | Exp::Inl(..)
+            | Exp::Inr(..)
+            | Exp::Rec(..)
+            | Exp::Struct(..)
+                if stg == Strategy::Head =>
+            {
+                Ok(self)
+            }
+            Exp::Absurd(typ, exp) => Ok(Exp::new_absurd(
+                typ.normalize(ctx, stg, fuel)?,
+                exp.normalize(ctx, stg, fuel)?,
+            )),
+            Exp::Sum(fst, snd) => Ok(Exp::new_sum(
+                fst.normalize(ctx, stg, fuel)?,
+                snd.normalize(ctx, stg, fuel)?,
+            )),
+            Exp::Inl(typ, exp) => Ok(Exp::new_inl(
+                typ.normalize(ctx, stg, fuel)?,
+                exp.normalize(ctx, stg, fuel)?,
+            )),
+            Exp::Inr(typ, exp) => Ok(Exp::new_inr(
+                typ.normalize(ctx, stg, fuel)?,
+                exp.normalize(ctx, stg, fuel)?,
+            )),
+            Exp::Rec(fields) => Ok(Exp::Rec(Exp::normalize_fields(fields, ctx, stg, fuel)?)),
+            Exp::Struct(fields) => Ok(Exp::Struct(Exp::normalize_fields(fields, ctx, stg, fuel)?)),
+            _ => Ok(self),
+        }
+    }
+
+    /// Reduce each of the fields of a record (or record type) using the given strategy.
+    fn normalize_fields(
+        fields: Vec<(Var, Exp)>,
+        ctx: &Ctx,
+        stg: Strategy,
+        fuel: &mut usize,
+    ) -> Result<Vec<(Var, Exp)>, TypingErr> {
+        fields
+            .into_iter()
+            .map(|(var, exp)| Ok((var, exp.normalize(ctx, stg, fuel)?)))
+            .collect()
+    }
+
+    /// Perform a primitive arithmetic operation on two natural numbers, which are reduced first, so
+    /// that the operation is computed on machine integers if both are literals, consuming a unit of
+    /// the given fuel. Reduction fails if the result overflows, rather than leaving the operation
+    /// as it is, since it would then differ from the operation on natural numbers.
+    fn arith(
+        fst: Exp,
+        snd: Exp,
+        ctx: &Ctx,
+        stg: Strategy,
+        fuel: &mut usize,
+        opr: fn(usize, usize) -> Option<usize>,
+        new: fn(Exp, Exp) -> Exp,
+    ) -> Result<Self, TypingErr> {
+        let fst = fst.normalize(ctx, stg, fuel)?;
+        let snd = snd.normalize(ctx, stg, fuel)?;
+        match (&fst, &snd) {
+            (Exp::Lit(m), Exp::Lit(n)) if *fuel > 0 => match opr(*m, *n) {
+                Some(val) => {
+                    *fuel -= 1;
+                    Ok(Exp::Lit(val))
+                }
+                None => Err(TypingErr::from(ReductionOverflowErr::new(&new(fst, snd)))),
+            },
+            _ => Ok(new(fst, snd)),
         }
-        Ok(self)
     }
 
     /// Replace all occurrences of the index with the given expression, in the current expression.
-    fn subst(self, loc: &Idx, can: &Exp) -> Self {
+    ///
+    /// The types of nested binders refer to the binder for the index by name, so occurrences of
+    /// that name are replaced as well, except where another binder of the same name shadows it.
+    /// The expression is [shifted][Exp::shift] as it passes under binders, so that its free
+    /// variables continue to refer to the same binders, and binders whose names would capture
+    /// its free variables are renamed.
+    pub(super) fn subst(self, loc: &Idx, can: &Exp) -> Self {
+        self.subst_at(loc, can, true)
+    }
+
+    /// Replace all occurrences of the index (and, if it is not shadowed, its name) with the given
+    /// expression, in the current expression.
+    fn subst_at(self, loc: &Idx, can: &Exp, named: bool) -> Self {
         match self {
             Exp::Var(varidx) => match varidx {
+                VarIdx::Var(var) if named && var == loc.1 => can.clone().named(0),
                 VarIdx::Var(var) => Exp::Var(VarIdx::Var(var)),
                 VarIdx::Idx(idx) => match idx.cmp(loc) {
-                    Ordering::Equal => can.clone(),
+                    Ordering::Equal => can.clone().shift(loc.0, 0),
                     Ordering::Greater => Exp::Var(VarIdx::Idx(idx.dec())),
                     Ordering::Less => Exp::Var(VarIdx::Idx(idx)),
                 },
             },
-            Exp::Abs(var, typ, exp) => Exp::Abs(var, typ, Box::new(exp.subst(&loc.inc(), can))),
-            Exp::For(var, typ, exp) => Exp::For(var, typ, Box::new(exp.subst(&loc.inc(), can))),
-            Exp::App(fst, snd) => {
-                Exp::App(Box::new(fst.subst(loc, can)), Box::new(snd.subst(loc, can)))
+            mut exp => {
+                if let Exp::Abs(var, _, body) | Exp::For(var, _, body) | Exp::Sig(var, _, body) =
+                    &mut exp
+                {
+                    if can.mentions(var, 0) && body.depends_on(&loc.1, loc.0 + 1) {
+                        let mut new = var.clone();
+                        while can.mentions(&new, 0) || body.has_name(&new) {
+                            new.0.push('\'');
+                        }
+                        **body = std::mem::take(body.as_mut()).rename(var, &new, 0, true);
+                        *var = new;
+                    } // renamed so as not to capture a free variable of the same name
+                }
+                exp.map_subterms(|exp, pos| match pos {
+                    Pos::Body(var) => exp.subst_at(&loc.inc(), can, named && var != &loc.1),
+                    _ => exp.subst_at(loc, can, named),
+                })
             }
-            Exp::TypeMeta => self,
-            Exp::KindMeta => self,
         }
     }
 
-    /// Format this expression into canonical form.
-    fn fmt(&self, f: &mut Formatter<'_>, flags: Branch) -> std::fmt::Result {
+    /// Convert the indices in this expression that are bound outside of it (at or beyond the given
+    /// cut-off) into the names of the variables they refer to, as is needed when placing it within
+    /// the type of a nested binder, which refers to variables by name.
+    fn named(self, cut: usize) -> Self {
         match self {
-            Self::Var(varidx) => varidx.fmt(f),
-            Self::Abs(var, typ, exp) => Exp::fmt_binder(f, flags, "λ", var, typ, exp),
-            Self::For(var, typ, exp) => Exp::fmt_binder(f, flags, "Π", var, typ, exp),
-            Self::App(fst, snd) => Exp::fmt_app(f, flags, fst, snd),
-            Self::TypeMeta => write!(f, "*"),
-            Self::KindMeta => write!(f, "□"),
-        }
-    }
-
-    /// Format a binder expression (λ abstraction or Π type).
-    fn fmt_binder(
-        f: &mut Formatter<'_>,
-        flags: Branch,
-        binder: &str,
-        var: &Var,
-        typ: &Exp,
-        exp: &Exp,
-    ) -> std::fmt::Result {
-        let func = |f: &mut Formatter<'_>| -> std::fmt::Result {
-            write!(f, "{}{} : ", binder, var)?;
-            typ.fmt(f, Default::default())?; // reset, always greedy
-            write!(f, " . ")?;
-            exp.fmt(f, Default::default()) // reset, always greedy
-        };
-        Exp::parens(f, flags.ltree, func) // parenthesize if on the left side of tree
+            Exp::Var(VarIdx::Idx(Idx(i, var))) if i >= cut => Exp::new_var(var),
+            _ => self.map_subterms(|exp, pos| match pos {
+                Pos::Body(_) => exp.named(cut + 1),
+                _ => exp.named(cut),
+            }),
+        }
     }
 
-    /// Format an application of one expression to another.
-    fn fmt_app(f: &mut Formatter<'_>, flags: Branch, fst: &Exp, snd: &Exp) -> std::fmt::Result {
-        let func = |f: &mut Formatter<'_>| -> std::fmt::Result {
-            fst.fmt(
-                f,
-                Branch {
-                    ltree: !flags.rtree, // true, but reset if current term is being parenthesized
-                    rtree: flags.rtree,  // inherit from parent
-                },
-            )?;
-            write!(f, " ")?;
-            snd.fmt(
-                f,
-                Branch {
-                    ltree: flags.ltree,  // inherit from parent
-                    rtree: !flags.rtree, // true, but reset if current term is being parenthesized
-                },
-            )
-        };
-        Exp::parens(f, flags.rtree, func) // parenthesize if on the right side of tree
+    /// Check whether the given name occurs free in this expression, either as a symbol or as the
+    /// name of an index that is bound outside of it (at or beyond the given level), in which case
+    /// a binder of that name would capture it.
+    fn mentions(&self, var: &Var, lvl: usize) -> bool {
+        match self {
+            Exp::Var(VarIdx::Var(v)) => v == var,
+            Exp::Var(VarIdx::Idx(Idx(i, v))) => *i >= lvl && v == var,
+            _ => self.any_subterm(|exp, pos| match pos {
+                Pos::Body(v) if v == var => exp.escapes(lvl + 1),
+                Pos::Body(_) => exp.mentions(var, lvl + 1),
+                _ => exp.mentions(var, lvl),
+            }),
+        }
     }
 
-    /// Parenthesize (or not) as specified, executing a closure to write the content within.
-    fn parens<F>(f: &mut Formatter<'_>, parens: bool, func: F) -> std::fmt::Result
-    where
-        F: FnOnce(&mut Formatter<'_>) -> std::fmt::Result,
-    {
-        if parens {
-            write!(f, "(")?;
+    /// Check whether this expression contains an index that is bound outside of it (at or beyond
+    /// the given level) under a binder of the same name, which can only have been placed there
+    /// by a substitution.
+    fn escapes(&self, lvl: usize) -> bool {
+        match self {
+            Exp::Var(VarIdx::Idx(Idx(i, _))) => *i >= lvl,
+            _ => self.any_subterm(|exp, pos| match pos {
+                Pos::Body(_) => exp.escapes(lvl + 1),
+                _ => exp.escapes(lvl),
+            }),
         }
-        func(f)?;
-        if parens {
-            write!(f, ")")?;
+    }
+
+    /// Rename the variable bound at the given level (both its index and, if it is not shadowed,
+    /// its name within the types of nested binders) in this expression.
+    fn rename(self, old: &Var, new: &Var, lvl: usize, named: bool) -> Self {
+        match self {
+            Exp::Var(VarIdx::Var(var)) if named && &var == old => Exp::new_var(new.clone()),
+            Exp::Var(VarIdx::Idx(Idx(i, _))) if i == lvl => {
+                Exp::Var(VarIdx::Idx(Idx(i, new.clone())))
+            }
+            _ => self.map_subterms(|exp, pos| match pos {
+                Pos::Body(var) => exp.rename(old, new, lvl + 1, named && var != old), // shadowed
+                _ => exp.rename(old, new, lvl, named),
+            }),
+        }
+    }
+
+    /// Increment the indices of the variables in this expression that are bound outside of it (at
+    /// or beyond the given cut-off), as is needed when placing it under the given number of
+    /// additional binders.
+    pub(super) fn shift(self, by: usize, cut: usize) -> Self {
+        match self {
+            Exp::Var(VarIdx::Idx(Idx(i, var))) if i >= cut => {
+                Exp::Var(VarIdx::Idx(Idx(i + by, var)))
+            }
+            _ => self.map_subterms(|exp, pos| match pos {
+                Pos::Body(_) => exp.shift(by, cut + 1),
+                _ => exp.shift(by, cut),
+            }),
+        }
+    }
+
+    /// Split this expression into the head and the arguments it is applied to, if any.
+    pub(super) fn spine(&self) -> (&Exp, Vec<&Exp>) {
+        let mut exp = self;
+        let mut args = vec![];
+        while let Exp::App(fst, snd) = exp {
+            args.push(snd.as_ref());
+            exp = fst;
+        }
+        args.reverse();
+        (exp, args)
+    }
+
+    /// Fetch the [notation][Notation] and operands, if this expression is the application of a
+    /// (free) variable to two operands for which an infix operator is declared in the context.
+    pub(super) fn as_infix<'a>(&'a self, ctx: &'a Ctx) -> Option<(&'a Notation, &'a Exp, &'a Exp)> {
+        if let Exp::App(fst, rhs) = self {
+            if let Exp::App(var, lhs) = fst.as_ref() {
+                if let Exp::Var(VarIdx::Var(var)) = var.as_ref() {
+                    return ctx
+                        .get_notation_for(var)
+                        .map(|ntn| (ntn, lhs.as_ref(), rhs.as_ref()));
+                }
+            }
+        }
+        None
+    }
+
+    /// Fetch a value that renders this expression using the [notation][Notation] declared in the
+    /// given context, in the same way as [Display] does otherwise.
+    pub fn display<'a>(&'a self, ctx: &'a Ctx) -> ExpDisplay<'a> {
+        ExpDisplay {
+            exp: self,
+            ctx,
+            ascii: false,
         }
-        Ok(())
     }
 }
 
 impl Default for Exp {
     fn default() -> Self {
-        Self::KindMeta
+        Self::get_kind_meta()
     }
 }
 
+/// Render the expression using Unicode glyphs (`λ`, `Π`, `Σ`, `*`, `□`), or using their ASCII
+/// equivalents (`\\`, `forall`, `exists`, `Type`, `Kind`) when formatted with the alternate flag
+/// (`{:#}`).
 impl Display for Exp {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        self.fmt(f, Branch::new())
+        write!(f, "{}", self.pretty(&PrintOptions::flat(f.alternate())))
     }
 }
 
-impl Branch {
-    /// Create a new instance of branch.
-    pub fn new() -> Self {
-        Branch {
-            ltree: false,
-            rtree: false,
-        }
+/// Parse an expression from source text written in the core fluxo language, using either Unicode
+/// glyphs or their ASCII equivalents.
+impl FromStr for Exp {
+    type Err = SyntaxErr;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        par::parse(src)
+    }
+}
+
+/// Helper that renders an [expression][Exp] using the notation declared in a context, returned
+/// by [Exp::display].
+pub struct ExpDisplay<'a> {
+    /// Expression to be rendered.
+    exp: &'a Exp,
+    /// Context that holds the notation to be applied.
+    ctx: &'a Ctx,
+    /// Whether the expression is rendered in ASCII, as it is with the alternate flag (`{:#}`).
+    ascii: bool,
+}
+
+impl ExpDisplay<'_> {
+    /// Render the expression in ASCII (rather than with Unicode glyphs) if the given flag is set,
+    /// as for terminals that lack Unicode glyphs.
+    pub fn ascii(mut self, ascii: bool) -> Self {
+        self.ascii = ascii;
+        self
+    }
+}
+
+impl Display for ExpDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let opts = PrintOptions::flat(f.alternate() || self.ascii);
+        write!(f, "{}", self.exp.pretty_in(self.ctx, &opts))
     }
 }
@@ -1,17 +1,36 @@
 //! Abstract syntax tree and related data logic.
 
+mod arn;
 mod ctx;
+mod dat;
 mod exp;
+mod ntn;
+mod occ;
+mod pty;
+mod red;
+mod unify;
 mod var;
+mod vis;
 
-pub use ctx::Ctx;
-pub use exp::Exp;
+pub use arn::{Arena, ExpId, Node};
+pub use ctx::{Ctx, Diff, Match};
+pub use dat::{Ctor, Data};
+pub use exp::{Exp, ExpDisplay, DEFAULT_FUEL};
+pub use ntn::{Fixity, Notation, MAX_PREC};
+pub use occ::{occurs, positivity, Polarity};
+pub use pty::{Parens, PrintOptions};
+pub use red::{Limits, Rule, Stats, Step, Strategy};
+pub use unify::{unify, MetaId, Metas};
 pub use var::{Idx, Var, VarIdx};
+pub use vis::{walk, Pos, Visitor};
 
 #[cfg(test)]
 mod test {
 
     use super::*;
+    use crate::err::{
+        ReductionBudgetErr, TypeDupDataErr, TypeHoleErr, TypeOccursErr, TypeUnknownErr, TypingErr,
+    };
     use std::error::Error;
 
     #[test]
@@ -93,7 +112,7 @@ mod test {
                 ),
             )
             .to_string(),
-            "Πx : t . λy : b . y"
+            "t → λy : b . y"
         );
     }
 
@@ -127,7 +146,7 @@ mod test {
                 ),
             )
             .to_string(),
-            "Πx : □ . λy : b . y"
+            "□ → λy : b . y"
         );
     }
 
@@ -354,10 +373,75 @@ mod test {
                 Exp::new_var(Var::new("k")),
             )
             .to_string(),
-            "(Πx : □ . λm : * . w) k"
+            "(□ → λm : * . w) k"
         );
     }
 
+    #[test]
+    fn test_exp_rend_0024() {
+        assert_eq!(
+            format!(
+                "{:#}",
+                Exp::new_app(
+                    Exp::new_for(
+                        Var::new("x"),
+                        Exp::get_kind_meta(),
+                        Exp::new_abs(
+                            Var::new("m"),
+                            Exp::get_type_meta(),
+                            Exp::new_var(Var::new("w")),
+                        ),
+                    ),
+                    Exp::new_var(Var::new("k")),
+                )
+            ),
+            "(Kind -> \\m : Type . w) k"
+        );
+    }
+
+    #[test]
+    fn test_exp_rend_0025() {
+        assert_eq!(
+            Exp::new_for(
+                Var::new("x"),
+                Exp::get_type_meta(),
+                Exp::new_for(
+                    Var::new("y"),
+                    Exp::new_var(Var::new("x")),
+                    Exp::get_type_meta(),
+                ),
+            )
+            .to_string(),
+            "Πx : * . x → *"
+        );
+    }
+
+    #[test]
+    fn test_exp_rend_0026() {
+        assert_eq!(
+            Exp::new_arr(
+                Exp::new_arr(Exp::get_type_meta(), Exp::get_type_meta()),
+                Exp::new_arr(Exp::get_type_meta(), Exp::get_kind_meta()),
+            )
+            .to_string(),
+            "(* → *) → * → □"
+        );
+    }
+
+    #[test]
+    fn test_exp_rend_0027() {
+        let mut ctx = Ctx::new();
+        let plus = Notation::new(Fixity::Infixl, 6, "+", Var::new("plus"));
+        let var = |v: &str| Exp::new_var(Var::new(v));
+        let exp = plus.apply(
+            plus.apply(var("a"), var("b")),
+            plus.apply(var("c"), var("d")),
+        );
+        assert_eq!(exp.to_string(), "plus (plus a b) (plus c d)");
+        ctx.put_notation(plus);
+        assert_eq!(exp.display(&ctx).to_string(), "a + b + (c + d)");
+    }
+
     #[test]
     fn test_exp_idx_0001() {
         // x Πy : * . (λw : * . w y) m
@@ -378,7 +462,7 @@ mod test {
             ),
         );
 
-        println!("{}", exp.to_string());
+        println!("{}", exp);
 
         if let Exp::App(_, t0) = exp {
             if let Exp::For(_, _, t1) = *t0 {
@@ -389,7 +473,7 @@ mod test {
                                 if i != 0 {
                                     panic!("Index should have been 0, but was {}!", i);
                                 }
-                                if s != "w".to_string() {
+                                if s != "w" {
                                     panic!("Variable should have been 'w' but was {}!", s);
                                 }
                             } else {
@@ -399,7 +483,7 @@ mod test {
                                 if i != 1 {
                                     panic!("Index should have been 1, but was {}!", i);
                                 }
-                                if s != "y".to_string() {
+                                if s != "y" {
                                     panic!("Variable should have been 'y' but was {}!", s);
                                 }
                             } else {
@@ -418,10 +502,881 @@ mod test {
         panic!("Expected Exp::App!");
     }
 
+    #[test]
+    fn test_type_calculation_002() -> Result<(), Box<dyn Error>> {
+        assert_eq!(
+            Exp::new_num(3).infer(&Ctx::new())?.to_string(),
+            "Πnat : * . (nat → nat) → nat → nat"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_type_calculation_003() {
+        let ctx = Ctx::new();
+        let exp = Exp::new_app(
+            Exp::new_abs(
+                Var::new("x"),
+                Exp::get_type_meta(),
+                Exp::new_var(Var::new("x")),
+            ),
+            Exp::get_hole(),
+        );
+        assert_eq!(
+            exp.infer(&ctx).map_err(TypingErr::into_cause),
+            Err(TypingErr::from(TypeHoleErr::new(&[&Exp::get_type_meta()])))
+        );
+        let exp = Exp::new_abs(Var::new("x"), Exp::get_hole(), Exp::new_var(Var::new("x")));
+        assert_eq!(
+            exp.infer(&ctx),
+            Err(TypingErr::from(TypeHoleErr::new(&[
+                &Exp::get_type_meta(),
+                &Exp::get_kind_meta()
+            ])))
+        );
+        assert_eq!(
+            Exp::get_hole().infer(&ctx),
+            Err(TypingErr::from(TypeHoleErr::new(&[])))
+        );
+    }
+
+    #[test]
+    fn test_type_calculation_004() -> Result<(), Box<dyn Error>> {
+        let mut ctx = Ctx::new();
+        ctx.put(&Var::new("A"), &Exp::get_type_meta())?;
+        ctx.put(&Var::new("P"), &"A → *".parse()?)?;
+        ctx.put(&Var::new("a"), &"A".parse()?)?;
+        ctx.put(&Var::new("pa"), &"P a".parse()?)?;
+        let typ = |src: &str| -> Result<String, Box<dyn Error>> {
+            Ok(src.parse::<Exp>()?.infer(&ctx)?.to_string())
+        };
+        let pair = "(a, pa : Σx : A . P x)";
+        assert_eq!(typ("Σx : A . P x")?, "*");
+        assert_eq!(typ("Σx : * . x")?, "□");
+        assert_eq!(typ(pair)?, "Σx : A . P x");
+        assert_eq!(typ(&format!("fst {}", pair))?, "A");
+        assert_eq!(typ(&format!("snd {}", pair))?, "P a");
+        assert!(typ("(a, a : Σx : A . P x)").is_err());
+        assert!(typ("(a, pa : A)").is_err());
+        assert!(typ("fst a").is_err());
+        let exp = format!("snd (fst ({}, a : Σp : (Σx : A . P x) . A))", pair);
+        assert_eq!(typ(&exp)?, "P a");
+        assert_eq!(exp.parse::<Exp>()?.reduce(&ctx)?.to_string(), "pa");
+        Ok(())
+    }
+
+    #[test]
+    fn test_type_calculation_005() -> Result<(), Box<dyn Error>> {
+        let mut ctx = Ctx::new();
+        ctx.put(&Var::new("A"), &Exp::get_type_meta())?;
+        ctx.put(&Var::new("a"), &"A".parse()?)?;
+        ctx.define(&Var::new("T"), &"A".parse()?, &Exp::get_type_meta())?;
+        ctx.define(&Var::new("id"), &"λx : T . x".parse()?, &"T → T".parse()?)?;
+        ctx.put(&Var::new("t"), &"T".parse()?)?;
+        let exp = "id a".parse::<Exp>()?;
+        assert_eq!(exp.infer(&ctx)?.to_string(), "A");
+        assert_eq!(exp.reduce(&ctx)?.to_string(), "a");
+        assert_eq!("id t".parse::<Exp>()?.reduce(&ctx)?.to_string(), "t");
+        assert_eq!(ctx.get_def(&Var::new("T")), Some(&"A".parse()?));
+        assert!(ctx
+            .define(&Var::new("b"), &"a".parse()?, &"A → A".parse()?)
+            .is_err());
+        assert!(ctx
+            .define(&Var::new("T"), &"A".parse()?, &"□".parse()?)
+            .is_err());
+        let ctx = ctx.extend(&Var::new("T"), &Exp::get_type_meta()); // shadow the definition
+        assert_eq!(ctx.get_def(&Var::new("T")), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_type_calculation_006() -> Result<(), Box<dyn Error>> {
+        let mut ctx = Ctx::new();
+        ctx.put(&Var::new("A"), &Exp::get_type_meta())?;
+        ctx.put(&Var::new("F"), &"□ → □".parse()?)?;
+        let typ = |src: &str| -> Result<String, Box<dyn Error>> {
+            Ok(src.parse::<Exp>()?.infer(&ctx)?.to_string())
+        };
+        assert_eq!(typ("*")?, "□");
+        assert_eq!(typ("□")?, "Type 2");
+        assert_eq!(typ("Type 2")?, "Type 3");
+        assert_eq!(typ("A → A")?, "*");
+        assert_eq!(typ("Πx : * . x → x")?, "□");
+        assert_eq!(typ("Σx : □ . x")?, "Type 2");
+        assert_eq!(typ("F A")?, "□"); // cumulativity: A : * ≤ □
+        assert_eq!(typ("λf : □ → □ . f A")?, "(□ → □) → □");
+        assert_eq!(typ("(λf : * → □ . f) (λx : * . x)")?, "* → □");
+        assert!(typ("(λf : □ → * . f) (λx : □ . x)").is_err());
+        assert!(typ("(λa : A . a) *").is_err());
+        assert!(matches!(
+            "λx : (λa : A . a) . x"
+                .parse::<Exp>()?
+                .infer(&ctx)
+                .map_err(TypingErr::into_cause),
+            Err(TypingErr::TypeSortErr(_))
+        ));
+        assert!(Exp::new_sort(usize::MAX).infer(&ctx).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_type_calculation_007() -> Result<(), Box<dyn Error>> {
+        let mut ctx = Ctx::new();
+        let nat = Data::new(
+            Var::new("Peano"),
+            vec![
+                Ctor::new(Var::new("zero"), vec![]),
+                Ctor::new(Var::new("succ"), vec!["Peano".parse()?]),
+            ],
+        );
+        ctx.declare(&nat)?;
+        let typ = |src: &str| -> Result<String, Box<dyn Error>> {
+            Ok(src.parse::<Exp>()?.infer(&ctx)?.to_string())
+        };
+        let red = |src: &str| -> Result<String, Box<dyn Error>> {
+            Ok(src.parse::<Exp>()?.reduce(&ctx)?.to_string())
+        };
+        assert_eq!(typ("succ zero")?, "Peano");
+        assert_eq!(
+            typ("Peano_rec")?,
+            "ΠP : Peano → □ . P zero → (Πa : Peano . P a → P (succ a)) → Πx : Peano . P x"
+        );
+        let dbl = "Peano_rec (λn : Peano . Peano) zero (λn : Peano . λr : Peano . succ (succ r))";
+        assert_eq!(typ(dbl)?, "Peano → Peano");
+        assert_eq!(red(&format!("{} zero", dbl))?, "zero");
+        assert_eq!(red(&format!("{} (succ zero)", dbl))?, "succ (succ zero)");
+        let add =
+            "λm : Peano . Peano_rec (λn : Peano . Peano) m (λn : Peano . λr : Peano . succ r)";
+        assert_eq!(typ(add)?, "Peano → Peano → Peano");
+        assert_eq!(red(&format!("({}) (succ zero) zero", add))?, "succ zero");
+        assert!(typ("Peano_rec (λn : Peano . Peano) zero zero").is_err());
+        let pick = "Peano_rec (λn : Peano . *) Peano (λn : Peano . λt : * . t → Peano)";
+        assert_eq!(red(&format!("{} zero", pick))?, "Peano");
+        assert_eq!(
+            ctx.declare(&nat),
+            Err(TypingErr::from(TypeDupDataErr::new(
+                &Var::new("Peano"),
+                &Var::new("Peano")
+            )))
+        );
+        let twice = Data::new(
+            Var::new("Two"),
+            vec![
+                Ctor::new(Var::new("one"), vec![]),
+                Ctor::new(Var::new("one"), vec![]),
+            ],
+        );
+        assert_eq!(
+            ctx.declare(&twice).unwrap_err().to_string(),
+            ":data Two\n    ↯ one is declared more than once\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_type_calculation_008() -> Result<(), Box<dyn Error>> {
+        let mut ctx = Ctx::new();
+        ctx.declare(&Data::new(
+            Var::new("N"),
+            vec![
+                Ctor::new(Var::new("z"), vec![]),
+                Ctor::new(Var::new("s"), vec!["N".parse()?]),
+            ],
+        ))?;
+        let ord = Data::new(
+            Var::new("Ord"),
+            vec![
+                Ctor::new(Var::new("oz"), vec![]),
+                Ctor::new(Var::new("lim"), vec!["N → Ord".parse()?]),
+            ],
+        );
+        ctx.declare(&ord)?;
+        assert_eq!(
+            ctx.get(&Var::new("Ord_rec"))?.to_string(),
+            "ΠP : Ord → □ . P oz → (Πa : N → Ord . (Πy : N . P (a y)) → P (lim a)) → Πx : Ord . P x"
+        );
+        let exp =
+            "Ord_rec (λo : Ord . N) z (λf : N → Ord . λg : N → N . g (s z)) (lim (λn : N . oz))";
+        assert_eq!(exp.parse::<Exp>()?.reduce(&ctx)?.to_string(), "z");
+        for (cns, arg) in [("mk", "Bad → Bad"), ("mk", "(Bad → N) → Bad"), ("mk", "*")] {
+            let bad = Data::new(
+                Var::new("Bad"),
+                vec![Ctor::new(Var::new(cns), vec![arg.parse()?])],
+            );
+            assert!(ctx.clone().declare(&bad).is_err());
+        }
+        let bad = Data::new(
+            Var::new("Bad"),
+            vec![Ctor::new(Var::new("mk"), vec!["Bad → N".parse()?])],
+        );
+        assert!(matches!(ctx.declare(&bad), Err(TypingErr::TypePosErr(_))));
+        assert!(ctx.get(&Var::new("Bad")).is_err());
+        let empty = Data::new(Var::new("Empty"), vec![]);
+        ctx.declare(&empty)?;
+        assert_eq!(
+            ctx.get(&Var::new("Empty_rec"))?.to_string(),
+            "ΠP : Empty → □ . Πx : Empty . P x"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_type_calculation_009() -> Result<(), Box<dyn Error>> {
+        let mut ctx = Ctx::new();
+        ctx.put(&Var::new("A"), &Exp::get_type_meta())?;
+        ctx.put(&Var::new("B"), &Exp::get_type_meta())?;
+        ctx.put(&Var::new("a"), &"A".parse()?)?;
+        ctx.put(&Var::new("f"), &"(A → B → A) → B".parse()?)?;
+        // an unannotated abstraction can be checked, but not inferred
+        let abs: Exp = "λx . λy . x".parse()?;
+        assert!(matches!(
+            abs.infer(&ctx).map_err(TypingErr::into_cause),
+            Err(TypingErr::TypeHoleErr(_))
+        ));
+        abs.check(&"A → B → A".parse()?, &ctx)?;
+        assert!(abs.check(&"A → B → B".parse()?, &ctx).is_err());
+        assert!(abs.check(&"A".parse()?, &ctx).is_err());
+        assert_eq!(
+            Exp::new_app("f".parse()?, abs.clone()).infer(&ctx)?,
+            "B".parse()?
+        );
+        // mismatches are reported for the sub-expression in which they occur
+        let err = "λx : A . λy : B . y"
+            .parse::<Exp>()?
+            .check(&"A → B → A".parse()?, &ctx);
+        assert_eq!(
+            err.unwrap_err().to_string(),
+            ":type y\n    = B\n    ∉ {A}\n    ↯ while checking the body of λy : B . y\n    ↯ while checking the body of λx : A . λy : B . y\n"
+        );
+        // a definition is annotated with the type it is checked against
+        ctx.define(&Var::new("k"), &abs, &"A → B → A".parse()?)?;
+        assert_eq!(
+            ctx.get_def(&Var::new("k")).unwrap().to_string(),
+            "λx : A . λy : B . x"
+        );
+        let exp = Exp::new_app("f".parse()?, abs).elaborate(&ctx);
+        assert_eq!(exp.reduce(&ctx)?.to_string(), "f λx : A . λy : B . x");
+        Ok(())
+    }
+
+    #[test]
+    fn test_type_calculation_010() -> Result<(), Box<dyn Error>> {
+        let mut ctx = Ctx::new();
+        ctx.put(&Var::new("A"), &Exp::get_type_meta())?;
+        ctx.put(&Var::new("B"), &Exp::get_type_meta())?;
+        ctx.put(&Var::new("a"), &"A".parse()?)?;
+        ctx.put(&Var::new("b"), &"B".parse()?)?;
+        ctx.put(&Var::new("v"), &"⊥".parse()?)?;
+        let typ = |src: &str| -> Result<String, Box<dyn Error>> {
+            Ok(src.parse::<Exp>()?.infer(&ctx)?.to_string())
+        };
+        let red = |src: &str| -> Result<String, Box<dyn Error>> {
+            Ok(src.parse::<Exp>()?.reduce(&ctx)?.to_string())
+        };
+        assert_eq!(typ("⊤")?, "*");
+        assert_eq!(typ("()")?, "⊤");
+        assert_eq!(typ("⊥")?, "*");
+        assert_eq!(typ("absurd A v")?, "A");
+        assert!(typ("absurd A a").is_err());
+        assert_eq!(typ("Sum A □")?, "Type 2");
+        assert_eq!(typ("inl B a")?, "Sum A B");
+        assert_eq!(typ("inr A b")?, "Sum A B");
+        assert!(typ("inl b a").is_err());
+        // case analysis computes on injections, with a (possibly dependent) motive
+        let swap = "case (λs : Sum A B . Sum B A) (λx : A . inr B x) (λy : B . inl A y)";
+        assert_eq!(typ(&format!("{} (inl B a)", swap))?, "Sum B A");
+        assert_eq!(red(&format!("{} (inl B a)", swap))?, "inr B a");
+        assert_eq!(red(&format!("{} (inr A b)", swap))?, "inl A b");
+        let pick = "λs : Sum A B . case (λs : Sum A B . *) (λx : A . ⊤) (λy : B . ⊥) s";
+        assert_eq!(typ(pick)?, "Sum A B → *");
+        let tag = format!("case ({}) (λx : A . ()) (λy : B . absurd ⊥ v)", pick);
+        assert_eq!(typ(&format!("{} (inl B a)", tag))?, "⊤");
+        assert_eq!(red(&format!("{} (inl B a)", tag))?, "()");
+        assert!(typ(&format!("{} (inl B a)", swap.replace("inr B x", "x"))).is_err());
+        assert!(typ("case (λs : Sum A B . A) (λx : A . x) (λy : B . y) (inl B a)").is_err());
+        let exp: Exp = format!("{} (inl B a)", swap).parse()?;
+        assert!(exp.reduce_with(&ctx, Strategy::Normal, 1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_exp_norm_0001() -> Result<(), Box<dyn Error>> {
+        let mut ctx = Ctx::new();
+        ctx.put(&Var::new("A"), &Exp::get_type_meta())?;
+        ctx.put(&Var::new("a"), &"A".parse()?)?;
+        let red = |src: &str| -> Result<String, Box<dyn Error>> {
+            Ok(src.parse::<Exp>()?.reduce(&ctx)?.to_string())
+        };
+        let mut src = "a".to_string();
+        for _ in 0..40 {
+            src = format!("(λx : A . x) ({})", src);
+        }
+        assert_eq!(red(&src)?, "a");
+        assert_eq!(
+            red("λy : A . (λf : A → A . f (f y)) (λx : A . x)")?,
+            "λy : A . y"
+        );
+        assert_eq!(
+            red("(λf : A → A . λg : A → A . f (g (f a))) (λx : A . x) (λx : A . (λz : A . z) x)")?,
+            "a"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_exp_norm_0003() -> Result<(), Box<dyn Error>> {
+        let mut ctx = Ctx::new();
+        ctx.declare(&Data::new(
+            Var::new("Peano"),
+            vec![
+                Ctor::new(Var::new("zero"), vec![]),
+                Ctor::new(Var::new("succ"), vec!["Peano".parse()?]),
+            ],
+        ))?;
+        ctx.define(
+            &Var::new("plus"),
+            &"λm : Peano . Peano_rec (λk : Peano . Peano) m (λk : Peano . λr : Peano . succ r)"
+                .parse()?,
+            &"Peano → Peano → Peano".parse()?,
+        )?;
+        let num = |n: usize| (0..n).fold("zero".to_string(), |exp, _| format!("succ ({})", exp));
+        let exp = format!(
+            "plus (plus ({}) ({})) (plus ({}) zero)",
+            num(2),
+            num(3),
+            num(4)
+        );
+        let exp = exp.parse::<Exp>()?.reduce(&ctx)?;
+        assert_eq!(exp, num(9).parse::<Exp>()?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_exp_norm_0004() -> Result<(), Box<dyn Error>> {
+        let mut ctx = Ctx::new();
+        ctx.put(&Var::new("A"), &Exp::get_type_meta())?;
+        ctx.put(&Var::new("a"), &"A".parse()?)?;
+        ctx.put(&Var::new("f"), &"A → A".parse()?)?;
+        let red = |src: &str, stg: Strategy, fuel: usize| -> Result<String, Box<dyn Error>> {
+            Ok(src
+                .parse::<Exp>()?
+                .reduce_with(&ctx, stg, fuel)?
+                .to_string())
+        };
+        let exp = "(λx : A . f x) ((λy : A . y) a)";
+        assert_eq!(red(exp, Strategy::Normal, DEFAULT_FUEL)?, "f a");
+        assert_eq!(red(exp, Strategy::Value, DEFAULT_FUEL)?, "f a");
+        assert_eq!(
+            red(exp, Strategy::Head, DEFAULT_FUEL)?,
+            "f ((λy : A . y) a)"
+        );
+        let exp = "λz : A . (λx : A . x) z";
+        assert_eq!(red(exp, Strategy::Normal, DEFAULT_FUEL)?, "λz : A . z");
+        assert_eq!(red(exp, Strategy::Value, DEFAULT_FUEL)?, exp);
+        assert_eq!(red(exp, Strategy::Head, DEFAULT_FUEL)?, exp);
+        let exp = "(λx : A . a) ((λy : A . y) a)";
+        assert_eq!(red(exp, Strategy::Normal, 1)?, "a");
+        assert!(red(exp, Strategy::Value, 1).is_err());
+        assert_eq!(red(exp, Strategy::Value, 2)?, "a");
+        assert_eq!(red(exp, Strategy::Head, 1)?, "a");
+        assert_eq!(
+            red(
+                "(λg : A → A . g) (λx : A . f ((λy : A . y) x))",
+                Strategy::Head,
+                9
+            )?,
+            "λx : A . f ((λy : A . y) x)"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_exp_norm_0002() -> Result<(), Box<dyn Error>> {
+        let mut ctx = Ctx::new();
+        ctx.put(&Var::new("A"), &Exp::get_type_meta())?;
+        ctx.put(&Var::new("a"), &"A".parse()?)?;
+        let exp = "(λx : A . x) ((λx : A . x) a)".parse::<Exp>()?;
+        for fuel in [0, 1] {
+            assert_eq!(
+                exp.clone().reduce_with(&ctx, Strategy::Normal, fuel),
+                Err(TypingErr::from(ReductionBudgetErr::new(&exp, fuel)))
+            );
+        }
+        assert_eq!(
+            exp.clone()
+                .reduce_with(&ctx, Strategy::Normal, 2)?
+                .to_string(),
+            "a"
+        );
+        assert_eq!(
+            exp.reduce_with(&ctx, Strategy::Normal, DEFAULT_FUEL)?
+                .to_string(),
+            "a"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_exp_norm_0005() -> Result<(), Box<dyn Error>> {
+        let mut ctx = Ctx::new();
+        ctx.put(&Var::new("A"), &Exp::get_type_meta())?;
+        ctx.put(&Var::new("a"), &"A".parse()?)?;
+        let exp = "(λx : A . x) ((λx : A . x) ((λx : A . x) a))".parse::<Exp>()?;
+        ctx.set_fuel(2);
+        assert!(matches!(
+            exp.clone().reduce(&ctx),
+            Err(TypingErr::ReductionBudgetErr(ReductionBudgetErr {
+                fuel: 2,
+                ..
+            }))
+        ));
+        ctx.set_fuel(3);
+        assert_eq!(exp.reduce(&ctx)?.to_string(), "a");
+        Ok(())
+    }
+
+    #[test]
+    fn test_exp_norm_0006() -> Result<(), Box<dyn Error>> {
+        let mut ctx = Ctx::new();
+        ctx.put(&Var::new("A"), &Exp::get_type_meta())?;
+        ctx.put(&Var::new("a"), &"A".parse()?)?;
+        let red =
+            |src: &str| -> Result<Exp, Box<dyn Error>> { Ok(src.parse::<Exp>()?.reduce(&ctx)?) };
+        // a free variable substituted under a binder continues to refer to its own binder
+        let exp = red("λz : A . (λx : A . λw : A . x) z")?;
+        assert_eq!(exp, "λz : A . λw : A . z".parse()?);
+        // ...even if it is substituted under a binder of the same name, which is renamed
+        let exp = red("λy : A . (λx : A . λy : A . x) y")?;
+        assert_eq!(exp.to_string(), "λy : A . λy' : A . y");
+        assert_eq!(exp, "λy : A . λy' : A . y".parse()?);
+        let exp = red("λy : A . (λx : A . λy : A . λy' : A . x) y")?;
+        assert_eq!(exp.to_string(), "λy : A . λy'' : A . λy' : A . y");
+        // symbols are not captured by binders either
+        assert_eq!(red("(λx : A . λa : A . x) a")?.to_string(), "λa' : A . a");
+        // nor are they captured in the types of binders, which refer to variables by name
+        let exp = red("(λX : * . λA : * . λz : X . z) A")?;
+        assert_eq!(exp.to_string(), "λA' : * . λz : A . z");
+        assert_eq!(exp.infer(&ctx)?.to_string(), "* → A → A");
+        // substitution stops at binders that shadow the variable
+        assert_eq!(red("(λx : A . λx : A . x) a")?.to_string(), "λx : A . x");
+        let exp = red("(λX : * . λX : * . λz : X . z) A")?;
+        assert_eq!(exp.to_string(), "λX : * . λz : X . z");
+        // a bound variable substituted into the types of nested binders is referred to by name
+        let exp = red("λB : * . (λX : * . λf : X → X . λx : X . f) B")?;
+        assert_eq!(exp.to_string(), "λB : * . λf : B → B . λx : B . f");
+        assert_eq!(exp.infer(&ctx)?.to_string(), "ΠB : * . (B → B) → B → B → B");
+        Ok(())
+    }
+
+    #[test]
+    fn test_exp_norm_0007() -> Result<(), Box<dyn Error>> {
+        let ctx = Ctx::new();
+        let typ = |src: &str| -> Result<String, Box<dyn Error>> {
+            Ok(src.parse::<Exp>()?.infer(&ctx)?.to_string())
+        };
+        let red = |src: &str, fuel: usize| -> Result<String, Box<dyn Error>> {
+            let exp = src.parse::<Exp>()?;
+            Ok(exp.reduce_with(&ctx, Strategy::Normal, fuel)?.to_string())
+        };
+        assert_eq!(typ("ℕ")?, "*");
+        assert_eq!(typ("add 2n (mul 3n 4n)")?, "ℕ");
+        assert!(typ("suc 2").is_err());
+        assert!(typ("mul 2n ℕ").is_err());
+        // arithmetic on literals takes a single step, however large the operands
+        assert_eq!(red("suc (suc 0n)", 3)?, "2n");
+        assert_eq!(red("add 2n (mul 3n 4n)", 3)?, "14n");
+        assert_eq!(red("mul 100000n 100000n", 2)?, "10000000000n");
+        // arithmetic that overflows the machine integers fails, rather than being left unreduced
+        for src in ["add {}n 1n", "mul {}n 2n", "suc {}n", "add 1n (mul 2n {}n)"] {
+            let err = (src.replace("{}", &usize::MAX.to_string()).parse::<Exp>()?)
+                .reduce_with(&ctx, Strategy::Normal, 3)
+                .unwrap_err();
+            assert!(matches!(err, TypingErr::ReductionOverflowErr(_)));
+        }
+        assert_eq!(
+            red(&format!("mul {}n 1n", usize::MAX), 2)?,
+            format!("{}n", usize::MAX)
+        );
+        // induction unfolds literals and successors alike
+        let dbl = "Nat_rec (λn : ℕ . ℕ) 0n (λn : ℕ . λr : ℕ . add r 2n)";
+        assert_eq!(typ(&format!("λm : ℕ . {} m", dbl))?, "ℕ → ℕ");
+        assert_eq!(red(&format!("{} 5n", dbl), 100)?, "10n");
+        assert_eq!(red(&format!("{} (suc (suc 0n))", dbl), 100)?, "4n");
+        assert!(typ(&format!("{} ℕ", dbl)).is_err());
+        assert!(typ("Nat_rec (λn : ℕ . ℕ) ℕ (λn : ℕ . λr : ℕ . r) 0n").is_err());
+        let vec = "Nat_rec (λn : ℕ . *) ⊤ (λn : ℕ . λt : * . Σx : ℕ . t) 2n";
+        assert_eq!(red(vec, 100)?, "Σx : ℕ . Σx : ℕ . ⊤");
+        Ok(())
+    }
+
+    #[test]
+    fn test_exp_norm_0008() -> Result<(), Box<dyn Error>> {
+        let mut ctx = Ctx::new();
+        ctx.put(&Var::new("A"), &Exp::get_type_meta())?;
+        ctx.put(&Var::new("a"), &"A".parse()?)?;
+        let typ = |src: &str, ctx: &Ctx| -> Result<String, Box<dyn Error>> {
+            Ok(src.parse::<Exp>()?.infer(ctx)?.to_string())
+        };
+        assert_eq!(typ("{x : ℕ, y : A}", &ctx)?, "*");
+        assert_eq!(typ("{x : *, y : A}", &ctx)?, "□");
+        assert_eq!(typ("{x = 1n, y = a}", &ctx)?, "{x : ℕ, y : A}");
+        assert_eq!(typ("{x = 1n, y = a}.y", &ctx)?, "A");
+        assert_eq!(
+            typ("λp : {x : ℕ, y : A} . p.x", &ctx)?,
+            "{x : ℕ, y : A} → ℕ"
+        );
+        assert_eq!(typ("{p = {q = a}}.p.q", &ctx)?, "A");
+        assert!(matches!(
+            "{x = a}.y".parse::<Exp>()?.infer(&ctx),
+            Err(TypingErr::TypeFieldErr(_))
+        ));
+        assert!(typ("a.x", &ctx).is_err());
+        assert!(typ("{x : a}", &ctx).is_err());
+        let err = "{x = (), x = 1n}".parse::<Exp>()?.infer(&ctx).unwrap_err();
+        assert!(matches!(&err, TypingErr::TypeDupFieldErr(e) if e.fld == Var::new("x")));
+        assert_eq!(err.culprit(), Some(Exp::Lit(1)));
+        // records are checked field by field against their expected type
+        let swap = "λp : {x : ℕ, y : ℕ} . {x = p.y, y = p.x}";
+        ctx.define(
+            &Var::new("swap"),
+            &swap.parse()?,
+            &"{x : ℕ, y : ℕ} → {x : ℕ, y : ℕ}".parse()?,
+        )?;
+        let exp: Exp = "swap {x = 1n, y = add 1n 1n}".parse()?;
+        assert_eq!(exp.reduce(&ctx)?.to_string(), "{x = 2n, y = 1n}");
+        let exp: Exp = "(swap (swap {x = 3n, y = 4n})).x".parse()?;
+        assert_eq!(exp.reduce(&ctx)?.to_string(), "3n");
+        assert!(ctx
+            .define(
+                &Var::new("bad"),
+                &"{y = 1n, x = 1n}".parse()?,
+                &"{x : ℕ, y : ℕ}".parse()?
+            )
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_exp_walk_0001() -> Result<(), Box<dyn Error>> {
+        // collect the symbols in an expression, along with the binders in scope for each
+        #[derive(Default)]
+        struct Symbols(Vec<Var>, Vec<String>);
+        impl Visitor for Symbols {
+            fn visit(&mut self, exp: &Exp) {
+                match exp {
+                    Exp::Var(VarIdx::Var(var)) => self.1.push(format!(
+                        "{} ⊢ {}",
+                        self.0
+                            .iter()
+                            .map(Var::to_string)
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                        var
+                    )),
+                    _ => walk(self, exp),
+                }
+            }
+            fn enter(&mut self, var: &Var) {
+                self.0.push(var.clone());
+            }
+            fn leave(&mut self, _: &Var) {
+                self.0.pop();
+            }
+        }
+        let mut vis = Symbols::default();
+        vis.visit(&"f (λx : A . Πy : x . g x y) (Σz : B . h z)".parse()?);
+        assert_eq!(
+            vis.1,
+            vec![" ⊢ f", " ⊢ A", "x ⊢ x", "x y ⊢ g", " ⊢ B", "z ⊢ h"]
+        );
+        // rename every symbol, using the positions of sub-expressions to skip binder types
+        fn upper(exp: Exp, pos: Pos) -> Exp {
+            match (exp, pos) {
+                (exp, Pos::Type(_)) => exp,
+                (Exp::Var(VarIdx::Var(var)), _) => Exp::new_var(Var(var.0.to_uppercase())),
+                (exp, _) => exp.map_subterms(upper),
+            }
+        }
+        let exp = upper("f (λx : a . g x) b".parse()?, Pos::Arg);
+        assert_eq!(exp.to_string(), "F (λx : a . G x) B");
+        let exp: Exp = "λx : A . λy : B . x y z".parse()?;
+        assert_eq!(exp.fold(0, |n, _, _| n + 1), 2);
+        assert!(!exp.any_subterm(|exp, _| exp == &Exp::new_var(Var::new("z"))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_exp_walk_0002() -> Result<(), Box<dyn Error>> {
+        let vars = |exp: &Exp| {
+            let mut vars: Vec<String> = exp.free_vars().iter().map(Var::to_string).collect();
+            vars.sort();
+            vars
+        };
+        let exp: Exp = "λx : A . Πy : x . f x y z".parse()?;
+        assert_eq!(vars(&exp), vec!["A", "f", "z"]);
+        assert!(!exp.is_closed());
+        let binders: Vec<String> = exp
+            .binders()
+            .iter()
+            .map(|(var, typ)| format!("{} : {}", var, typ))
+            .collect();
+        assert_eq!(binders, vec!["x : A", "y : x"]);
+        let exp: Exp = "λA : * . λx : A . Σy : A . x".parse()?;
+        assert!(exp.is_closed());
+        assert_eq!(exp.binders().len(), 3);
+        // a binder only binds the name within its body, not within its own type
+        assert_eq!(vars(&"λA : A . A".parse()?), vec!["A"]);
+        // the body of a binder is open if it refers to the binder
+        let Exp::Abs(_, _, body) = "λx : A . λy : A . x".parse()? else {
+            unreachable!()
+        };
+        assert_eq!(vars(&body), vec!["A", "x"]);
+        assert!(Exp::get_kind_meta().is_closed());
+        Ok(())
+    }
+
+    #[test]
+    fn test_exp_walk_0003() -> Result<(), Box<dyn Error>> {
+        let metrics = |src: &str| -> Result<_, Box<dyn Error>> {
+            let exp: Exp = src.parse()?;
+            Ok((exp.size(), exp.depth(), exp.count_redexes()))
+        };
+        assert_eq!(metrics("x")?, (1, 1, 0));
+        assert_eq!(metrics("f x y")?, (5, 3, 0));
+        assert_eq!(metrics("(λx : A . x) y")?, (5, 3, 1));
+        assert_eq!(
+            metrics("λy : A . (λx : A . x) ((λz : A . z) y)")?,
+            (11, 5, 2)
+        );
+        assert_eq!(metrics("add 1n (mul 2n 3n)")?, (5, 3, 1));
+        assert_eq!(metrics("{x = fst (a, b : A), y = ()}.x")?, (8, 5, 2));
+        assert_eq!(metrics("add 18446744073709551615n 1n")?, (3, 2, 0));
+        // the normal form has no redexes left, and is usually (though not always) smaller
+        let exp: Exp = "(λx : * . λy : x . y) ⊤ ()".parse()?;
+        let red = exp.clone().reduce(&Ctx::new())?;
+        assert_eq!((exp.count_redexes(), red.count_redexes()), (1, 0));
+        assert!(red.size() < exp.size());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_exp_serde_0001() -> Result<(), Box<dyn Error>> {
+        let exp: Exp = "λA : * . λx : A . f x".parse()?;
+        let json = serde_json::to_string(&exp)?;
+        assert_eq!(serde_json::from_str::<Exp>(&json)?, exp);
+        let json = serde_json::to_string(&Exp::new_var(Var::new("x")))?;
+        assert_eq!(json, r#"{"Var":{"Var":"x"}}"#);
+        let mut ctx = Ctx::new();
+        ctx.declare(&Data::new(
+            Var::new("Bool"),
+            vec![
+                Ctor::new(Var::new("true"), vec![]),
+                Ctor::new(Var::new("false"), vec![]),
+            ],
+        ))?;
+        ctx.set_strategy(Strategy::Head);
+        let mut ctx: Ctx = serde_json::from_str(&serde_json::to_string(&ctx)?)?;
+        assert_eq!(ctx.get(&Var::new("true"))?, &"Bool".parse()?);
+        assert!(ctx.get_data(&Var::new("Bool")).is_some());
+        assert_eq!(ctx.get_strategy(), Strategy::Head);
+        // a context that is not well-formed is rejected as it is loaded
+        let json = serde_json::to_string(&ctx)?.replace(r#"{"Var":"Bool"}"#, r#"{"Var":"Boo"}"#);
+        assert!(serde_json::from_str::<Ctx>(&json).is_err());
+        assert!(Ctx::from_reader(json.as_bytes()).is_err());
+        // a context written out is read back as it was
+        let mut buf = Vec::new();
+        ctx.define(&Var::new("b"), &"true".parse()?, &"Bool".parse()?)?;
+        ctx.to_writer(&mut buf)?;
+        let ctx = Ctx::from_reader(buf.as_slice())?;
+        assert_eq!(ctx.get_def(&Var::new("b")), Some(&"true".parse()?));
+        assert_eq!(ctx.iter().count(), 5); // Bool, its constructors and eliminator, and b
+        Ok(())
+    }
+
+    #[test]
+    fn test_exp_rend_0028() -> Result<(), Box<dyn Error>> {
+        let exp: Exp = "λA : * . λf : A → A . λg : A → A . λx : A . f (g (f x))".parse()?;
+        let opts = PrintOptions::default();
+        assert_eq!(exp.pretty(&opts), exp.to_string());
+        let opts = PrintOptions {
+            width: 28,
+            ..Default::default()
+        };
+        assert_eq!(
+            exp.pretty(&opts),
+            "λA : * .\n  λf : A → A .\n    λg : A → A .\n      λx : A . f (g (f x))"
+        );
+        let opts = PrintOptions {
+            width: 20,
+            indent: 3,
+            ascii: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            exp.pretty(&opts),
+            "\\A : Type .\n   \\f : A -> A .\n      \\g : A -> A .\n         \\x : A .\n            f\n               (g\n                  (f\n                     x))"
+        );
+        let exp: Exp = "f (g a b) (λx : A → B . h x y)".parse()?;
+        let opts = PrintOptions {
+            parens: Parens::Explicit,
+            ..Default::default()
+        };
+        assert_eq!(
+            exp.pretty(&opts),
+            "(f ((g a) b)) (λx : (A → B) . ((h x) y))"
+        );
+        assert_eq!(
+            format!("{}", exp.pretty(&opts).parse::<Exp>()?),
+            exp.to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_exp_unify_0001() -> Result<(), Box<dyn Error>> {
+        let mut ctx = Ctx::new();
+        ctx.put(&Var::new("A"), &Exp::get_type_meta())?;
+        ctx.put(&Var::new("a"), &"A".parse()?)?;
+        ctx.put(&Var::new("id"), &"ΠX : * . X → X".parse()?)?;
+        let typ = Exp::get_type_meta();
+        let m = ctx.fresh_meta(&typ);
+        assert_eq!(m.to_string(), "?0");
+        unify(&m, &"A".parse()?, &ctx)?;
+        assert_eq!(m.clone().zonk(&ctx), "A".parse()?);
+        assert_eq!(ctx.get_unsolved(), vec![]);
+        // a metavariable is solved while type-checking the application that constrains it
+        let m = ctx.fresh_meta(&typ);
+        let exp = Exp::new_app(Exp::new_app("id".parse()?, m.clone()), "a".parse()?);
+        assert_eq!(exp.infer(&ctx)?, "A".parse()?);
+        assert_eq!(m.zonk(&ctx), "A".parse()?);
+        // solutions may be neither cyclic, nor dependent on bound variables, nor ill-typed
+        let m = ctx.fresh_meta(&typ);
+        let arr = Exp::new_arr(m.clone(), "A".parse()?);
+        assert!(matches!(
+            unify(&m, &arr, &ctx),
+            Err(TypingErr::TypeOccursErr(_))
+        ));
+        let abs = |exp: Exp| Exp::new_abs(Var::new("x"), "A".parse().unwrap(), exp);
+        let n = ctx.fresh_meta(&"A".parse()?);
+        assert!(unify(&abs(n.clone()), &abs(Exp::new_var(Var::new("x"))), &ctx).is_err());
+        unify(&abs(n.clone()), &abs("a".parse()?), &ctx)?;
+        assert_eq!(n.zonk(&ctx), "a".parse()?);
+        assert!(unify(&ctx.fresh_meta(&"A".parse()?), &"A".parse()?, &ctx).is_err());
+        assert_eq!(ctx.get_unsolved().len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_exp_occurs_0001() -> Result<(), Box<dyn Error>> {
+        let pol = |src: &str| -> Result<Polarity, Box<dyn Error>> {
+            Ok(positivity(&Var::new("X"), &src.parse()?))
+        };
+        assert_eq!(pol("A → B")?, Polarity::Absent);
+        assert_eq!(pol("ΠX : * . X → X")?, Polarity::Absent);
+        assert_eq!(pol("X")?, Polarity::Strict);
+        assert_eq!(pol("A → Sum X {x : X}")?, Polarity::Strict);
+        assert_eq!(pol("(X → A) → B")?, Polarity::Positive);
+        assert_eq!(pol("X → A")?, Polarity::Negative);
+        assert_eq!(pol("X → X")?, Polarity::Mixed);
+        assert_eq!(pol("A → F X")?, Polarity::Mixed);
+        // a metavariable may not be solved with an expression in which it occurs
+        let ctx = Ctx::new();
+        let m = ctx.fresh_meta(&Exp::get_type_meta());
+        let Exp::Meta(id) = m else { unreachable!() };
+        let arr = Exp::new_arr(Exp::Unit, m.clone());
+        assert!(occurs(id, &arr) && !occurs(id, &Exp::Unit));
+        let err = unify(&arr, &m, &ctx).unwrap_err();
+        assert_eq!(err, TypingErr::from(TypeOccursErr::new(id, &arr)));
+        assert!(err.to_string().contains("?0 occurs in the expression"));
+        let mut ctx = Ctx::new();
+        ctx.put(&Var::new("N"), &Exp::get_type_meta())?;
+        let bad = Data::new(
+            Var::new("Bad"),
+            vec![Ctor::new(Var::new("mk"), vec!["(Bad → N) → N".parse()?])],
+        );
+        let err = ctx.declare(&bad).unwrap_err().to_string();
+        assert!(err.contains("Bad occurs in a position that is not strictly positive"));
+        let bad = Data::new(
+            Var::new("Bad"),
+            vec![Ctor::new(Var::new("mk"), vec!["Bad → N".parse()?])],
+        );
+        let err = ctx.declare(&bad).unwrap_err().to_string();
+        assert!(err.contains("Bad occurs in a negative position"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_exp_unify_0002() -> Result<(), Box<dyn Error>> {
+        let mut ctx = Ctx::new();
+        ctx.put(&Var::new("A"), &Exp::get_type_meta())?;
+        ctx.put(&Var::new("a"), &"A".parse()?)?;
+        ctx.put(&Var::new("B"), &Exp::get_type_meta())?;
+        ctx.put(&Var::new("b"), &"B".parse()?)?;
+        ctx.put(&Var::new("id"), &"Π{X : *} . X → X".parse()?)?;
+        ctx.put(&Var::new("k"), &"Π{X : *} . Π{Y : *} . X → Y → X".parse()?)?;
+        // implicit arguments are inserted ahead of explicit ones, and solved by unification
+        let exp = "id a".parse::<Exp>()?.elaborate(&ctx);
+        assert_eq!(exp.to_string(), "id ?0 a");
+        assert_eq!(exp.infer(&ctx)?, "A".parse()?);
+        assert_eq!(exp.zonk(&ctx).to_string(), "id A a");
+        let exp = "k b a".parse::<Exp>()?.elaborate(&ctx);
+        assert_eq!(exp.infer(&ctx)?.zonk(&ctx), "B".parse()?);
+        assert_eq!(exp.zonk(&ctx).to_string(), "k B A b a");
+        // a function that is not applied is left as it is, and has its implicit type
+        let exp = "id".parse::<Exp>()?.elaborate(&ctx);
+        assert_eq!(exp.infer(&ctx)?.to_string(), "Π{X : *} . X → X");
+        // an implicit abstraction has an implicit Π type, and reduces as usual
+        let abs: Exp = "λ{X : *} . λx : X . x".parse()?;
+        assert_eq!(abs.infer(&ctx)?.to_string(), "Π{X : *} . X → X");
+        let exp = Exp::new_app(abs, "b".parse()?).elaborate(&ctx);
+        assert_eq!(exp.reduce(&ctx)?.zonk(&ctx), "b".parse()?);
+        assert!("id A".parse::<Exp>()?.elaborate(&ctx).infer(&ctx).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_exp_intern_0001() -> Result<(), Box<dyn Error>> {
+        let mut arn = Arena::new();
+        assert!(arn.is_empty());
+        let exp: Exp = "f (g a) (g a)".parse()?;
+        let id = arn.intern(exp.clone());
+        assert_eq!(arn.len(), 6); // f, g, a, g a, f (g a), and the whole expression
+        assert_eq!(arn.get(id), exp);
+        assert_eq!(arn.intern("f (g a) (g a)".parse()?), id);
+        let node = arn.node(id).clone();
+        assert_eq!(arn.node(node.kids[0]).kids[1], node.kids[1]);
+        assert_ne!(arn.intern("f (g a) (g b)".parse()?), id);
+        let exp: Exp = "λA : * . λx : A . x".parse()?;
+        let len = arn.len();
+        let id = arn.intern(exp.clone());
+        assert_eq!(arn.get(id), exp);
+        assert_eq!(arn.intern(exp), id);
+        assert_eq!(arn.len(), len + 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_exp_intern_0002() -> Result<(), Box<dyn Error>> {
+        // equality up to the names of bound variables, and cumulativity of universes
+        let mut arn = Arena::new();
+        let fst = arn.intern("λA : * . λx : ⊤ . A".parse()?);
+        let snd = arn.intern("λB : * . λy : ⊤ . B".parse()?);
+        assert_ne!(fst, snd);
+        assert!(arn.alpha_eq(fst, snd));
+        let oth = arn.intern("λA : * . λx : ⊤ . x".parse()?);
+        assert!(!arn.alpha_eq(fst, oth));
+        let fst = arn.intern("Π A : * . *".parse()?);
+        let snd = arn.intern("Π B : * . □".parse()?);
+        assert!(arn.is_subtype(fst, snd));
+        assert!(!arn.is_subtype(snd, fst));
+        Ok(())
+    }
+
     #[test]
     fn test_type_calculation_001() -> Result<(), Box<dyn Error>> {
         let mut ctx = Ctx::new();
-        ctx.put(&Var::new("w"), &Exp::TypeMeta)?;
+        ctx.put(&Var::new("w"), &Exp::get_type_meta())?;
         // λx : * . λm : * . w
         let can = Exp::new_abs(
             Var::new("x"),
@@ -444,8 +1399,335 @@ mod test {
             Exp::get_type_meta(),
             Exp::new_for(Var::new("m"), Exp::get_type_meta(), Exp::get_type_meta()),
         );
-        let act = can.calculate_type(&ctx)?;
+        let act = can.infer(&ctx)?;
         assert_eq!(act, typ);
         Ok(())
     }
+
+    #[test]
+    fn test_ctx_0001() -> Result<(), Box<dyn Error>> {
+        let mut ctx = Ctx::new();
+        for (var, typ) in [("B", "*"), ("A", "*"), ("b", "B"), ("a", "A")] {
+            ctx.put(&Var::new(var), &typ.parse()?)?;
+        }
+        ctx.define(&Var::new("f"), &"λx : A . b".parse()?, &"A → B".parse()?)?;
+        let vars = |ctx: &Ctx| {
+            ctx.iter()
+                .map(|(var, typ)| format!("{} : {}", var, typ))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            vars(&ctx),
+            vec!["B : *", "A : *", "b : B", "a : A", "f : A → B"]
+        );
+        // later bindings shadow earlier ones, which reappear once the later ones are removed
+        let inner = ctx.extend(&Var::new("a"), &"B".parse()?);
+        assert_eq!(inner.get(&Var::new("a"))?, &"B".parse()?);
+        assert_eq!(vars(&inner).last().map(String::as_str), Some("a : B"));
+        assert_eq!(
+            inner.subtract(&Var::new("a"))?.get(&Var::new("a"))?,
+            &"A".parse()?
+        );
+        assert!(ctx
+            .subtract(&Var::new("a"))?
+            .subtract(&Var::new("a"))
+            .is_err());
+        let inner = ctx.extend(&Var::new("f"), &"A".parse()?);
+        assert_eq!(inner.get_def(&Var::new("f")), None);
+        let outer = inner.subtract(&Var::new("f"))?;
+        assert_eq!(
+            outer.get_def(&Var::new("f")).map(Exp::to_string),
+            Some("λx : A . b".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_ctx_0002() -> Result<(), Box<dyn Error>> {
+        let mut ctx = Ctx::new();
+        ctx.put(&Var::new("A"), &Exp::get_type_meta())?;
+        ctx.define(&Var::new("a"), &"λx : A . x".parse()?, &"A → A".parse()?)?;
+        ctx.validate()?;
+        assert!(ctx.extend(&Var::new("B"), &"A".parse()?).validate().is_ok());
+        // each type may only refer to the variables before it
+        let bad = Ctx::new()
+            .extend(&Var::new("x"), &"A".parse()?)
+            .extend(&Var::new("A"), &Exp::get_type_meta());
+        assert!(matches!(bad.validate(), Err(TypingErr::TypeUnknownErr(_))));
+        assert!(ctx
+            .extend(&Var::new("y"), &"A A".parse()?)
+            .validate()
+            .is_err());
+        assert!(ctx
+            .extend(&Var::new("z"), &"Type 1 → Type 1".parse()?)
+            .validate()
+            .is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ctx_0003() -> Result<(), Box<dyn Error>> {
+        let mut ctx = Ctx::new();
+        ctx.put(&Var::new("A"), &Exp::get_type_meta())?;
+        // long telescopes are extended (and dropped) without copying (or recursing)
+        let deep = (0..100_000).fold(ctx.clone(), |ctx, i| {
+            ctx.extend(&Var::new(&format!("x{}", i)), &Exp::new_var(Var::new("A")))
+        });
+        assert_eq!(deep.iter().count(), 100_001);
+        assert_eq!(deep.get(&Var::new("x0"))?, &"A".parse()?);
+        let inner = deep.subtract(&Var::new("x99999"))?;
+        assert_eq!(inner.iter().count(), 100_000);
+        assert!(inner.get(&Var::new("x99999")).is_err());
+        drop(deep);
+        assert_eq!(inner.get(&Var::new("x99998"))?, &"A".parse()?);
+        // contexts extended from one another are unaffected by changes to either
+        let mut outer = ctx.extend(&Var::new("a"), &"A".parse()?);
+        outer.define(&Var::new("b"), &"a".parse()?, &"A".parse()?)?;
+        assert!(ctx.get(&Var::new("b")).is_err());
+        assert_eq!(outer.subtract(&Var::new("a"))?.iter().count(), 2);
+        assert_eq!(outer.get_def(&Var::new("b")), Some(&"a".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn test_ctx_0004() -> Result<(), Box<dyn Error>> {
+        let vars = |ctx: &Ctx| ctx.iter().map(|(v, _)| v.to_string()).collect::<Vec<_>>();
+        let mut ctx = Ctx::new();
+        ctx.put(&Var::new("A"), &Exp::get_type_meta())?;
+        ctx.put(&Var::new("B"), &Exp::get_type_meta())?;
+        ctx.put(&Var::new("P"), &"A → *".parse()?)?;
+        ctx.put(&Var::new("a"), &"A".parse()?)?;
+        ctx.put(&Var::new("p"), &"P a".parse()?)?;
+        ctx.define(&Var::new("b"), &"a".parse()?, &"A".parse()?)?;
+        // the prefix before a variable is the context in which its type was formed
+        assert_eq!(
+            vars(&ctx.prefix_before(&Var::new("p"))?),
+            ["A", "B", "P", "a"]
+        );
+        assert!(ctx.prefix_before(&Var::new("q")).is_err());
+        let inner = ctx.extend(&Var::new("a"), &"B".parse()?);
+        assert_eq!(
+            inner.prefix_before(&Var::new("a"))?.get(&Var::new("a"))?,
+            &"A".parse()?
+        );
+        // a restricted context keeps the bindings that the given variables depend on
+        assert_eq!(vars(&ctx.restrict([&Var::new("p")])?), ["A", "P", "a", "p"]);
+        assert_eq!(vars(&ctx.restrict([&Var::new("b")])?), ["A", "a", "b"]);
+        assert_eq!(vars(&inner.restrict([&Var::new("a")])?), ["B", "a"]);
+        assert!(ctx.restrict([&Var::new("q")]).is_err());
+        let exp: Exp = "p".parse()?;
+        let sub = ctx.restrict(&exp.free_vars())?;
+        sub.validate()?;
+        assert_eq!(exp.infer(&sub)?, exp.infer(&ctx)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ctx_0005() -> Result<(), Box<dyn Error>> {
+        let pairs = |vars: &[(Var, Exp)]| {
+            (vars.iter())
+                .map(|(v, t)| format!("{} : {}", v, t))
+                .collect::<Vec<_>>()
+        };
+        let mut ctx = Ctx::new();
+        ctx.put(&Var::new("A"), &Exp::Sort(0))?;
+        ctx.put(&Var::new("a"), &"A".parse()?)?;
+        let mut upd = ctx.extend(&Var::new("a"), &"A → A".parse()?);
+        upd.put(&Var::new("b"), &"A".parse()?)?;
+        let dif = ctx.diff(&upd);
+        assert_eq!(pairs(&dif.added), ["b : A"]);
+        assert!(dif.removed.is_empty());
+        assert_eq!(
+            dif.changed,
+            [(Var::new("a"), "A".parse()?, "A → A".parse()?)]
+        );
+        assert_eq!(dif.to_string(), "+ b : A\n~ a : A ≠ A → A\n");
+        assert_eq!(pairs(&upd.diff(&ctx).removed), ["b : A"]);
+        assert!(ctx.diff(&ctx.clone()).is_empty());
+        // a variable that is bound too late is reported along with where it is bound
+        let bad = Ctx::new()
+            .extend(&Var::new("x"), &"A".parse()?)
+            .extend(&Var::new("A"), &Exp::Sort(0));
+        let Err(TypingErr::TypeUnknownErr(err)) = bad.validate() else {
+            panic!("expected the context to be rejected")
+        };
+        assert_eq!(pairs(&err.dif.added), ["A : *"]);
+        assert_eq!(
+            err.to_string(),
+            ":type A = ?\n    ↯ the context expected differs from the one in scope by\n    + A : *\n"
+        );
+        assert!(TypeUnknownErr::new(&Var::new("A")).dif.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ctx_0006() -> Result<(), Box<dyn Error>> {
+        let var = Var::new("map").qualify(&Var::new("data.list"));
+        assert_eq!(var, Var::new("data.list.map"));
+        assert_eq!(var.namespace(), Some(Var::new("data.list")));
+        assert_eq!(var.base(), "map");
+        assert!(var.is_within(&Var::new("data")) && var.is_within(&Var::new("data.list")));
+        assert!(!var.is_within(&Var::new("dat")) && !var.is_within(&var));
+        assert_eq!(Var::new("map").namespace(), None);
+        let mut ctx = Ctx::new();
+        ctx.put(&Var::new("A"), &Exp::Sort(0))?;
+        ctx.put(&Var::new("a"), &"A".parse()?)?;
+        ctx.define(&Var::new("nat.id"), &"λx . x".parse()?, &"A → A".parse()?)?;
+        ctx.put(&Var::new("fin.id"), &"A → A".parse()?)?;
+        let typ = |src: &str, ctx: &Ctx| -> Result<String, Box<dyn Error>> {
+            let exp: Exp = src.parse()?;
+            Ok(exp.elaborate(ctx).infer(ctx)?.to_string())
+        };
+        // qualified names are written with a `.`, like projections, but resolve to variables
+        assert_eq!(typ("nat.id a", &ctx)?, "A");
+        assert!(typ("id a", &ctx).is_err());
+        assert!(ctx.open(&Var::new("int")).is_err());
+        ctx.open(&Var::new("nat"))?;
+        assert_eq!(
+            "id a".parse::<Exp>()?.elaborate(&ctx),
+            "nat.id a".parse::<Exp>()?.elaborate(&ctx)
+        );
+        assert_eq!(
+            "id a".parse::<Exp>()?.elaborate(&ctx).reduce(&ctx)?,
+            "a".parse()?
+        );
+        // namespaces opened later take precedence, and bound variables shadow opened names
+        ctx.open(&Var::new("fin"))?;
+        assert_eq!(
+            "id".parse::<Exp>()?.elaborate(&ctx),
+            Exp::new_var(Var::new("fin.id"))
+        );
+        assert_eq!(typ("λid : A . id", &ctx)?, "A → A");
+        let inner = ctx.extend(&Var::new("id"), &"A".parse()?);
+        assert_eq!(typ("id", &inner)?, "A");
+        // projections from a bound record are left as they are
+        let inner = ctx.extend(&Var::new("nat"), &"{id : A}".parse()?);
+        assert_eq!(typ("nat.id", &inner)?, "A");
+        // a qualified name that is not bound is reported in full, rather than by its namespace
+        let err = "nat.two".parse::<Exp>()?.elaborate(&ctx).infer(&ctx);
+        assert!(matches!(err, Err(TypingErr::TypeUnknownErr(e)) if e.var == Var::new("nat.two")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_ctx_0007() -> Result<(), Box<dyn Error>> {
+        let mut ctx = Ctx::new();
+        ctx.put(&Var::new("A"), &Exp::Sort(0))?;
+        let Exp::Meta(id) = ctx.fresh_meta(&Exp::Sort(0)) else {
+            panic!("a fresh metavariable is expected");
+        };
+        // the context may be handed to another thread (such as the one that evaluates a command),
+        // which shares its metavariables with the copies left behind
+        let copy = ctx.clone();
+        let typ = std::thread::spawn(move || {
+            ctx.solve_meta(id, Exp::new_var(Var::new("A")));
+            let exp: Exp = "λx : A . x".parse().unwrap();
+            exp.infer(&ctx).map(|typ| typ.to_string())
+        });
+        assert_eq!(typ.join().unwrap()?, "A → A");
+        assert_eq!(copy.get_meta(id), Some(Exp::new_var(Var::new("A"))));
+        assert!(copy.get_unsolved().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_exp_norm_0009() -> Result<(), Box<dyn Error>> {
+        let div = |fst: &str, snd: &str| -> Result<Option<(String, String)>, Box<dyn Error>> {
+            let (fst, snd) = (fst.parse::<Exp>()?, snd.parse::<Exp>()?);
+            Ok(fst
+                .diverge(&snd)
+                .map(|(one, two)| (one.to_string(), two.to_string())))
+        };
+        assert_eq!(div("λx : ℕ . f x", "λy : ℕ . f y")?, None);
+        assert_eq!(
+            div("f (g 1n) 2n", "f (g 3n) 4n")?,
+            Some(("1n".into(), "3n".into()))
+        );
+        assert_eq!(
+            div("{x = a}", "{y = a}")?,
+            Some(("{x = a}".into(), "{y = a}".into()))
+        );
+        assert_eq!(div("p.x", "q.y")?, Some(("p.x".into(), "q.y".into())));
+        assert_eq!(
+            div("fst p", "snd p")?,
+            Some(("fst p".into(), "snd p".into()))
+        );
+        // abstractions are compared with other expressions up to eta-conversion
+        assert_eq!(div("λx : ℕ . f x", "f")?, None);
+        assert_eq!(div("f", "λx : ℕ . f x")?, None);
+        assert_eq!(
+            div("λx : ℕ . f x x", "f")?,
+            Some(("f x".into(), "f".into()))
+        );
+        assert!(div("λx : ℕ . x", "f")?.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_exp_norm_0010() -> Result<(), Box<dyn Error>> {
+        let mut ctx = Ctx::new();
+        ctx.put(&Var::new("A"), &Exp::get_type_meta())?;
+        ctx.put(&Var::new("a"), &"A".parse()?)?;
+        let exp = "(λx : A . x) a".parse::<Exp>()?;
+        // the flag is shared with copies of the context, and checked before each step
+        let irq = ctx.get_limits().irq.clone();
+        irq.store(true, std::sync::atomic::Ordering::Relaxed);
+        assert!(ctx.isolated().get_limits().is_interrupted());
+        assert!(matches!(
+            exp.clone().reduce(&ctx).map_err(TypingErr::into_cause),
+            Err(TypingErr::InterruptErr(_))
+        ));
+        irq.store(false, std::sync::atomic::Ordering::Relaxed);
+        assert_eq!(exp.reduce(&ctx)?.to_string(), "a");
+        Ok(())
+    }
+
+    #[test]
+    fn test_type_calculation_011() -> Result<(), Box<dyn Error>> {
+        let mut ctx = Ctx::new();
+        ctx.put(&Var::new("A"), &Exp::get_type_meta())?;
+        ctx.put(&Var::new("a"), &"A".parse()?)?;
+        ctx.put(&Var::new("f"), &"A → A".parse()?)?;
+        // an error deep within a term is reported along with the steps that it arose within
+        let err = "λx : A . f (f (x, a : Σy : A . A))"
+            .parse::<Exp>()?
+            .infer(&ctx)
+            .unwrap_err();
+        let crumbs = (err.trail().iter())
+            .map(|crumb| format!("{} of {}", crumb.step, crumb.exp))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            crumbs,
+            [
+                "checking the argument of f (x, a : Σy : A . A)",
+                "checking the argument of f (f (x, a : Σy : A . A))",
+                "inferring the body of λx : A . f (f (x, a : Σy : A . A))",
+            ]
+        );
+        assert!(matches!(err.cause(), TypingErr::TypeCompatErr(_)));
+        assert_eq!(err.culprit(), err.cause().culprit());
+        assert!(err
+            .to_string()
+            .ends_with("    ↯ while inferring the body of λx : A . f (f (x, a : Σy : A . A))\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_type_calculation_012() -> Result<(), Box<dyn Error>> {
+        let mut ctx = Ctx::new();
+        ctx.put(&Var::new("A"), &Exp::get_type_meta())?;
+        ctx.put(&Var::new("a"), &"A".parse()?)?;
+        ctx.put(&Var::new("f"), &"A → A".parse()?)?;
+        // a deeply nested term is checked within the stack of a test thread, trail and all
+        let app = |arg| (0..100).fold(arg, |exp, _| Exp::new_app(Exp::new_var(Var::new("f")), exp));
+        assert_eq!(app(Exp::new_var(Var::new("a"))).infer(&ctx)?, "A".parse()?);
+        assert!(Exp::new_num(100).infer(&ctx).is_ok());
+        let err = app(Exp::new_var(Var::new("A"))).infer(&ctx).unwrap_err();
+        assert_eq!(err.trail().len(), 100);
+        assert!(err
+            .trail()
+            .iter()
+            .all(|crumb| crumb.step == "checking the argument"));
+        Ok(())
+    }
 }
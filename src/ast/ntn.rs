@@ -0,0 +1,80 @@
+//! User-defined notation for infix operators and related structures.
+
+use super::{Exp, Var};
+use std::fmt::{Display, Formatter};
+
+/// Largest precedence permitted for an infix operator (binding tightest).
+pub const MAX_PREC: usize = 9;
+
+/// Associativity of an infix operator, which determines how a chain of operators of equal
+/// precedence is grouped.
+#[derive(Debug, Clone, Copy, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Fixity {
+    /// Left-associative, declared with `infixl`, so that `a + b + c` means `(a + b) + c`.
+    #[default]
+    Infixl,
+    /// Right-associative, declared with `infixr`, so that `a + b + c` means `a + (b + c)`.
+    Infixr,
+    /// Non-associative, declared with `infix`, so that `a + b + c` is rejected.
+    Infix,
+}
+
+/// Declaration of an infix operator, which stands for the application of a variable to the
+/// operands on either side of it:
+///
+/// ```text
+/// infixr 5 "⊕" := plus
+/// ```
+///
+/// ...so that `a ⊕ b` denotes `plus a b`.
+#[derive(Debug, Clone, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Notation {
+    /// Associativity of the operator.
+    pub fix: Fixity,
+    /// Precedence of the operator, from `0` (binding loosest) to [MAX_PREC] (binding tightest).
+    pub prec: usize,
+    /// Symbol that denotes the operator.
+    pub opr: String,
+    /// Variable that the operator stands for.
+    pub var: Var,
+}
+
+impl Notation {
+    /// Create a new instance of notation.
+    pub fn new(fix: Fixity, prec: usize, opr: &str, var: Var) -> Self {
+        Notation {
+            fix,
+            prec,
+            opr: opr.to_string(),
+            var,
+        }
+    }
+
+    /// Create the [expression][Exp] denoted by this operator when applied to the given operands.
+    pub fn apply(&self, lhs: Exp, rhs: Exp) -> Exp {
+        Exp::new_app(Exp::new_app(Exp::new_var(self.var.clone()), lhs), rhs)
+    }
+}
+
+impl Display for Fixity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Infixl => write!(f, "infixl"),
+            Self::Infixr => write!(f, "infixr"),
+            Self::Infix => write!(f, "infix"),
+        }
+    }
+}
+
+/// Render the notation as the declaration that introduces it.
+impl Display for Notation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} \"{}\" := {}",
+            self.fix, self.prec, self.opr, self.var
+        )
+    }
+}
@@ -0,0 +1,74 @@
+//! Occurs-check and positivity analysis of expressions.
+
+use super::{Exp, MetaId, Pos, Var, VarIdx};
+
+/// Polarity of the occurrences of a variable within a type, as determined by [positivity].
+#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq)]
+pub enum Polarity {
+    /// The variable does not occur in the type.
+    Absent,
+    /// The variable occurs only in strictly positive positions, that is, never within the domain
+    /// of a Π type (as in `A → X` or `Sum X A`).
+    Strict,
+    /// The variable occurs only in positive positions, some of which are within the domain of a
+    /// Π type that is itself the domain of a Π type (as in `(X → A) → B`).
+    Positive,
+    /// The variable occurs only in negative positions, within the domain of a Π type (as in
+    /// `X → A`).
+    Negative,
+    /// The variable occurs in both positive and negative positions, or in the argument of an
+    /// application, whose polarity cannot be determined without knowing the function applied.
+    Mixed,
+}
+
+/// Check whether the given metavariable occurs in the given expression, in which case solving the
+/// metavariable with the expression would produce a cyclic (infinite) solution.
+pub fn occurs(id: MetaId, exp: &Exp) -> bool {
+    match exp {
+        Exp::Meta(m) => *m == id,
+        _ => exp.any_subterm(|exp, _| occurs(id, exp)),
+    }
+}
+
+/// Determine the [polarity][Polarity] of the (free) occurrences of the given variable within the
+/// given type.
+pub fn positivity(var: &Var, typ: &Exp) -> Polarity {
+    let mut pol = Polarity::Absent;
+    collect(var, typ, true, true, &mut pol);
+    pol
+}
+
+/// Combine the polarity of the occurrences found so far with that of another occurrence, which
+/// lies in a positive position (or else a negative one) that may or may not be strictly positive.
+fn combine(pol: Polarity, pos: bool, strict: bool) -> Polarity {
+    match (pol, pos, strict) {
+        (Polarity::Absent | Polarity::Strict, true, true) => Polarity::Strict,
+        (Polarity::Absent | Polarity::Strict | Polarity::Positive, true, _) => Polarity::Positive,
+        (Polarity::Absent | Polarity::Negative, false, _) => Polarity::Negative,
+        _ => Polarity::Mixed,
+    }
+}
+
+/// Collect the polarity of the occurrences of the variable within the given expression, which
+/// lies in a positive position (or else a negative one) that may or may not be strictly positive.
+fn collect(var: &Var, exp: &Exp, pos: bool, strict: bool, pol: &mut Polarity) {
+    match exp {
+        Exp::Var(VarIdx::Var(v)) if v == var => *pol = combine(*pol, pos, strict),
+        Exp::For(v, dom, cod) => {
+            collect(var, dom, !pos, false, pol);
+            if v != var {
+                collect(var, cod, pos, strict, pol); // unless shadowed
+            }
+        }
+        Exp::App(fun, arg) => {
+            collect(var, fun, pos, strict, pol);
+            if arg.free_vars().contains(var) {
+                *pol = Polarity::Mixed;
+            }
+        }
+        _ => exp.fold((), |(), sub, at| match at {
+            Pos::Body(v) if v == var => {} // shadowed
+            _ => collect(var, sub, pos, strict, pol),
+        }),
+    }
+}
@@ -0,0 +1,466 @@
+//! Pretty printer for expressions and related structures.
+
+use super::{Ctx, Exp, Fixity, Notation, Var};
+use unicode_width::UnicodeWidthStr;
+
+/// Options that govern how an [expression][Exp] is [pretty-printed][Exp::pretty].
+#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq)]
+pub struct PrintOptions {
+    /// Maximum width of a line, beyond which the expression is broken across lines where possible.
+    pub width: usize,
+    /// Number of columns by which the bodies of binders and the arguments of applications are
+    /// indented when broken onto lines of their own.
+    pub indent: usize,
+    /// Flag that selects ASCII glyphs (`\\`, `forall`, `exists`, `->`) over Unicode glyphs.
+    pub ascii: bool,
+    /// Policy that determines which sub-expressions are parenthesized.
+    pub parens: Parens,
+}
+
+/// Policy that determines which sub-expressions are parenthesized when an [expression][Exp] is
+/// pretty-printed.
+#[derive(Debug, Clone, Copy, Default, Eq, Hash, PartialEq)]
+pub enum Parens {
+    /// Parenthesize sub-expressions only where necessary to preserve the structure of the
+    /// expression when it is parsed again.
+    #[default]
+    Implicit,
+    /// Parenthesize every sub-expression that is not atomic, so that the structure of the
+    /// expression is evident without knowledge of precedence or associativity.
+    Explicit,
+}
+
+/// Document that describes the possible layouts of some text, given by the (Wadler-style)
+/// combinators below, from which the layout that best fits the line width is chosen.
+#[derive(Debug, Clone)]
+enum Doc {
+    /// Text, which must not contain line breaks.
+    Text(String),
+    /// Line break, or a single space where the enclosing group is laid out on one line.
+    Line,
+    /// Concatenation of two documents.
+    Cat(Box<Doc>, Box<Doc>),
+    /// Document whose line breaks are followed by additional indentation.
+    Nest(usize, Box<Doc>),
+    /// Document that is laid out on one line if it fits, or otherwise with all its line breaks.
+    Group(Box<Doc>),
+}
+
+/// Structure that indicates the current state of which branch of the application tree we're
+/// exclusively in.
+///
+/// Within the core fluxo language, the [Exp::App] type drives the notion of 'left sub-tree' and
+/// 'right sub-tree'. As the tree is traversed, you can be in one of the following states:
+///
+/// * Left sub-tree only
+/// * Right sub-tree only
+/// * Neither sub-tree exclusively (default state)
+#[derive(Copy, Clone, Debug, Default)]
+struct Branch {
+    /// Left sub-tree.
+    ltree: bool,
+    /// Right sub-tree.
+    rtree: bool,
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        PrintOptions {
+            width: 80,
+            indent: 2,
+            ascii: false,
+            parens: Parens::default(),
+        }
+    }
+}
+
+impl PrintOptions {
+    /// Create a new instance of options that lay out expressions on a single line (of unlimited
+    /// width), as they are rendered by [Display][std::fmt::Display].
+    pub fn flat(ascii: bool) -> Self {
+        PrintOptions {
+            width: usize::MAX,
+            ascii,
+            ..Default::default()
+        }
+    }
+}
+
+impl Doc {
+    /// Create a new text document.
+    fn text(txt: &str) -> Self {
+        Doc::Text(txt.to_string())
+    }
+
+    /// Concatenate this document with another.
+    fn cat(self, other: Doc) -> Self {
+        Doc::Cat(Box::new(self), Box::new(other))
+    }
+
+    /// Indent the line breaks within this document by the given number of additional columns.
+    fn nest(self, indent: usize) -> Self {
+        Doc::Nest(indent, Box::new(self))
+    }
+
+    /// Group this document, so that it is laid out on one line if it fits.
+    fn group(self) -> Self {
+        Doc::Group(Box::new(self))
+    }
+
+    /// Parenthesize (or not) as specified.
+    fn parens(self, parens: bool) -> Self {
+        if parens {
+            Doc::text("(").cat(self).cat(Doc::text(")"))
+        } else {
+            self
+        }
+    }
+
+    /// Lay out this document within the given line width.
+    fn render(&self, width: usize) -> String {
+        let mut out = String::new();
+        let mut col = 0;
+        let mut stack = vec![(0, false, self)]; // indentation, flatness and document, last first
+        while let Some((ind, flat, doc)) = stack.pop() {
+            match doc {
+                Doc::Text(txt) => {
+                    out.push_str(txt);
+                    col += txt.width();
+                }
+                Doc::Line if flat => {
+                    out.push(' ');
+                    col += 1;
+                }
+                Doc::Line => {
+                    out.push('\n');
+                    out.push_str(&" ".repeat(ind));
+                    col = ind;
+                }
+                Doc::Cat(fst, snd) => {
+                    stack.push((ind, flat, snd));
+                    stack.push((ind, flat, fst));
+                }
+                Doc::Nest(by, doc) => stack.push((ind + by, flat, doc)),
+                Doc::Group(doc) => {
+                    let flat = flat || Doc::fits(width.saturating_sub(col), doc, &stack);
+                    stack.push((ind, flat, doc));
+                }
+            }
+        }
+        out
+    }
+
+    /// Check whether the given document fits within the remaining width when laid out on one
+    /// line, along with the rest of the line that follows it (up to the next line break).
+    fn fits(mut rem: usize, doc: &Doc, rest: &[(usize, bool, &Doc)]) -> bool {
+        let mut stack = vec![(true, doc)];
+        let mut rest = rest.iter().rev();
+        loop {
+            let (flat, doc) = match stack.pop() {
+                Some(item) => item,
+                None => match rest.next() {
+                    Some((_, flat, doc)) => (*flat, *doc),
+                    None => return true,
+                },
+            };
+            match doc {
+                Doc::Text(txt) => match rem.checked_sub(txt.width()) {
+                    Some(val) => rem = val,
+                    None => return false,
+                },
+                Doc::Line if flat => match rem.checked_sub(1) {
+                    Some(val) => rem = val,
+                    None => return false,
+                },
+                Doc::Line => return true, // the rest is on the next line
+                Doc::Cat(fst, snd) => {
+                    stack.push((flat, snd));
+                    stack.push((flat, fst));
+                }
+                Doc::Nest(_, doc) | Doc::Group(doc) => stack.push((flat, doc)),
+            }
+        }
+    }
+}
+
+impl Exp {
+    /// Pretty-print this expression with the given [options][PrintOptions], breaking it across
+    /// lines (and indenting the pieces) so as to fit within the line width where possible.
+    pub fn pretty(&self, opts: &PrintOptions) -> String {
+        self.pretty_in(&Ctx::new(), opts)
+    }
+
+    /// Pretty-print this expression with the given [options][PrintOptions], using the
+    /// [notation][Notation] declared in the given context.
+    pub fn pretty_in(&self, ctx: &Ctx, opts: &PrintOptions) -> String {
+        self.doc(Branch::default(), ctx, opts).render(opts.width)
+    }
+
+    /// Check whether this expression is atomic, so that it never needs to be parenthesized.
+    fn is_atomic(&self) -> bool {
+        match self {
+            Exp::Var(_) | Exp::Sort(0) | Exp::Sort(1) | Exp::Hole | Exp::Meta(_) | Exp::Imp(_) => {
+                true
+            }
+            Exp::Unit | Exp::Triv | Exp::Void | Exp::Nat | Exp::Lit(_) => true,
+            Exp::Rec(_) | Exp::Struct(_) | Exp::Proj(..) => true,
+            Exp::Abs(..) => self.as_num().is_some(),
+            _ => false,
+        }
+    }
+
+    /// Build the document for a sub-expression, which is parenthesized as the flags require, or
+    /// always (unless it is atomic) if parentheses are explicit.
+    fn doc_sub(&self, flags: Branch, ctx: &Ctx, opts: &PrintOptions) -> Doc {
+        if opts.parens == Parens::Explicit && !self.is_atomic() {
+            self.doc(Branch::default(), ctx, opts).parens(true)
+        } else {
+            self.doc(flags, ctx, opts)
+        }
+    }
+
+    /// Build the document for this expression in canonical form.
+    fn doc(&self, flags: Branch, ctx: &Ctx, opts: &PrintOptions) -> Doc {
+        let ascii = opts.ascii;
+        match self {
+            Self::Var(varidx) => Doc::text(&varidx.to_string()),
+            Self::Abs(..) if self.as_num().is_some() => {
+                Doc::text(&self.as_num().unwrap().to_string())
+            }
+            Self::Abs(var, typ, exp) => {
+                let binder = if ascii { "\\" } else { "λ" };
+                Exp::doc_binder(flags, ctx, opts, binder, var, typ, exp)
+            }
+            Self::For(_, typ, exp) if self.is_arrow() => Exp::doc_arrow(flags, ctx, opts, typ, exp),
+            Self::For(var, typ, exp) => {
+                let binder = if ascii { "forall " } else { "Π" };
+                Exp::doc_binder(flags, ctx, opts, binder, var, typ, exp)
+            }
+            Self::App(..) if self.as_infix(ctx).is_some() => {
+                let (ntn, lhs, rhs) = self.as_infix(ctx).unwrap();
+                Exp::doc_infix(flags, ctx, opts, ntn, lhs, rhs)
+            }
+            Self::App(fst, snd) => Exp::doc_app(flags, ctx, opts, fst, snd),
+            Self::Sig(var, typ, exp) => {
+                let binder = if ascii { "exists " } else { "Σ" };
+                Exp::doc_binder(flags, ctx, opts, binder, var, typ, exp)
+            }
+            Self::Pair(fst, snd, typ) => {
+                let sub = |exp: &Exp| exp.doc_sub(Default::default(), ctx, opts); // always delimited
+                Doc::text("(")
+                    .cat(sub(fst))
+                    .cat(Doc::text(","))
+                    .cat(Doc::Line)
+                    .cat(sub(snd))
+                    .cat(Doc::text(" :"))
+                    .cat(Doc::Line)
+                    .cat(sub(typ))
+                    .nest(opts.indent)
+                    .cat(Doc::text(")"))
+                    .group()
+            }
+            Self::Fst(exp) => Exp::doc_app(flags, ctx, opts, &Exp::new_var(Var::new("fst")), exp),
+            Self::Snd(exp) => Exp::doc_app(flags, ctx, opts, &Exp::new_var(Var::new("snd")), exp),
+            Self::Sort(0) => Doc::text(if ascii { "Type" } else { "*" }),
+            Self::Sort(1) => Doc::text(if ascii { "Kind" } else { "□" }),
+            Self::Sort(lvl) => Doc::text(&format!("Type {}", lvl)).parens(flags.rtree),
+            Self::Hole => Doc::text("_"),
+            Self::Meta(id) => Doc::text(&id.to_string()),
+            Self::Unit => Doc::text(if ascii { "Unit" } else { "⊤" }),
+            Self::Triv => Doc::text("()"),
+            Self::Void => Doc::text(if ascii { "Void" } else { "⊥" }),
+            Self::Absurd(typ, exp) => {
+                Exp::doc_keyword(flags, ctx, opts, "absurd", &[typ.as_ref(), exp.as_ref()])
+            }
+            Self::Sum(fst, snd) => {
+                Exp::doc_keyword(flags, ctx, opts, "Sum", &[fst.as_ref(), snd.as_ref()])
+            }
+            Self::Inl(typ, exp) => {
+                Exp::doc_keyword(flags, ctx, opts, "inl", &[typ.as_ref(), exp.as_ref()])
+            }
+            Self::Inr(typ, exp) => {
+                Exp::doc_keyword(flags, ctx, opts, "inr", &[typ.as_ref(), exp.as_ref()])
+            }
+            Self::Case(mot, fst, snd, exp) => Exp::doc_keyword(
+                flags,
+                ctx,
+                opts,
+                "case",
+                &[mot.as_ref(), fst.as_ref(), snd.as_ref(), exp.as_ref()],
+            ),
+            Self::Nat => Doc::text(if ascii { "Nat" } else { "ℕ" }),
+            Self::Lit(val) => Doc::text(&format!("{}n", val)),
+            Self::Suc(exp) => Exp::doc_keyword(flags, ctx, opts, "suc", &[exp.as_ref()]),
+            Self::Add(fst, snd) => {
+                Exp::doc_keyword(flags, ctx, opts, "add", &[fst.as_ref(), snd.as_ref()])
+            }
+            Self::Mul(fst, snd) => {
+                Exp::doc_keyword(flags, ctx, opts, "mul", &[fst.as_ref(), snd.as_ref()])
+            }
+            Self::NatRec(mot, zer, suc, exp) => Exp::doc_keyword(
+                flags,
+                ctx,
+                opts,
+                "Nat_rec",
+                &[mot.as_ref(), zer.as_ref(), suc.as_ref(), exp.as_ref()],
+            ),
+            Self::Rec(fields) => Exp::doc_fields(ctx, opts, ":", fields),
+            Self::Struct(fields) => Exp::doc_fields(ctx, opts, "=", fields),
+            Self::Proj(exp, var) => exp
+                .doc(Default::default(), ctx, opts)
+                .parens(!exp.is_atomic())
+                .cat(Doc::text(&format!(".{}", var))),
+            Self::Imp(typ) => Doc::text("{")
+                .cat(typ.doc_sub(Default::default(), ctx, opts))
+                .cat(Doc::text("}")),
+        }
+    }
+
+    /// Build the document for a binder expression (λ abstraction, Π type or Σ type), whose body
+    /// is indented on a line of its own if it does not fit. A variable bound implicitly is
+    /// enclosed in braces along with its type.
+    fn doc_binder(
+        flags: Branch,
+        ctx: &Ctx,
+        opts: &PrintOptions,
+        binder: &str,
+        var: &Var,
+        typ: &Exp,
+        exp: &Exp,
+    ) -> Doc {
+        let (opn, cls, typ) = match typ {
+            Exp::Imp(typ) => ("{", "}", typ.as_ref()),
+            _ => ("", "", typ),
+        };
+        Doc::text(&format!("{}{}{} : ", binder, opn, var))
+            .cat(typ.doc_sub(Default::default(), ctx, opts)) // reset, always greedy
+            .cat(Doc::text(&format!("{} .", cls)))
+            .cat(
+                Doc::Line
+                    .cat(exp.doc_sub(Default::default(), ctx, opts))
+                    .nest(opts.indent),
+            ) // reset, always greedy
+            .group()
+            .parens(flags.ltree) // parenthesize if on the left side of tree
+    }
+
+    /// Build the document for a record (or record type), whose fields are separated from their
+    /// values (or types) by the given separator, and are placed on lines of their own if they do
+    /// not fit.
+    fn doc_fields(ctx: &Ctx, opts: &PrintOptions, sep: &str, fields: &[(Var, Exp)]) -> Doc {
+        let doc = fields
+            .iter()
+            .enumerate()
+            .fold(Doc::text("{"), |doc, (i, (var, exp))| {
+                let doc = match i {
+                    0 => doc,
+                    _ => doc.cat(Doc::text(",")).cat(Doc::Line),
+                };
+                doc.cat(Doc::text(&format!("{} {} ", var, sep)))
+                    .cat(exp.doc_sub(Default::default(), ctx, opts)) // always delimited
+            });
+        doc.nest(opts.indent).cat(Doc::text("}")).group()
+    }
+
+    /// Build the document for a non-dependent Π type, as an arrow from its domain to its
+    /// codomain, which continues on the next line if it does not fit.
+    fn doc_arrow(flags: Branch, ctx: &Ctx, opts: &PrintOptions, typ: &Exp, exp: &Exp) -> Doc {
+        let dom = typ.doc_sub(
+            Branch {
+                ltree: true, // parenthesize binders and arrows, which would otherwise be greedy
+                rtree: false,
+            },
+            ctx,
+            opts,
+        );
+        dom.cat(Doc::text(if opts.ascii { " ->" } else { " →" }))
+            .cat(Doc::Line)
+            .cat(exp.doc_sub(Default::default(), ctx, opts)) // reset, always greedy
+            .group()
+            .parens(flags.ltree) // parenthesize if on the left side of tree
+    }
+
+    /// Build the document for an application of an infix operator to its operands, parenthesizing
+    /// operands that are themselves applications of operators which bind looser (or associate the
+    /// other way).
+    fn doc_infix(
+        flags: Branch,
+        ctx: &Ctx,
+        opts: &PrintOptions,
+        ntn: &Notation,
+        lhs: &Exp,
+        rhs: &Exp,
+    ) -> Doc {
+        let parens = |exp: &Exp, fix: Fixity| match exp.as_infix(ctx) {
+            Some((sub, ..)) => {
+                sub.prec < ntn.prec || (sub.prec == ntn.prec && (ntn.fix != fix || sub.fix != fix))
+            }
+            None => exp.is_arrow(),
+        };
+        let operand = |exp: &Exp, fix: Fixity, ltree: bool| {
+            if parens(exp, fix) {
+                exp.doc(Default::default(), ctx, opts).parens(true)
+            } else {
+                exp.doc_sub(
+                    Branch {
+                        ltree, // parenthesize binders, which would otherwise be greedy
+                        rtree: false,
+                    },
+                    ctx,
+                    opts,
+                )
+            }
+        };
+        operand(lhs, Fixity::Infixl, true)
+            .cat(Doc::text(&format!(" {}", ntn.opr)))
+            .cat(Doc::Line)
+            .cat(operand(rhs, Fixity::Infixr, flags.ltree)) // inherit from parent
+            .group()
+    }
+
+    /// Build the document for an application of one expression to another, whose argument is
+    /// indented on a line of its own if it does not fit.
+    fn doc_app(flags: Branch, ctx: &Ctx, opts: &PrintOptions, fst: &Exp, snd: &Exp) -> Doc {
+        let fst = Exp::doc_operand(
+            fst,
+            Branch {
+                ltree: true,  // parenthesize binders, which would otherwise be greedy
+                rtree: false, // applications associate to the left
+            },
+            ctx,
+            opts,
+        );
+        let snd = Exp::doc_operand(
+            snd,
+            Branch {
+                ltree: flags.ltree && !flags.rtree, // inherit, but reset if parenthesized
+                rtree: true,                        // parenthesize nested applications
+            },
+            ctx,
+            opts,
+        );
+        fst.cat(Doc::Line.cat(snd).nest(opts.indent))
+            .group()
+            .parens(flags.rtree) // parenthesize if on the right side of tree
+    }
+
+    /// Build the document for a keyword of the core language applied to its operands, which is
+    /// laid out in the same way as an application.
+    fn doc_keyword(flags: Branch, ctx: &Ctx, opts: &PrintOptions, key: &str, args: &[&Exp]) -> Doc {
+        let (last, init) = args.split_last().unwrap(); // keywords take at least one operand
+        let fst = init.iter().fold(Exp::new_var(Var::new(key)), |fun, arg| {
+            Exp::new_app(fun, (*arg).clone())
+        });
+        Exp::doc_app(flags, ctx, opts, &fst, last)
+    }
+
+    /// Build the document for an operand of an application, always parenthesizing arrows and
+    /// infix operators, which bind loosest.
+    fn doc_operand(exp: &Exp, flags: Branch, ctx: &Ctx, opts: &PrintOptions) -> Doc {
+        if exp.is_arrow() || exp.as_infix(ctx).is_some() {
+            exp.doc(Default::default(), ctx, opts).parens(true)
+        } else {
+            exp.doc_sub(flags, ctx, opts)
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! Reduction strategies and related structures.
+
+use super::{Ctx, Exp, VarIdx};
+use crate::err::TypingErr;
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Strategy that determines which redexes are contracted when an [expression][super::Exp] is
+/// reduced, and hence the form that it is reduced to.
+#[derive(Debug, Clone, Copy, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Strategy {
+    /// Normal order, which contracts the leftmost outermost redex first, including those under
+    /// binders, so that the expression is reduced to normal form (if it has one).
+    #[default]
+    Normal,
+    /// Call-by-value, which reduces the argument of an application before substituting it, but
+    /// does not reduce under binders.
+    Value,
+    /// Weak head reduction, which contracts only the redex at the head of the expression, until
+    /// it is an abstraction, a type, a pair, or a variable applied to arguments.
+    Head,
+}
+
+/// Reduction rule by which a redex is contracted in a single [step][Step].
+#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq)]
+pub enum Rule {
+    /// Substitution of the argument of an application for the variable bound by the λ abstraction
+    /// it is applied to.
+    Beta,
+    /// Unfolding of a defined variable (or a solved metavariable) to its definition.
+    Delta,
+    /// Application of an eliminator to a constructor, including induction and arithmetic on
+    /// natural numbers.
+    Iota,
+    /// Projection of a component out of a pair, or of a field out of a record.
+    Proj,
+}
+
+/// Single reduction step, which contracts one redex of an [expression][Exp], as performed by
+/// [Exp::reduce_step].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Step {
+    /// Rule by which the redex was contracted.
+    pub rule: Rule,
+    /// Positions of the sub-expressions (counted in the order of [Exp::fold]) that lead from the
+    /// expression down to the redex.
+    pub path: Vec<usize>,
+    /// Redex that was contracted.
+    pub redex: Exp,
+    /// Expression that the redex was contracted to.
+    pub contractum: Exp,
+    /// Expression that results from the step.
+    pub exp: Exp,
+}
+
+/// Statistics of the reduction of an [expression][Exp], as gathered by [Exp::reduce_stats].
+#[derive(Debug, Clone, Copy, Default, Eq, Hash, PartialEq)]
+pub struct Stats {
+    /// Number of reduction steps taken, each of which contracts a single redex.
+    pub steps: usize,
+    /// Largest [size][Exp::size] reached by the expression during the reduction, as measured after
+    /// each pass over it.
+    pub peak: usize,
+}
+
+/// Limits on the reductions performed in a [context][Ctx] beyond the fuel of each, namely a flag
+/// that interrupts them once it is raised (such as by another thread), the time that they may
+/// take, and the size that expressions may grow to while they are reduced.
+///
+/// The limits are handed to the context by whoever evaluates a command (as for commands evaluated
+/// in batch, which must not hang on expressions that diverge), and are not serialized with it.
+#[derive(Debug, Clone, Default)]
+pub struct Limits {
+    /// Flag that interrupts the reductions once it is raised, which is shared with the copies of
+    /// the limits (and hence of the context).
+    pub irq: Arc<AtomicBool>,
+    /// Time that the reductions may take, counted from when the limits are created.
+    pub tmo: Option<Duration>,
+    /// Size that expressions may grow to while they are reduced.
+    pub max: Option<usize>,
+    /// Instant at which the time permitted runs out.
+    dln: Option<Instant>,
+}
+
+impl Limits {
+    /// Create new limits with the given flag, time and size, the time being counted from now.
+    pub fn new(irq: Arc<AtomicBool>, tmo: Option<Duration>, max: Option<usize>) -> Self {
+        Limits {
+            irq,
+            tmo,
+            max,
+            dln: tmo.map(|tmo| Instant::now() + tmo),
+        }
+    }
+
+    /// Check whether the reductions have been interrupted.
+    pub fn is_interrupted(&self) -> bool {
+        self.irq.load(Ordering::Relaxed)
+    }
+
+    /// Check whether the time permitted for the reductions has run out.
+    pub fn is_timed_out(&self) -> bool {
+        self.dln.is_some_and(|dln| Instant::now() >= dln)
+    }
+}
+
+impl Strategy {
+    /// All reduction strategies, in order of declaration.
+    pub const ALL: [Strategy; 3] = [Strategy::Normal, Strategy::Value, Strategy::Head];
+}
+
+impl Rule {
+    /// Determine the rule by which the given redex is contracted.
+    fn of(redex: &Exp) -> Self {
+        match redex {
+            Exp::Var(VarIdx::Var(_)) | Exp::Meta(_) => Rule::Delta,
+            Exp::App(fst, _) if matches!(fst.as_ref(), Exp::Abs(..)) => Rule::Beta,
+            Exp::Fst(_) | Exp::Snd(_) | Exp::Proj(..) => Rule::Proj,
+            _ => Rule::Iota,
+        }
+    }
+}
+
+impl Exp {
+    /// Perform a single reduction step on this expression (unfolding definitions) using the given
+    /// [strategy][Strategy], contracting the redex that the strategy would contract first, or
+    /// return nothing if the expression is already reduced.
+    pub fn reduce_step(self, ctx: &Ctx, stg: Strategy) -> Result<Option<Step>, TypingErr> {
+        let exp = self.clone().normalize(ctx, stg, &mut 1)?;
+        if exp == self {
+            return Ok(None);
+        }
+        let mut path = vec![];
+        let (redex, contractum) = Exp::locate(&self, &exp, &mut path);
+        Ok(Some(Step {
+            rule: Rule::of(redex),
+            path,
+            redex: redex.clone(),
+            contractum: contractum.clone(),
+            exp,
+        }))
+    }
+
+    /// Locate the smallest sub-expression in which the given expressions differ, descending
+    /// through their sub-expressions for as long as they differ in exactly one of them.
+    fn locate<'a>(old: &'a Exp, new: &'a Exp, path: &mut Vec<usize>) -> (&'a Exp, &'a Exp) {
+        let (olds, news) = (old.subterms(), new.subterms());
+        let same = std::mem::discriminant(old) == std::mem::discriminant(new);
+        let diff: Vec<usize> = (0..olds.len().min(news.len()))
+            .filter(|&i| olds[i] != news[i])
+            .collect();
+        match diff[..] {
+            [i] if same && olds.len() == news.len() => {
+                path.push(i);
+                Exp::locate(olds[i], news[i], path)
+            }
+            _ => (old, new),
+        }
+    }
+
+    /// Replace the sub-expression at the end of the given path (as found by [Exp::reduce_step]) with the
+    /// given expression.
+    pub fn replace_at(self, path: &[usize], new: Exp) -> Exp {
+        match path.split_first() {
+            None => new,
+            Some((&at, rest)) => {
+                let (mut new, mut pos) = (Some(new), 0);
+                self.map_subterms(|sub, _| {
+                    pos += 1;
+                    match new.take() {
+                        Some(new) if pos - 1 == at => sub.replace_at(rest, new),
+                        val => {
+                            new = val; // not yet at the position
+                            sub
+                        }
+                    }
+                })
+            }
+        }
+    }
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Beta => write!(f, "β"),
+            Self::Delta => write!(f, "δ"),
+            Self::Iota => write!(f, "ι"),
+            Self::Proj => write!(f, "π"),
+        }
+    }
+}
+
+impl Display for Strategy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Normal => write!(f, "normal"),
+            Self::Value => write!(f, "cbv"),
+            Self::Head => write!(f, "whnf"),
+        }
+    }
+}
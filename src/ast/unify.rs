@@ -0,0 +1,290 @@
+//! Metavariables and the unification engine that solves them.
+
+use super::{occurs, Ctx, Exp, Idx, Pos, Var, VarIdx};
+use crate::err::{TypeOccursErr, TypeUnifyErr, TypingErr};
+use std::fmt::{Display, Formatter};
+
+/// Identifier of a metavariable, denoted by `?n`, which stands for an expression that is not yet
+/// known, and which is solved by [unification][unify] as expressions are type-checked.
+#[derive(Debug, Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MetaId(pub usize);
+
+/// Store of metavariables, holding the type of each along with its solution (once known).
+#[derive(Debug, Clone, Default)]
+pub struct Metas {
+    typ: Vec<Exp>,
+    sol: Vec<Option<Exp>>,
+}
+
+impl Metas {
+    /// Create a new instance of an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new (unsolved) metavariable of the given type.
+    pub fn fresh(&mut self, typ: &Exp) -> MetaId {
+        self.typ.push(typ.clone());
+        self.sol.push(None);
+        MetaId(self.typ.len() - 1)
+    }
+
+    /// Fetch the type of a metavariable, if it exists.
+    pub fn get_type(&self, id: MetaId) -> Option<&Exp> {
+        self.typ.get(id.0)
+    }
+
+    /// Fetch the solution of a metavariable, if it exists and is solved.
+    pub fn get_solution(&self, id: MetaId) -> Option<&Exp> {
+        self.sol.get(id.0)?.as_ref()
+    }
+
+    /// Fetch the metavariables that are yet to be solved, in order of creation.
+    pub fn unsolved(&self) -> Vec<MetaId> {
+        (0..self.sol.len())
+            .filter(|i| self.sol[*i].is_none())
+            .map(MetaId)
+            .collect()
+    }
+
+    /// Record the solution of a metavariable.
+    pub(super) fn solve(&mut self, id: MetaId, exp: Exp) {
+        self.sol[id.0] = Some(exp);
+    }
+}
+
+/// Unify two expressions, solving the metavariables that occur in them (in the given context) so
+/// that the expressions become equal, up to the unfolding of definitions, beta-reduction and the
+/// names of bound variables.
+///
+/// A metavariable is only solved with an expression that does not depend on the variables bound
+/// within the expressions being unified, and that does not contain the metavariable itself, which
+/// is reported as a failure of the [occurs-check][occurs].
+pub fn unify(fst: &Exp, snd: &Exp, ctx: &Ctx) -> Result<(), TypingErr> {
+    let lhs = fst.clone().reduce(ctx)?;
+    let rhs = snd.clone().reduce(ctx)?;
+    unify_at(&lhs, &rhs, 0, ctx).map_err(|err| match err {
+        TypingErr::TypeUnifyErr(_) => TypingErr::from(TypeUnifyErr::new(fst, snd)),
+        err => err,
+    })
+}
+
+/// Unify two expressions (in normal form) under the given number of binders.
+fn unify_at(fst: &Exp, snd: &Exp, lvl: usize, ctx: &Ctx) -> Result<(), TypingErr> {
+    let all = |pairs: &[(&Exp, &Exp)]| pairs.iter().try_for_each(|(e, f)| unify_at(e, f, lvl, ctx));
+    match (fst, snd) {
+        (Exp::Meta(m), Exp::Meta(n)) if m == n => Ok(()),
+        (Exp::Meta(id), exp) | (exp, Exp::Meta(id)) => match ctx.get_meta(*id) {
+            Some(sol) => unify_at(&sol.shift(lvl, 0).reduce(ctx)?, exp, lvl, ctx),
+            None => solve(*id, exp, lvl, ctx),
+        },
+        (Exp::Var(VarIdx::Idx(Idx(i, _))), Exp::Var(VarIdx::Idx(Idx(j, _)))) if i == j => Ok(()),
+        (Exp::Var(VarIdx::Idx(_)), Exp::Var(VarIdx::Idx(_))) => {
+            Err(TypingErr::from(TypeUnifyErr::new(fst, snd)))
+        }
+        (Exp::Var(v), Exp::Var(w)) if v.get_var() == w.get_var() => Ok(()),
+        (Exp::Abs(_, t, e), Exp::Abs(_, u, f))
+        | (Exp::For(_, t, e), Exp::For(_, u, f))
+        | (Exp::Sig(_, t, e), Exp::Sig(_, u, f)) => {
+            unify_at(t, u, lvl, ctx)?;
+            unify_at(e, f, lvl + 1, ctx)
+        }
+        (Exp::App(e, f), Exp::App(g, h))
+        | (Exp::Absurd(e, f), Exp::Absurd(g, h))
+        | (Exp::Sum(e, f), Exp::Sum(g, h))
+        | (Exp::Inl(e, f), Exp::Inl(g, h))
+        | (Exp::Inr(e, f), Exp::Inr(g, h))
+        | (Exp::Add(e, f), Exp::Add(g, h))
+        | (Exp::Mul(e, f), Exp::Mul(g, h)) => all(&[(e, g), (f, h)]),
+        (Exp::Case(p, e, f, m), Exp::Case(q, g, h, n))
+        | (Exp::NatRec(p, e, f, m), Exp::NatRec(q, g, h, n)) => {
+            all(&[(p, q), (e, g), (f, h), (m, n)])
+        }
+        (Exp::Pair(e, f, t), Exp::Pair(g, h, u)) => all(&[(e, g), (f, h), (t, u)]),
+        (Exp::Fst(e), Exp::Fst(f))
+        | (Exp::Snd(e), Exp::Snd(f))
+        | (Exp::Imp(e), Exp::Imp(f))
+        | (Exp::Suc(e), Exp::Suc(f)) => unify_at(e, f, lvl, ctx),
+        (Exp::Rec(fs), Exp::Rec(gs)) | (Exp::Struct(fs), Exp::Struct(gs))
+            if fs.len() == gs.len() && fs.iter().zip(gs).all(|((v, _), (w, _))| v == w) =>
+        {
+            let pairs: Vec<_> = fs.iter().zip(gs).map(|((_, e), (_, f))| (e, f)).collect();
+            all(&pairs)
+        }
+        (Exp::Proj(e, v), Exp::Proj(f, w)) if v == w => unify_at(e, f, lvl, ctx),
+        _ if fst == snd => Ok(()),
+        _ => Err(TypingErr::from(TypeUnifyErr::new(fst, snd))),
+    }
+}
+
+/// Solve a metavariable with the given expression, found under the given number of binders, if
+/// the expression is a valid solution (of the type of the metavariable).
+fn solve(id: MetaId, exp: &Exp, lvl: usize, ctx: &Ctx) -> Result<(), TypingErr> {
+    let meta = Exp::Meta(id);
+    if occurs(id, exp) {
+        return Err(TypingErr::from(TypeOccursErr::new(id, exp))); // the solution would be cyclic
+    }
+    if exp.binds_within(lvl, 0) {
+        return Err(TypingErr::from(TypeUnifyErr::new(&meta, exp))); // it depends on the binders
+    }
+    let sol = exp.clone().unshift(lvl, 0);
+    match ctx.get_meta_type(id) {
+        Some(typ) if sol.validate_type(&[&typ], ctx).is_ok() => {
+            ctx.solve_meta(id, sol);
+            Ok(())
+        }
+        _ => Err(TypingErr::from(TypeUnifyErr::new(&meta, exp))),
+    }
+}
+
+impl Exp {
+    /// Check whether any metavariables occur in this expression, solved or not.
+    pub fn has_metas(&self) -> bool {
+        match self {
+            Exp::Meta(_) => true,
+            _ => self.any_subterm(|exp, _| exp.has_metas()),
+        }
+    }
+
+    /// Elaborate this expression in the given context, applying each function to a fresh
+    /// metavariable for every variable that its type binds implicitly ahead of the arguments it is
+    /// applied to, so that the metavariables are solved by [unification][unify] once the
+    /// expression is type-checked.
+    ///
+    /// Functions that are not applied to any arguments are left as they are.
+    ///
+    /// Each free variable is also [resolved][Ctx::resolve] to the (qualified) variable it refers
+    /// to, as is each projection that spells out a qualified name (as in `list.map`) rather than
+    /// projecting a field from a record.
+    pub fn elaborate(&self, ctx: &Ctx) -> Self {
+        match self {
+            Exp::Var(VarIdx::Var(var)) => {
+                ctx.resolve(var).map_or_else(|| self.clone(), Exp::new_var)
+            }
+            Exp::Proj(..) => match self.qualified(ctx) {
+                Some(var) => Exp::new_var(var),
+                None => self.elaborate_subterms(ctx),
+            },
+            Exp::App(..) => {
+                let (head, args) = self.spine();
+                args.into_iter().fold(head.elaborate(ctx), |fun, arg| {
+                    let fun = fun.instantiate(ctx);
+                    let arg = match fun.infer(ctx) {
+                        Ok(Exp::For(_, typ, _)) => arg.elaborate_as(&typ, ctx),
+                        _ => arg.elaborate(ctx),
+                    };
+                    Exp::new_app(fun, arg)
+                })
+            }
+            _ => self.elaborate_subterms(ctx),
+        }
+    }
+
+    /// Elaborate the immediate subterms of this expression in the given context, in which the
+    /// body of a binder is elaborated once extended with the variable it binds.
+    fn elaborate_subterms(&self, ctx: &Ctx) -> Self {
+        let mut inner = None; // context for the body of a binder, once its type is known
+        self.clone().map_subterms(|exp, pos| match pos {
+            Pos::Type(var) => {
+                let typ = exp.elaborate(ctx);
+                inner = Some(ctx.extend(var, &typ));
+                typ
+            }
+            Pos::Body(_) => exp.elaborate(inner.as_ref().unwrap_or(ctx)),
+            Pos::Arg => exp.elaborate(ctx),
+        })
+    }
+
+    /// Elaborate this expression in the given context as an expression of the given type, which
+    /// also annotates the variable bound by a λ abstraction that is not annotated with the domain
+    /// of the Π type it is [checked][Exp::check] against.
+    pub fn elaborate_as(&self, typ: &Exp, ctx: &Ctx) -> Self {
+        if let (Exp::Abs(var, dom, exp), Ok(Exp::For(arg, typ, cod))) =
+            (self, typ.clone().reduce(ctx))
+        {
+            if let Exp::Hole = dom.as_ref() {
+                let cod = cod.subst(&Idx::new(&arg), &Exp::new_var(var.clone()));
+                let exp = exp.elaborate_as(&cod, &ctx.extend(var, &typ));
+                return Exp::Abs(var.clone(), typ, Box::new(exp));
+            }
+        }
+        self.elaborate(ctx)
+    }
+
+    /// Read this projection as the qualified name it spells out (as in `list.map`), if the
+    /// variable at its root is not bound in the given context, [resolved][Ctx::resolve] to the
+    /// variable it refers to, or else left as it is, so that it is reported as unknown in full.
+    fn qualified(&self, ctx: &Ctx) -> Option<Var> {
+        let mut exp = self;
+        let mut segs = vec![];
+        while let Exp::Proj(rec, fld) = exp {
+            segs.push(fld);
+            exp = rec;
+        }
+        match exp {
+            Exp::Var(VarIdx::Var(root)) if ctx.resolve(root).is_none() => {
+                let var = (segs.into_iter().rev()).fold(root.clone(), |ns, fld| fld.qualify(&ns));
+                Some(ctx.resolve(&var).unwrap_or(var))
+            }
+            _ => None,
+        }
+    }
+
+    /// Apply this function to a fresh metavariable for each variable that its type binds
+    /// implicitly, up to the first that it binds explicitly.
+    fn instantiate(self, ctx: &Ctx) -> Self {
+        let mut exp = self;
+        while let Ok(Exp::For(_, typ, _)) = exp.infer(ctx) {
+            match *typ {
+                Exp::Imp(typ) => exp = Exp::new_app(exp, ctx.fresh_meta(&typ)),
+                _ => break,
+            }
+        }
+        exp
+    }
+
+    /// Replace the solved metavariables in this expression with their solutions.
+    pub fn zonk(self, ctx: &Ctx) -> Self {
+        match self {
+            Exp::Meta(id) => match ctx.get_meta(id) {
+                Some(sol) => sol.zonk(ctx),
+                None => self,
+            },
+            _ => self.map_subterms(|exp, _| exp.zonk(ctx)),
+        }
+    }
+
+    /// Check whether this expression refers to any of the given number of binders that enclose
+    /// it, beyond those (up to the given cut-off) that are within it.
+    fn binds_within(&self, lvl: usize, cut: usize) -> bool {
+        match self {
+            Exp::Var(VarIdx::Idx(Idx(i, _))) => *i >= cut && *i < cut + lvl,
+            _ => self.any_subterm(|exp, pos| match pos {
+                Pos::Body(_) => exp.binds_within(lvl, cut + 1),
+                _ => exp.binds_within(lvl, cut),
+            }),
+        }
+    }
+
+    /// Decrement the indices of the variables in this expression that are bound outside of it (at
+    /// or beyond the given cut-off), as is needed when removing it from under the given number of
+    /// binders, on which it does not depend.
+    fn unshift(self, by: usize, cut: usize) -> Self {
+        match self {
+            Exp::Var(VarIdx::Idx(Idx(i, var))) if i >= cut => {
+                Exp::Var(VarIdx::Idx(Idx(i - by, var)))
+            }
+            _ => self.map_subterms(|exp, pos| match pos {
+                Pos::Body(_) => exp.unshift(by, cut + 1),
+                _ => exp.unshift(by, cut),
+            }),
+        }
+    }
+}
+
+impl Display for MetaId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "?{}", self.0)
+    }
+}
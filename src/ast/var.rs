@@ -5,17 +5,24 @@ use std::fmt::{Display, Formatter};
 
 /// Structure that represents a variable, either symbolic or indexed against a parent binder.
 #[derive(Debug, Clone, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum VarIdx {
     Var(Var),
     Idx(Idx),
 }
 
 /// Structure that represents a symbolic variable.
-#[derive(Debug, Clone, Eq, Hash, PartialEq)]
+///
+/// A variable may be qualified by the namespace it belongs to, with each segment of its name
+/// separated by a `.` (as in `list.map`), so that names declared in different namespaces (such as
+/// those of a module or the prelude) do not collide.
+#[derive(Debug, Clone, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Var(pub String);
 
 /// Structure that represents a variable indexed against a parent binder.
 #[derive(Debug, Clone, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Idx(pub usize, pub Var);
 
 impl VarIdx {
@@ -52,6 +59,28 @@ impl Var {
     pub fn new(val: &str) -> Self {
         Var(val.to_string())
     }
+
+    /// Create a new instance of this variable qualified by the given namespace.
+    pub fn qualify(&self, ns: &Var) -> Self {
+        Var(format!("{}.{}", ns.0, self.0))
+    }
+
+    /// Get the namespace that qualifies this variable, if it is qualified.
+    pub fn namespace(&self) -> Option<Var> {
+        self.0.rsplit_once('.').map(|(ns, _)| Var::new(ns))
+    }
+
+    /// Get the name of this variable within its namespace, that is, its last segment.
+    pub fn base(&self) -> &str {
+        self.0.rsplit_once('.').map_or(&self.0, |(_, base)| base)
+    }
+
+    /// Check whether this variable belongs to the given namespace, or to one nested within it.
+    pub fn is_within(&self, ns: &Var) -> bool {
+        self.0
+            .strip_prefix(&ns.0)
+            .is_some_and(|rest| rest.starts_with('.'))
+    }
 }
 
 impl Display for Var {
@@ -85,7 +114,7 @@ impl Display for Idx {
 
 impl PartialOrd for Idx {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.0.partial_cmp(&other.0)
+        Some(self.cmp(other))
     }
 }
 
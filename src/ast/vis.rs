@@ -0,0 +1,400 @@
+//! Traversal of expressions and related structures.
+
+use super::{Ctx, Exp, Idx, Var, VarIdx};
+use crate::err::Lint;
+use std::collections::HashSet;
+
+/// Position of an immediate sub-expression within its parent [expression][Exp], which determines
+/// the binders that are in scope for it.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Pos<'a> {
+    /// Operand of an application, a pair, a record or a projection, in the same scope as its
+    /// parent.
+    Arg,
+    /// Type of the binder for the given variable, in the same scope as the binder itself.
+    Type(&'a Var),
+    /// Body of the binder for the given variable, in the scope of the binder.
+    Body(&'a Var),
+}
+
+/// Visitor that walks an [expression][Exp] from the top down, such as to search or analyze it.
+///
+/// Every method has a default implementation, so that implementations only need to override the
+/// methods for the parts of the expression they are interested in, calling [walk] from
+/// [Visitor::visit] to continue into the sub-expressions.
+pub trait Visitor {
+    /// Visit the given expression, which by default visits each of its sub-expressions in turn.
+    fn visit(&mut self, exp: &Exp) {
+        walk(self, exp);
+    }
+
+    /// Enter the scope of the binder for the given variable, before visiting its body.
+    fn enter(&mut self, _var: &Var) {}
+
+    /// Leave the scope of the binder for the given variable, after visiting its body.
+    fn leave(&mut self, _var: &Var) {}
+}
+
+/// Visit each of the immediate sub-expressions of the given expression with the visitor, entering
+/// and leaving the scope of the binder around its body.
+pub fn walk<V: Visitor + ?Sized>(vis: &mut V, exp: &Exp) {
+    exp.fold((), |(), sub, pos| {
+        if let Pos::Body(var) = pos {
+            vis.enter(var);
+            vis.visit(sub);
+            vis.leave(var);
+        } else {
+            vis.visit(sub);
+        }
+    })
+}
+
+impl Exp {
+    /// Replace each of the immediate sub-expressions of this expression with the result of the
+    /// given function, which is also told the [position][Pos] of the sub-expression.
+    pub fn map_subterms(self, mut f: impl FnMut(Exp, Pos) -> Exp) -> Self {
+        match self {
+            Exp::Var(_) | Exp::Sort(_) | Exp::Hole | Exp::Meta(_) => self,
+            Exp::Unit | Exp::Triv | Exp::Void | Exp::Nat | Exp::Lit(_) => self,
+            Exp::Abs(var, typ, exp) => {
+                let typ = f(*typ, Pos::Type(&var));
+                let exp = f(*exp, Pos::Body(&var));
+                Exp::Abs(var, Box::new(typ), Box::new(exp))
+            }
+            Exp::For(var, typ, exp) => {
+                let typ = f(*typ, Pos::Type(&var));
+                let exp = f(*exp, Pos::Body(&var));
+                Exp::For(var, Box::new(typ), Box::new(exp))
+            }
+            Exp::Sig(var, typ, exp) => {
+                let typ = f(*typ, Pos::Type(&var));
+                let exp = f(*exp, Pos::Body(&var));
+                Exp::Sig(var, Box::new(typ), Box::new(exp))
+            }
+            Exp::App(fst, snd) => {
+                let fst = f(*fst, Pos::Arg);
+                Exp::App(Box::new(fst), Box::new(f(*snd, Pos::Arg)))
+            }
+            Exp::Pair(fst, snd, typ) => {
+                let fst = f(*fst, Pos::Arg);
+                let snd = f(*snd, Pos::Arg);
+                Exp::Pair(Box::new(fst), Box::new(snd), Box::new(f(*typ, Pos::Arg)))
+            }
+            Exp::Fst(exp) => Exp::Fst(Box::new(f(*exp, Pos::Arg))),
+            Exp::Snd(exp) => Exp::Snd(Box::new(f(*exp, Pos::Arg))),
+            Exp::Imp(exp) => Exp::Imp(Box::new(f(*exp, Pos::Arg))),
+            Exp::Suc(exp) => Exp::Suc(Box::new(f(*exp, Pos::Arg))),
+            Exp::Proj(exp, var) => Exp::Proj(Box::new(f(*exp, Pos::Arg)), var),
+            Exp::Rec(fields) => Exp::Rec(
+                fields
+                    .into_iter()
+                    .map(|(var, exp)| (var, f(exp, Pos::Arg)))
+                    .collect(),
+            ),
+            Exp::Struct(fields) => Exp::Struct(
+                fields
+                    .into_iter()
+                    .map(|(var, exp)| (var, f(exp, Pos::Arg)))
+                    .collect(),
+            ),
+            Exp::Add(fst, snd) => {
+                let fst = f(*fst, Pos::Arg);
+                Exp::Add(Box::new(fst), Box::new(f(*snd, Pos::Arg)))
+            }
+            Exp::Mul(fst, snd) => {
+                let fst = f(*fst, Pos::Arg);
+                Exp::Mul(Box::new(fst), Box::new(f(*snd, Pos::Arg)))
+            }
+            Exp::NatRec(mot, zer, suc, exp) => {
+                let mot = f(*mot, Pos::Arg);
+                let zer = f(*zer, Pos::Arg);
+                let suc = f(*suc, Pos::Arg);
+                Exp::NatRec(
+                    Box::new(mot),
+                    Box::new(zer),
+                    Box::new(suc),
+                    Box::new(f(*exp, Pos::Arg)),
+                )
+            }
+            Exp::Absurd(typ, exp) => {
+                let typ = f(*typ, Pos::Arg);
+                Exp::Absurd(Box::new(typ), Box::new(f(*exp, Pos::Arg)))
+            }
+            Exp::Sum(fst, snd) => {
+                let fst = f(*fst, Pos::Arg);
+                Exp::Sum(Box::new(fst), Box::new(f(*snd, Pos::Arg)))
+            }
+            Exp::Inl(typ, exp) => {
+                let typ = f(*typ, Pos::Arg);
+                Exp::Inl(Box::new(typ), Box::new(f(*exp, Pos::Arg)))
+            }
+            Exp::Inr(typ, exp) => {
+                let typ = f(*typ, Pos::Arg);
+                Exp::Inr(Box::new(typ), Box::new(f(*exp, Pos::Arg)))
+            }
+            Exp::Case(mot, fst, snd, exp) => {
+                let mot = f(*mot, Pos::Arg);
+                let fst = f(*fst, Pos::Arg);
+                let snd = f(*snd, Pos::Arg);
+                Exp::Case(
+                    Box::new(mot),
+                    Box::new(fst),
+                    Box::new(snd),
+                    Box::new(f(*exp, Pos::Arg)),
+                )
+            }
+        }
+    }
+
+    /// Combine the immediate sub-expressions of this expression (in order) into an accumulated
+    /// value, using the given function, which is also told the [position][Pos] of each.
+    pub fn fold<'a, T>(&'a self, acc: T, mut f: impl FnMut(T, &'a Exp, Pos<'a>) -> T) -> T {
+        match self {
+            Exp::Var(_) | Exp::Sort(_) | Exp::Hole | Exp::Meta(_) => acc,
+            Exp::Unit | Exp::Triv | Exp::Void | Exp::Nat | Exp::Lit(_) => acc,
+            Exp::Abs(var, typ, exp) | Exp::For(var, typ, exp) | Exp::Sig(var, typ, exp) => {
+                let acc = f(acc, typ, Pos::Type(var));
+                f(acc, exp, Pos::Body(var))
+            }
+            Exp::App(fst, snd)
+            | Exp::Absurd(fst, snd)
+            | Exp::Sum(fst, snd)
+            | Exp::Inl(fst, snd)
+            | Exp::Inr(fst, snd)
+            | Exp::Add(fst, snd)
+            | Exp::Mul(fst, snd) => {
+                let acc = f(acc, fst, Pos::Arg);
+                f(acc, snd, Pos::Arg)
+            }
+            Exp::Case(mot, fst, snd, exp) | Exp::NatRec(mot, fst, snd, exp) => {
+                let acc = f(acc, mot, Pos::Arg);
+                let acc = f(acc, fst, Pos::Arg);
+                let acc = f(acc, snd, Pos::Arg);
+                f(acc, exp, Pos::Arg)
+            }
+            Exp::Pair(fst, snd, typ) => {
+                let acc = f(acc, fst, Pos::Arg);
+                let acc = f(acc, snd, Pos::Arg);
+                f(acc, typ, Pos::Arg)
+            }
+            Exp::Fst(exp) | Exp::Snd(exp) | Exp::Imp(exp) | Exp::Suc(exp) | Exp::Proj(exp, _) => {
+                f(acc, exp, Pos::Arg)
+            }
+            Exp::Rec(fields) | Exp::Struct(fields) => fields
+                .iter()
+                .fold(acc, |acc, (_, exp)| f(acc, exp, Pos::Arg)),
+        }
+    }
+
+    /// Collect the immediate sub-expressions of this expression, in order.
+    pub fn subterms(&self) -> Vec<&Exp> {
+        self.fold(vec![], |mut subs, sub, _| {
+            subs.push(sub);
+            subs
+        })
+    }
+
+    /// Check whether the given predicate holds for any of the immediate sub-expressions of this
+    /// expression, stopping at the first one for which it does.
+    pub fn any_subterm<'a>(&'a self, mut f: impl FnMut(&'a Exp, Pos<'a>) -> bool) -> bool {
+        self.fold(false, |acc, exp, pos| acc || f(exp, pos))
+    }
+
+    /// Collect the variables that occur free in this expression, that is, those that are not
+    /// bound by any of its binders, and which must therefore be declared in the context.
+    pub fn free_vars(&self) -> HashSet<Var> {
+        let mut vis = FreeVars::default();
+        vis.visit(self);
+        vis.vars
+    }
+
+    /// Check whether this expression is closed, that is, whether no variables occur free in it.
+    pub fn is_closed(&self) -> bool {
+        self.free_vars().is_empty()
+    }
+
+    /// Collect the binders in this expression (the variables they bind, along with their types),
+    /// from the outside in and from left to right.
+    pub fn binders(&self) -> Vec<(&Var, &Exp)> {
+        let mut bnd = vec![];
+        self.collect_binders(&mut bnd);
+        bnd
+    }
+
+    /// Collect the [warnings][Lint] about the binders in this expression, in the given context:
+    /// those of λ abstractions and Π types whose variables do not occur in their bodies, and
+    /// those whose variables shadow others already in scope, from the outside in.
+    ///
+    /// Variables whose names start with `_` (as those of arrows do) are meant to be unused, and
+    /// numerals are left alone, since they are desugared rather than written out. Neither are the
+    /// leading binders of the arguments of an eliminator (such as its motive) warned about being
+    /// unused, since their number is dictated by the type of the eliminator rather than chosen.
+    pub fn lints(&self, ctx: &Ctx) -> Vec<Lint> {
+        let mut vis = Lints {
+            ctx,
+            scope: vec![],
+            lints: vec![],
+        };
+        vis.visit(self);
+        vis.lints
+    }
+
+    /// Count the nodes of this expression, that is, the expression itself along with all of its
+    /// sub-expressions (at any depth).
+    pub fn size(&self) -> usize {
+        self.fold(1, |acc, exp, _| acc + exp.size())
+    }
+
+    /// Measure the depth of this expression, that is, the number of nodes along the longest path
+    /// from the expression down to one of its leaves (so that a variable has depth 1).
+    pub fn depth(&self) -> usize {
+        1 + self.fold(0, |acc, exp, _| acc.max(exp.depth()))
+    }
+
+    /// Count the redexes in this expression (at any depth), that is, the sub-expressions that
+    /// can be contracted by a single reduction step regardless of the context.
+    ///
+    /// Variables that would be unfolded to their definitions, and eliminators of [data types]
+    /// [super::Data] applied to their constructors, are only known to be redexes in a context,
+    /// so they are not counted.
+    pub fn count_redexes(&self) -> usize {
+        let own = usize::from(self.is_redex());
+        self.fold(own, |acc, exp, _| acc + exp.count_redexes())
+    }
+
+    /// Check whether this expression is itself a redex, as counted by [Exp::count_redexes].
+    fn is_redex(&self) -> bool {
+        match self {
+            Exp::App(fst, _) => matches!(fst.as_ref(), Exp::Abs(..)),
+            Exp::Fst(exp) | Exp::Snd(exp) => matches!(exp.as_ref(), Exp::Pair(..)),
+            Exp::Case(.., exp) => matches!(exp.as_ref(), Exp::Inl(..) | Exp::Inr(..)),
+            Exp::NatRec(.., exp) => matches!(exp.as_ref(), Exp::Lit(_) | Exp::Suc(_)),
+            Exp::Suc(exp) => matches!(exp.as_ref(), Exp::Lit(n) if *n < usize::MAX),
+            Exp::Add(fst, snd) => matches!((fst.as_ref(), snd.as_ref()),
+                (Exp::Lit(m), Exp::Lit(n)) if m.checked_add(*n).is_some()),
+            Exp::Mul(fst, snd) => matches!((fst.as_ref(), snd.as_ref()),
+                (Exp::Lit(m), Exp::Lit(n)) if m.checked_mul(*n).is_some()),
+            Exp::Proj(exp, var) => match exp.as_ref() {
+                Exp::Struct(fields) => fields.iter().any(|(v, _)| v == var),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Collect the binders in this expression into the given list.
+    fn collect_binders<'a>(&'a self, bnd: &mut Vec<(&'a Var, &'a Exp)>) {
+        if let Exp::Abs(var, typ, _) | Exp::For(var, typ, _) | Exp::Sig(var, typ, _) = self {
+            bnd.push((var, typ));
+        }
+        self.fold((), |(), exp, _| exp.collect_binders(bnd));
+    }
+}
+
+/// Visitor that collects the free variables of an expression.
+#[derive(Default)]
+struct FreeVars {
+    /// Variables bound by the binders in scope, from the outside in.
+    scope: Vec<Var>,
+    /// Free variables found so far.
+    vars: HashSet<Var>,
+}
+
+impl Visitor for FreeVars {
+    fn visit(&mut self, exp: &Exp) {
+        match exp {
+            Exp::Var(VarIdx::Var(var)) if !self.scope.contains(var) => {
+                self.vars.insert(var.clone());
+            }
+            Exp::Var(VarIdx::Idx(Idx(i, var))) if *i >= self.scope.len() => {
+                self.vars.insert(var.clone()); // bound outside of the expression
+            }
+            _ => walk(self, exp),
+        }
+    }
+
+    fn enter(&mut self, var: &Var) {
+        self.scope.push(var.clone());
+    }
+
+    fn leave(&mut self, _var: &Var) {
+        self.scope.pop();
+    }
+}
+
+/// Visitor that collects the [warnings][Lint] about the binders of an expression.
+struct Lints<'a> {
+    /// Context that the expression is evaluated in.
+    ctx: &'a Ctx,
+    /// Variables bound by the binders in scope, from the outside in.
+    scope: Vec<Var>,
+    /// Warnings found so far.
+    lints: Vec<Lint>,
+}
+
+impl Visitor for Lints<'_> {
+    fn visit(&mut self, exp: &Exp) {
+        if exp.as_num().is_some() {
+            return;
+        }
+        if let Some(args) = self.methods(exp) {
+            return args.into_iter().for_each(|arg| self.method(arg));
+        }
+        if let Exp::Abs(var, _, body) | Exp::For(var, _, body) = exp {
+            if !var.0.starts_with('_') && !body.depends_on(var, 0) {
+                self.lints.push(Lint::Unused(var.clone(), exp.clone()));
+            }
+            self.shadows(var, exp);
+        }
+        walk(self, exp);
+    }
+
+    fn enter(&mut self, var: &Var) {
+        self.scope.push(var.clone());
+    }
+
+    fn leave(&mut self, _var: &Var) {
+        self.scope.pop();
+    }
+}
+
+impl Lints<'_> {
+    /// Fetch the arguments of the given expression if it is the application of an eliminator
+    /// (built in, or that of a data type declared in the context), or nothing otherwise.
+    fn methods<'e>(&self, exp: &'e Exp) -> Option<Vec<&'e Exp>> {
+        match exp {
+            Exp::NatRec(..) | Exp::Case(..) => Some(exp.subterms()),
+            Exp::App(..) => match exp.spine() {
+                (Exp::Var(VarIdx::Var(var)), args) if self.ctx.get_elim(var).is_some() => {
+                    Some(args)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Visit the given argument of an eliminator, warning about its leading λ binders only if
+    /// they shadow other variables, and about the rest of it as usual.
+    fn method(&mut self, exp: &Exp) {
+        match exp {
+            Exp::Abs(var, typ, body) => {
+                self.shadows(var, exp);
+                self.visit(typ);
+                self.enter(var);
+                self.method(body);
+                self.leave(var);
+            }
+            _ => self.visit(exp),
+        }
+    }
+
+    /// Warn if the given variable, bound by the given binder, shadows a variable in scope (unless
+    /// its name starts with `_`).
+    fn shadows(&mut self, var: &Var, exp: &Exp) {
+        if !var.0.starts_with('_') && (self.scope.contains(var) || self.ctx.resolve(var).is_some())
+        {
+            self.lints.push(Lint::Shadowed(var.clone(), exp.clone()));
+        }
+    }
+}
@@ -1,13 +1,17 @@
 use fluxo::app;
 use fluxo::pmt::Prompt;
+use fluxo::thm::Theme;
 use std::process;
 
 /// Main function and entry-point for the operating system process.
 fn main() {
     process::exit(match app::run() {
-        Ok(()) => exitcode::OK,
+        Ok(code) => code,
         Err(e) => {
-            eprint!("{}", Prompt::show_failure(&e.to_string()));
+            eprint!(
+                "{}",
+                Prompt::show_failure(&e.to_string(), &Theme::default(), false)
+            );
             exitcode::IOERR
         }
     })
@@ -1,9 +1,65 @@
 //! Editor buffer and related utilities.
 
-use crate::pmt::Prompt;
+use crate::ast::{Ctx, Exp, Match, Pos, Var};
+use crate::cmd::{Cmd, Out, Settings, Status};
+use crate::dir::xdg_dir;
+use crate::err::TypingErr;
+use crate::par::{self, Cat, Span, Stream, Tok};
+use crate::pmt::{Gutter, Prompt};
+use crate::thm::{Paint, Theme};
+use crossterm::style::Color;
+use std::fs;
+use std::path::{Path, PathBuf};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 const CHR_ESC: char = '\\';
 
+/// Digraphs typed after the escape character, along with the glyphs that they stand for, which
+/// users may add to (or override) in their own [table][Digraphs::default_path].
+pub const DIGRAPHS: [(&str, &str); 19] = [
+    ("\\", "\\"),
+    ("l", "λ"),
+    ("p", "Π"),
+    ("S", "Σ"),
+    ("u", "□"),
+    ("->", "→"),
+    ("N", "ℕ"),
+    ("T", "⊤"),
+    ("F", "⊥"),
+    ("G", "Γ"),
+    ("D", "Δ"),
+    ("a", "α"),
+    ("b", "β"),
+    ("g", "γ"),
+    ("d", "δ"),
+    ("e", "ε"),
+    ("==", "≡"),
+    ("<=", "≤"),
+    (">=", "≥"),
+];
+
+/// Table of the digraphs typed after the escape character (such as `\->`), each of which is
+/// replaced with the glyph that it stands for (such as `→`).
+#[derive(Debug, Clone)]
+pub struct Digraphs {
+    /// Digraphs along with their glyphs, where a later entry overrides an earlier one.
+    pub ents: Vec<(String, String)>,
+}
+
+/// Number of regions kept in a [kill ring][Ring], beyond which the earliest are dropped.
+pub const RING_SIZE: usize = 16;
+
+/// Kill ring that holds the regions most recently killed (deleted) from buffers, which may be
+/// yanked (reinserted) into a buffer, starting with the latest.
+#[derive(Debug, Default)]
+pub struct Ring {
+    /// Regions killed, the latest last.
+    pub ents: Vec<String>,
+    /// Index of the region that is yanked next.
+    pub pos: usize,
+}
+
 /// Buffer object.
 #[derive(Debug, Default)]
 pub struct Buf {
@@ -13,6 +69,231 @@ pub struct Buf {
     pub idx: usize,
     /// Whether or not this buffer is currently in escape mode.
     pub esc: bool,
+    /// Characters typed in escape mode so far, which begin one or more [digraphs][Digraphs].
+    pub seq: String,
+    /// Table of the digraphs that may be typed in escape mode.
+    pub dig: Digraphs,
+    /// Stream of tokens in the buffer, kept in sync with the raw data as it is edited.
+    pub lex: Stream,
+    /// Snapshots of the raw data and cursor location before each group of edits, the latest last,
+    /// which are restored by [undo][Self::undo].
+    pub undo: Vec<(String, usize)>,
+    /// Snapshots of the raw data and cursor location before each edit undone, the latest last,
+    /// which are restored by [redo][Self::redo] until the buffer is edited again.
+    pub redo: Vec<(String, usize)>,
+    /// Whether the latest edit inserted a character within a word, such that inserting the rest
+    /// of the word is undone along with it.
+    pub grp: bool,
+    /// Column (in terms of display width) that the cursor is kept at, as nearly as possible, while
+    /// it is moved from line to line, which is cleared once the cursor is moved otherwise.
+    pub col: Option<usize>,
+    /// Horizontal offset (in terms of display width) of the first column shown of each line, so
+    /// that the cursor stays in view when it is moved beyond the width of the terminal.
+    pub off: usize,
+    /// Index of the first line shown, so that the cursor stays in view when it is moved beyond
+    /// the height of the terminal.
+    pub top: usize,
+    /// Range of the text inserted by the latest edit, if that edit was a [yank][Self::yank], which
+    /// [yank_pop][Self::yank_pop] replaces.
+    pub yanked: Option<(usize, usize)>,
+}
+
+/// Hole in the input that the cursor is on, along with what is known about the term to fill it
+/// with, as shown in the side panel while proving interactively.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Goal {
+    /// Type(s) that a term filling the hole is expected to have, as far as is known, or else the
+    /// error met while checking the input.
+    pub typ: String,
+    /// Variables bound around the hole along with their types, from the innermost outward.
+    pub vars: Vec<String>,
+    /// Terms that the hole may be [refined][Buf::refine] with, from the closest fit onwards.
+    pub fits: Vec<String>,
+}
+
+/// Innermost sub-expression of the input that the cursor is within, as found by [Buf::focus].
+struct Focus<'a> {
+    /// Sub-expression itself.
+    exp: &'a Exp,
+    /// Span of the input that the sub-expression was parsed from.
+    span: &'a Span,
+    /// Index of each sub-expression descended into on the way, among those of its parent.
+    path: Vec<usize>,
+    /// Variables bound by the binders descended into, along with their types (or a hole, where
+    /// the type is not given), from the outermost inward.
+    vars: Vec<(&'a Var, Exp)>,
+    /// Context extended with the variables bound whose types are given.
+    ctx: Ctx,
+}
+
+/// Fill each hole in the given expression that stands for a term (rather than the type of a
+/// binder) with a fresh metavariable of an unknown type, in the given context, except the hole at
+/// the given path of [sub-expressions][Exp::subterms] (if any).
+fn fill(exp: Exp, path: Option<&[usize]>, ctx: &Ctx) -> Exp {
+    if exp == Exp::Hole {
+        return match path {
+            Some([]) => exp,
+            _ => ctx.fresh_meta(&ctx.fresh_meta(&Exp::get_type_meta())),
+        };
+    }
+    let mut idx = 0;
+    exp.map_subterms(|sub, pos| {
+        let path = (path.and_then(<[usize]>::split_first))
+            .filter(|(first, _)| **first == idx)
+            .map(|(_, rest)| rest);
+        idx += 1;
+        match (sub, pos) {
+            (Exp::Hole, Pos::Type(_)) => Exp::Hole,
+            (sub, _) => fill(sub, path, ctx),
+        }
+    })
+}
+
+/// Fetch the terms that a hole of the given type may be refined with in the given context: the
+/// variables whose types [match][Ctx::search] it (applied to holes for the arguments that they
+/// still need), and an abstraction if it is the type of a function.
+fn refinements(typ: &Exp, ctx: &Ctx) -> Vec<String> {
+    let mut fits: Vec<String> = (ctx.search(typ).into_iter())
+        .map(|(fit, var, _)| match fit {
+            Match::Result(args) => format!("({}{})", var, " _".repeat(args)),
+            Match::Exact | Match::Instance => var.to_string(),
+        })
+        .collect();
+    if let Ok(Exp::For(var, dom, _)) = typ.clone().reduce(ctx) {
+        if !dom.is_implicit() {
+            let var = match var.to_string().starts_with(char::is_alphabetic) {
+                true => var.to_string(),
+                false => "x".to_string(),
+            };
+            fits.push(format!("(λ{} : {} . _)", var, dom.display(ctx)));
+        }
+    }
+    fits
+}
+
+/// Fetch the color (of the given theme) that a token of the given category is highlighted with,
+/// unless it is left as it is (as punctuation is).
+fn color(cat: &Cat, thm: &Theme) -> Option<Color> {
+    thm.color(cat.part()?)
+}
+
+/// Check whether the given grapheme is part of a word (a name or a number).
+fn is_word(grapheme: &str) -> bool {
+    grapheme
+        .chars()
+        .next()
+        .is_some_and(|chr| chr == '_' || chr == '\'' || chr.is_alphanumeric())
+}
+
+/// Fetch the indices of the delimiters among the given tokens that are not balanced, namely those
+/// that close a delimiter of another kind (or none at all), and those that are never closed.
+fn unbalanced(toks: &[Tok]) -> Vec<usize> {
+    let mut bad = vec![];
+    let mut open: Vec<(usize, &Cat)> = vec![];
+    for (i, tok) in toks.iter().enumerate() {
+        match (&tok.cat, open.last()) {
+            (Cat::Opn | Cat::Lbr, _) => open.push((i, &tok.cat)),
+            (Cat::Cls, Some((_, Cat::Opn))) | (Cat::Rbr, Some((_, Cat::Lbr))) => {
+                open.pop();
+            }
+            (Cat::Cls | Cat::Rbr, _) => bad.push(i),
+            _ => {}
+        }
+    }
+    bad.extend(open.into_iter().map(|(i, _)| i));
+    bad
+}
+
+impl Default for Digraphs {
+    fn default() -> Self {
+        Self {
+            ents: (DIGRAPHS.iter())
+                .map(|(seq, glyph)| (seq.to_string(), glyph.to_string()))
+                .collect(),
+        }
+    }
+}
+
+impl Digraphs {
+    /// Create a new instance of the table of [default digraphs][DIGRAPHS].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the table of default digraphs, along with those in the file at the given path (if it
+    /// exists), each on a line of its own, followed by its glyph (as in `-> →`), where blank
+    /// lines and those starting with `#` are ignored.
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let mut dig = Self::new();
+        let src = match fs::read_to_string(path) {
+            Ok(src) => src,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(dig),
+            Err(e) => return Err(e),
+        };
+        for line in src.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match line.split_whitespace().collect::<Vec<_>>()[..] {
+                [seq, glyph] => dig.ents.push((seq.to_string(), glyph.to_string())),
+                _ => {
+                    let msg = format!("expected a digraph and its glyph, found {}", line);
+                    return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg));
+                }
+            }
+        }
+        Ok(dig)
+    }
+
+    /// Fetch the path of the file that users keep their own digraphs in by default, namely
+    /// `$XDG_CONFIG_HOME/fluxo/digraphs` (where `$XDG_CONFIG_HOME` defaults to `~/.config`).
+    pub fn default_path() -> Option<PathBuf> {
+        Some(xdg_dir("XDG_CONFIG_HOME", ".config")?.join("digraphs"))
+    }
+
+    /// Fetch the glyph that the given digraph stands for, if there is one.
+    pub fn get(&self, seq: &str) -> Option<&str> {
+        (self.ents.iter().rev())
+            .find(|(key, _)| key == seq)
+            .map(|(_, glyph)| glyph.as_str())
+    }
+
+    /// Check whether any digraph extends the given (incomplete) one.
+    pub fn extends(&self, seq: &str) -> bool {
+        (self.ents.iter()).any(|(key, _)| key.len() > seq.len() && key.starts_with(seq))
+    }
+}
+
+impl Ring {
+    /// Create a new instance of an empty kill ring.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the given region as the latest killed, which is yanked next, dropping the earliest
+    /// region if the ring is full.
+    pub fn push(&mut self, text: String) {
+        self.ents.push(text);
+        if self.ents.len() > RING_SIZE {
+            self.ents.remove(0);
+        }
+        self.pos = self.ents.len() - 1;
+    }
+
+    /// Fetch the region that is yanked next, unless the ring is empty.
+    pub fn current(&self) -> Option<&str> {
+        self.ents.get(self.pos).map(String::as_str)
+    }
+
+    /// Rotate the ring to the region killed before the one that is yanked next (or else to the
+    /// latest region, after the earliest), returning it, unless the ring is empty.
+    pub fn rotate(&mut self) -> Option<&str> {
+        if self.ents.is_empty() {
+            return None;
+        }
+        self.pos = self.pos.checked_sub(1).unwrap_or(self.ents.len() - 1);
+        self.current()
+    }
 }
 
 impl Buf {
@@ -22,59 +303,1288 @@ impl Buf {
             raw: String::new(),
             idx: 0,
             esc: false,
+            seq: String::new(),
+            dig: Digraphs::new(),
+            lex: Stream::new(),
+            undo: Vec::new(),
+            redo: Vec::new(),
+            grp: false,
+            col: None,
+            off: 0,
+            top: 0,
+            yanked: None,
         }
     }
 
+    /// Create a new instance of the buffer, in which the given digraphs may be typed.
+    pub fn with_digraphs(dig: Digraphs) -> Self {
+        Self { dig, ..Self::new() }
+    }
+
     /// Push a character into the buffer at the current location.
+    ///
+    /// In escape mode, the characters pushed are held back until they spell out a
+    /// [digraph][Digraphs] (that no other extends), which is replaced with its glyph, or else
+    /// until they cannot, in which case they are inserted as they are, after the escape character.
     pub fn push(&mut self, chr: char) {
-        if self.esc {
-            match chr {
-                'l' => self.insert('λ'),
-                'p' => self.insert('Π'),
-                'u' => self.insert('□'),
-                _ => self.insert(chr),
-            }
-            self.esc = false;
-        } else {
+        if !self.esc {
             match chr {
                 CHR_ESC => self.esc = true,
                 _ => self.insert(chr),
             }
+            return;
+        }
+        let pre = std::mem::take(&mut self.seq);
+        let seq = format!("{}{}", pre, chr);
+        if self.dig.extends(&seq) {
+            self.seq = seq;
+            return;
+        }
+        self.esc = false;
+        if let Some(glyph) = self.dig.get(&seq).map(String::from) {
+            glyph.chars().for_each(|chr| self.insert(chr));
+            return;
+        }
+        match self.dig.get(&pre).map(String::from) {
+            Some(glyph) if !pre.is_empty() => glyph.chars().for_each(|chr| self.insert(chr)),
+            _ => {
+                self.insert(CHR_ESC); // keep the escape character, which denotes λ in ASCII
+                pre.chars().for_each(|chr| self.insert(chr));
+            }
         }
+        self.push(chr);
     }
 
-    /// Move the cursor left (if possible).
-    pub fn move_left(&mut self) {}
+    /// Replace the contents of the buffer with the given text, placing the cursor at its end.
+    pub fn replace(&mut self, raw: &str) {
+        self.save();
+        self.grp = false;
+        self.restore((raw.to_string(), raw.len()));
+    }
+
+    /// Undo the latest group of edits (if there is one), returning whether there was.
+    pub fn undo(&mut self) -> bool {
+        match self.undo.pop() {
+            Some(snap) => {
+                self.redo.push((self.raw.clone(), self.idx));
+                self.grp = false;
+                self.restore(snap);
+                true
+            }
+            None => false,
+        }
+    }
 
-    /// Move the cursor right (if possible).
-    pub fn move_right(&mut self) {}
+    /// Redo the latest group of edits undone (if there is one), returning whether there was.
+    pub fn redo(&mut self) -> bool {
+        match self.redo.pop() {
+            Some(snap) => {
+                self.undo.push((self.raw.clone(), self.idx));
+                self.grp = false;
+                self.restore(snap);
+                true
+            }
+            None => false,
+        }
+    }
 
-    /// Fetch a rendering of this buffer for display.
-    pub fn render(&self) -> String {
-        format!(
-            "{} {}",
-            &Prompt::Ready,
-            &self
-                .raw
-                .replace("\r\n", &self.prefix(&Prompt::Continue, "\r\n"))
+    /// Kill the text from the cursor to the end of its line (or else the line break at the cursor,
+    /// if the cursor is at the end of its line), returning the text killed, unless there was none.
+    pub fn kill_to_end(&mut self) -> Option<String> {
+        let end = match self.raw[self.idx..].find('\n') {
+            Some(0) => self.idx + 1,
+            Some(off) => {
+                self.idx
+                    + self.raw[self.idx..self.idx + off]
+                        .trim_end_matches('\r')
+                        .len()
+            }
+            None => self.raw.len(),
+        };
+        let end = match end {
+            end if end == self.idx && self.raw[end..].starts_with("\r\n") => end + 2,
+            end => end,
+        };
+        self.kill(self.idx, end)
+    }
+
+    /// Kill the text from the start of the line that the cursor is on to the cursor, returning the
+    /// text killed, unless there was none.
+    pub fn kill_to_start(&mut self) -> Option<String> {
+        self.kill(self.line_start(self.idx), self.idx)
+    }
+
+    /// Yank (reinsert) the given text at the cursor, placing the cursor after it.
+    pub fn yank(&mut self, text: &str) {
+        self.splice(self.idx, self.idx, text);
+        self.yanked = Some((self.idx - text.len(), self.idx));
+    }
+
+    /// Replace the text inserted by the latest edit (if it was a [yank][Self::yank]) with the
+    /// given text, returning whether it was replaced.
+    pub fn yank_pop(&mut self, text: &str) -> bool {
+        match self.yanked {
+            Some((beg, end)) => {
+                self.splice(beg, end, text);
+                self.yanked = Some((beg, beg + text.len()));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Kill the text between the given byte offsets, returning it, unless there was none.
+    fn kill(&mut self, beg: usize, end: usize) -> Option<String> {
+        if beg == end {
+            return None;
+        }
+        let text = self.raw[beg..end].to_string();
+        self.splice(beg, end, "");
+        Some(text)
+    }
+
+    /// Replace the text between the given byte offsets with the given text, as a single edit
+    /// (which may be undone), placing the cursor after it.
+    fn splice(&mut self, beg: usize, end: usize, text: &str) {
+        self.save();
+        self.raw.replace_range(beg..end, text);
+        self.idx = beg + text.len();
+        self.grp = false;
+        self.col = None;
+        self.yanked = None;
+        self.lex.sync(&self.raw, self.idx);
+    }
+
+    /// Move the cursor up to the line before (if possible), returning whether it was moved.
+    pub fn move_up(&mut self) -> bool {
+        let beg = self.line_start(self.idx);
+        if beg == 0 {
+            return false;
+        }
+        self.move_to_line(self.line_start(beg - 1))
+    }
+
+    /// Move the cursor down to the line after (if possible), returning whether it was moved.
+    pub fn move_down(&mut self) -> bool {
+        match self.raw[self.idx..].find('\n') {
+            Some(off) => self.move_to_line(self.idx + off + 1),
+            None => false,
+        }
+    }
+
+    /// Fetch the byte offset of the start of the line that the given byte offset lies on.
+    fn line_start(&self, idx: usize) -> usize {
+        self.raw[..idx].rfind('\n').map_or(0, |i| i + 1)
+    }
+
+    /// Move the cursor to the line starting at the given byte offset, placing it before the first
+    /// grapheme that would extend beyond the goal column (or at the end of the line, if it is too
+    /// short), where the goal column is the current column unless the cursor was last moved from
+    /// line to line as well.
+    fn move_to_line(&mut self, beg: usize) -> bool {
+        let cur = self.line_start(self.idx);
+        let col = *self
+            .col
+            .get_or_insert_with(|| self.raw[cur..self.idx].width());
+        let end = self.raw[beg..]
+            .find('\n')
+            .map_or(self.raw.len(), |i| beg + i);
+        let line = self.raw[beg..end].trim_end_matches('\r');
+        let mut off = line.len();
+        let mut pos = 0;
+        for (i, grapheme) in line.grapheme_indices(true) {
+            pos += grapheme.width();
+            if pos > col {
+                off = i;
+                break;
+            }
+        }
+        self.idx = beg + off;
+        self.lex.sync(&self.raw, self.idx);
+        true
+    }
+
+    /// Move the cursor left past one grapheme (if possible), returning whether it was moved.
+    pub fn move_left(&mut self) -> bool {
+        match self.raw[..self.idx].grapheme_indices(true).next_back() {
+            Some((i, _)) => self.move_to(i),
+            None => false,
+        }
+    }
+
+    /// Move the cursor right past one grapheme (if possible), returning whether it was moved.
+    pub fn move_right(&mut self) -> bool {
+        match self.raw[self.idx..].graphemes(true).next() {
+            Some(grapheme) => self.move_to(self.idx + grapheme.len()),
+            None => false,
+        }
+    }
+
+    /// Move the cursor left to the start of the word before it (if possible), returning whether it
+    /// was moved.
+    pub fn move_word_left(&mut self) -> bool {
+        let mut idx = self.idx;
+        let mut seen = false; // whether a word has been passed over
+        for (i, grapheme) in self.raw[..self.idx].grapheme_indices(true).rev() {
+            match is_word(grapheme) {
+                false if seen => break,
+                word => seen |= word,
+            }
+            idx = i;
+        }
+        idx != self.idx && self.move_to(idx)
+    }
+
+    /// Move the cursor right to the end of the word after it (if possible), returning whether it
+    /// was moved.
+    pub fn move_word_right(&mut self) -> bool {
+        let mut idx = self.idx;
+        let mut seen = false; // whether a word has been passed over
+        for (i, grapheme) in self.raw[self.idx..].grapheme_indices(true) {
+            match is_word(grapheme) {
+                false if seen => break,
+                word => seen |= word,
+            }
+            idx = self.idx + i + grapheme.len();
+        }
+        idx != self.idx && self.move_to(idx)
+    }
+
+    /// Move the cursor to the start of its line, returning whether it was moved.
+    pub fn move_home(&mut self) -> bool {
+        let beg = self.line_start(self.idx);
+        beg != self.idx && self.move_to(beg)
+    }
+
+    /// Move the cursor to the end of its line, returning whether it was moved.
+    pub fn move_end(&mut self) -> bool {
+        let end = self.line_end(self.idx);
+        end != self.idx && self.move_to(end)
+    }
+
+    /// Delete the grapheme before the cursor (if there is one), returning whether there was.
+    pub fn delete_back(&mut self) -> bool {
+        match self.raw[..self.idx].grapheme_indices(true).next_back() {
+            Some((i, _)) => self.kill(i, self.idx).is_some(),
+            None => false,
+        }
+    }
+
+    /// Delete the grapheme at the cursor (if there is one), returning whether there was.
+    pub fn delete_forward(&mut self) -> bool {
+        match self.raw[self.idx..].graphemes(true).next() {
+            Some(grapheme) => self.kill(self.idx, self.idx + grapheme.len()).is_some(),
+            None => false,
+        }
+    }
+
+    /// Swap the grapheme before the cursor with the one at the cursor (or with the one before it,
+    /// at the end of a line), placing the cursor after both, returning whether they were swapped.
+    pub fn transpose(&mut self) -> bool {
+        let beg = self.line_start(self.idx);
+        let end = self.line_end(self.idx);
+        let idx = match self.raw[self.idx..end].graphemes(true).next() {
+            Some(grapheme) => self.idx + grapheme.len(),
+            None => self.idx,
+        };
+        let mut before = self.raw[beg..idx].grapheme_indices(true).rev();
+        match (before.next(), before.next()) {
+            (Some((j, snd)), Some((i, fst))) => {
+                let text = format!("{}{}", snd, fst);
+                self.splice(beg + i, beg + j + snd.len(), &text);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Fetch the byte offset of the end of the line that the given byte offset lies on (before
+    /// its line break).
+    fn line_end(&self, idx: usize) -> usize {
+        let end = self.raw[idx..]
+            .find('\n')
+            .map_or(self.raw.len(), |i| idx + i);
+        idx + self.raw[idx..end].trim_end_matches('\r').len()
+    }
+
+    /// Move the cursor to the given byte offset (at a grapheme boundary) within its line.
+    fn move_to(&mut self, idx: usize) -> bool {
+        self.idx = idx;
+        self.col = None;
+        self.grp = false;
+        self.lex.sync(&self.raw, self.idx);
+        true
+    }
+
+    /// Fetch the byte offset of the first grapheme boundary at or after the given byte offset,
+    /// which is where the cursor is placed, so that it never splits a grapheme (as inserting a
+    /// combining mark or a zero-width joiner may otherwise do, by joining graphemes around it).
+    fn boundary(&self, idx: usize) -> usize {
+        self.raw
+            .grapheme_indices(true)
+            .map(|(i, grapheme)| i + grapheme.len())
+            .find(|end| *end >= idx)
+            .unwrap_or(idx)
+    }
+
+    /// Interpret the contents of this buffer as a [command][Cmd], using the notation declared in
+    /// the given context and the aliases declared in the given settings.
+    pub fn value(&self, ctx: &Ctx, set: &Settings) -> Cmd {
+        par::parse_cmd(&self.raw, &self.lex.toks(), ctx, set)
+            .unwrap_or_else(|err| Cmd::Fail(TypingErr::from(err)))
+    }
+
+    /// Fetch the completions of the word that ends at the cursor (if there is one), namely the
+    /// command keywords that extend it (if it begins the input), or else the expression keywords
+    /// and the variables bound in the given context that do, in alphabetical order.
+    pub fn completions(&self, ctx: &Ctx) -> Vec<String> {
+        let Some((pos, pre)) = self.word() else {
+            return vec![];
+        };
+        let mut words: Vec<String> = match pos {
+            0 => par::COMMANDS.iter().map(|key| key.to_string()).collect(),
+            _ => (par::KEYWORDS.iter().map(|key| key.to_string()))
+                .chain(ctx.iter().map(|(var, _)| var.to_string()))
+                .collect(),
+        };
+        words.retain(|word| word.starts_with(pre) && word != pre);
+        words.sort();
+        words.dedup();
+        words
+    }
+
+    /// Replace the word that ends at the cursor (if there is one) with the given completion, as a
+    /// single edit (which may be undone).
+    pub fn complete(&mut self, word: &str) {
+        if let Some((_, pre)) = self.word() {
+            self.splice(self.idx - pre.len(), self.idx, word);
+        }
+    }
+
+    /// Fetch the word (a name or keyword) that ends at the cursor, along with the position of its
+    /// token within the input, unless the cursor does not follow a word.
+    fn word(&self) -> Option<(usize, &str)> {
+        let toks = self.lex.toks();
+        let toks = toks
+            .iter()
+            .filter(|tok| !matches!(tok.cat, Cat::Cur | Cat::Rem(_)));
+        let (pos, tok) = toks.enumerate().find(|(_, tok)| tok.end == self.idx)?;
+        let text = &self.raw[tok.beg..tok.end];
+        let mut chars = text.chars();
+        let word = chars
+            .next()
+            .is_some_and(|chr| chr == '_' || (chr.is_alphabetic() && !"λΠΣ".contains(chr)))
+            && chars.all(|chr| chr == '_' || chr == '\'' || chr.is_alphanumeric());
+        word.then_some((pos, text))
+    }
+
+    /// Fetch the line of the syntax error in this buffer, parsed using the notation declared in
+    /// the given context and the aliases declared in the given settings, unless there is none, or
+    /// the error lies at the end of the input (which is merely incomplete so far).
+    pub fn error_line(&self, ctx: &Ctx, set: &Settings) -> Option<usize> {
+        match par::parse_cmd(&self.raw, &self.lex.toks(), ctx, set) {
+            Err(err) if err.beg < self.raw.trim_end().len() => {
+                Some(self.raw[..err.beg].matches('\n').count())
+            }
+            _ => None,
+        }
+    }
+
+    /// Check the input typed so far, parsed using the notation declared in the given context and
+    /// type-checked in it with the given settings (permitting at most the given number of steps for
+    /// each reduction), without changing either, and return a line that reports the type of the expression that the
+    /// input holds (as in `: ℕ`), or else the first error found.
+    ///
+    /// There is nothing to report if the input is merely incomplete so far, or if its command
+    /// holds no expression (other than a definition or declaration, which is checked as well).
+    pub fn check(&self, ctx: &Ctx, set: &Settings, fuel: usize) -> Option<String> {
+        let (mut ctx, mut set) = (ctx.isolated(), set.clone());
+        ctx.set_fuel(fuel.min(ctx.get_fuel()));
+        let fail = |msg: &str| format!("✗ {}", msg.lines().next().unwrap_or_default().trim());
+        let cmd = match par::parse_cmd(&self.raw, &self.lex.toks(), &ctx, &set) {
+            Ok(cmd) => cmd,
+            Err(err) if err.beg < self.raw.trim_end().len() => return Some(fail(&err.to_string())),
+            Err(_) => return None,
+        };
+        match &cmd {
+            Cmd::Show(exp)
+            | Cmd::Type(exp)
+            | Cmd::Whnf(exp)
+            | Cmd::Time(exp)
+            | Cmd::Trace(exp)
+            | Cmd::Exec(exp)
+            | Cmd::Step(Some(exp)) => {
+                let (out, _) = Cmd::type_of(exp, &ctx, &set);
+                let msg = out.msg.unwrap_or_default();
+                match out.status {
+                    Status::Failure => Some(fail(&msg)),
+                    _ => Some(format!(": {}", msg)),
+                }
+            }
+            Cmd::Let(..) | Cmd::Assume(..) => match cmd.eval(&mut ctx, &mut set) {
+                Out {
+                    status: Status::Failure,
+                    msg,
+                    ..
+                } => Some(fail(&msg.unwrap_or_default())),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Infer the type of the innermost sub-expression of the input that the cursor is within (or
+    /// just after), in an isolated copy of the given context with the given settings, spending at
+    /// most the given fuel,
+    /// without submitting the input, and return a message with the sub-expression and its type
+    /// (or the error that prevents it from being typed).
+    ///
+    /// The cursor is mapped back to a sub-expression through the [spans][par::Span] of the parsed
+    /// expression, descending into the body of each binder under the cursor with its variable
+    /// bound. Nothing is reported unless the input is a command that takes an expression, and the
+    /// cursor is within it.
+    pub fn inspect(&self, ctx: &Ctx, set: &Settings, fuel: usize) -> Option<String> {
+        let mut ctx = ctx.isolated();
+        ctx.set_fuel(fuel.min(ctx.get_fuel()));
+        let fail = |msg: &str| format!("✗ {}", msg.lines().next().unwrap_or_default().trim());
+        let (exp, span) = match par::parse_cmd_exp(&self.raw, &self.lex.toks(), &ctx)? {
+            Ok(node) => node,
+            Err(err) => return Some(fail(&err.to_string())),
+        };
+        if !span.contains(self.idx) {
+            return None;
+        }
+        let foc = self.focus(&exp, &span, &ctx);
+        let (out, _) = Cmd::type_of(foc.exp, &foc.ctx, set);
+        let msg = out.msg.unwrap_or_default();
+        match out.status {
+            Status::Failure => Some(fail(&msg)),
+            _ => Some(format!(
+                "{} : {}",
+                self.raw[foc.span.beg..foc.span.end].trim(),
+                msg
+            )),
+        }
+    }
+
+    /// Descend from the given expression (parsed from the input, with the given span) to the
+    /// innermost sub-expression that the cursor is within, binding the variable of each binder
+    /// whose body it descends into (if its type is given) in a copy of the given context.
+    fn focus<'a>(&self, exp: &'a Exp, span: &'a Span, ctx: &Ctx) -> Focus<'a> {
+        let mut foc = Focus {
+            exp,
+            span,
+            path: vec![],
+            vars: vec![],
+            ctx: ctx.clone(),
+        };
+        loop {
+            let subs = foc.exp.fold(vec![], |mut subs, sub, pos| {
+                subs.push((sub, pos));
+                subs
+            });
+            if subs.len() != foc.span.subs.len() {
+                break; // desugared, so its parts cannot be told apart in the source text
+            }
+            let Some((idx, ((sub, pos), span))) = (subs.iter().zip(&foc.span.subs))
+                .enumerate()
+                .find(|(_, (_, span))| span.contains(self.idx))
+            else {
+                break;
+            };
+            if let Pos::Body(var) = pos {
+                match subs.iter().find(|(_, p)| *p == Pos::Type(var)) {
+                    Some((Exp::Hole, _)) | None => foc.vars.push((var, Exp::Hole)),
+                    Some((typ, _)) => {
+                        let typ = typ.elaborate(&foc.ctx);
+                        foc.ctx = foc.ctx.extend(var, &typ);
+                        foc.vars.push((var, typ));
+                    }
+                }
+            }
+            foc.path.push(idx);
+            (foc.exp, foc.span) = (sub, span);
+        }
+        foc
+    }
+
+    /// Fetch the byte offsets of the start and end of the hole that the cursor is on (or just
+    /// after), if it is on one.
+    pub fn hole(&self) -> Option<(usize, usize)> {
+        (self.holes().into_iter()).find(|(beg, end)| *beg <= self.idx && self.idx <= *end)
+    }
+
+    /// Fetch the byte offsets of the start and end of each hole in the input, in order.
+    fn holes(&self) -> Vec<(usize, usize)> {
+        (self.lex.toks().iter())
+            .filter(|tok| tok.cat == Cat::Hole)
+            .map(|tok| (tok.beg, tok.end))
+            .collect()
+    }
+
+    /// Move the cursor to the start of the next hole in the input (or else the first, wrapping
+    /// around), returning whether it was moved.
+    pub fn next_hole(&mut self) -> bool {
+        let holes = self.holes();
+        match holes
+            .iter()
+            .find(|(beg, _)| *beg > self.idx)
+            .or(holes.first())
+        {
+            Some(&(beg, _)) => beg != self.idx && self.move_to(beg),
+            None => false,
+        }
+    }
+
+    /// Move the cursor to the start of the previous hole in the input (or else the last, wrapping
+    /// around), returning whether it was moved.
+    pub fn prev_hole(&mut self) -> bool {
+        let holes = self.holes();
+        match holes
+            .iter()
+            .rfind(|(beg, _)| *beg < self.idx)
+            .or(holes.last())
+        {
+            Some(&(beg, _)) => beg != self.idx && self.move_to(beg),
+            None => false,
+        }
+    }
+
+    /// Fetch the [goal][Goal] of the hole that the cursor is on, in an isolated copy of the given
+    /// context (rendered with the given settings), spending at most the given fuel, unless the cursor is not on a hole within the
+    /// expression of a command.
+    ///
+    /// Each other hole that stands for a term is filled with a metavariable for the time being, so
+    /// that checking the expression reaches the hole at the cursor and reports the type that it
+    /// is expected to have.
+    pub fn goal(&self, ctx: &Ctx, set: &Settings, fuel: usize) -> Option<Goal> {
+        self.hole()?;
+        let mut ctx = ctx.isolated();
+        ctx.set_fuel(fuel.min(ctx.get_fuel()));
+        let (exp, span) = par::parse_cmd_exp(&self.raw, &self.lex.toks(), &ctx)?.ok()?;
+        let foc = self.focus(&exp, &span, &ctx);
+        if *foc.exp != Exp::Hole {
+            return None;
+        }
+        let vars = (foc.vars.iter().rev())
+            .map(|(var, typ)| format!("{} : {}", var, typ.display(&foc.ctx).ascii(set.asc)))
+            .collect();
+        let filled = fill(exp.clone(), Some(&foc.path), &ctx);
+        let acc = match filled
+            .elaborate(&ctx)
+            .infer(&ctx)
+            .map_err(TypingErr::into_cause)
+        {
+            Err(TypingErr::TypeHoleErr(err)) => err.acc,
+            Err(err) => {
+                let msg = err.to_string();
+                let typ = format!("✗ {}", msg.lines().next().unwrap_or_default().trim());
+                let fits = vec![];
+                return Some(Goal { typ, vars, fits });
+            }
+            Ok(_) => vec![],
+        };
+        let acc: Vec<Exp> = acc.into_iter().map(|typ| typ.zonk(&ctx)).collect();
+        let typ = match acc.is_empty() {
+            true => "?".to_string(),
+            false => (acc.iter())
+                .map(|typ| typ.display(&foc.ctx).ascii(set.asc).to_string())
+                .collect::<Vec<_>>()
+                .join(" or "),
+        };
+        let fits = match &acc[..] {
+            [typ] => refinements(typ, &foc.ctx),
+            _ => vec![],
+        };
+        Some(Goal { typ, vars, fits })
+    }
+
+    /// Replace the hole that the cursor is on (if it is on one) with the given term, as a single
+    /// edit (which may be undone), returning whether it was on one.
+    pub fn refine(&mut self, term: &str) -> bool {
+        match self.hole() {
+            Some((beg, end)) => {
+                self.splice(beg, end, term);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Fetch the width of the gutter of the given kind that precedes each line of this buffer,
+    /// which fits the number of the last line, if lines are numbered, along with the widest of
+    /// the prompts (in ASCII, if so specified).
+    pub fn gutter(&self, gut: Gutter, ascii: bool) -> usize {
+        let pmt = Prompt::input_width(ascii);
+        match self.digits(gut) {
+            Some(digits) => digits + pmt + 2,
+            None => pmt + 1,
+        }
+    }
+
+    /// Fetch the number of digits that each line number takes up in the gutter of the given kind,
+    /// unless lines are not numbered (as they are not while there is just one).
+    fn digits(&self, gut: Gutter) -> Option<usize> {
+        let lines = self.raw.matches('\n').count() + 1;
+        match gut {
+            Gutter::Numbers if lines > 1 => Some(lines.to_string().len()),
+            _ => None,
+        }
+    }
+
+    /// Fetch a rendering of this buffer for display, with its text [highlighted][Self::highlight]
+    /// in the colors of the given theme and each line prefixed with a [gutter][Gutter] of the given kind, showing only the given
+    /// number of lines from the [top line][Self::top] onwards, and the given number of columns of
+    /// each line from the [horizontal offset][Self::off] onwards.
+    ///
+    /// The line of the syntax error given (if any) is marked in place of its prompt, and prompts
+    /// are rendered in ASCII if so specified.
+    pub fn render(
+        &self,
+        cols: usize,
+        rows: usize,
+        gut: Gutter,
+        err: Option<usize>,
+        thm: &Theme,
+        ascii: bool,
+    ) -> String {
+        let digits = self.digits(gut);
+        let width = Prompt::input_width(ascii);
+        let pmt = |ln| {
+            let pmt = match ln {
+                _ if err == Some(ln) => &Prompt::Failure,
+                0 => &Prompt::Ready,
+                _ => &Prompt::Continue,
+            };
+            // each prompt is padded to the width of the widest, so that the lines stay aligned
+            let pad = " ".repeat(width - pmt.glyph(ascii).width() + 1);
+            match digits {
+                Some(digits) => {
+                    let num = format!("{:>digits$}", ln + 1);
+                    format!("{} {}{}", num.paint(thm.muted), pmt.styled(thm, ascii), pad)
+                }
+                None => format!("{}{}", pmt.styled(thm, ascii), pad),
+            }
+        };
+        let mut out = pmt(self.top);
+        let mut ln = 0; // line within the buffer
+        let mut col = 0; // display column within the current line
+        for (text, color) in self.spans(thm) {
+            let mut piece = String::new();
+            let flush = |piece: &mut String, out: &mut String| {
+                match color {
+                    Some(color) => out.push_str(&piece.as_str().paint(color).to_string()),
+                    None => out.push_str(piece),
+                }
+                piece.clear();
+            };
+            for grapheme in text.graphemes(true) {
+                if grapheme.ends_with('\n') {
+                    flush(&mut piece, &mut out);
+                    ln += 1;
+                    col = 0;
+                    if ln > self.top && ln < self.top + rows {
+                        out.push_str(&format!("\r\n{}", pmt(ln)));
+                    }
+                    continue;
+                }
+                let end = col + grapheme.width();
+                let shown = ln >= self.top && ln < self.top + rows;
+                if shown && col >= self.off && end <= self.off + cols {
+                    piece.push_str(grapheme);
+                }
+                col = end;
+            }
+            if !piece.is_empty() {
+                flush(&mut piece, &mut out);
+            }
+        }
+        out
+    }
+
+    /// Scroll this buffer (as little as possible) so that the cursor is within the given number of
+    /// lines from the [top line][Self::top], and within the given number of columns from the
+    /// [horizontal offset][Self::off].
+    pub fn scroll(&mut self, cols: usize, rows: usize) {
+        let (row, col) = self.cursor();
+        if col < self.off {
+            self.off = col;
+        } else if col >= self.off + cols {
+            self.off = col + 1 - cols;
+        }
+        if row < self.top {
+            self.top = row;
+        } else if row >= self.top + rows {
+            self.top = row + 1 - rows;
+        }
+    }
+
+    /// Fetch the number of lines of this buffer shown from the [top line][Self::top] onwards,
+    /// given the number of lines that fit on the screen.
+    pub fn shown(&self, rows: usize) -> usize {
+        let lines = self.raw.matches('\n').count() + 1;
+        lines.saturating_sub(self.top).clamp(1, rows)
+    }
+
+    /// Fetch the line and column (in terms of display width) of the cursor within this buffer.
+    pub fn cursor(&self) -> (usize, usize) {
+        let beg = self.line_start(self.idx);
+        (
+            self.raw[..beg].matches('\n').count(),
+            self.raw[beg..self.idx].width(),
         )
     }
 
-    /// Prefix the given prompt to the specified output.
-    fn prefix(&self, pmt: &Prompt, output: &str) -> String {
-        output
-            .lines()
-            .map(|s| format!("{} {}\r\n", pmt, s.trim_end()))
-            .collect::<String>()
+    /// Fetch the text of this buffer with each of its tokens colored by category (in the colors of
+    /// the given theme), so that binders,
+    /// sorts and variables (among others) stand apart, and any delimiter that is not balanced by
+    /// a matching one stands out.
+    pub fn highlight(&self, thm: &Theme) -> String {
+        self.spans(thm)
+            .into_iter()
+            .map(|(text, color)| match color {
+                Some(color) => text.paint(color).to_string(),
+                None => text.to_string(),
+            })
+            .collect()
+    }
+
+    /// Split the text of this buffer into spans, each with the color (if any) that it is
+    /// [highlighted][Self::highlight] with.
+    fn spans(&self, thm: &Theme) -> Vec<(&str, Option<Color>)> {
+        let toks: Vec<Tok> = (self.lex.toks().into_iter())
+            .filter(|tok| tok.cat != Cat::Cur)
+            .collect();
+        let bad = unbalanced(&toks);
+        let mut spans = vec![];
+        let mut end = 0;
+        for (i, tok) in toks.iter().enumerate() {
+            spans.push((&self.raw[end..tok.beg], None));
+            let color = match &tok.cat {
+                _ if bad.contains(&i) => Some(thm.error),
+                cat => color(cat, thm),
+            };
+            spans.push((&self.raw[tok.beg..tok.end], color));
+            end = tok.end;
+        }
+        spans.push((&self.raw[end..], None));
+        spans.retain(|(text, _)| !text.is_empty());
+        spans
+    }
+
+    /// Keep a snapshot of the buffer before an edit, so that the edit may be undone, which
+    /// discards the edits undone before (since they can no longer be redone).
+    fn save(&mut self) {
+        self.undo.push((self.raw.clone(), self.idx));
+        self.redo.clear();
     }
 
-    /// Insert a character at the current location and advance the cursor.
+    /// Restore the raw data and cursor location of the given snapshot.
+    fn restore(&mut self, (raw, idx): (String, usize)) {
+        self.raw = raw;
+        self.idx = idx;
+        self.col = None;
+        self.yanked = None;
+        self.esc = false;
+        self.seq.clear();
+        self.lex.sync(&self.raw, self.idx);
+    }
+
+    /// Insert a character at the current location and advance the cursor past the grapheme that
+    /// it becomes part of, which (for a combining mark, say) may join the graphemes around it.
+    ///
+    /// A character within a word is grouped with the insertion just before it (if there was one),
+    /// so that a word is undone at once, rather than one character at a time.
     fn insert(&mut self, chr: char) {
-        // Remember the current length of the raw string before updating it.
-        let old_len = self.raw.len();
+        if !self.grp || chr.is_whitespace() {
+            self.save();
+        }
+        self.grp = !chr.is_whitespace();
         // Insert the character at the current location.
         self.raw.insert(self.idx, chr);
-        // Increment the index by the same amount that the raw string's length has increased by.
-        self.idx += self.raw.len() - old_len;
+        // Advance the index past the character, and any grapheme that it has joined.
+        self.idx = self.boundary(self.idx + chr.len_utf8());
+        self.col = None;
+        self.yanked = None;
+        // Re-tokenize the region of the buffer affected by the insertion.
+        self.lex.sync(&self.raw, self.idx);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crossterm::style::Stylize;
+
+    #[test]
+    fn test_buf_0001() {
+        let mut buf = Buf::new();
+        "show ab".chars().for_each(|chr| buf.push(chr));
+        assert!(buf.undo());
+        assert_eq!((buf.raw.as_str(), buf.idx), ("show ", 5));
+        assert!(buf.undo());
+        assert_eq!(buf.raw, "show");
+        assert!(buf.undo());
+        assert_eq!(buf.raw, "");
+        assert!(!buf.undo());
+        assert!(buf.redo());
+        assert!(buf.redo());
+        assert_eq!((buf.raw.as_str(), buf.idx), ("show ", 5));
+        // editing the buffer discards the edits undone, which can no longer be redone
+        buf.push('\\');
+        buf.push('l');
+        assert_eq!(buf.raw, "show λ");
+        assert!(!buf.redo());
+        assert!(buf.undo());
+        assert_eq!(buf.raw, "show ");
+        buf.replace("type x");
+        assert!(buf.undo());
+        assert_eq!(buf.raw, "show ");
+        assert_eq!(buf.lex.toks().len(), 2); // the tokens follow the raw data
+    }
+
+    #[test]
+    fn test_buf_0002() {
+        let mut buf = Buf::new();
+        buf.replace("let idéntity : ⊤\r\n  = 漢字 ()\n  ()");
+        buf.idx = "let idé".len();
+        assert!(!buf.move_up());
+        // the goal column is kept across lines that are too short, or have wide graphemes
+        assert!(buf.move_down());
+        assert_eq!(&buf.raw[buf.idx..], "字 ()\n  ()");
+        assert!(buf.move_down());
+        assert_eq!(&buf.raw[buf.idx..], "");
+        assert!(!buf.move_down());
+        assert!(buf.move_up());
+        assert!(buf.move_up());
+        assert_eq!(&buf.raw[..buf.idx], "let idé");
+        // the goal column is reset once the buffer is edited
+        buf.push('e');
+        assert!(buf.move_down());
+        assert_eq!(&buf.raw[buf.idx..], " ()\n  ()");
+        // a grapheme made of several characters is never split
+        buf.replace("ab\ne\u{301}x");
+        buf.idx = 1;
+        assert!(buf.move_down());
+        assert_eq!(&buf.raw[buf.idx..], "x");
+    }
+
+    #[test]
+    fn test_buf_0003() {
+        let mut buf = Buf::new();
+        let mut ring = Ring::new();
+        buf.replace("let x : ℕ\r\n  = 1n\n  show x");
+        buf.idx = "let x".len();
+        ring.push(buf.kill_to_end().unwrap());
+        assert_eq!(ring.current(), Some(" : ℕ"));
+        ring.push(buf.kill_to_end().unwrap());
+        assert_eq!(ring.current(), Some("\r\n"));
+        assert_eq!(buf.raw, "let x  = 1n\n  show x");
+        buf.idx = buf.raw.len() - 1;
+        ring.push(buf.kill_to_start().unwrap());
+        assert_eq!(buf.kill_to_start(), None);
+        assert_eq!(buf.raw, "let x  = 1n\nx");
+        // yanking reinserts the latest region, which may then be replaced with earlier ones
+        buf.yank(ring.current().unwrap());
+        assert_eq!(buf.raw, "let x  = 1n\n  show x");
+        assert!(buf.yank_pop(ring.rotate().unwrap()));
+        assert_eq!(buf.raw, "let x  = 1n\n\r\nx");
+        assert!(buf.yank_pop(ring.rotate().unwrap()));
+        assert_eq!(buf.raw, "let x  = 1n\n : ℕx");
+        assert_eq!(ring.rotate(), Some("  show "));
+        buf.push('y');
+        assert!(!buf.yank_pop("z"));
+        // each kill and yank is undone on its own
+        assert!(buf.undo() && buf.undo());
+        assert_eq!(buf.raw, "let x  = 1n\n\r\nx");
+        (0..RING_SIZE).for_each(|i| ring.push(i.to_string()));
+        assert_eq!(ring.ents.len(), RING_SIZE);
+        assert_eq!(ring.rotate(), Some("14"));
+    }
+
+    #[test]
+    fn test_buf_0004() {
+        let mut buf = Buf::new();
+        let paint = |text: &str, color| text.with(color).to_string();
+        buf.replace("show (λx : * . x) {- ok -} 1n");
+        assert_eq!(
+            buf.highlight(&Theme::default()),
+            format!(
+                "{} ({}{} : {} . {}) {} {}",
+                paint("show", Color::Cyan),
+                paint("λ", Color::Magenta),
+                paint("x", Color::Cyan),
+                paint("*", Color::Yellow),
+                paint("x", Color::Cyan),
+                paint("{- ok -}", Color::DarkGrey),
+                paint("1n", Color::DarkCyan),
+            )
+        );
+        // delimiters that close another kind, or are never closed, are marked
+        buf.replace("({)} (");
+        let red = |text| paint(text, Color::Red);
+        assert_eq!(
+            buf.highlight(&Theme::default()),
+            format!("{}{{{}}} {}", red("("), red(")"), red("("))
+        );
+        buf.replace("((){})");
+        assert_eq!(buf.highlight(&Theme::default()), "((){})");
+    }
+
+    #[test]
+    fn test_buf_0005() {
+        let plain = |text: String| {
+            let mut out = String::new();
+            let mut esc = false;
+            for chr in text.chars() {
+                match chr {
+                    '\u{1b}' => esc = true,
+                    'm' if esc => esc = false,
+                    _ if esc => {}
+                    _ => out.push(chr),
+                }
+            }
+            out
+        };
+        let mut buf = Buf::new();
+        buf.replace("show abcdefghij");
+        buf.scroll(8, 1);
+        assert_eq!(buf.off, 8);
+        assert_eq!(buf.cursor(), (0, 15));
+        assert_eq!(
+            plain(buf.render(8, 1, Gutter::Prompts, None, &Theme::default(), false)),
+            "» defghij"
+        );
+        // the view only scrolls back once the cursor passes its left edge
+        buf.idx = 10;
+        buf.scroll(8, 1);
+        assert_eq!(buf.off, 8);
+        buf.idx = 6;
+        buf.scroll(8, 1);
+        assert_eq!(buf.off, 6);
+        // graphemes cut by either edge are left out, and every line is scrolled alike
+        buf.replace("λ漢字 x\r\n  = ()");
+        buf.off = 2;
+        assert_eq!(
+            plain(buf.render(3, 2, Gutter::Prompts, None, &Theme::default(), false)),
+            "» 字\r\n↳ = ("
+        );
+        buf.idx = "λ漢字".len();
+        assert_eq!(buf.cursor(), (0, 5));
+        buf.move_down();
+        assert_eq!(buf.cursor(), (1, 5));
+    }
+
+    #[test]
+    fn test_buf_0006() {
+        let mut buf = Buf::new();
+        buf.replace("a\nb\nc\nd\ne");
+        assert_eq!(buf.shown(3), 3);
+        buf.scroll(10, 3);
+        assert_eq!(buf.top, 2);
+        assert_eq!(buf.shown(3), 3);
+        let lines = buf.render(10, 3, Gutter::Prompts, None, &Theme::default(), false);
+        assert_eq!(lines.matches("\r\n").count(), 2);
+        assert!(lines.ends_with('e'.with(Color::Cyan).to_string().as_str()));
+        assert!(lines.starts_with(&Prompt::Continue.to_string()));
+        // the view only scrolls back once the cursor passes its top edge
+        buf.move_up();
+        buf.scroll(10, 3);
+        assert_eq!(buf.top, 2);
+        buf.move_up();
+        buf.move_up();
+        buf.scroll(10, 3);
+        assert_eq!(buf.top, 1);
+        assert_eq!(buf.cursor(), (1, 1));
+        buf.idx = 0;
+        buf.scroll(10, 3);
+        assert!(buf
+            .render(10, 3, Gutter::Prompts, None, &Theme::default(), false)
+            .starts_with(&Prompt::Ready.to_string()));
+        buf.top = 4;
+        assert_eq!(buf.shown(3), 1);
+    }
+
+    #[test]
+    fn test_buf_0007() {
+        let ctx = Ctx::new();
+        let mut buf = Buf::new();
+        buf.replace("show (λx");
+        assert_eq!(buf.gutter(Gutter::Numbers, false), 2);
+        assert_eq!(buf.error_line(&ctx, &Settings::default()), None);
+        buf.replace("show x\n  )\n  y\n\n\n\n\n\n\n\n");
+        assert_eq!(buf.gutter(Gutter::Prompts, false), 2);
+        assert_eq!(buf.gutter(Gutter::Numbers, false), 5);
+        assert_eq!(buf.gutter(Gutter::Numbers, true), 6);
+        assert_eq!(buf.error_line(&ctx, &Settings::default()), Some(1));
+        buf.idx = 0;
+        let lines = buf.render(10, 2, Gutter::Numbers, Some(1), &Theme::default(), false);
+        let lines: Vec<_> = lines.split("\r\n").collect();
+        assert!(lines[0].starts_with(&format!(
+            "{} {} ",
+            " 1".with(Color::DarkGrey),
+            Prompt::Ready
+        )));
+        assert!(lines[1].starts_with(&format!(
+            "{} {} ",
+            " 2".with(Color::DarkGrey),
+            Prompt::Failure
+        )));
+        // the ASCII prompts differ in width, but are padded to the same width
+        let lines = buf.render(10, 3, Gutter::Prompts, Some(1), &Theme::default(), true);
+        let lines: Vec<_> = lines.split("\r\n").collect();
+        let thm = Theme::default();
+        assert!(lines[0].starts_with(&format!("{}  ", Prompt::Ready.styled(&thm, true))));
+        assert!(lines[1].starts_with(&format!("{}  ", Prompt::Failure.styled(&thm, true))));
+        assert!(lines[2].starts_with(&format!("{} ", Prompt::Continue.styled(&thm, true))));
+    }
+
+    #[test]
+    fn test_buf_0008() {
+        let mut buf = Buf::new();
+        // a combining mark joins the grapheme before it, and the cursor moves past both
+        "cafe\u{301}".chars().for_each(|chr| buf.push(chr));
+        assert_eq!(buf.idx, buf.raw.len());
+        assert_eq!(buf.cursor(), (0, 4));
+        assert!(buf.move_left());
+        assert_eq!(buf.idx, 3);
+        buf.push('x');
+        assert_eq!(buf.raw, "cafxe\u{301}");
+        assert!(buf.move_right());
+        assert_eq!(buf.idx, buf.raw.len());
+        assert!(!buf.move_right());
+        // a zero-width joiner inserted between two emoji joins them into one grapheme
+        buf.replace("👨👩");
+        assert!(buf.move_left());
+        assert_eq!(buf.idx, "👨".len());
+        buf.push('\u{200d}');
+        assert_eq!(buf.raw, "👨\u{200d}👩");
+        assert_eq!(buf.idx, buf.raw.len());
+        assert!(buf.move_left());
+        assert_eq!(buf.idx, 0);
+        assert!(!buf.move_left());
+        // so do regional indicators, which pair up into flags
+        buf.replace("");
+        "🇫🇷🇩🇪".chars().for_each(|chr| buf.push(chr));
+        assert!(buf.move_left());
+        assert_eq!(buf.idx, "🇫🇷".len());
+        buf.push(' ');
+        assert_eq!(buf.raw, "🇫🇷 🇩🇪");
+        assert!(buf.highlight(&Theme::default()).contains("🇩🇪"));
+    }
+
+    #[test]
+    fn test_buf_0009() -> Result<(), TypingErr> {
+        use crate::ast::{Exp, Var};
+        let mut ctx = Ctx::new();
+        let nat = Exp::Nat;
+        ctx.assume(&Var::new("double"), &nat)?;
+        ctx.assume(&Var::new("data_size"), &nat)?;
+        let mut buf = Buf::new();
+        "s".chars().for_each(|chr| buf.push(chr));
+        assert_eq!(
+            buf.completions(&ctx),
+            ["search", "session", "set", "show", "step"]
+        );
+        "et".chars().for_each(|chr| buf.push(chr));
+        assert!(buf.completions(&ctx).is_empty()); // the word is already complete
+        buf.replace("show d");
+        assert_eq!(buf.completions(&ctx), ["data_size", "double"]);
+        buf.complete("double");
+        assert_eq!(buf.raw, "show double");
+        assert_eq!(buf.idx, buf.raw.len());
+        buf.push(' ');
+        assert!(buf.completions(&ctx).is_empty()); // no word ends at the cursor
+        "fo".chars().for_each(|chr| buf.push(chr));
+        assert_eq!(buf.completions(&ctx), ["forall"]);
+        buf.idx = 2;
+        assert!(buf.completions(&ctx).is_empty()); // nor does one end within a word
+        assert!(buf.undo());
+        assert_eq!(buf.raw, "show double ");
+        Ok(())
+    }
+
+    #[test]
+    fn test_buf_0010() -> std::io::Result<()> {
+        let typed = |dig: &Digraphs, src: &str| {
+            let mut buf = Buf::with_digraphs(dig.clone());
+            src.chars().for_each(|chr| buf.push(chr));
+            buf.raw
+        };
+        let dig = Digraphs::new();
+        assert_eq!(typed(&dig, r"\G \a \-> \S \u"), "Γ α → Σ □");
+        assert_eq!(typed(&dig, r"\\ \x. x"), r"\ \x. x");
+        // a digraph that no other extends is replaced at once
+        let mut buf = Buf::new();
+        buf.push('\\');
+        buf.push('-');
+        assert_eq!((buf.raw.as_str(), buf.seq.as_str()), ("", "-"));
+        buf.push('>');
+        assert_eq!((buf.raw.as_str(), buf.esc), ("→", false));
+        // but characters that do not spell one out are kept, after the escape character
+        assert_eq!(typed(&dig, r"\-x \=\l"), r"\-x \=λ");
+        // the users' own digraphs extend (and override) the defaults
+        let dir = std::env::temp_dir().join(format!("fluxo-dig-{}", std::process::id()));
+        let path = dir.join("digraphs");
+        assert_eq!(Digraphs::load(&path)?.ents.len(), DIGRAPHS.len());
+        fs::create_dir_all(&dir)?;
+        fs::write(&path, "# mine\n\nx ×\nl ƛ\nl= ≤\n")?;
+        let dig = Digraphs::load(&path)?;
+        assert_eq!(typed(&dig, r"\x \l \l= \a"), "× ƛ ≤ α");
+        fs::write(&path, "->\n")?;
+        assert!(Digraphs::load(&path).is_err());
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_buf_0011() {
+        let mut buf = Buf::new();
+        buf.replace("show foo_1 (bar')\r\n  baz");
+        assert!(buf.move_word_left());
+        assert_eq!(&buf.raw[buf.idx..], "baz");
+        assert!(buf.move_word_left());
+        assert_eq!(&buf.raw[buf.idx..buf.idx + 4], "bar'");
+        assert!(buf.move_word_left());
+        assert_eq!(&buf.raw[buf.idx..buf.idx + 5], "foo_1");
+        assert!(buf.move_word_right());
+        assert_eq!(&buf.raw[buf.idx..buf.idx + 2], " (");
+        assert!(buf.move_end());
+        assert!(buf.raw[buf.idx..].starts_with("\r\n"));
+        assert!(!buf.move_end());
+        assert!(buf.move_home());
+        assert_eq!(buf.idx, 0);
+        assert!(!buf.move_word_left());
+        // deleting and transposing act on whole graphemes, within the line
+        buf.replace("ab\r\ne\u{301}x");
+        assert!(buf.transpose());
+        assert_eq!(buf.raw, "ab\r\nxe\u{301}");
+        assert!(buf.delete_back());
+        assert_eq!(buf.raw, "ab\r\nx");
+        assert!(buf.move_home());
+        assert!(!buf.transpose());
+        assert!(buf.delete_back());
+        assert_eq!(buf.raw, "abx");
+        assert!(buf.transpose());
+        assert_eq!((buf.raw.as_str(), buf.idx), ("axb", 3));
+        assert!(!buf.delete_forward());
+        assert!(buf.undo());
+        assert_eq!(buf.raw, "abx");
+    }
+
+    #[test]
+    fn test_buf_0012() {
+        let mut ctx = Ctx::new();
+        let src = "assume A : *";
+        par::parse_cmd(src, &par::tokenize(src), &ctx, &Settings::default())
+            .unwrap()
+            .eval(&mut ctx, &mut Settings::default());
+        let check = |src: &str| {
+            let mut buf = Buf::new();
+            src.chars().for_each(|chr| buf.push(chr));
+            buf.check(&ctx, &Settings::default(), 100)
+        };
+        assert_eq!(check("type 2n"), Some(": ℕ".to_string()));
+        assert_eq!(check("show λx : A . x"), Some(": A → A".to_string()));
+        assert!(check("type y").is_some_and(|msg| msg.starts_with("✗ ")));
+        assert!(check("let b : A = 1n").is_some_and(|msg| msg.starts_with("✗ ")));
+        // incomplete input and commands without an expression are not reported
+        assert_eq!(check("show λx : A"), None);
+        assert_eq!(check("let a : A = "), None);
+        assert_eq!(check("set fuel 10"), None);
+        assert_eq!(check("assume a : A"), None);
+        // the context is left as it is
+        assert!(ctx.get(&crate::ast::Var::new("a")).is_err() && ctx.get_unsolved().is_empty());
+    }
+
+    #[test]
+    fn test_buf_0013() {
+        let mut ctx = Ctx::new();
+        let src = "assume A : *";
+        par::parse_cmd(src, &par::tokenize(src), &ctx, &Settings::default())
+            .unwrap()
+            .eval(&mut ctx, &mut Settings::default());
+        let inspect = |src: &str, idx: usize| {
+            let mut buf = Buf::new();
+            src.chars().for_each(|chr| buf.push(chr));
+            buf.idx = idx;
+            buf.inspect(&ctx, &Settings::default(), 100)
+        };
+        let src = "show λx : A . λf : A → A . f x";
+        let at = |part: &str| src.rfind(part).unwrap();
+        assert_eq!(inspect(src, at("x")), Some("x : A".to_string()));
+        assert_eq!(inspect(src, at("f")), Some("f : A → A".to_string()));
+        assert_eq!(inspect(src, at("A →")), Some("A : *".to_string()));
+        assert_eq!(
+            inspect(src, at("λf")),
+            Some("λf : A → A . f x : (A → A) → A".to_string())
+        );
+        assert!(inspect("type y", 5).is_some_and(|msg| msg.starts_with("✗ ")));
+        // the cursor must be within an expression of a command that takes one
+        assert_eq!(inspect(src, 2), None);
+        assert_eq!(inspect("set fuel 10", 10), None);
+    }
+
+    #[test]
+    fn test_buf_0014() {
+        let mut ctx = Ctx::new();
+        for src in ["assume A : *", "assume a : A", "assume f : A → A"] {
+            par::parse_cmd(src, &par::tokenize(src), &ctx, &Settings::default())
+                .unwrap()
+                .eval(&mut ctx, &mut Settings::default());
+        }
+        let mut buf = Buf::new();
+        "show (λx : A . f _) _"
+            .chars()
+            .for_each(|chr| buf.push(chr));
+        buf.move_home();
+        assert_eq!(buf.goal(&ctx, &Settings::default(), 100), None);
+        assert!(buf.next_hole());
+        assert_eq!(buf.hole(), Some((18, 19)));
+        let goal = buf.goal(&ctx, &Settings::default(), 100).unwrap();
+        assert_eq!(
+            (goal.typ.as_str(), &goal.vars[..]),
+            ("A", &["x : A".to_string()][..])
+        );
+        assert_eq!(goal.fits, ["a", "x", "(f _)"]);
+        assert!(buf.next_hole());
+        let goal = buf.goal(&ctx, &Settings::default(), 100).unwrap();
+        assert_eq!((goal.typ.as_str(), goal.vars.len()), ("A", 0));
+        // moving past the last hole wraps around to the first, and back again
+        assert!(buf.next_hole());
+        assert_eq!(buf.hole(), Some((18, 19)));
+        assert!(buf.prev_hole());
+        assert!(buf.refine("(f _)"));
+        assert_eq!(buf.raw, "show (λx : A . f _) (f _)");
+        assert!(!buf.refine("a"));
+        let mut buf = Buf::new();
+        "show (λg : A → A . g a) _"
+            .chars()
+            .for_each(|chr| buf.push(chr));
+        buf.prev_hole();
+        let goal = buf.goal(&ctx, &Settings::default(), 100).unwrap();
+        assert_eq!(goal.typ, "A → A");
+        assert_eq!(goal.fits, ["f", "(λx : A . _)"]);
+        // the metavariables that fill the other holes are discarded along with the copy
+        assert!(ctx.get_unsolved().is_empty());
+    }
+
+    #[test]
+    fn test_buf_0015() {
+        let ctx = Ctx::new();
+        let mut buf = Buf::new();
+        buf.replace("frobnicate 1n");
+        // input that is not a command fails with a message naming what was typed
+        let Cmd::Fail(err) = buf.value(&ctx, &Settings::default()) else {
+            panic!("expected a failure")
+        };
+        assert!(err
+            .to_string()
+            .starts_with(":parse unknown command ‘frobnicate’"));
+        buf.replace("show 1n");
+        assert_eq!(
+            buf.value(&ctx, &Settings::default()),
+            Cmd::Show(Exp::Lit(1))
+        );
     }
 }
@@ -1,27 +1,159 @@
 //! Command structures for executing based on user input.
 
-use crate::ast::{Ctx, Exp};
-use crate::err::TypingErr;
-use crossterm::style::{Color, Stylize};
+use crate::ast::{Ctx, Data, Exp, Limits, Notation, Step, Strategy, Var};
+use crate::err::{Diagnostic, Lint, Severity, TypingErr};
+use crate::key::{self, KeySet};
+use crate::par::{
+    locate_err, locate_lint, parse_cmd, parse_cmd_unlocated, split_commands, tokenize,
+};
+use crate::pmt::Gutter;
+use crate::thm::{Paint, Preset, Theme};
+use crate::xpt::Markup;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use unicode_width::UnicodeWidthStr;
 
+/// Number of variables shown on each page of the listing of the session context.
+pub const ENV_PAGE: usize = 20;
+
+/// Source text of the prelude bundled with fluxo, which [import][Cmd::Import] brings into the
+/// session context.
+pub const PRELUDE: &str = include_str!("prelude.fx");
+
 /// Command object that represents possible instructions derived from user input.
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Cmd {
+    /// Declare the associated variable as an alias for the associated (partial) command, which
+    /// stands in for the alias wherever it begins a command, for the rest of the session.
+    Alias(Var, String),
+    /// Assume a constant of the associated type, named by the associated variable, for the rest of
+    /// the session.
+    Assume(Var, Exp),
+    /// List the keys bound in the editor, along with the [actions][crate::key::Action] they take.
+    Bindings,
+    /// Declare the associated inductive [data type][Data] for the rest of the session.
+    Data(Data),
+    /// List the variables in the session context, showing the associated page of the listing.
+    Env(usize),
     /// Execute the associated [expression][Exp].
     Exec(Exp),
+    /// Check whether the associated expressions are convertible, that is, whether they reduce to
+    /// the same normal form.
+    Eq(Exp, Exp),
     /// Exit the integrated development environment.
     Exit,
+    /// Show the associated expression, along with its type, in the associated [markup][Markup].
+    Export(Markup, Exp),
     /// Fail with the associated [typing error][TypingErr].
     Fail(TypingErr),
     /// Show help information.
     Help(Option<Box<Cmd>>),
+    /// Evaluate the commands in the bundled library named by the associated variable, in order.
+    Import(Var),
+    /// Define the associated variable, with the associated type and value (in that order), for the
+    /// rest of the session.
+    Let(Var, Exp, Exp),
+    /// Evaluate the commands in the file at the associated path, in order.
+    Load(PathBuf),
     /// Perform no operation.
     Noop,
+    /// Declare the associated [notation][Notation] for the rest of the session.
+    Note(Notation),
+    /// Open the associated namespace for the rest of the session.
+    Open(Var),
+    /// Restore the session context to the one it started with, retracting every change made
+    /// during the session.
+    Reset,
+    /// Search the session context for the variables whose types match the associated type.
+    Search(Exp),
+    /// Save or restore the session, as described by the associated [operation][Session].
+    Session(Session),
+    /// Change the associated [setting][Setting] for the rest of the session.
+    Set(Setting),
     /// Show the associated [expression][Exp].
     Show(Exp),
+    /// Contract a single redex of the associated expression, or else of the expression that
+    /// resulted from the previous step.
+    Step(Option<Exp>),
+    /// Show every step of the reduction of the associated expression.
+    Trace(Exp),
+    /// Show the associated expression along with its type, and the statistics of its evaluation.
+    Time(Exp),
     /// Show the type of the associated expression.
     Type(Exp),
+    /// Show the weak head normal form of the associated expression.
+    Whnf(Exp),
+    /// Retract the most recent change to the session context (such as a definition).
+    Undo,
+}
+
+/// Setting that governs how [commands][Cmd] are evaluated, which may be changed during the session.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Setting {
+    /// Reduction [strategy][Strategy] used to show expressions.
+    Strategy(Strategy),
+    /// Number of steps permitted for each reduction, beyond which reduction fails.
+    Fuel(usize),
+    /// Kind of [gutter][Gutter] shown beside the input.
+    Gutter(Gutter),
+    /// Set of [key bindings][KeySet] of the editor.
+    KeySet(KeySet),
+    /// Built-in [theme][Preset] that the user interface is rendered with.
+    Theme(Preset),
+    /// Whether the user interface is rendered in ASCII (for terminals that lack Unicode glyphs).
+    Ascii(bool),
+    /// Whether [warnings][Lint] are reported about the commands that are evaluated.
+    Warnings(bool),
+}
+
+/// Settings of the session that are kept apart from its [context][Ctx], since they govern how the
+/// session is presented and how long its commands may run rather than what is well-typed, which
+/// are changed with [set][Cmd::Set] (except for the strategy and fuel, which the context holds),
+/// along with the aliases declared with [alias][Cmd::Alias].
+///
+/// Unlike the context, the settings are neither saved with the session nor retracted by
+/// [undo][Cmd::Undo].
+#[derive(Clone, Debug)]
+pub struct Settings {
+    /// Kind of [gutter][Gutter] shown beside the input.
+    pub gut: Gutter,
+    /// Set of [key bindings][KeySet] of the editor.
+    pub key: KeySet,
+    /// Theme that the user interface is rendered with.
+    pub thm: Theme,
+    /// Whether the user interface (including expressions) is rendered in ASCII, rather than with
+    /// Unicode glyphs.
+    pub asc: bool,
+    /// Whether [warnings][Lint] are reported about the commands that are evaluated.
+    pub lnt: bool,
+    /// Time permitted for the reductions of each command, if it is limited.
+    pub tmo: Option<Duration>,
+    /// Largest [size][Exp::size] that an expression may grow to while it is reduced, if it is
+    /// limited.
+    pub max: Option<usize>,
+    /// Flag that interrupts the command being evaluated while it is raised, which may be raised
+    /// from another thread.
+    pub irq: Arc<AtomicBool>,
+    /// [Aliases][Cmd::Alias] declared for commands, each along with the (partial) command that it
+    /// stands for.
+    pub als: Vec<(Var, String)>,
+}
+
+/// Operation on the session as a whole, namely its [context][Ctx] (the declarations, definitions,
+/// notation and reduction settings made during the session), which may be saved to a file and restored.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Session {
+    /// Save the session to the file at the associated path.
+    Save(PathBuf),
+    /// Restore the session from the file at the associated path, replacing the current one.
+    Load(PathBuf),
+    /// Restore the session that ended unexpectedly before this one, as of its latest
+    /// [checkpoint][crate::rcv::Recovery], replacing the current one.
+    Recover,
 }
 
 /// Output object created when a [command][Cmd] is evaluated.
@@ -33,10 +165,16 @@ pub struct Out<'a> {
     pub msg: Option<String>,
     /// Flag that signals the parent process to terminate.
     pub trm: bool,
+    /// Error that the command failed with, if it failed with one.
+    pub err: Option<TypingErr>,
+    /// Warnings about the command, if it succeeded but likely does not do what was meant.
+    pub lints: Vec<Lint>,
 }
 
 /// Disposition of the output generated by [command][Cmd] evaluation.
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum Status {
     /// Output is generated by successful evaluation of a [command][Cmd].
     Success,
@@ -44,30 +182,99 @@ pub enum Status {
     Failure,
     /// Output is neutral and represents a diagnostic message.
     Content,
+    /// Output reports statistics gathered while a [command][Cmd] was evaluated.
+    Diagnostics,
+    /// Output warns that a [command][Cmd] that succeeded likely does not do what was meant.
+    Warning,
+}
+
+/// Format that the output of commands evaluated outside the IDE (such as those in a file that is
+/// run, or piped in) is written in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    /// Plain text, as it would be shown in a terminal (without any styling).
+    #[default]
+    Text,
+    /// A [report][Report] for each command, as a JSON object on a line of its own, for tools to
+    /// consume.
+    #[cfg(feature = "serde")]
+    Json,
+}
+
+/// Report of the evaluation of a command (or of a diagnostic about it), as written in the
+/// [JSON][Format::Json] format.
+#[cfg(feature = "serde")]
+#[derive(Clone, serde::Serialize)]
+pub struct Report<'a> {
+    /// Path of the file that the command is in, if there is one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<&'a str>,
+    /// Source text of the command, if it is known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<&'a str>,
+    /// Disposition of the output of the command.
+    pub status: &'a Status,
+    /// Message generated by the command, as it would be rendered in plain text.
+    pub text: &'a str,
+    /// Where the command failed and why, or what it warns about, if either.
+    pub error: Option<Diagnostic>,
+}
+
+#[cfg(feature = "serde")]
+impl Report<'_> {
+    /// Write this report to the given writer, as a JSON object on a line of its own.
+    pub fn to_writer(&self, mut writer: impl std::io::Write) -> std::io::Result<()> {
+        serde_json::to_writer(&mut writer, self)?;
+        writeln!(writer)
+    }
 }
 
 impl Cmd {
-    /// Evaluate this command and return the [output][Out].
-    pub fn eval(&self, ctx: &Ctx) -> Out {
+    /// Evaluate this command in the given context with the given settings (either of which it may
+    /// change), and return the [output][Out].
+    pub fn eval(&self, ctx: &mut Ctx, set: &mut Settings) -> Out<'_> {
+        ctx.set_limits(set.limits());
         match self {
             Cmd::Noop => Out::new(),
             Cmd::Exit => Out::with_trm(true),
-            Cmd::Fail(err) => Out::with_msg(&err.to_string(), &Status::Failure),
+            Cmd::Fail(err) => Out::with_err(err),
             Cmd::Help(tgt) => {
+                let thm = set.thm;
                 let mut msg = String::new();
                 msg.push_str("COMMAND REFERENCE:\n");
-                let commands = vec![
+                let commands = [
                     Cmd::Help(None),
                     Cmd::Exit,
                     Cmd::Show(Default::default()),
                     Cmd::Type(Default::default()),
+                    Cmd::Whnf(Default::default()),
+                    Cmd::Eq(Default::default(), Default::default()),
+                    Cmd::Export(Default::default(), Default::default()),
+                    Cmd::Time(Default::default()),
+                    Cmd::Step(None),
+                    Cmd::Trace(Default::default()),
                     Cmd::Exec(Default::default()),
+                    Cmd::Note(Default::default()),
+                    Cmd::Open(Default::default()),
+                    Cmd::Alias(Default::default(), Default::default()),
+                    Cmd::Data(Default::default()),
+                    Cmd::Let(Default::default(), Default::default(), Default::default()),
+                    Cmd::Assume(Default::default(), Default::default()),
+                    Cmd::Set(Default::default()),
+                    Cmd::Load(Default::default()),
+                    Cmd::Import(Default::default()),
+                    Cmd::Env(1),
+                    Cmd::Bindings,
+                    Cmd::Search(Default::default()),
+                    Cmd::Session(Default::default()),
+                    Cmd::Undo,
+                    Cmd::Reset,
                 ];
                 let targets: Vec<&Cmd> = commands
                     .iter()
                     .filter(|cmd| {
                         if let Some(val) = tgt {
-                            **cmd == **val
+                            std::mem::discriminant(*cmd) == std::mem::discriminant(&**val)
                         } else {
                             true
                         }
@@ -79,31 +286,666 @@ impl Cmd {
                     .map(|(key, _)| key.width())
                     .max()
                     .unwrap(); // assumes at least one help line
-                for cmd in targets {
+                for cmd in &targets {
                     cmd.help().iter().for_each(|(key, val)| {
                         msg.push_str(&format!(
                             "‣ {} {}.... {}\r\n",
                             key.split_once(' ')
-                                .map(|(cmd, args)| format!("{} {}", cmd.with(Color::Red), args))
-                                .unwrap_or_else(|| format!("{}", key.with(Color::Red))),
+                                .map(|(cmd, args)| format!("{} {}", cmd.paint(thm.command), args))
+                                .unwrap_or_else(|| format!("{}", key.paint(thm.command))),
                             ".".repeat(max - key.width()),
                             val
                         ))
                     });
                 }
+                // a single command is described further, with examples and related commands
+                if let [cmd] = targets[..] {
+                    let examples = cmd.examples();
+                    if !examples.is_empty() {
+                        msg.push_str("EXAMPLES:\r\n");
+                        examples
+                            .iter()
+                            .for_each(|src| msg.push_str(&format!("  {}\r\n", src)));
+                    }
+                    let related = cmd.related();
+                    if !related.is_empty() {
+                        let keys: Vec<_> = related
+                            .iter()
+                            .map(|key| key.paint(thm.command).to_string())
+                            .collect();
+                        msg.push_str(&format!("SEE ALSO: {}\r\n", keys.join(", ")));
+                    }
+                }
                 Out::with_msg(&msg, &Status::Content)
             }
-            Cmd::Show(exp) => match exp.clone().reduce(ctx) {
-                Ok(exp) => Out::with_msg(&exp.to_string(), &Status::Success),
-                Err(e) => Out::with_msg(&e.to_string(), &Status::Failure),
-            },
-            Cmd::Type(exp) => match exp.calculate_type(ctx) {
-                Ok(exp) => Out::with_msg(&exp.to_string(), &Status::Success),
-                Err(e) => Out::with_msg(&e.to_string(), &Status::Failure),
+            Cmd::Show(exp) => Self::show(exp, ctx, set).0,
+            Cmd::Whnf(exp) => {
+                match exp
+                    .elaborate(ctx)
+                    .reduce_with(ctx, Strategy::Head, ctx.get_fuel())
+                {
+                    Ok(exp) => {
+                        Out::with_msg(&exp.zonk(ctx).display(ctx).ascii(set.asc).to_string(), &Status::Success)
+                    }
+                    Err(e) => Out::with_err(&e),
+                }
+            }
+            Cmd::Type(exp) => Self::type_of(exp, ctx, set).0,
+            Cmd::Step(Some(exp)) => Self::step(exp, ctx, set).0,
+            Cmd::Trace(exp) => Self::trace(exp, ctx, set),
+            Cmd::Eq(fst, snd) => Self::eq(fst, snd, ctx, set),
+            Cmd::Export(mrk, exp) => match exp.elaborate(ctx).infer(ctx) {
+                Ok(typ) => Out::with_msg(&mrk.typing(exp, &typ.zonk(ctx), ctx), &Status::Content),
+                Err(e) => Out::with_err(&e),
             },
+            Cmd::Time(exp) => Self::time(exp, ctx, set),
+            Cmd::Step(None) => Out::with_msg(
+                "step\n    ↯ only the interactive environment keeps the result of the previous step\n",
+                &Status::Failure,
+            ),
             Cmd::Exec(_) => {
                 Out::with_msg("Code execution is not yet implemented.", &Status::Failure)
             }
+            Cmd::Note(ntn) => {
+                ctx.put_notation(ntn.clone());
+                Out::with_msg(&ntn.to_string(), &Status::Success)
+            }
+            Cmd::Let(var, typ, val) => {
+                let typ = typ.elaborate(ctx);
+                match ctx.define(var, val, &typ) {
+                    Ok(()) => {
+                        let typ = typ.zonk(ctx);
+                        Out::with_msg(&format!("{} : {}", var, typ.display(ctx).ascii(set.asc)), &Status::Success)
+                    }
+                    Err(e) => Out::with_err(&e),
+                }
+            }
+            Cmd::Assume(var, typ) => {
+                let typ = typ.elaborate(ctx);
+                match ctx.assume(var, &typ) {
+                    Ok(()) => {
+                        let typ = typ.zonk(ctx);
+                        Out::with_msg(&format!("{} : {}", var, typ.display(ctx).ascii(set.asc)), &Status::Success)
+                    }
+                    Err(e) => Out::with_err(&e),
+                }
+            }
+            Cmd::Undo | Cmd::Reset => Out::with_msg(
+                &format!(
+                    "{}\n    ↯ only the interactive environment keeps the checkpoints to restore\n",
+                    self.help()[0].0
+                ),
+                &Status::Failure,
+            ),
+            Cmd::Env(page) => Self::env(*page, ctx, set),
+            Cmd::Bindings => Self::bindings(set),
+            Cmd::Search(typ) => {
+                let found = ctx.search(typ);
+                if found.is_empty() {
+                    let msg = format!(
+                        "search {}\n    ↯ no variables have a matching type\n",
+                        typ.display(ctx).ascii(set.asc)
+                    );
+                    return Out::with_msg(&msg, &Status::Failure);
+                }
+                let mut msg = format!("search {} ({} found)\n", typ.display(ctx).ascii(set.asc), found.len());
+                for (fit, var, typ) in found {
+                    msg.push_str(&format!("{:<8} {} : {}\n", fit.to_string(), var, typ.display(ctx).ascii(set.asc)));
+                }
+                Out::with_msg(&msg, &Status::Content)
+            }
+            Cmd::Load(path) => match std::fs::read_to_string(path) {
+                Ok(src) => Self::load(&src, &format!("load {:?}", path.display().to_string()), ctx, set),
+                Err(e) => Out::with_msg(
+                    &format!("load {:?}\n    {}\n", path.display().to_string(), e),
+                    &Status::Failure,
+                ),
+            },
+            Cmd::Import(lib) => match lib.0.as_str() {
+                "prelude" => Self::load(PRELUDE, &format!("import {}", lib), ctx, set),
+                _ => Out::with_msg(
+                    &format!("import {}\n    ↯ there is no library named {}\n", lib, lib),
+                    &Status::Failure,
+                ),
+            },
+            Cmd::Alias(name, cmd) => {
+                set.put_alias(name, cmd);
+                Out::with_msg(&format!("alias {} = {}", name, cmd), &Status::Success)
+            }
+            Cmd::Open(ns) => match ctx.open(ns) {
+                Ok(()) => Out::with_msg(&format!("open {}", ns), &Status::Success),
+                Err(e) => Out::with_err(&TypingErr::from(e)),
+            },
+            Cmd::Set(opt) => {
+                match opt {
+                    Setting::Strategy(stg) => ctx.set_strategy(*stg),
+                    Setting::Fuel(fuel) => ctx.set_fuel(*fuel),
+                    Setting::Gutter(gut) => set.gut = *gut,
+                    Setting::KeySet(key) => set.key = *key,
+                    Setting::Theme(pre) => set.thm = pre.theme(),
+                    Setting::Ascii(on) => set.asc = *on,
+                    Setting::Warnings(on) => set.lnt = *on,
+                }
+                Out::with_msg(&opt.to_string(), &Status::Success)
+            }
+            Cmd::Session(ses) => match ses.apply(ctx) {
+                Ok(()) => Out::with_msg(&ses.to_string(), &Status::Success),
+                Err(e) => Out::with_msg(&format!("{}\n    {}\n", ses, e), &Status::Failure),
+            },
+            Cmd::Data(dat) => match ctx.declare(dat) {
+                Ok(()) => {
+                    let msg = dat
+                        .vars()
+                        .iter()
+                        .filter_map(|var| {
+                            let typ = ctx.get(var).ok()?;
+                            Some(format!("{} : {}", var, typ.display(ctx).ascii(set.asc)))
+                        })
+                        .intersperse("\n".to_string())
+                        .collect::<String>();
+                    Out::with_msg(&msg, &Status::Success)
+                }
+                Err(e) => Out::with_err(&e),
+            },
+        }
+    }
+
+    /// Check whether evaluating this command may change the session context, in which case the
+    /// context it is evaluated in is kept as a checkpoint, which [undo][Cmd::Undo] restores.
+    pub fn is_change(&self) -> bool {
+        matches!(
+            self,
+            Cmd::Assume(..)
+                | Cmd::Data(_)
+                | Cmd::Import(_)
+                | Cmd::Let(..)
+                | Cmd::Load(_)
+                | Cmd::Note(_)
+                | Cmd::Open(_)
+                | Cmd::Session(Session::Load(_) | Session::Recover)
+                | Cmd::Set(_)
+        )
+    }
+
+    /// Collect the [warnings][Lint] about this command, once it has been evaluated in the given
+    /// context (which it changed from the first context given to the second): those about the
+    /// binders in the expressions it takes, and those about the variables in opened namespaces
+    /// that it made unreachable.
+    pub fn lint(&self, pre: &Ctx, ctx: &Ctx) -> Vec<Lint> {
+        let exps = match self {
+            Cmd::Let(_, typ, val) => vec![typ, val],
+            Cmd::Assume(_, typ) => vec![typ],
+            Cmd::Eq(fst, snd) => vec![fst, snd],
+            Cmd::Show(exp)
+            | Cmd::Type(exp)
+            | Cmd::Whnf(exp)
+            | Cmd::Time(exp)
+            | Cmd::Trace(exp)
+            | Cmd::Exec(exp)
+            | Cmd::Export(_, exp)
+            | Cmd::Step(Some(exp)) => vec![exp],
+            _ => vec![],
+        };
+        let mut lints = exps
+            .into_iter()
+            .flat_map(|exp| exp.lints(pre))
+            .collect::<Vec<_>>();
+        let old = pre.unreachable();
+        lints.extend(
+            (ctx.unreachable().into_iter())
+                .filter(|var| !old.contains(var))
+                .map(|(var, name)| Lint::Unreachable(var, name)),
+        );
+        lints
+    }
+
+    /// List the variables in the given context (sorted by name, along with their types and their
+    /// values, if they are defined), returning the [output][Out] that shows the given page of the
+    /// listing, with [ENV_PAGE] variables on each page.
+    ///
+    /// Variables that are shadowed by later bindings of the same name are left out.
+    fn env(page: usize, ctx: &Ctx, set: &Settings) -> Out<'static> {
+        let vars: BTreeMap<_, _> = ctx.iter().map(|(var, typ)| (&var.0, (var, typ))).collect();
+        let pages = vars.len().div_ceil(ENV_PAGE).max(1);
+        if page > pages {
+            let msg = format!("env {}\n    ↯ there are only {} pages\n", page, pages);
+            return Out::with_msg(&msg, &Status::Failure);
+        }
+        let mut msg = format!("env (page {} of {})\n", page, pages);
+        for (var, typ) in vars.values().skip((page - 1) * ENV_PAGE).take(ENV_PAGE) {
+            msg.push_str(&format!("{} : {}", var, typ.display(ctx).ascii(set.asc)));
+            if let Some(val) = ctx.get_def(var) {
+                msg.push_str(&format!(" = {}", val.display(ctx).ascii(set.asc)));
+            }
+            msg.push('\n');
+        }
+        Out::with_msg(&msg, &Status::Content)
+    }
+
+    /// List the keys bound by the keymaps of the set of key bindings of the given settings, along
+    /// with the actions that they take, as [listed][key::listing] in order of precedence.
+    ///
+    /// Layers registered with an editor that embeds the environment are not listed, since they
+    /// are not kept in the settings.
+    fn bindings(set: &Settings) -> Out<'static> {
+        let rows = key::listing(&set.key.keymaps());
+        let max = rows.iter().map(|(key, _)| key.width()).max().unwrap_or(0);
+        let mut msg = format!("bindings ({} keymap)\n", set.key);
+        for (key, act) in rows {
+            msg.push_str(&format!(
+                "{}{} {:<14} {}\n",
+                key,
+                " ".repeat(max - key.width()),
+                act.to_string(),
+                act.describe()
+            ));
+        }
+        Out::with_msg(&msg, &Status::Content)
+    }
+
+    /// Perform a single reduction step on the given expression, using the reduction strategy of
+    /// the given context, and return the [output][Out] that shows the expression with its redex
+    /// underlined (and labeled with the [rule][crate::ast::Rule] by which it is contracted),
+    /// followed by the result, along with the result itself (unless the expression is already
+    /// reduced), which is the target of the next step.
+    pub fn step(exp: &Exp, ctx: &Ctx, set: &Settings) -> (Out<'static>, Option<Exp>) {
+        let exp = match Self::prepare(exp, ctx) {
+            Ok(exp) => exp,
+            Err(e) => return (Out::with_err(&e), None),
+        };
+        match exp.clone().reduce_step(ctx, ctx.get_strategy()) {
+            Ok(Some(step)) => {
+                let msg = format!(
+                    "{}\n{}\n{}",
+                    exp.display(ctx).ascii(set.asc),
+                    Self::underline(&exp, &step, ctx, set),
+                    step.exp.display(ctx).ascii(set.asc)
+                );
+                (Out::with_msg(&msg, &Status::Success), Some(step.exp))
+            }
+            Ok(None) => {
+                let msg = format!(
+                    "{}\n    ∎ there are no redexes left\n",
+                    exp.display(ctx).ascii(set.asc)
+                );
+                (Out::with_msg(&msg, &Status::Success), None)
+            }
+            Err(e) => (Out::with_err(&e), None),
+        }
+    }
+
+    /// Reduce the given expression one step at a time, using the reduction strategy of the given
+    /// context, and return the [output][Out] that lists every expression along the way (numbered
+    /// by the step that produced it, and labeled with the [rule][crate::ast::Rule] applied).
+    ///
+    /// Reduction fails if the steps permitted by the [fuel][Ctx::get_fuel] of the context are used
+    /// up before the expression is reduced, in which case the steps taken so far are listed.
+    fn trace(exp: &Exp, ctx: &Ctx, set: &Settings) -> Out<'static> {
+        let mut exp = match Self::prepare(exp, ctx) {
+            Ok(exp) => exp,
+            Err(e) => return Out::with_err(&e),
+        };
+        let mut log = format!("{:>4}   {}\n", 0, exp.display(ctx).ascii(set.asc));
+        for num in 1..=ctx.get_fuel() {
+            match exp.reduce_step(ctx, ctx.get_strategy()) {
+                Ok(Some(step)) => {
+                    log.push_str(&format!(
+                        "{:>4} {} {}\n",
+                        num,
+                        step.rule,
+                        step.exp.display(ctx).ascii(set.asc)
+                    ));
+                    exp = step.exp;
+                }
+                Ok(None) => return Out::with_msg(&log, &Status::Success),
+                Err(e) => return Out::with_msg(&format!("{}{}", log, e), &Status::Failure),
+            }
+        }
+        match exp.clone().reduce_step(ctx, ctx.get_strategy()) {
+            Ok(None) => Out::with_msg(&log, &Status::Success),
+            _ => Out::with_msg(
+                &format!(
+                    "trace ↯ not reduced within {} steps\n{}",
+                    ctx.get_fuel(),
+                    log
+                ),
+                &Status::Failure,
+            ),
+        }
+    }
+
+    /// Check whether the given expressions are convertible in the given context, by reducing both
+    /// to normal form, and return the [output][Out] that shows the normal form they share, or else
+    /// the first sub-expressions at which their normal forms differ.
+    fn eq(fst: &Exp, snd: &Exp, ctx: &Ctx, set: &Settings) -> Out<'static> {
+        let red = |exp: &Exp| -> Result<Exp, TypingErr> {
+            let exp = exp.elaborate(ctx);
+            Ok(exp
+                .reduce_with(ctx, Strategy::Normal, ctx.get_fuel())?
+                .zonk(ctx))
+        };
+        let (one, two) = match (red(fst), red(snd)) {
+            (Ok(one), Ok(two)) => (one, two),
+            (Err(e), _) | (_, Err(e)) => return Out::with_err(&e),
+        };
+        let (fst, snd) = (
+            fst.display(ctx).ascii(set.asc),
+            snd.display(ctx).ascii(set.asc),
+        );
+        match one.diverge(&two) {
+            None => Out::with_msg(
+                &format!(
+                    "{} ≡ {}\n    both reduce to {}",
+                    fst,
+                    snd,
+                    one.display(ctx).ascii(set.asc)
+                ),
+                &Status::Success,
+            ),
+            Some((one, two)) => Out::with_msg(
+                &format!(
+                    "{} ≢ {}\n    ↯ their normal forms differ at {} and {}\n",
+                    fst,
+                    snd,
+                    one.display(ctx).ascii(set.asc),
+                    two.display(ctx).ascii(set.asc)
+                ),
+                &Status::Failure,
+            ),
+        }
+    }
+
+    /// Reduce the given expression with the strategy and fuel of the given context, and return the
+    /// [output][Out] that shows the result, along with the result and its type, if its type can be
+    /// inferred (as it may be [kept][Self::keep]).
+    pub fn show(exp: &Exp, ctx: &Ctx, set: &Settings) -> (Out<'static>, Option<(Exp, Exp)>) {
+        let exp = exp.elaborate(ctx);
+        match exp
+            .clone()
+            .reduce_with(ctx, ctx.get_strategy(), ctx.get_fuel())
+        {
+            Ok(val) => {
+                let val = val.zonk(ctx);
+                let out = Out::with_msg(
+                    &val.display(ctx).ascii(set.asc).to_string(),
+                    &Status::Success,
+                );
+                (out, exp.infer(ctx).ok().map(|typ| (val, typ.zonk(ctx))))
+            }
+            Err(e) => (Out::with_err(&e), None),
+        }
+    }
+
+    /// Infer the type of the given expression, and return the [output][Out] that shows it, along
+    /// with the type and its own type (as it may be [kept][Self::keep]).
+    pub fn type_of(exp: &Exp, ctx: &Ctx, set: &Settings) -> (Out<'static>, Option<(Exp, Exp)>) {
+        match exp.elaborate(ctx).infer(ctx) {
+            Ok(typ) => {
+                let typ = typ.zonk(ctx);
+                let out = Out::with_msg(
+                    &typ.display(ctx).ascii(set.asc).to_string(),
+                    &Status::Success,
+                );
+                (out, typ.infer(ctx).ok().map(|srt| (typ, srt.zonk(ctx))))
+            }
+            Err(e) => (Out::with_err(&e), None),
+        }
+    }
+
+    /// Keep the given result (of the given type) of a command in the given context, as the
+    /// variable `%N` for the given number `N`, and bind `it` to the same result (replacing the
+    /// one kept before), returning the variable.
+    pub fn keep(num: usize, val: &Exp, typ: &Exp, ctx: &mut Ctx) -> Var {
+        let var = Var::new(&format!("%{}", num));
+        ctx.rebind(&var, val, typ);
+        ctx.rebind(&Var::new("it"), &Exp::new_var(var.clone()), typ);
+        var
+    }
+
+    /// Infer the type of the given expression and reduce it (in the same way as [Cmd::Type] and
+    /// [Cmd::Show] do), and return the [output][Out] that shows both along with the wall-clock
+    /// time taken, the number of reduction steps and the peak size of the expression.
+    fn time(exp: &Exp, ctx: &Ctx, set: &Settings) -> Out<'static> {
+        let beg = Instant::now();
+        let exp = exp.elaborate(ctx);
+        let res = exp.infer(ctx).and_then(|typ| {
+            let (val, stats) = exp.reduce_stats(ctx, ctx.get_strategy(), ctx.get_fuel())?;
+            Ok((val.zonk(ctx), typ.zonk(ctx), stats))
+        });
+        let dur = beg.elapsed();
+        match res {
+            Ok((val, typ, stats)) => Out::with_msg(
+                &format!(
+                    "{} : {}\n    wall-clock time {:?}\n    reduction steps {}\n    peak term size  {}\n",
+                    val.display(ctx).ascii(set.asc),
+                    typ.display(ctx).ascii(set.asc),
+                    dur,
+                    stats.steps,
+                    stats.peak
+                ),
+                &Status::Diagnostics,
+            ),
+            Err(e) => Out::with_err(&e),
+        }
+    }
+
+    /// Elaborate the given expression in the given context, and check that it is well-typed,
+    /// returning the elaborated expression with its solved metavariables replaced, ready to be
+    /// reduced one step at a time.
+    fn prepare(exp: &Exp, ctx: &Ctx) -> Result<Exp, TypingErr> {
+        let exp = exp.elaborate(ctx);
+        exp.infer(ctx)?;
+        Ok(exp.zonk(ctx))
+    }
+
+    /// Underline the redex of the given step within the rendered expression, followed by the rule
+    /// by which it is contracted, or show the redex itself if it cannot be found in the rendering.
+    fn underline(exp: &Exp, step: &Step, ctx: &Ctx, set: &Settings) -> String {
+        let full = exp.display(ctx).ascii(set.asc).to_string();
+        let mark = exp
+            .clone()
+            .replace_at(&step.path, Exp::new_var(Var::new("◊")))
+            .display(ctx)
+            .ascii(set.asc)
+            .to_string();
+        let redex = step.redex.display(ctx).ascii(set.asc).to_string();
+        let beg = mark.find('◊').and_then(|i| {
+            let rest = full.get(i..)?;
+            match rest.strip_prefix('(') {
+                Some(rest) if rest.starts_with(&redex) => Some(i + 1), // parenthesized in place
+                _ => rest.starts_with(&redex).then_some(i),
+            }
+        });
+        match beg {
+            Some(beg) => format!(
+                "{}{} {}",
+                " ".repeat(full[..beg].width()),
+                "^".repeat(redex.width()),
+                step.rule
+            ),
+            None => format!("{} {}", redex, step.rule),
+        }
+    }
+
+    /// Evaluate the commands in the given source text (such as the contents of a file), in order,
+    /// and return the [output][Out] that reports the outcome of each (by its line number) under the
+    /// given header, which is a failure if any of the commands fails.
+    ///
+    /// Each command starts at the beginning of a line, and continues onto the lines after it that
+    /// are indented, as described for [split_commands].
+    fn load(src: &str, hdr: &str, ctx: &mut Ctx, set: &mut Settings) -> Out<'static> {
+        let toks = tokenize(src);
+        let mut log = String::new();
+        let mut failed = 0;
+        let cmds = split_commands(src, &toks);
+        for (line, toks) in &cmds {
+            let end = toks.last().map_or(0, |tok| tok.end);
+            let (ok, msg) = match parse_cmd(&src[..end], toks, ctx, set) {
+                Ok(cmd) => {
+                    let out = cmd.eval(ctx, set);
+                    (!matches!(out.status, Status::Failure), out.msg)
+                }
+                Err(e) => (false, Some(e.to_string())),
+            };
+            failed += usize::from(!ok);
+            let mark = if ok { "∴" } else { "✗" };
+            let msg = msg.unwrap_or_default();
+            let mut lines = msg.lines();
+            log.push_str(&format!(
+                "{:>4} {} {}\n",
+                line,
+                mark,
+                lines.next().unwrap_or("")
+            ));
+            lines.for_each(|msg| log.push_str(&format!("       {}\n", msg)));
+        }
+        match failed {
+            0 => Out::with_msg(&format!("{}\n{}", hdr, log), &Status::Success),
+            _ => Out::with_msg(
+                &format!(
+                    "{} ↯ {} of {} commands failed\n{}",
+                    hdr,
+                    failed,
+                    cmds.len(),
+                    log
+                ),
+                &Status::Failure,
+            ),
+        }
+    }
+
+    /// Type-check the commands in the given source text (such as the contents of a file) in order,
+    /// in the given context with the given settings, returning a [diagnostic][Diagnostic] for each
+    /// that fails, which does
+    /// not stop the commands after it from being checked, and (unless they are turned off) one
+    /// with the [severity][Severity] of a warning for each [warning][Cmd::lint] about the others.
+    ///
+    /// Commands that reduce an expression (such as [show][Cmd::Show]) only infer its type, so
+    /// that checking is not held up by costly reductions, while those that change the context are
+    /// evaluated as usual, so that the commands after them are checked in the changed context.
+    pub fn check_source(src: &str, ctx: &mut Ctx, set: &mut Settings) -> Vec<Diagnostic> {
+        let toks = tokenize(src);
+        let mut diags = vec![];
+        for (_, toks) in split_commands(src, &toks) {
+            let end = toks.last().map_or(0, |tok| tok.end);
+            let cmd = match parse_cmd_unlocated(&src[..end], toks, ctx, set) {
+                Ok(cmd) => cmd,
+                Err(e) => {
+                    diags.push(Diagnostic::spanning(src, (e.beg, e.end), &e.to_string()));
+                    continue;
+                }
+            };
+            let cmd = match cmd {
+                Cmd::Show(exp)
+                | Cmd::Whnf(exp)
+                | Cmd::Time(exp)
+                | Cmd::Trace(exp)
+                | Cmd::Exec(exp)
+                | Cmd::Step(Some(exp)) => Cmd::Type(exp),
+                Cmd::Step(None) => Cmd::Noop,
+                cmd => cmd,
+            };
+            let pre = ctx.clone();
+            let out = cmd.eval(ctx, set);
+            let beg = toks.first().map_or(0, |tok| tok.beg);
+            if let Status::Failure = out.status {
+                let pos = (out.err.as_ref()).and_then(|e| locate_err(&src[..end], toks, ctx, e));
+                let msg = out.msg.unwrap_or_default();
+                diags.push(Diagnostic::spanning(src, pos.unwrap_or((beg, end)), &msg));
+            } else if set.lnt {
+                for lint in cmd.lint(&pre, ctx) {
+                    let pos = locate_lint(&src[..end], toks, &pre, &lint);
+                    diags.push(Diagnostic {
+                        severity: Severity::Warning,
+                        ..Diagnostic::spanning(src, pos.unwrap_or((beg, end)), &lint.to_string())
+                    });
+                }
+            }
+            if out.trm {
+                break;
+            }
+        }
+        diags
+    }
+
+    /// Fetch examples of the use of the command, shown by [help][Cmd::Help] for the command alone.
+    pub fn examples(&self) -> Vec<&'static str> {
+        match self {
+            Cmd::Fail(_) | Cmd::Noop => vec![],
+            Cmd::Help(_) => vec!["help", "help show"],
+            Cmd::Exit => vec!["exit"],
+            Cmd::Show(_) => vec!["show (λx : ℕ . add x x) 2n", "show it"],
+            Cmd::Type(_) => vec!["type λA : * . λx : A . x", "type %1"],
+            Cmd::Eq(..) => vec!["eq (add 1n 1n) 2n", "eq (λx : ℕ . x) (λy : ℕ . y)"],
+            Cmd::Export(..) => vec!["export latex λx : ℕ . suc x", "export html ℕ → ℕ"],
+            Cmd::Time(_) => vec!["time mul 100n 100n"],
+            Cmd::Whnf(_) => vec!["whnf (λx : ℕ . suc (add x x)) 1n"],
+            Cmd::Step(_) => vec!["step (λx : ℕ . suc x) 1n", "step"],
+            Cmd::Trace(_) => vec!["trace (λf : ℕ → ℕ . f (f 1n)) (λx : ℕ . add x x)"],
+            Cmd::Exec(_) => vec![],
+            Cmd::Note(_) => vec!["infixl 6 \"+\" := plus", "infixr 5 \"∘\" := compose"],
+            Cmd::Let(..) => vec!["let double : ℕ → ℕ = λx . add x x"],
+            Cmd::Assume(..) => vec!["assume A : *", "assume a : A"],
+            Cmd::Undo => vec!["undo"],
+            Cmd::Reset => vec!["reset"],
+            Cmd::Env(_) => vec!["env", "env 2"],
+            Cmd::Bindings => vec!["bindings"],
+            Cmd::Search(_) => vec!["search ℕ → ℕ", "search _ → ℕ"],
+            Cmd::Load(_) => vec!["load \"lib/nat.fx\""],
+            Cmd::Import(_) => vec!["import prelude"],
+            Cmd::Open(_) => vec!["open List"],
+            Cmd::Alias(..) => vec!["alias t = type", "alias nf = set strategy normal"],
+            Cmd::Set(_) => vec![
+                "set strategy cbv",
+                "set fuel 1000",
+                "set gutter numbers",
+                "set keymap emacs",
+                "set theme light",
+                "set ascii on",
+                "set warnings off",
+            ],
+            Cmd::Session(_) => vec![
+                "session save \"work.json\"",
+                "session load \"work.json\"",
+                "session recover",
+            ],
+            Cmd::Data(_) => vec![
+                "data Bool := true | false",
+                "data List := nil | cons ℕ List",
+            ],
+        }
+    }
+
+    /// Fetch the keywords of the commands related to the command, shown by [help][Cmd::Help] for
+    /// the command alone.
+    pub fn related(&self) -> Vec<&'static str> {
+        match self {
+            Cmd::Fail(_) | Cmd::Noop | Cmd::Exit => vec![],
+            Cmd::Help(_) => vec!["env"],
+            Cmd::Show(_) => vec!["type", "whnf", "step", "set"],
+            Cmd::Type(_) => vec!["show", "search"],
+            Cmd::Eq(..) => vec!["show", "whnf"],
+            Cmd::Export(..) => vec!["type", "show"],
+            Cmd::Time(_) => vec!["show", "set"],
+            Cmd::Whnf(_) => vec!["show", "step"],
+            Cmd::Step(_) => vec!["trace", "whnf"],
+            Cmd::Trace(_) => vec!["step", "time"],
+            Cmd::Exec(_) => vec!["show"],
+            Cmd::Note(_) => vec!["let"],
+            Cmd::Let(..) => vec!["assume", "data", "undo"],
+            Cmd::Assume(..) => vec!["let", "data"],
+            Cmd::Undo => vec!["reset"],
+            Cmd::Reset => vec!["undo"],
+            Cmd::Env(_) => vec!["search"],
+            Cmd::Bindings => vec!["set", "help"],
+            Cmd::Search(_) => vec!["env", "type"],
+            Cmd::Load(_) => vec!["import", "session"],
+            Cmd::Import(_) => vec!["load"],
+            Cmd::Open(_) => vec!["env"],
+            Cmd::Alias(..) => vec!["help", "session"],
+            Cmd::Set(_) => vec!["show", "time"],
+            Cmd::Session(_) => vec!["load", "reset"],
+            Cmd::Data(_) => vec!["let", "assume"],
         }
     }
 
@@ -112,14 +954,147 @@ impl Cmd {
         match self {
             Cmd::Fail(_) => vec![], // not invocable by the user, so no user help
             Cmd::Noop => vec![],    // not invocable by the user, so no user help
-            Cmd::Help(_) => vec![("help", "Print this help message")],
+            Cmd::Help(_) => vec![
+                ("help", "Print this help message"),
+                ("help CMD", "Print help for the command CMD"),
+            ],
             Cmd::Exit => vec![
                 ("exit", "Exit the integrated development environment"),
                 ("quit", "Alias for “exit”"),
             ],
             Cmd::Show(_) => vec![("show EXP", "Show the normalized form of the expression EXP")],
             Cmd::Type(_) => vec![("type EXP", "Show the type of the expression EXP")],
+            Cmd::Eq(..) => vec![(
+                "eq EXP EXP",
+                "Check whether the two expressions reduce to the same normal form",
+            )],
+            Cmd::Export(..) => vec![
+                ("export latex EXP", "Show the expression EXP and its type as LaTeX"),
+                ("export html EXP", "Show the expression EXP and its type as HTML"),
+            ],
+            Cmd::Time(_) => vec![(
+                "time EXP",
+                "Show EXP and its type, with the time and steps taken to reduce it",
+            )],
+            Cmd::Whnf(_) => vec![(
+                "whnf EXP",
+                "Show the weak head normal form of EXP, leaving its arguments unreduced",
+            )],
+            Cmd::Step(_) => vec![
+                (
+                    "step EXP",
+                    "Contract the next redex of EXP, showing the rule applied",
+                ),
+                (
+                    "step",
+                    "Take the next step from the result of the previous step",
+                ),
+            ],
+            Cmd::Trace(_) => vec![(
+                "trace EXP",
+                "Show every step of the reduction of EXP, with the rule applied",
+            )],
             Cmd::Exec(_) => vec![("exec EXP", "Execute the program denoted by the expression")],
+            Cmd::Note(_) => vec![
+                (
+                    "infixl PREC \"OP\" := VAR",
+                    "Declare OP as a left-associative operator for VAR",
+                ),
+                (
+                    "infixr PREC \"OP\" := VAR",
+                    "Declare OP as a right-associative operator for VAR",
+                ),
+                (
+                    "infix PREC \"OP\" := VAR",
+                    "Declare OP as a non-associative operator for VAR",
+                ),
+            ],
+            Cmd::Let(..) => vec![(
+                "let VAR : TYPE = EXP",
+                "Define VAR as the expression EXP, which is checked against TYPE",
+            )],
+            Cmd::Assume(..) => vec![(
+                "assume VAR : TYPE",
+                "Assume VAR as a constant of type TYPE, which has no definition",
+            )],
+            Cmd::Undo => vec![(
+                "undo",
+                "Retract the most recent change to the session, such as a definition",
+            )],
+            Cmd::Reset => vec![("reset", "Retract every change made during the session")],
+            Cmd::Bindings => vec![(
+                "bindings",
+                "List the keys bound in the editor, with the actions they take",
+            )],
+            Cmd::Env(_) => vec![
+                ("env", "List the variables in the session, with their types"),
+                ("env N", "List the variables on page N of the listing"),
+            ],
+            Cmd::Search(_) => vec![(
+                "search TYPE",
+                "List the variables whose types match TYPE, in which _ stands for any type",
+            )],
+            Cmd::Load(_) => vec![(
+                "load \"PATH\"",
+                "Evaluate the commands in the file at PATH, one after another",
+            )],
+            Cmd::Import(_) => vec![(
+                "import prelude",
+                "Bring the bundled prelude (combinators, booleans, pairs, Church numerals) into scope",
+            )],
+            Cmd::Alias(..) => vec![(
+                "alias NAME = CMD",
+                "Let NAME stand for CMD (such as a command without its arguments)",
+            )],
+            Cmd::Open(_) => vec![(
+                "open NS",
+                "Refer to the variables in the namespace NS by their unqualified names",
+            )],
+            Cmd::Set(_) => vec![
+                (
+                    "set strategy STG",
+                    "Show expressions reduced with STG (normal, cbv or whnf)",
+                ),
+                ("set fuel N", "Permit at most N steps for each reduction"),
+                (
+                    "set gutter GUT",
+                    "Show line numbers (numbers) or only prompts (prompts) beside the input",
+                ),
+                (
+                    "set keymap KEYS",
+                    "Edit with the standard key bindings (standard) or those of Emacs (emacs)",
+                ),
+                (
+                    "set theme NAME",
+                    "Render with the colors of NAME (dark, light or high-contrast)",
+                ),
+                (
+                    "set ascii on|off",
+                    "Render prompts and expressions in ASCII (on) or with Unicode glyphs (off)",
+                ),
+                (
+                    "set warnings on|off",
+                    "Report (on) or leave out (off) warnings about unused or shadowed variables",
+                ),
+            ],
+            Cmd::Session(_) => vec![
+                (
+                    "session save \"PATH\"",
+                    "Save the session to the file at PATH",
+                ),
+                (
+                    "session load \"PATH\"",
+                    "Restore the session saved to the file at PATH",
+                ),
+                (
+                    "session recover",
+                    "Restore the session that ended unexpectedly before this one",
+                ),
+            ],
+            Cmd::Data(_) => vec![(
+                "data T := C ARGS | …",
+                "Declare the data type T with the given constructors",
+            )],
         }
     }
 }
@@ -131,9 +1106,18 @@ impl<'a> Out<'a> {
             status: &Status::Success,
             msg: None,
             trm: false,
+            err: None,
+            lints: Vec::new(),
         }
     }
 
+    /// Create a new instance that reports the given error, which the command failed with.
+    pub fn with_err(err: &TypingErr) -> Out<'a> {
+        let mut res = Self::with_msg(&err.to_string(), &Status::Failure);
+        res.err = Some(err.clone());
+        res
+    }
+
     /// Create a new instance with the given message.
     pub fn with_msg(msg: &str, status: &'a Status) -> Out<'a> {
         let mut res = Self::new();
@@ -155,3 +1139,696 @@ impl Default for &Status {
         &Status::Success
     }
 }
+
+impl Session {
+    /// Apply this operation to the given context, which holds the state of the session.
+    #[cfg(feature = "serde")]
+    fn apply(&self, ctx: &mut Ctx) -> std::io::Result<()> {
+        use std::fs::File;
+        use std::io::{BufReader, BufWriter};
+        match self {
+            Session::Save(path) => ctx.to_writer(BufWriter::new(File::create(path)?)),
+            Session::Load(path) => {
+                *ctx = Ctx::from_reader(BufReader::new(File::open(path)?))?;
+                Ok(())
+            }
+            Session::Recover => Err(Self::unrecoverable()),
+        }
+    }
+
+    /// Apply this operation to the given context, which always fails, since sessions can only be
+    /// saved and restored when the `serde` feature is enabled.
+    #[cfg(not(feature = "serde"))]
+    fn apply(&self, _: &mut Ctx) -> std::io::Result<()> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "sessions can only be saved and restored with the “serde” feature enabled",
+        ))
+    }
+
+    /// Fetch the error that [recover][Session::Recover] fails with when there is no session to
+    /// recover (since the session that the checkpoint was recovered into holds it, if any).
+    pub fn unrecoverable() -> std::io::Error {
+        std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "there is no session that ended unexpectedly to recover",
+        )
+    }
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self::Save(PathBuf::new())
+    }
+}
+
+/// Render the operation as the command that performs it.
+impl Display for Session {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Save(path) => write!(f, "session save {:?}", path.display().to_string()),
+            Self::Load(path) => write!(f, "session load {:?}", path.display().to_string()),
+            Self::Recover => write!(f, "session recover"),
+        }
+    }
+}
+
+impl Settings {
+    /// Create the [limits][Limits] on the reductions of a command that is about to be evaluated
+    /// with these settings, from which the time permitted for them starts.
+    pub fn limits(&self) -> Limits {
+        Limits::new(Arc::clone(&self.irq), self.tmo, self.max)
+    }
+
+    /// Declare the given name as an alias for the given (partial) command, replacing any earlier
+    /// declaration of the same alias.
+    pub fn put_alias(&mut self, name: &Var, cmd: &str) {
+        self.als.retain(|(n, _)| n != name);
+        self.als.push((name.clone(), cmd.to_string()));
+    }
+
+    /// Fetch the (partial) command that the given name is an alias for.
+    pub fn get_alias(&self, name: &Var) -> Option<&str> {
+        self.als
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, cmd)| cmd.as_str())
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            gut: Gutter::default(),
+            key: KeySet::default(),
+            thm: Theme::default(),
+            asc: false,
+            lnt: true,
+            tmo: None,
+            max: None,
+            irq: Default::default(),
+            als: vec![],
+        }
+    }
+}
+
+impl Default for Setting {
+    fn default() -> Self {
+        Self::Strategy(Strategy::default())
+    }
+}
+
+/// Render the setting as the command that changes it.
+impl Display for Setting {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Strategy(stg) => write!(f, "set strategy {}", stg),
+            Self::Fuel(fuel) => write!(f, "set fuel {}", fuel),
+            Self::Gutter(gut) => write!(f, "set gutter {}", gut),
+            Self::KeySet(key) => write!(f, "set keymap {}", key),
+            Self::Theme(pre) => write!(f, "set theme {}", pre),
+            Self::Ascii(on) => write!(f, "set ascii {}", if *on { "on" } else { "off" }),
+            Self::Warnings(on) => write!(f, "set warnings {}", if *on { "on" } else { "off" }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::err::SyntaxErr;
+    use crate::par::parse;
+    use crate::pmt::Prompt;
+
+    #[test]
+    fn test_cmd_0001() -> Result<(), Box<dyn std::error::Error>> {
+        // definitions persist in the session, where later commands may refer to them
+        let mut ctx = Ctx::new();
+        let mut set = Settings::default();
+        let mut run = |src: &str| -> Result<(bool, Option<String>), SyntaxErr> {
+            let cmd = parse_cmd(src, &tokenize(src), &ctx, &set)?;
+            let out = cmd.eval(&mut ctx, &mut set);
+            Ok((matches!(out.status, Status::Success), out.msg))
+        };
+        assert_eq!(
+            run("let two : ℕ = add 1n 1n")?,
+            (true, Some("two : ℕ".into()))
+        );
+        assert_eq!(run("show mul two two")?, (true, Some("4n".into())));
+        assert!(!run("let two : * = ℕ")?.0);
+        assert!(!run("let three : ℕ = λx . x")?.0);
+        assert!(!run("type three")?.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cmd_0002() -> Result<(), Box<dyn std::error::Error>> {
+        // assumed constants may be used, but do not reduce
+        let mut ctx = Ctx::new();
+        let mut set = Settings::default();
+        let mut run = |src: &str| -> Result<(bool, Option<String>), SyntaxErr> {
+            let cmd = parse_cmd(src, &tokenize(src), &ctx, &set)?;
+            let out = cmd.eval(&mut ctx, &mut set);
+            Ok((matches!(out.status, Status::Success), out.msg))
+        };
+        assert_eq!(run("assume A : *")?, (true, Some("A : *".into())));
+        assert_eq!(run("assume f : A → A")?, (true, Some("f : A → A".into())));
+        assert_eq!(run("assume a : A")?, (true, Some("a : A".into())));
+        assert_eq!(run("show f a")?, (true, Some("f a".into())));
+        assert!(!run("assume b : a")?.0);
+        assert!(!run("assume a : * → *")?.0);
+        assert!(!run("type b")?.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cmd_0003() -> Result<(), Box<dyn std::error::Error>> {
+        // the commands in a file are evaluated in order, and their outcomes reported by line
+        let path = std::env::temp_dir().join(format!("fluxo-{}.fx", std::process::id()));
+        std::fs::write(
+            &path,
+            "assume A : *\nassume a : A\nlet b : A =\n  a\nlet c : B = a\n",
+        )?;
+        let mut ctx = Ctx::new();
+        let mut set = Settings::default();
+        let load = Cmd::Load(path.clone());
+        let out = load.eval(&mut ctx, &mut set);
+        std::fs::remove_file(&path)?;
+        assert!(matches!(out.status, Status::Failure));
+        let msg = out.msg.unwrap_or_default();
+        let log: Vec<_> = msg.lines().skip(1).take(4).collect();
+        assert_eq!(
+            log,
+            [
+                "   1 ∴ A : *",
+                "   2 ∴ a : A",
+                "   3 ∴ b : A",
+                "   5 ✗ :type B = ?"
+            ]
+        );
+        assert!(msg
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .ends_with("↯ 1 of 4 commands failed"));
+        assert_eq!(ctx.get_def(&Var::new("b")), Some(&parse("a")?));
+        assert!(matches!(
+            Cmd::Load(path).eval(&mut ctx, &mut set).status,
+            Status::Failure
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_cmd_0004() -> Result<(), Box<dyn std::error::Error>> {
+        // variables are listed by name, along with their values if they are defined
+        let mut ctx = Ctx::new();
+        let mut set = Settings::default();
+        for src in [
+            "assume B : *",
+            "assume A : *",
+            "assume a : A",
+            "let b : A = a",
+        ] {
+            parse_cmd(src, &tokenize(src), &ctx, &set)?.eval(&mut ctx, &mut set);
+        }
+        let env = Cmd::Env(1);
+        let out = env.eval(&mut ctx, &mut set);
+        assert_eq!(
+            out.msg.as_deref(),
+            Some("env (page 1 of 1)\nA : *\nB : *\na : A\nb : A = a\n")
+        );
+        for i in 0..ENV_PAGE {
+            ctx.put(&Var::new(&format!("x{:02}", i)), &parse("A")?)?;
+        }
+        let env = Cmd::Env(2);
+        let out = env.eval(&mut ctx, &mut set);
+        let msg = out.msg.unwrap_or_default();
+        assert_eq!(msg.lines().next(), Some("env (page 2 of 2)"));
+        assert_eq!(msg.lines().nth(1), Some("x16 : A"));
+        let env = Cmd::Env(3);
+        assert!(matches!(
+            env.eval(&mut ctx, &mut set).status,
+            Status::Failure
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_cmd_0005() -> Result<(), Box<dyn std::error::Error>> {
+        // each step underlines the redex that is contracted, labeled with the rule applied
+        let mut ctx = Ctx::new();
+        ctx.put(&Var::new("A"), &Exp::Sort(0))?;
+        ctx.put(&Var::new("f"), &"A → A".parse()?)?;
+        ctx.define(&Var::new("id"), &"λx . x".parse()?, &"A → A".parse()?)?;
+        ctx.put(&Var::new("a"), &"A".parse()?)?;
+        let step = |src: &str, ctx: &Ctx| -> Result<(String, Option<Exp>), SyntaxErr> {
+            let (out, res) = Cmd::step(&parse(src)?, ctx, &Settings::default());
+            Ok((out.msg.unwrap_or_default(), res))
+        };
+        let (msg, res) = step("f ((λx : A . x) (id a))", &ctx)?;
+        assert_eq!(
+            msg,
+            "f ((λx : A . x) (id a))\n   ^^^^^^^^^^^^^^^^^^^ β\nf (id a)"
+        );
+        let (msg, res) = step(&res.unwrap().to_string(), &ctx)?;
+        assert_eq!(msg, "f (id a)\n   ^^ δ\nf ((λx : A . x) a)");
+        let (msg, res) = step(&res.unwrap().to_string(), &ctx)?;
+        assert_eq!(msg, "f ((λx : A . x) a)\n   ^^^^^^^^^^^^^^ β\nf a");
+        let (msg, res) = step(&res.unwrap().to_string(), &ctx)?;
+        assert_eq!(
+            (msg.as_str(), res),
+            ("f a\n    ∎ there are no redexes left\n", None)
+        );
+        let (msg, _) = step("fst ((a, a : Σx : A . A))", &ctx)?;
+        assert!(msg.ends_with("π\na"));
+        assert!(step("f f", &ctx)?.1.is_none());
+        // without the interactive environment, there is no previous step to continue from
+        assert!(matches!(
+            Cmd::Step(None)
+                .eval(&mut ctx, &mut Settings::default())
+                .status,
+            Status::Failure
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_cmd_0006() -> Result<(), Box<dyn std::error::Error>> {
+        // every step is listed, with the rule applied, until no redexes are left
+        let mut ctx = Ctx::new();
+        let mut set = Settings::default();
+        let mut run = |src: &str| -> Result<(bool, Option<String>), SyntaxErr> {
+            let cmd = parse_cmd(src, &tokenize(src), &ctx, &set)?;
+            let out = cmd.eval(&mut ctx, &mut set);
+            Ok((matches!(out.status, Status::Success), out.msg))
+        };
+        run("let double : ℕ → ℕ = λn . add n n")?;
+        assert_eq!(
+            run("trace double (suc 1n)")?,
+            (
+                true,
+                Some("   0   double (suc 1n)\n   1 δ (λn : ℕ . add n n) (suc 1n)\n   2 β add (suc 1n) (suc 1n)\n   3 ι add 2n (suc 1n)\n   4 ι add 2n 2n\n   5 ι 4n\n".into())
+            )
+        );
+        assert_eq!(run("trace 4n")?, (true, Some("   0   4n\n".into())));
+        assert!(!run("trace double ℕ")?.0);
+        // the steps are bounded by the fuel, and those taken before it runs out are listed
+        run("set fuel 2")?;
+        let (ok, msg) = run("trace double 1n")?;
+        assert!(!ok);
+        assert_eq!(msg.unwrap().lines().count(), 4);
+        run("set fuel 3")?;
+        assert!(run("trace double 1n")?.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cmd_0007() -> Result<(), Box<dyn std::error::Error>> {
+        // only the head is reduced, whatever the strategy of the session
+        let mut ctx = Ctx::new();
+        let mut set = Settings::default();
+        let mut run = |src: &str| -> Result<(bool, Option<String>), SyntaxErr> {
+            let cmd = parse_cmd(src, &tokenize(src), &ctx, &set)?;
+            let out = cmd.eval(&mut ctx, &mut set);
+            Ok((matches!(out.status, Status::Success), out.msg))
+        };
+        run("assume f : ℕ → ℕ")?;
+        run("let g : ℕ → ℕ = λn . f (add n n)")?;
+        assert_eq!(run("whnf g 1n")?, (true, Some("f (add 1n 1n)".into())));
+        assert_eq!(run("show g 1n")?, (true, Some("f 2n".into())));
+        assert_eq!(
+            run("whnf λn : ℕ . g n")?,
+            (true, Some("λn : ℕ . g n".into()))
+        );
+        assert!(!run("whnf g ℕ")?.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cmd_0008() -> Result<(), Box<dyn std::error::Error>> {
+        // expressions are convertible if they reduce to the same normal form (up to eta)
+        let mut ctx = Ctx::new();
+        let mut set = Settings::default();
+        let mut run = |src: &str| -> Result<(bool, Option<String>), SyntaxErr> {
+            let cmd = parse_cmd(src, &tokenize(src), &ctx, &set)?;
+            let out = cmd.eval(&mut ctx, &mut set);
+            Ok((matches!(out.status, Status::Success), out.msg))
+        };
+        run("assume f : ℕ → ℕ")?;
+        run("let double : ℕ → ℕ = λn . add n n")?;
+        assert_eq!(
+            run("eq (double 2n) mul 2n 2n")?,
+            (
+                true,
+                Some("double 2n ≡ mul 2n 2n\n    both reduce to 4n".into())
+            )
+        );
+        assert!(run("eq f λn : ℕ . f n")?.0);
+        assert!(run("eq (λn : ℕ . f n) f")?.0);
+        assert_eq!(
+            run("eq (f (double 1n)) f 3n")?,
+            (
+                false,
+                Some("f (double 1n) ≢ f 3n\n    ↯ their normal forms differ at 2n and 3n\n".into())
+            )
+        );
+        assert!(!run("eq double f")?.0);
+        assert!(!run("eq (f ℕ) f")?.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cmd_0009() -> Result<(), Box<dyn std::error::Error>> {
+        // the value and type are shown, along with the statistics of the evaluation
+        let mut ctx = Ctx::new();
+        let mut set = Settings::default();
+        let mut run = |src: &str| -> Result<(&'static str, Option<String>), SyntaxErr> {
+            let cmd = parse_cmd(src, &tokenize(src), &ctx, &set)?;
+            let out = cmd.eval(&mut ctx, &mut set);
+            let status = match out.status {
+                Status::Diagnostics => "diagnostics",
+                Status::Failure => "failure",
+                _ => "other",
+            };
+            Ok((status, out.msg))
+        };
+        run("let double : ℕ → ℕ = λn . add n n")?;
+        let (status, msg) = run("time double (double 1n)")?;
+        let msg = msg.unwrap_or_default();
+        let lines: Vec<&str> = msg.lines().collect();
+        assert_eq!(status, "diagnostics");
+        assert_eq!(lines[0], "4n : ℕ");
+        assert!(lines[1].starts_with("    wall-clock time "));
+        assert_eq!(lines[2], "    reduction steps 6");
+        assert_eq!(lines[3], "    peak term size  7");
+        assert_eq!(run("time double")?.0, "diagnostics");
+        assert_eq!(run("time double ℕ")?.0, "failure");
+        run("set fuel 3")?;
+        assert_eq!(run("time double (double 1n)")?.0, "failure");
+        Ok(())
+    }
+
+    #[test]
+    fn test_cmd_0010() -> Result<(), Box<dyn std::error::Error>> {
+        // variables are listed from the closest match to the loosest
+        let mut ctx = Ctx::new();
+        let mut set = Settings::default();
+        let mut run = |src: &str| -> Result<(bool, Option<String>), SyntaxErr> {
+            let cmd = parse_cmd(src, &tokenize(src), &ctx, &set)?;
+            let out = cmd.eval(&mut ctx, &mut set);
+            Ok((!matches!(out.status, Status::Failure), out.msg))
+        };
+        run("assume id : Π{A : *} . A → A")?;
+        run("assume double : ℕ → ℕ")?;
+        run("assume add3 : ℕ → ℕ → ℕ → ℕ")?;
+        run("assume zero : ℕ")?;
+        run("assume isZero : ℕ → ⊤")?;
+        assert_eq!(
+            run("search ℕ → ℕ")?,
+            (
+                true,
+                Some(
+                    "search ℕ → ℕ (3 found)\n\
+                     exact    double : ℕ → ℕ\n\
+                     instance id : Π{A : *} . A → A\n\
+                     result   add3 : ℕ → ℕ → ℕ → ℕ\n"
+                        .into()
+                )
+            )
+        );
+        let (ok, msg) = run("search ℕ → _")?;
+        let names: Vec<_> = (msg.unwrap().lines().skip(1))
+            .map(|line| {
+                line.split_whitespace()
+                    .take(2)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect();
+        assert!(ok);
+        assert_eq!(
+            names,
+            [
+                "instance add3",
+                "instance double",
+                "instance id",
+                "instance isZero"
+            ]
+        );
+        assert!(run("search ℕ")?
+            .1
+            .unwrap()
+            .starts_with("search ℕ (4 found)\nexact    zero"));
+        assert!(matches!(
+            Cmd::Search(Exp::Nat)
+                .eval(&mut Ctx::new(), &mut Settings::default())
+                .status,
+            Status::Failure
+        ));
+        // shadowed variables are left out, and the metavariables solved are discarded
+        let inner = ctx.extend(&Var::new("zero"), &Exp::Unit);
+        let found = inner.search(&Exp::Nat);
+        assert_eq!(found.len(), 3);
+        assert!(found.iter().all(|(_, var, _)| var.0 != "zero"));
+        assert!(ctx.get_unsolved().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_cmd_0011() -> Result<(), Box<dyn std::error::Error>> {
+        // every command of the prelude succeeds, and its definitions are then in scope
+        let mut ctx = Ctx::new();
+        let mut set = Settings::default();
+        let mut run = |src: &str| -> Result<(bool, Option<String>), SyntaxErr> {
+            let cmd = parse_cmd(src, &tokenize(src), &ctx, &set)?;
+            let out = cmd.eval(&mut ctx, &mut set);
+            Ok((!matches!(out.status, Status::Failure), out.msg))
+        };
+        let (ok, msg) = run("import prelude")?;
+        assert!(ok, "{}", msg.unwrap_or_default());
+        assert!(msg.unwrap().starts_with("import prelude\n"));
+        assert_eq!(
+            run("show not (and true false)")?,
+            (true, Some("true".into()))
+        );
+        assert_eq!(run("show if (or false true) 1n 2n")?.1, Some("1n".into()));
+        assert_eq!(
+            run("show cnat (cmul 2 (csuc 2))")?,
+            (true, Some("6n".into()))
+        );
+        assert_eq!(
+            run("show fst (swap (pair 1n true))")?.1,
+            Some("true".into())
+        );
+        assert_eq!(
+            run("show compose not (flip and true) false")?.1,
+            Some("true".into())
+        );
+        // only the libraries bundled with fluxo may be imported
+        let (ok, msg) = run("import std")?;
+        assert!(!ok);
+        assert_eq!(
+            msg,
+            Some("import std\n    ↯ there is no library named std\n".into())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_cmd_0012() -> Result<(), Box<dyn std::error::Error>> {
+        // results are kept under successive numbers, and the latest is also bound to it
+        let mut ctx = Ctx::new();
+        let mut set = Settings::default();
+        let (out, res) = Cmd::show(&parse("add 1n 2n")?, &ctx, &set);
+        assert_eq!(out.msg, Some("3n".into()));
+        let (val, typ) = res.ok_or("no result")?;
+        assert_eq!(Cmd::keep(1, &val, &typ, &mut ctx), Var::new("%1"));
+        let mut run = |src: &str| -> Result<Option<String>, SyntaxErr> {
+            let cmd = parse_cmd(src, &tokenize(src), &ctx, &set)?;
+            Ok(cmd.eval(&mut ctx, &mut set).msg)
+        };
+        assert_eq!(run("show mul %1 it")?, Some("9n".into()));
+        assert_eq!(run("type it")?, Some("ℕ".into()));
+        let (out, res) = Cmd::type_of(&parse("λx : ℕ . x")?, &ctx, &set);
+        assert_eq!(out.msg, Some("ℕ → ℕ".into()));
+        let (typ, srt) = res.ok_or("no result")?;
+        assert_eq!(Cmd::keep(2, &typ, &srt, &mut ctx), Var::new("%2"));
+        // it may be rebound to a result of another type, while %1 is left alone
+        let mut run = |src: &str| -> Result<Option<String>, SyntaxErr> {
+            let cmd = parse_cmd(src, &tokenize(src), &ctx, &set)?;
+            Ok(cmd.eval(&mut ctx, &mut set).msg)
+        };
+        assert_eq!(run("show it")?, Some("ℕ → ℕ".into()));
+        assert_eq!(run("type it")?, Some("*".into()));
+        assert_eq!(run("show %1")?, Some("3n".into()));
+        // an expression that is ill-typed leaves no result
+        let (_, res) = Cmd::show(&parse("fst 1n")?, &Ctx::new(), &set);
+        assert_eq!(res, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cmd_0013() -> Result<(), Box<dyn std::error::Error>> {
+        let cmd = |src: &str| parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default());
+        // help for a single command is extended with examples and related commands
+        let Some(Cmd::Help(Some(tgt))) = cmd("help show").ok() else {
+            panic!("expected help for a command")
+        };
+        let msg = Cmd::Help(Some(tgt.clone()))
+            .eval(&mut Ctx::new(), &mut Settings::default())
+            .msg
+            .unwrap();
+        assert!(msg.contains("EXAMPLES:\r\n  show (λx : ℕ . add x x) 2n\r\n"));
+        assert!(msg.contains("SEE ALSO: "));
+        assert!(!msg.contains("Show the type of"));
+        let msg = Cmd::Help(None)
+            .eval(&mut Ctx::new(), &mut Settings::default())
+            .msg
+            .unwrap();
+        assert!(msg.contains("Show the type of") && !msg.contains("EXAMPLES:"));
+        // every example is a command of the same kind, and every related command exists
+        let keys =
+            "help exit show type whnf eq time step trace exec infixl open data let assume set \
+                    load import env search session undo reset";
+        for src in keys.split_whitespace() {
+            let tgt = cmd(&format!("help {}", src))?;
+            let Cmd::Help(Some(tgt)) = tgt else {
+                panic!("expected help for {}", src)
+            };
+            for src in tgt.examples() {
+                let exm = cmd(src).map_err(|e| format!("{}: {}", src, e))?;
+                assert_eq!(std::mem::discriminant(&exm), std::mem::discriminant(&*tgt));
+            }
+            for key in tgt.related() {
+                assert!(cmd(&format!("help {}", key)).is_ok(), "{}", key);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_cmd_0014() {
+        let (mut ctx, mut set) = (Ctx::new(), Settings::default());
+        let out = Cmd::Set(Setting::Gutter(Gutter::Numbers)).eval(&mut ctx, &mut set);
+        assert_eq!(out.msg.as_deref(), Some("set gutter numbers"));
+        assert_eq!(set.gut, Gutter::Numbers);
+    }
+
+    #[test]
+    fn test_cmd_0015() {
+        let (mut ctx, mut set) = (Ctx::new(), Settings::default());
+        Cmd::Set(Setting::KeySet(KeySet::Emacs)).eval(&mut ctx, &mut set);
+        assert_eq!(set.key, KeySet::Emacs);
+    }
+
+    #[test]
+    fn test_cmd_0016() -> Result<(), Box<dyn std::error::Error>> {
+        let mut ctx = Ctx::new();
+        let mut set = Settings::default();
+        let mut run = |src: &str| -> Result<String, SyntaxErr> {
+            let cmd = parse_cmd(src, &tokenize(src), &ctx, &set)?;
+            Ok(cmd.eval(&mut ctx, &mut set).msg.unwrap_or_default())
+        };
+        let out = run("bindings")?;
+        let row = |key: &str| {
+            out.lines()
+                .find(|line| line.starts_with(key))
+                .map(String::from)
+        };
+        assert!(out.starts_with("bindings (standard keymap)\n"));
+        assert!(row("Ctrl+Y").is_some_and(|row| row.contains(" redo ")));
+        assert!(row("any character").is_some_and(|row| row.contains(" insert ")));
+        assert!(row("Ctrl+A").is_none());
+        run("set keymap emacs")?;
+        let out = run("bindings")?;
+        let rows = |key: &str| {
+            (out.lines())
+                .filter(|line| line.starts_with(key))
+                .collect::<Vec<_>>()
+        };
+        // a key bound by the Emacs keymap shadows the standard binding of the same key
+        assert!(matches!(rows("Ctrl+Y ")[..], [row] if row.contains(" yank ")));
+        assert!(matches!(rows("Ctrl+X u")[..], [row] if row.contains(" undo ")));
+        assert_eq!(rows("Ctrl+Z").len(), 1);
+        assert!(run("bindings emacs").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_cmd_0017() -> Result<(), Box<dyn std::error::Error>> {
+        let (mut ctx, mut set) = (Ctx::new(), Settings::default());
+        // expressions are then shown with the ASCII equivalents of their glyphs, as are prompts
+        let out = Cmd::Set(Setting::Ascii(true)).eval(&mut ctx, &mut set);
+        assert!(set.asc);
+        assert_eq!(out.msg.as_deref(), Some("set ascii on"));
+        let show = Cmd::Show(parse("λA : * . A → A")?);
+        let out = show.eval(&mut ctx, &mut set);
+        assert_eq!(out.msg.as_deref(), Some("\\A : Type . A -> A"));
+        let out = Prompt::show_plain(out.msg.as_deref().unwrap(), out.status, true);
+        assert_eq!(out, "=> \\A : Type . A -> A\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_cmd_0018() {
+        let src = "assume A : *\nshow y\nlet id : A → A =\n  λx : A . x\nshow (id\n  show id A\nshow id\n";
+        let diags = Cmd::check_source(src, &mut Ctx::new(), &mut Settings::default());
+        let diags = diags.iter().map(ToString::to_string).collect::<Vec<_>>();
+        // checking goes on past each failure, and later commands see the definitions before them
+        assert_eq!(
+            diags,
+            [
+                "2:6: error: :type y = ?",
+                "6:12: error: :parse expected ‘)’, found end of input"
+            ]
+        );
+        let mut ctx = Ctx::new();
+        let mut set = Settings::default();
+        assert!(Cmd::check_source("assume A : *\nquit\nshow y\n", &mut ctx, &mut set).is_empty());
+        assert!(ctx.get(&Var::new("A")).is_ok());
+    }
+
+    #[test]
+    fn test_cmd_0019() {
+        let (mut ctx, mut set) = (Ctx::new(), Settings::default());
+        assert!(set.lnt);
+        let out = Cmd::Set(Setting::Warnings(false)).eval(&mut ctx, &mut set);
+        assert!(!set.lnt);
+        assert_eq!(out.msg.as_deref(), Some("set warnings off"));
+    }
+
+    #[test]
+    fn test_cmd_0020() {
+        let src = "assume A : *\nlet k : A → A → A = λx : A . λy : A . x\nlet f : A → A → A = λx : A . λx : A . x\nlet g : A → A → A = λx : A . λ_y : A . x\n";
+        let diags = Cmd::check_source(src, &mut Ctx::new(), &mut Settings::default());
+        // unused and shadowing binders are warned about, unless named with a leading underscore
+        assert!(diags.iter().all(|diag| diag.severity == Severity::Warning));
+        let diags = diags.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            diags,
+            [
+                "2:30: warning: :type λy : A . x ↯ y is bound but never used (name it _y if this is meant)",
+                "3:21: warning: :type λx : A . λx : A . x ↯ x is bound but never used (name it _x if this is meant)",
+                "3:30: warning: :type λx : A . x ↯ x shadows a variable of the same name",
+            ]
+        );
+        // the binders of the arguments of an eliminator are dictated by its type, so an ordinary
+        // recursion is not warned about, though shadowing within it still is
+        let src = "data N := z | s N\nlet dbl : N → N = N_rec (λn : N . N) z (λn : N . λr : N . s (s r))\nlet pred : ℕ → ℕ = λm : ℕ . Nat_rec (λn : ℕ . ℕ) 0n (λn : ℕ . λr : ℕ . n) m\nlet bad : N → N = N_rec (λn : N . N) z (λz : N . λr : N . r)\n";
+        let diags = Cmd::check_source(src, &mut Ctx::new(), &mut Settings::default());
+        let diags = diags.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            diags,
+            ["4:40: warning: :type λz : N . λr : N . r ↯ z shadows a variable of the same name"]
+        );
+        // an entry that an opened namespace leaves out of reach of its short name is warned about
+        let src = "let a.two : * = ℕ\nlet two : * = ℕ\nopen a\n";
+        let diags = Cmd::check_source(src, &mut Ctx::new(), &mut Settings::default());
+        assert_eq!(diags.len(), 1);
+        assert!(diags[0].to_string().starts_with("3:1: warning: :env a.two"));
+        // and none are, once warnings are turned off
+        let src = format!("set warnings off\n{}", src);
+        assert!(Cmd::check_source(&src, &mut Ctx::new(), &mut Settings::default()).is_empty());
+    }
+}
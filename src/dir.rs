@@ -0,0 +1,36 @@
+//! Directories that fluxo keeps its files in, as laid out by the XDG base directory specification.
+
+use std::path::PathBuf;
+
+/// Fetch the directory that fluxo keeps its files of one kind in, namely the `fluxo` directory
+/// under the base directory named by the given environment variable (such as `XDG_CONFIG_HOME`),
+/// or else under the given fallback path within the home directory (such as `.config`), unless
+/// neither variable is set.
+pub fn xdg_dir(env_key: &str, fallback: &str) -> Option<PathBuf> {
+    let var = |key| std::env::var_os(key).filter(|val| !val.is_empty());
+    let dir = match var(env_key) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(var("HOME")?).join(fallback),
+    };
+    Some(dir.join(env!("CARGO_PKG_NAME")))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_dir_0001() {
+        // a variable of its own is set, since the tests share their environment
+        std::env::set_var("FLUXO_TEST_DIR_0001", "/base");
+        let dir = xdg_dir("FLUXO_TEST_DIR_0001", ".config");
+        assert_eq!(dir, Some(PathBuf::from("/base/fluxo")));
+        std::env::set_var("FLUXO_TEST_DIR_0001", "");
+        let dir = xdg_dir("FLUXO_TEST_DIR_0001", ".local/share");
+        let home = std::env::var_os("HOME").filter(|val| !val.is_empty());
+        assert_eq!(
+            dir,
+            home.map(|home| PathBuf::from(home).join(".local/share/fluxo"))
+        );
+    }
+}
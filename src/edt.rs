@@ -1,94 +1,727 @@
 //! Editor and related utilities.
 
-use crate::buf::Buf;
-use crate::cmd::Cmd;
-use crossterm::event::{Event, KeyCode};
-use crossterm::{cursor, event, execute, queue};
+use crate::ast::Ctx;
+use crate::buf::{Buf, Digraphs, Ring};
+use crate::cmd::{Cmd, Settings};
+use crate::hst::History;
+use crate::key::{self, Action, Key, KeyMap, Layers, Lookup};
+use crate::lay::{Details, Layout, Panel, Scrollback, StatusBar};
+use crate::rcv::Recovery;
+use crate::thm::{Paint, Theme};
+use crossterm::event::{self, Event};
+use crossterm::{cursor, queue, terminal};
 use std::io::{stdout, Result, Write};
+use std::time::Duration;
+use unicode_width::UnicodeWidthStr;
+
+/// Number of completions listed at once in the [completion menu][Menu].
+pub const MENU_SIZE: usize = 8;
+
+/// Time for which typing has to pause before the input is [checked][Buf::check] as it stands.
+pub const CHECK_DELAY: Duration = Duration::from_millis(150);
+
+/// Number of steps permitted for each reduction while the input is [checked][Buf::check] as it
+/// is typed, which keeps the editor responsive however costly the input is to check.
+pub const CHECK_FUEL: usize = 1000;
+
+/// Menu of the completions of the word being typed (or of the terms that the hole at the cursor
+/// may be refined with), which is shown under the cursor.
+#[derive(Debug, Default)]
+pub struct Menu {
+    /// Completions listed, in order.
+    pub items: Vec<String>,
+    /// Index of the completion selected, if one has been.
+    pub sel: Option<usize>,
+    /// Whether the items are refinements of the hole at the cursor, rather than completions.
+    pub hole: bool,
+}
+
+impl Menu {
+    /// Create a new instance of a menu listing the given completions, none of them selected.
+    pub fn new(items: Vec<String>) -> Self {
+        Self {
+            items,
+            sel: None,
+            hole: false,
+        }
+    }
+
+    /// Create a new instance of a menu listing the given refinements of the hole at the cursor,
+    /// none of them selected.
+    pub fn refinements(items: Vec<String>) -> Self {
+        Self {
+            hole: true,
+            ..Self::new(items)
+        }
+    }
+
+    /// Select the completion after the one selected (or else the first), wrapping around.
+    pub fn select_next(&mut self) {
+        let len = self.items.len().max(1);
+        self.sel = Some(self.sel.map_or(0, |sel| (sel + 1) % len));
+    }
+
+    /// Select the completion before the one selected (or else the last), wrapping around.
+    pub fn select_prev(&mut self) {
+        let len = self.items.len().max(1);
+        self.sel = Some(self.sel.map_or(len - 1, |sel| (sel + len - 1) % len));
+    }
+
+    /// Fetch the completion selected, if one has been.
+    pub fn selected(&self) -> Option<&str> {
+        self.items.get(self.sel?).map(String::as_str)
+    }
+
+    /// Fetch the width (in terms of display width) of the lines of this menu.
+    fn width(&self) -> usize {
+        self.items
+            .iter()
+            .map(|item| item.width())
+            .max()
+            .unwrap_or(0)
+            + 2
+    }
+
+    /// Fetch the lines of this menu for display, at most the given number of them, scrolled so
+    /// that the completion selected is among them.
+    fn render(&self, rows: usize, thm: &Theme) -> Vec<String> {
+        let rows = rows.min(MENU_SIZE);
+        let skip = self.sel.map_or(0, |sel| (sel + 1).saturating_sub(rows));
+        let max = self.width() - 2;
+        (self.items.iter().enumerate().skip(skip).take(rows))
+            .map(|(i, item)| {
+                let item = format!(" {}{} ", item, " ".repeat(max - item.width()));
+                match self.sel {
+                    Some(sel) if sel == i => item.invert().to_string(),
+                    _ => item.paint_on(thm.muted).to_string(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Outcome of an [action][Action] taken by the editor as it reads the input.
+#[derive(Debug, PartialEq)]
+enum Flow {
+    /// The input goes on being edited.
+    Edit,
+    /// The input is submitted, to be parsed into the command read.
+    Submit,
+    /// The input is discarded, and the given command is read instead.
+    Read(Cmd),
+}
 
 /// Editor that accepts single-line and multi-line structured user input.
 #[derive(Default)]
-pub struct Editor;
+pub struct Editor {
+    /// History of the lines submitted so far, which may be navigated from an empty buffer.
+    pub hst: History,
+    /// Kill ring that holds the regions killed from the buffer, kept from one command to the next.
+    pub ring: Ring,
+    /// Menu of the completions of the word being typed, which is empty unless it is shown.
+    pub menu: Menu,
+    /// Table of the digraphs that may be typed after the escape character.
+    pub dig: Digraphs,
+    /// Keymaps registered as layers above (or beneath) the built-in keymaps.
+    pub layers: Layers,
+    /// Layout of the screen, which is split between the editor and the side panel.
+    pub lay: Layout,
+    /// Side panel that shows the goals and the context as of the latest command.
+    pub panel: Panel,
+    /// Transcript of the session, which the screen above the buffer is a window onto.
+    pub scr: Scrollback,
+    /// Status bar shown on the bottom row of the screen.
+    pub bar: StatusBar,
+    /// Details of the latest failure, which may be expanded between the transcript and the input.
+    pub details: Details,
+    /// Type of the input (or the first error in it) as of when it was last checked, if any.
+    pub live: Option<String>,
+    /// Directory that the input is checkpointed to as it is typed, if there is one.
+    pub rcv: Option<Recovery>,
+    /// Input that the buffer starts out with the next time input is read (such as that of a
+    /// session that ended unexpectedly), which is empty unless there is some.
+    pub draft: String,
+    /// Input submitted most recently, which the failure of the command it holds is located in,
+    /// or nothing if the latest command was read otherwise (such as by cancelling the input).
+    pub input: String,
+}
 
 impl Editor {
-    /// Create a new instance of editor.
+    /// Create a new instance of editor, with an empty history.
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Create a new instance of editor with the given history.
+    pub fn with_history(hst: History) -> Self {
+        Self {
+            hst,
+            ring: Ring::new(),
+            menu: Menu::default(),
+            dig: Digraphs::new(),
+            layers: Layers::new(),
+            lay: Layout::new(),
+            panel: Panel::default(),
+            scr: Scrollback::new(),
+            bar: StatusBar::default(),
+            details: Details::default(),
+            live: None,
+            rcv: None,
+            draft: String::new(),
+            input: String::new(),
+        }
     }
 
-    /// Read input into a [command][Cmd] and return it.
-    pub fn read(&self) -> Result<Cmd> {
+    /// Set the table of the digraphs that may be typed after the escape character.
+    pub fn with_digraphs(self, dig: Digraphs) -> Self {
+        Self { dig, ..self }
+    }
+
+    /// Register the given keymap as a [layer][Layers] of this editor with the given priority, so
+    /// that it takes precedence over the built-in keymaps if the priority is positive, and
+    /// otherwise binds only the keys that they leave unbound.
+    pub fn with_keymap(mut self, prio: i32, map: Box<dyn KeyMap>) -> Self {
+        self.layers.register(prio, map);
+        self
+    }
+
+    /// Read input into a [command][Cmd] and return it, parsing expressions using the notation
+    /// declared in the given context, and rendering the input with the given settings.
+    ///
+    /// Each key pressed is looked up in the [keymaps][KeyMap] of the
+    /// [set of key bindings][crate::key::KeySet] chosen in the settings, stacked along with the
+    /// [layers][Layers] registered with this editor, the first of which to bind it determines the
+    /// [action][Action] taken, while keys that are not bound at all are ignored.
+    /// A key that begins a [chord][key::Chord] (such as Ctrl+X followed by U) is held back until
+    /// the rest of the chord is pressed, or until a key that does not continue it is.
+    ///
+    /// A backslash begins a [digraph][Digraphs] (such as `\->`), which is replaced with the glyph
+    /// that it stands for (such as `→`) once it is typed.
+    ///
+    /// Ctrl+Z undoes the latest group of edits to the buffer, and Ctrl+Y redoes it. Ctrl+K kills
+    /// (cuts) the rest of the line and Ctrl+U the start of the line, into the [kill ring][Ring],
+    /// while Ctrl+V yanks (pastes) the latest region killed, which Alt+V then replaces with the one
+    /// killed before it.
+    ///
+    /// Up and Down navigate the [history][History] while the buffer is empty (or holds the entry
+    /// navigated to, unless the cursor may move up or down within it), and the input submitted is
+    /// recorded in it, so that it may be recalled and edited. Left and Right move the cursor one
+    /// grapheme at a time, never splitting one.
+    ///
+    /// Ctrl+C discards the input, and Ctrl+D exits if the input is empty (or else deletes the
+    /// grapheme at the cursor).
+    ///
+    /// As a word is typed (or when Tab is pressed), a [menu][Menu] of its completions is shown
+    /// under the cursor, in which Up and Down (or Tab and Shift+Tab) select a completion, Enter
+    /// accepts the one selected and Esc closes the menu.
+    ///
+    /// The [side panel][Panel] shows the goals and the context as they stand before the input is
+    /// read, and Alt+Left widens it while Alt+Right narrows it (or hides it, once it is narrow).
+    /// PageUp and PageDown scroll the rows above the input through the [transcript][Scrollback]
+    /// of the session, which returns to the latest output as soon as the input is edited. Alt+E
+    /// expands the [details][Details] of the latest failure between the transcript and the input
+    /// (and collapses them), until the input is submitted. The
+    /// bottom row holds the [status bar][StatusBar], whose warning (if any) is cleared once the
+    /// input is submitted. When the terminal is resized, the whole screen is redrawn to fit it.
+    ///
+    /// Whenever typing pauses, the input is [checked][Buf::check] with a small budget of fuel, and
+    /// the type of its expression (or the first error in it) is shown dimly under it. The input
+    /// is checkpointed then as well, so that it may be [recovered][Recovery] if the session ends
+    /// unexpectedly before it is submitted. Alt+T shows
+    /// the type of the sub-expression under the cursor in its place, until the input is edited.
+    ///
+    /// Alt+N and Alt+P move the cursor to the next and previous hole in the input, and while it is
+    /// on one, the side panel shows the [goal][crate::buf::Goal] of the hole: the type expected of
+    /// the term to fill it, and the variables bound around it. Alt+R then lists the terms that may
+    /// fill it in the menu, where Enter splices the one selected in place of the hole.
+    pub fn read(&mut self, ctx: &Ctx, set: &Settings) -> Result<Cmd> {
         let mut stdout = stdout();
-        execute!(stdout, cursor::SavePosition)?;
+        let (_, mut top) = cursor::position()?;
 
-        let mut buf = Buf::new();
+        let mut buf = Buf::with_digraphs(self.dig.clone());
+        if !self.draft.is_empty() {
+            buf.replace(&std::mem::take(&mut self.draft));
+        }
+        self.menu = Menu::default();
+        self.panel = Panel::new(ctx);
+        self.input.clear();
+        let builtin = set.key.keymaps();
+        let mut keys = Vec::new(); // keys pressed so far of the chord under way
+        let mut res = None; // command read other than by parsing the input
+        let mut stale = !buf.raw.is_empty(); // whether the input is yet to be checked as it stands
+        self.live = None;
 
         loop {
-            self.show(&buf)?;
-            if let Event::Key(evt) = event::read()? {
-                match evt.code {
-                    KeyCode::Char(chr) => {
-                        buf.push(chr);
-                        self.show(&buf)?;
+            self.show(&mut stdout, terminal::size()?, &mut buf, &mut top, ctx, set)?;
+            if stale && !event::poll(CHECK_DELAY)? {
+                self.live = buf.check(ctx, set, CHECK_FUEL);
+                Self::checkpoint(&self.rcv, &mut self.bar, &buf.raw);
+                stale = false;
+                continue;
+            }
+            let evt = match event::read()? {
+                Event::Key(evt) => evt,
+                Event::Resize(_, rows) => {
+                    Self::resize(&mut stdout, rows, &mut top)?;
+                    continue;
+                }
+                _ => continue,
+            };
+            keys.push(Key::from(evt));
+            let act = match key::lookup(&self.layers.stack(&builtin), &keys) {
+                Lookup::Bound(act) => act,
+                Lookup::Prefix => continue, // wait for the rest of the chord
+                Lookup::Unbound => {
+                    keys.clear(); // the keys are not bound to anything
+                    continue;
+                }
+            };
+            keys.clear();
+            let (raw, idx) = (buf.raw.clone(), buf.idx);
+            match self.act(act, &mut buf, top, ctx, set) {
+                Flow::Edit => {}
+                Flow::Submit => break, // Exit the loop and return the finalized command.
+                Flow::Read(cmd) => {
+                    res = Some(cmd);
+                    break;
+                }
+            }
+            // the type shown by inspecting the input stays until the input is next edited
+            stale = (stale && act != Action::Inspect) || buf.raw != raw;
+            if buf.raw != raw || buf.idx != idx {
+                self.panel.goal = buf.goal(ctx, set, CHECK_FUEL);
+            }
+        }
+
+        // the input is submitted (or discarded), so there is none left to recover
+        Self::checkpoint(&self.rcv, &mut self.bar, "");
+
+        // the input is kept in the transcript as it is finally shown, without the menu or details
+        self.scr.back = 0;
+        self.menu = Menu::default();
+        self.details.open = false;
+        self.live = None;
+        let input = self.show(&mut stdout, terminal::size()?, &mut buf, &mut top, ctx, set)?;
+        self.scr.push(&input);
+
+        // the output that follows begins after the last line shown, wherever the cursor is
+        let (_, height) = terminal::size()?;
+        let last = top + buf.shown(usize::from(height.saturating_sub(1)).max(1)) as u16 - 1;
+        queue!(stdout, cursor::MoveTo(0, last))?;
+        write!(stdout, "\r\n")?;
+        queue!(stdout, terminal::Clear(terminal::ClearType::FromCursorDown))?;
+
+        Ok(res.unwrap_or_else(|| buf.value(ctx, set)))
+    }
+
+    /// Checkpoint the given input to the given [recovery][Recovery] directory (if there is one),
+    /// so that it is not lost if the session ends unexpectedly, with a warning in the given status
+    /// bar if it cannot be written.
+    fn checkpoint(rcv: &Option<Recovery>, bar: &mut StatusBar, input: &str) {
+        if let Some(Err(e)) = rcv.as_ref().map(|rcv| rcv.save_input(input)) {
+            bar.warn = Some(format!("input could not be saved: {}", e));
+        }
+    }
+
+    /// Take the given action on the given buffer, whose first line is shown on the given row,
+    /// and return whether the input goes on being edited, is submitted, or is discarded for
+    /// another command.
+    fn act(&mut self, act: Action, buf: &mut Buf, top: u16, ctx: &Ctx, set: &Settings) -> Flow {
+        let menu = !self.menu.items.is_empty();
+        if !matches!(act, Action::PageUp | Action::PageDown) {
+            self.scr.back = 0; // any other action returns to the latest output
+        }
+        if !matches!(
+            act,
+            Action::Up | Action::Down | Action::Complete | Action::CompletePrev | Action::Submit
+        ) {
+            self.menu = Menu::default(); // any other action closes the menu
+        }
+        match act {
+            Action::Down | Action::Complete if menu => self.menu.select_next(),
+            Action::Up | Action::CompletePrev if menu => self.menu.select_prev(),
+            Action::Submit if self.menu.selected().is_some() => {
+                match self.menu.selected() {
+                    Some(term) if self.menu.hole => {
+                        buf.refine(term);
                     }
-                    KeyCode::Enter => {
-                        break; // Exit the loop and return the finalized command.
+                    Some(word) => buf.complete(word),
+                    None => {}
+                }
+                self.menu = Menu::default();
+            }
+            Action::Submit => {
+                // the session goes on even if the history cannot be written
+                self.bar.warn = (self.hst.push(&buf.raw).err())
+                    .map(|e| format!("history could not be written: {}", e));
+                self.input = buf.raw.clone();
+                return Flow::Submit;
+            }
+            Action::Cancel => {
+                self.bar.warn = None;
+                return Flow::Read(Cmd::Noop);
+            }
+            Action::Eof if buf.raw.is_empty() => return Flow::Read(Cmd::Exit),
+            Action::Eof => {
+                buf.delete_forward();
+            }
+            Action::Insert(chr) => {
+                buf.push(chr);
+                self.menu = Menu::new(buf.completions(ctx));
+            }
+            Action::Complete => self.menu = Menu::new(buf.completions(ctx)),
+            Action::CompletePrev | Action::Dismiss => {}
+            Action::Inspect => {
+                self.live = buf.inspect(ctx, set, CHECK_FUEL);
+            }
+            Action::NextHole => {
+                buf.next_hole();
+            }
+            Action::PrevHole => {
+                buf.prev_hole();
+            }
+            Action::Refine => {
+                if let Some(goal) = &self.panel.goal {
+                    self.menu = Menu::refinements(goal.fits.clone());
+                }
+            }
+            Action::Details => {
+                if !self.details.toggle() {
+                    self.bar.warn = Some("there are no further details to show".to_string());
+                }
+            }
+            Action::GrowPanel => self.lay.grow(),
+            Action::ShrinkPanel => self.lay.shrink(),
+            Action::PageUp => {
+                self.scr.page_up(usize::from(top));
+            }
+            Action::PageDown => {
+                self.scr.page_down(usize::from(top));
+            }
+            Action::KillToEnd => {
+                if let Some(text) = buf.kill_to_end() {
+                    self.ring.push(text);
+                }
+            }
+            Action::KillToStart => {
+                if let Some(text) = buf.kill_to_start() {
+                    self.ring.push(text);
+                }
+            }
+            Action::Yank => {
+                if let Some(text) = self.ring.current() {
+                    buf.yank(text);
+                }
+            }
+            Action::YankPop => {
+                if buf.yanked.is_some() {
+                    if let Some(text) = self.ring.rotate() {
+                        buf.yank_pop(text);
                     }
-                    KeyCode::Esc => todo!(),
-                    KeyCode::Backspace => todo!(),
-                    KeyCode::Delete => todo!(),
-                    KeyCode::Left => todo!(),
-                    KeyCode::Right => todo!(),
-                    KeyCode::Up => todo!(),
-                    KeyCode::Down => todo!(),
-                    KeyCode::Home => todo!(),
-                    KeyCode::End => todo!(),
-                    KeyCode::PageUp => todo!(),
-                    KeyCode::PageDown => todo!(),
-                    KeyCode::Tab => todo!(),
-                    KeyCode::BackTab => todo!(),
-
-                    // Ignore all remaining keys
-                    KeyCode::Insert => continue,
-                    KeyCode::F(_) => continue,
-                    KeyCode::Null => continue,
-                    KeyCode::CapsLock => continue,
-                    KeyCode::ScrollLock => continue,
-                    KeyCode::NumLock => continue,
-                    KeyCode::PrintScreen => continue,
-                    KeyCode::Pause => continue,
-                    KeyCode::Menu => continue,
-                    KeyCode::KeypadBegin => continue,
-                    KeyCode::Media(_) => continue,
-                    KeyCode::Modifier(_) => continue,
                 }
             }
+            Action::Undo => {
+                buf.undo();
+            }
+            Action::Redo => {
+                buf.redo();
+            }
+            Action::Left => {
+                buf.move_left();
+            }
+            Action::Right => {
+                buf.move_right();
+            }
+            Action::WordLeft => {
+                buf.move_word_left();
+            }
+            Action::WordRight => {
+                buf.move_word_right();
+            }
+            Action::Home => {
+                buf.move_home();
+            }
+            Action::End => {
+                buf.move_end();
+            }
+            Action::Backspace => {
+                buf.delete_back();
+            }
+            Action::Delete => {
+                buf.delete_forward();
+            }
+            Action::Transpose => {
+                buf.transpose();
+            }
+            Action::Up if self.is_navigable(buf) && buf.cursor().0 == 0 => {
+                if let Some(line) = self.hst.back() {
+                    buf.replace(line);
+                }
+            }
+            Action::Down if self.is_navigable(buf) && !buf.raw[buf.idx..].contains('\n') => {
+                if let Some(line) = self.hst.forward() {
+                    buf.replace(line);
+                }
+            }
+            Action::Up => {
+                buf.move_up();
+            }
+            Action::Down => {
+                buf.move_down();
+            }
         }
+        Flow::Edit
+    }
 
-        // This is a parser stub. In a real implementation, the buffer's raw string should never
-        // be accessed directly, and the buffer needs to be smarter in knowing how to render parsed
-        // input.
+    /// Keep the input, which starts at the given row, in view of the given terminal once it has
+    /// been resized to the given number of rows (of which the bottom one holds the status bar), and
+    /// clear the screen, so that the whole of it is drawn anew at the new size when next shown.
+    fn resize(out: &mut impl Write, rows: u16, top: &mut u16) -> Result<()> {
+        *top = (*top).min(rows.saturating_sub(2));
+        queue!(out, terminal::Clear(terminal::ClearType::All))
+    }
 
-        write!(stdout, "\r\n")?;
+    /// Check whether the history may be navigated from the given buffer, namely if it is empty or
+    /// holds (unchanged) the entry navigated to.
+    fn is_navigable(&self, buf: &Buf) -> bool {
+        match self.hst.current() {
+            Some(line) => buf.raw == line,
+            None => buf.raw.is_empty(),
+        }
+    }
+
+    /// Show the editor's updated buffer on the given terminal of the given size (in columns and
+    /// rows), starting at the given row, which is moved up if the terminal has to scroll to make
+    /// room for the lines shown, and return the lines of the buffer as rendered.
+    ///
+    /// The rows above the buffer show a window onto the [transcript][Scrollback] of the session,
+    /// which ends with the latest output unless it has been scrolled back with PageUp.
+    ///
+    /// Lines wider than the terminal are not wrapped, but scrolled horizontally instead, and only
+    /// as many lines as fit in the terminal are shown, scrolling vertically within them, so that
+    /// the cursor always stays in view.
+    ///
+    /// Each line is preceded by the gutter of the given settings, in which a line that holds a
+    /// syntax error is marked.
+    ///
+    /// The buffer is confined to the main pane of the [layout][Layout], and the side panel is drawn
+    /// over every row of the screen to the right of it, which is why the panel is redrawn whenever
+    /// the buffer is, even though its content does not change as the input is edited.
+    fn show(
+        &self,
+        out: &mut impl Write,
+        (full, height): (u16, u16),
+        buf: &mut Buf,
+        top: &mut u16,
+        ctx: &Ctx,
+        set: &Settings,
+    ) -> Result<String> {
+        let height = height.saturating_sub(1).max(1); // the bottom row holds the status bar
+        let (width, side) = self.lay.split(full);
+        let (gut, ascii) = (set.gut, set.asc);
+        let cols = usize::from(width)
+            .saturating_sub(buf.gutter(gut, ascii) + 1)
+            .max(1);
+        let live = usize::from(self.live.is_some()); // the line under the input that shows its type
+        let rows = usize::from(height).saturating_sub(live).max(1);
+        buf.scroll(cols, rows);
+        let (row, col) = buf.cursor();
+        let (row, col) = (row - buf.top, buf.gutter(gut, ascii) + col - buf.off);
+        let thm = set.thm;
+        let menu = self.menu.render(rows.saturating_sub(row + 1), &thm);
+        let shown = (buf.shown(rows) + live).max(row + 1 + menu.len()) as u16;
+        if *top + shown > height {
+            let up = *top + shown - height;
+            queue!(out, terminal::ScrollUp(up))?;
+            *top -= up;
+        }
+        // the details (if expanded) take up the rows above the input, which is moved down to make
+        // room for them if need be
+        let det = self
+            .details
+            .height(usize::from(height.saturating_sub(shown)));
+        *top = (*top).max(det as u16);
+        queue!(
+            out,
+            cursor::MoveTo(0, *top),
+            terminal::Clear(terminal::ClearType::FromCursorDown)
+        )?;
+        let above = usize::from(*top) - det;
+        let details = self.details.render(usize::from(width), det, &thm);
+        let window = self.scr.window(above);
+        for (row, line) in window
+            .into_iter()
+            .chain(details.iter().map(String::as_str))
+            .enumerate()
+        {
+            queue!(
+                out,
+                cursor::MoveTo(0, row as u16),
+                terminal::Clear(terminal::ClearType::CurrentLine)
+            )?;
+            write!(out, "{}", line)?;
+        }
+        queue!(out, cursor::MoveTo(0, *top))?;
+        let err = buf.error_line(ctx, set);
+        let input = buf.render(cols, rows, gut, err, &thm, ascii);
+        write!(out, "{}", input)?;
+        if let Some(live) = &self.live {
+            let below = *top + buf.shown(rows) as u16;
+            queue!(out, cursor::MoveTo(0, below))?;
+            write!(out, "{}", format!("  {}", live).paint(thm.muted))?;
+        }
+        let row = *top as usize + row;
+        let left = col.min(usize::from(width).saturating_sub(self.menu.width()));
+        for (i, line) in menu.iter().enumerate() {
+            queue!(out, cursor::MoveTo(left as u16, (row + 1 + i) as u16))?;
+            write!(out, "{}", line)?;
+        }
+        if side > 0 {
+            let lines = self
+                .panel
+                .render(usize::from(side) - 2, usize::from(height), &thm);
+            for row in 0..height {
+                queue!(
+                    out,
+                    cursor::MoveTo(width, row),
+                    terminal::Clear(terminal::ClearType::UntilNewLine)
+                )?;
+                let line = lines.get(usize::from(row)).map_or("", String::as_str);
+                write!(out, "{} {}", "│".paint(thm.muted), line)?;
+            }
+        }
+        queue!(
+            out,
+            cursor::MoveTo(0, height),
+            terminal::Clear(terminal::ClearType::CurrentLine)
+        )?;
+        write!(out, "{}", self.bar.render(usize::from(full), buf.esc, &thm))?;
+        queue!(out, cursor::MoveTo(col as u16, row as u16))?;
+        out.flush()?;
+        Ok(input)
+    }
+}
 
-        let res = buf.raw.trim_end();
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyModifiers};
 
-        if res.is_empty() {
-            Ok(Cmd::Noop)
-        } else if res == "quit" || res == "exit" {
-            Ok(Cmd::Exit)
-        } else if res == "help" {
-            Ok(Cmd::Help(None))
-        } else {
-            Ok(Cmd::Fail(Default::default()))
+    /// Press the given keys in turn in the given editor, as it reads input into the given buffer
+    /// with the default settings, and return the outcome of the action bound to the last of them.
+    fn press(edt: &mut Editor, buf: &mut Buf, keys: &[Key]) -> Flow {
+        let set = Settings::default();
+        let builtin = set.key.keymaps();
+        let mut flow = Flow::Edit;
+        for key in keys {
+            if let Lookup::Bound(act) = key::lookup(&edt.layers.stack(&builtin), &[*key]) {
+                flow = edt.act(act, buf, 0, &Ctx::new(), &set);
+            }
         }
+        flow
     }
 
-    /// Show the editor's updated buffer on the screen.
-    fn show(&self, buf: &Buf) -> Result<()> {
-        let mut stdout = stdout();
-        queue!(stdout, cursor::RestorePosition)?;
-        write!(stdout, "{}", buf.render())?;
-        stdout.flush()
+    /// Fetch the keys that type the given text.
+    fn typed(text: &str) -> Vec<Key> {
+        text.chars()
+            .map(|chr| Key::plain(KeyCode::Char(chr)))
+            .collect()
+    }
+
+    #[test]
+    fn test_edt_0001() {
+        let (up, down) = (Key::plain(KeyCode::Up), Key::plain(KeyCode::Down));
+        let enter = Key::plain(KeyCode::Enter);
+        let mut edt = Editor::new();
+        let mut buf = Buf::with_digraphs(Digraphs::new());
+        press(&mut edt, &mut buf, &typed("show 1n"));
+        assert_eq!(press(&mut edt, &mut buf, &[enter]), Flow::Submit);
+        assert_eq!(edt.input, "show 1n");
+        // Up on an empty buffer recalls the input submitted, as it was typed, to be edited
+        let mut buf = Buf::with_digraphs(Digraphs::new());
+        press(&mut edt, &mut buf, &[up, Key::plain(KeyCode::Backspace)]);
+        assert_eq!(buf.raw, "show 1");
+        press(&mut edt, &mut buf, &[Key::plain(KeyCode::Esc)]);
+        buf.replace("type λx : ℕ .\n  x");
+        assert_eq!(press(&mut edt, &mut buf, &[enter]), Flow::Submit);
+        // input of several lines is recalled whole, and Up and Down move within it before leaving it
+        let mut buf = Buf::with_digraphs(Digraphs::new());
+        press(&mut edt, &mut buf, &[up]);
+        assert_eq!(
+            (buf.raw.as_str(), buf.cursor().0),
+            ("type λx : ℕ .\n  x", 1)
+        );
+        press(&mut edt, &mut buf, &[up]);
+        assert_eq!(
+            (buf.raw.as_str(), buf.cursor().0),
+            ("type λx : ℕ .\n  x", 0)
+        );
+        press(&mut edt, &mut buf, &[up]);
+        assert_eq!(buf.raw, "show 1n");
+        press(&mut edt, &mut buf, &[down]);
+        assert_eq!(
+            (buf.raw.as_str(), buf.cursor().0),
+            ("type λx : ℕ .\n  x", 1)
+        );
+        press(&mut edt, &mut buf, &[down]);
+        assert_eq!(buf.raw, "");
+        assert_eq!(press(&mut edt, &mut buf, &[down]), Flow::Edit);
+        assert_eq!(buf.raw, "");
+    }
+
+    #[test]
+    fn test_edt_0002() {
+        let mut edt = Editor::new();
+        let mut buf = Buf::with_digraphs(Digraphs::new());
+        // characters typed with Ctrl or Alt are never inserted, while those typed with Shift are
+        let shift = Key::new(KeyCode::Char('A'), KeyModifiers::SHIFT);
+        press(&mut edt, &mut buf, &[shift, Key::ctrl('q'), Key::alt('q')]);
+        assert_eq!(buf.raw, "A");
+        // Ctrl+C discards the input, whatever it holds
+        let flow = press(&mut edt, &mut buf, &[Key::ctrl('c')]);
+        assert_eq!(flow, Flow::Read(Cmd::Noop));
+        // Ctrl+D deletes forward, unless there is no input, which it exits on
+        press(
+            &mut edt,
+            &mut buf,
+            &[Key::plain(KeyCode::Home), Key::ctrl('d')],
+        );
+        assert_eq!(buf.raw, "");
+        assert_eq!(
+            press(&mut edt, &mut buf, &[Key::ctrl('d')]),
+            Flow::Read(Cmd::Exit)
+        );
+        assert!(edt.hst.back().is_none());
+    }
+
+    #[test]
+    fn test_edt_0003() -> Result<()> {
+        // rows that the cursor is moved to (from one) as the output is drawn
+        let rows = |out: &[u8]| -> Vec<u16> {
+            let out = String::from_utf8_lossy(out);
+            (out.split("\x1b[").skip(1))
+                .filter_map(|esc| esc.split_once('H'))
+                .filter_map(|(pos, _)| pos.split_once(';')?.0.parse().ok())
+                .collect()
+        };
+        let (ctx, set) = (Ctx::new(), Settings::default());
+        let edt = Editor::new();
+        let mut buf = Buf::with_digraphs(Digraphs::new());
+        buf.replace("show 1n");
+        let (mut out, mut top) = (vec![], 30);
+        edt.show(&mut out, (80, 40), &mut buf, &mut top, &ctx, &set)?;
+        assert_eq!(rows(&out).into_iter().max(), Some(40));
+        // once the terminal shrinks, the screen is cleared, and redrawn within it with the input
+        // kept in view above the status bar, which moves to the new bottom row
+        let mut out = vec![];
+        Editor::resize(&mut out, 20, &mut top)?;
+        assert_eq!(top, 18);
+        assert!(String::from_utf8_lossy(&out).contains("\x1b[2J"));
+        let input = edt.show(&mut out, (80, 20), &mut buf, &mut top, &ctx, &set)?;
+        assert!(input.contains("show") && input.contains("1n"));
+        let rows = rows(&out);
+        assert!(rows.contains(&19) && rows.contains(&20));
+        assert_eq!(rows.into_iter().max(), Some(20));
+        Ok(())
     }
 }
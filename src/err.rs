@@ -1,8 +1,10 @@
 //! Utilities related to error traces and diagnostics.
 
-use crate::ast::{Exp, Var};
+use crate::ast::{positivity, Diff, Exp, MetaId, Polarity, Var};
+use crate::par;
 use std::error::Error;
 use std::fmt::{Display, Formatter, Result};
+use std::time::Duration;
 
 /// Top-level error that represents a failure to type-check the program.
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -12,6 +14,21 @@ pub enum TypingErr {
     TypeUndefErr(TypeUndefErr),
     TypeUnknownErr(TypeUnknownErr),
     TypeRedeclErr(TypeRedeclErr),
+    TypeHoleErr(TypeHoleErr),
+    TypeSortErr(TypeSortErr),
+    TypePosErr(TypePosErr),
+    TypeDupDataErr(TypeDupDataErr),
+    TypeUnifyErr(TypeUnifyErr),
+    TypeFieldErr(TypeFieldErr),
+    TypeDupFieldErr(TypeDupFieldErr),
+    TypeOccursErr(TypeOccursErr),
+    ReductionBudgetErr(ReductionBudgetErr),
+    ReductionTimeoutErr(ReductionTimeoutErr),
+    ReductionSizeErr(ReductionSizeErr),
+    ReductionOverflowErr(ReductionOverflowErr),
+    InterruptErr(InterruptErr),
+    SyntaxErr(SyntaxErr),
+    TrailErr(TrailErr),
 }
 
 impl From<TypeCompatErr> for TypingErr {
@@ -38,6 +55,96 @@ impl From<TypeRedeclErr> for TypingErr {
     }
 }
 
+impl From<TypeHoleErr> for TypingErr {
+    fn from(e: TypeHoleErr) -> Self {
+        TypingErr::TypeHoleErr(e)
+    }
+}
+
+impl From<TypeSortErr> for TypingErr {
+    fn from(e: TypeSortErr) -> Self {
+        TypingErr::TypeSortErr(e)
+    }
+}
+
+impl From<TypePosErr> for TypingErr {
+    fn from(e: TypePosErr) -> Self {
+        TypingErr::TypePosErr(e)
+    }
+}
+
+impl From<TypeDupDataErr> for TypingErr {
+    fn from(e: TypeDupDataErr) -> Self {
+        TypingErr::TypeDupDataErr(e)
+    }
+}
+
+impl From<TypeUnifyErr> for TypingErr {
+    fn from(e: TypeUnifyErr) -> Self {
+        TypingErr::TypeUnifyErr(e)
+    }
+}
+
+impl From<TypeFieldErr> for TypingErr {
+    fn from(e: TypeFieldErr) -> Self {
+        TypingErr::TypeFieldErr(e)
+    }
+}
+
+impl From<TypeDupFieldErr> for TypingErr {
+    fn from(e: TypeDupFieldErr) -> Self {
+        TypingErr::TypeDupFieldErr(e)
+    }
+}
+
+impl From<TypeOccursErr> for TypingErr {
+    fn from(e: TypeOccursErr) -> Self {
+        TypingErr::TypeOccursErr(e)
+    }
+}
+
+impl From<ReductionBudgetErr> for TypingErr {
+    fn from(e: ReductionBudgetErr) -> Self {
+        TypingErr::ReductionBudgetErr(e)
+    }
+}
+
+impl From<ReductionTimeoutErr> for TypingErr {
+    fn from(e: ReductionTimeoutErr) -> Self {
+        TypingErr::ReductionTimeoutErr(e)
+    }
+}
+
+impl From<ReductionSizeErr> for TypingErr {
+    fn from(e: ReductionSizeErr) -> Self {
+        TypingErr::ReductionSizeErr(e)
+    }
+}
+
+impl From<ReductionOverflowErr> for TypingErr {
+    fn from(e: ReductionOverflowErr) -> Self {
+        TypingErr::ReductionOverflowErr(e)
+    }
+}
+
+impl From<InterruptErr> for TypingErr {
+    fn from(e: InterruptErr) -> Self {
+        TypingErr::InterruptErr(e)
+    }
+}
+
+impl From<SyntaxErr> for TypingErr {
+    fn from(e: SyntaxErr) -> Self {
+        TypingErr::SyntaxErr(e)
+    }
+}
+
+impl From<TrailErr> for TypingErr {
+    fn from(e: TrailErr) -> Self {
+        TypingErr::TrailErr(e)
+    }
+}
+
 /// Error that indicates that a expression has an unexpected type.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TypeCompatErr {
@@ -63,6 +170,9 @@ pub struct TypeUndefErr {
 pub struct TypeUnknownErr {
     /// Variable whose type is not known.
     pub var: Var,
+    /// Difference between the context in scope and the context expected (with respect to the
+    /// variable), such as one in which the variable is only bound later.
+    pub dif: Box<Diff>,
 }
 
 /// Error that indicates that a variable has a different previously declared or inferred type.
@@ -74,6 +184,218 @@ pub struct TypeRedeclErr {
     pub typ: Exp,
     /// Newly declared type of the variable.
     pub upd: Exp,
+    /// Difference between the context in scope and the context expected (with respect to the
+    /// variable).
+    pub dif: Box<Diff>,
+}
+
+/// Error that indicates that an expression still contains a hole, which needs to be filled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeHoleErr {
+    /// Expected type(s) of the expression that fills the hole (if known).
+    pub acc: Vec<Exp>,
+}
+
+/// Error that indicates that an expression used as a type is not a type, since its own type is
+/// not a universe (`Type n` for some level `n`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeSortErr {
+    /// Expression that is not a type.
+    pub exp: Exp,
+    /// Actual calculated type of the expression.
+    pub typ: Exp,
+}
+
+/// Error that indicates that an inductive data type occurs in the argument of one of its own
+/// constructors in a position that is not strictly positive (such as the domain of a function).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypePosErr {
+    /// Variable that denotes the data type.
+    pub var: Var,
+    /// Constructor whose argument is not strictly positive.
+    pub cns: Var,
+    /// Type of the offending argument.
+    pub typ: Exp,
+}
+
+/// Error that indicates that an inductive data type declares a name (of the type, a constructor
+/// or the eliminator) that is already bound, or that it declares more than once itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeDupDataErr {
+    /// Variable that denotes the data type.
+    pub var: Var,
+    /// Name that is declared more than once.
+    pub dup: Var,
+}
+
+/// Error that indicates that two expressions cannot be made equal by solving the metavariables
+/// that occur in them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeUnifyErr {
+    /// First expression to be unified.
+    pub fst: Exp,
+    /// Second expression to be unified.
+    pub snd: Exp,
+}
+
+/// Error that indicates that a field is projected out of a record whose type has no such field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeFieldErr {
+    /// Record that the field is projected out of.
+    pub exp: Exp,
+    /// Actual calculated type of the record.
+    pub typ: Exp,
+    /// Label of the missing field.
+    pub fld: Var,
+}
+
+/// Error that indicates that a record (or record type) has more than one field with the same label.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeDupFieldErr {
+    /// Record (or record type) whose fields are labelled.
+    pub exp: Exp,
+    /// Label that is declared more than once.
+    pub fld: Var,
+    /// Value (or type) of the field that repeats the label.
+    pub val: Exp,
+}
+
+/// Error that indicates that a metavariable cannot be solved with an expression in which it occurs
+/// itself, since the solution would be cyclic (infinite).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeOccursErr {
+    /// Metavariable to be solved.
+    pub id: MetaId,
+    /// Expression in which the metavariable occurs.
+    pub exp: Exp,
+}
+
+/// Error that indicates that an expression could not be reduced to normal form within the budget
+/// of reduction steps, as happens for expressions that do not have one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReductionBudgetErr {
+    /// Expression being reduced.
+    pub exp: Exp,
+    /// Number of reduction steps performed before giving up.
+    pub fuel: usize,
+}
+
+/// Error that indicates that the reduction of an expression ran out of the time permitted for the
+/// command that it belongs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReductionTimeoutErr {
+    /// Expression being reduced when the time ran out.
+    pub exp: Exp,
+    /// Time permitted for the command.
+    pub time: Duration,
+}
+
+/// Error that indicates that an expression grew beyond the largest [size][Exp::size] permitted
+/// while it was reduced, as happens for expressions whose reduction diverges by growing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReductionSizeErr {
+    /// Expression being reduced.
+    pub exp: Exp,
+    /// Size that the expression (or the part of it being reduced) grew to.
+    pub size: usize,
+    /// Largest size permitted.
+    pub max: usize,
+}
+
+/// Error that indicates that arithmetic on [natural numbers][Exp::Nat] overflowed the machine
+/// integers that they are represented by during reduction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReductionOverflowErr {
+    /// Arithmetic operation on literals whose result overflows.
+    pub exp: Exp,
+}
+
+/// Error that indicates that the reduction of an expression was interrupted (by the user) before
+/// it finished.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterruptErr {
+    /// Expression being reduced when the reduction was interrupted.
+    pub exp: Exp,
+}
+
+/// Prefix of the lines of a message that describe the steps in the trail of a [TrailErr].
+const WHILE: &str = "↯ while ";
+
+/// Error that arose within one of the steps of type-checking (or reducing) a larger expression,
+/// along with the trail of steps that it arose within, so that it may be found in a large term.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrailErr {
+    /// Error that arose, which is never a trail itself.
+    pub err: Box<TypingErr>,
+    /// Steps that the error arose within, innermost first.
+    pub trail: Vec<Crumb>,
+}
+
+/// Step of type-checking (or reducing) an expression, within which an error arose.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Crumb {
+    /// Description of the step, such as `checking the argument`.
+    pub step: &'static str,
+    /// Expression that the step is part of, such as an application.
+    pub exp: Exp,
+}
+
+/// Diagnostic reported about a command in source text (such as a file), when it fails or is found
+/// to be wrong by [checking][crate::cmd::Cmd::check_source] it, at the (1-based) line and column
+/// that it concerns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Diagnostic {
+    /// Severity of the diagnostic.
+    pub severity: Severity,
+    /// Line that the diagnostic concerns.
+    pub line: usize,
+    /// Column (in characters) that the diagnostic concerns within its line.
+    pub col: usize,
+    /// Number of characters (from the column on, and at least one) that the diagnostic concerns
+    /// within its line.
+    pub width: usize,
+    /// Message of the diagnostic, which may span several lines.
+    pub msg: String,
+}
+
+/// Severity of a [diagnostic][Diagnostic], which tells whether the command it concerns fails, or
+/// merely [looks like a mistake][Lint].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Severity {
+    /// The command fails.
+    #[default]
+    Error,
+    /// The command succeeds, but likely does not do what was meant.
+    Warning,
+}
+
+/// Warning about a command that succeeds, but likely does not do what was meant, which is reported
+/// (unless [turned off][crate::cmd::Setting::Warnings]) with the [severity][Severity] of a
+/// warning.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Lint {
+    /// Variable bound by the associated λ abstraction or Π type, which does not occur in its body.
+    Unused(Var, Exp),
+    /// Variable bound by the associated λ abstraction or Π type, which shadows a variable of the
+    /// same name already in scope (whether bound by an enclosing binder or in the context).
+    Shadowed(Var, Exp),
+    /// Variable in an opened namespace that can no longer be referred to by the associated name
+    /// (within the namespace), since the name now refers to another variable.
+    Unreachable(Var, Var),
+}
+
+/// Error that indicates that the source text does not conform to the grammar of the language.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxErr {
+    /// Byte offset of the start of the offending text (inclusive).
+    pub beg: usize,
+    /// Byte offset of the end of the offending text (exclusive).
+    pub end: usize,
+    /// Message explaining the syntax error.
+    pub msg: String,
 }
 
 impl Error for TypeCompatErr {}
@@ -129,14 +451,25 @@ impl Error for TypeUnknownErr {}
 
 impl TypeUnknownErr {
     pub fn new(var: &Var) -> Self {
-        TypeUnknownErr { var: var.clone() }
+        TypeUnknownErr {
+            var: var.clone(),
+            dif: Box::default(),
+        }
+    }
+
+    /// Attach the difference between the context in scope and the context expected.
+    pub fn with_diff(self, dif: Diff) -> Self {
+        TypeUnknownErr {
+            dif: Box::new(dif),
+            ..self
+        }
     }
 }
 
 impl Display for TypeUnknownErr {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         writeln!(f, ":type {} = ?", self.var)?;
-        Ok(())
+        write_diff(f, &self.dif)
     }
 }
 
@@ -148,6 +481,15 @@ impl TypeRedeclErr {
             var: var.clone(),
             typ: typ.clone(),
             upd: upd.clone(),
+            dif: Box::default(),
+        }
+    }
+
+    /// Attach the difference between the context in scope and the context expected.
+    pub fn with_diff(self, dif: Diff) -> Self {
+        TypeRedeclErr {
+            dif: Box::new(dif),
+            ..self
         }
     }
 }
@@ -157,12 +499,527 @@ impl Display for TypeRedeclErr {
         writeln!(f, ":type {}", self.var)?;
         writeln!(f, "    = {}", self.typ)?;
         writeln!(f, "    ≠ {}", self.upd)?;
+        write_diff(f, &self.dif)
+    }
+}
+
+/// Write the difference between the context in scope and the context expected (if any), marking
+/// the bindings that the expected context adds to (or removes from) the context in scope.
+fn write_diff(f: &mut Formatter<'_>, dif: &Diff) -> Result {
+    if !dif.is_empty() {
+        writeln!(
+            f,
+            "    ↯ the context expected differs from the one in scope by"
+        )?;
+        for line in dif.to_string().lines() {
+            writeln!(f, "    {}", line)?;
+        }
+    }
+    Ok(())
+}
+
+impl Error for TypeHoleErr {}
+
+impl TypeHoleErr {
+    pub fn new(acc: &[&Exp]) -> Self {
+        TypeHoleErr {
+            acc: acc.iter().copied().cloned().collect(),
+        }
+    }
+}
+
+impl Display for TypeHoleErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(f, ":type _")?;
+        if self.acc.is_empty() {
+            writeln!(f, "    = ?")
+        } else {
+            writeln!(
+                f,
+                "    ∈ {{{}}}",
+                self.acc
+                    .iter()
+                    .map(Exp::to_string)
+                    .intersperse(", ".to_string())
+                    .collect::<String>()
+            )
+        }
+    }
+}
+
+impl Error for TypeSortErr {}
+
+impl TypeSortErr {
+    pub fn new(exp: &Exp, typ: &Exp) -> Self {
+        TypeSortErr {
+            exp: exp.clone(),
+            typ: typ.clone(),
+        }
+    }
+}
+
+impl Display for TypeSortErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(f, ":type {}", self.exp)?;
+        writeln!(f, "    = {}", self.typ)?;
+        writeln!(f, "    ∉ {{*, □, Type 2, …}}")?;
+        Ok(())
+    }
+}
+
+impl Error for TypePosErr {}
+
+impl TypePosErr {
+    pub fn new(var: &Var, cns: &Var, typ: &Exp) -> Self {
+        TypePosErr {
+            var: var.clone(),
+            cns: cns.clone(),
+            typ: typ.clone(),
+        }
+    }
+}
+
+impl Display for TypePosErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(f, ":data {}", self.var)?;
+        writeln!(f, "    {} : … {} …", self.cns, self.typ)?;
+        let pos = match positivity(&self.var, &self.typ) {
+            Polarity::Negative | Polarity::Mixed => "a negative position",
+            _ => "a position that is not strictly positive",
+        };
+        writeln!(f, "    ↯ {} occurs in {}", self.var, pos)?;
+        Ok(())
+    }
+}
+
+impl Error for TypeDupDataErr {}
+
+impl TypeDupDataErr {
+    pub fn new(var: &Var, dup: &Var) -> Self {
+        TypeDupDataErr {
+            var: var.clone(),
+            dup: dup.clone(),
+        }
+    }
+}
+
+impl Display for TypeDupDataErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(f, ":data {}", self.var)?;
+        writeln!(f, "    ↯ {} is declared more than once", self.dup)?;
+        Ok(())
+    }
+}
+
+impl Error for TypeUnifyErr {}
+
+impl TypeUnifyErr {
+    pub fn new(fst: &Exp, snd: &Exp) -> Self {
+        TypeUnifyErr {
+            fst: fst.clone(),
+            snd: snd.clone(),
+        }
+    }
+}
+
+impl Display for TypeUnifyErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(f, ":unify {}", self.fst)?;
+        writeln!(f, "    ≠ {}", self.snd)?;
+        Ok(())
+    }
+}
+
+impl Error for TypeFieldErr {}
+
+impl TypeFieldErr {
+    pub fn new(exp: &Exp, typ: &Exp, fld: &Var) -> Self {
+        TypeFieldErr {
+            exp: exp.clone(),
+            typ: typ.clone(),
+            fld: fld.clone(),
+        }
+    }
+}
+
+impl Display for TypeFieldErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(f, ":type {}", self.exp)?;
+        writeln!(f, "    = {}", self.typ)?;
+        writeln!(f, "    ↯ has no field {}", self.fld)?;
+        Ok(())
+    }
+}
+
+impl Error for TypeDupFieldErr {}
+
+impl TypeDupFieldErr {
+    pub fn new(exp: &Exp, fld: &Var, val: &Exp) -> Self {
+        TypeDupFieldErr {
+            exp: exp.clone(),
+            fld: fld.clone(),
+            val: val.clone(),
+        }
+    }
+}
+
+impl Display for TypeDupFieldErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(f, ":type {}", self.exp)?;
+        writeln!(f, "    ↯ field {} is declared more than once", self.fld)?;
+        Ok(())
+    }
+}
+
+impl Error for TypeOccursErr {}
+
+impl TypeOccursErr {
+    pub fn new(id: MetaId, exp: &Exp) -> Self {
+        TypeOccursErr {
+            id,
+            exp: exp.clone(),
+        }
+    }
+}
+
+impl Display for TypeOccursErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(f, ":unify {}", self.id)?;
+        writeln!(f, "    ≠ {}", self.exp)?;
+        writeln!(
+            f,
+            "    ↯ {} occurs in the expression it would be solved with",
+            self.id
+        )?;
+        Ok(())
+    }
+}
+
+impl Error for ReductionBudgetErr {}
+
+impl ReductionBudgetErr {
+    pub fn new(exp: &Exp, fuel: usize) -> Self {
+        ReductionBudgetErr {
+            exp: exp.clone(),
+            fuel,
+        }
+    }
+}
+
+impl Display for ReductionBudgetErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(f, ":show {}", self.exp)?;
+        writeln!(f, "    ↯ not reduced within {} steps", self.fuel)?;
+        Ok(())
+    }
+}
+
+impl Error for ReductionTimeoutErr {}
+
+impl ReductionTimeoutErr {
+    pub fn new(exp: &Exp, time: Duration) -> Self {
+        ReductionTimeoutErr {
+            exp: exp.clone(),
+            time,
+        }
+    }
+}
+
+impl Display for ReductionTimeoutErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(f, ":show {}", self.exp)?;
+        writeln!(f, "    ↯ not reduced within {:?}", self.time)?;
+        Ok(())
+    }
+}
+
+impl Error for ReductionSizeErr {}
+
+impl ReductionSizeErr {
+    pub fn new(exp: &Exp, size: usize, max: usize) -> Self {
+        ReductionSizeErr {
+            exp: exp.clone(),
+            size,
+            max,
+        }
+    }
+}
+
+impl Display for ReductionSizeErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(f, ":show {}", self.exp)?;
+        writeln!(
+            f,
+            "    ↯ grew to size {} when reduced (beyond {})",
+            self.size, self.max
+        )?;
+        Ok(())
+    }
+}
+
+impl Error for ReductionOverflowErr {}
+
+impl ReductionOverflowErr {
+    pub fn new(exp: &Exp) -> Self {
+        ReductionOverflowErr { exp: exp.clone() }
+    }
+}
+
+impl Display for ReductionOverflowErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(f, ":show {}", self.exp)?;
+        writeln!(
+            f,
+            "    ↯ overflows the natural numbers (beyond {})",
+            usize::MAX
+        )?;
+        Ok(())
+    }
+}
+
+impl Error for InterruptErr {}
+
+impl InterruptErr {
+    pub fn new(exp: &Exp) -> Self {
+        InterruptErr { exp: exp.clone() }
+    }
+}
+
+impl Display for InterruptErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(f, ":show {}", self.exp)?;
+        writeln!(f, "    ↯ interrupted")?;
+        Ok(())
+    }
+}
+
+impl Error for SyntaxErr {}
+
+impl SyntaxErr {
+    pub fn new(beg: usize, end: usize, msg: &str) -> Self {
+        SyntaxErr {
+            beg,
+            end,
+            msg: msg.to_string(),
+        }
+    }
+}
+
+impl Error for TrailErr {}
+
+impl Display for TrailErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{}", self.err)?;
+        for crumb in &self.trail {
+            writeln!(f, "    {}{} of {}", WHILE, crumb.step, crumb.exp)?;
+        }
+        Ok(())
+    }
+}
+
+impl Diagnostic {
+    pub fn new(line: usize, col: usize, msg: &str) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            line,
+            col,
+            width: 1,
+            msg: msg.to_string(),
+        }
+    }
+
+    /// Create a new instance of diagnostic with the given message, concerning the given range of
+    /// bytes of the given source text (as far as the end of the line that the range starts on).
+    pub fn spanning(src: &str, (beg, end): (usize, usize), msg: &str) -> Self {
+        let (line, col) = par::locate(src, beg);
+        let text = src.get(beg..end).unwrap_or_default();
+        let width = text
+            .split('\n')
+            .next()
+            .unwrap_or_default()
+            .trim_end()
+            .chars()
+            .count();
+        Diagnostic {
+            width: width.max(1),
+            ..Diagnostic::new(line, col, msg)
+        }
+    }
+
+    /// Render the diagnostic in full, given the source text that it concerns, in the manner of
+    /// `rustc`: the first line of the message (prefixed as `FILE:LINE:COL: error:` if the path of
+    /// the file is given, as tools expect, or `warning:` for a warning), followed by the line of the source text that the
+    /// diagnostic concerns, underlined with carets and labeled with the rest of the message, and
+    /// then by a note for each step in the [trail][TrailErr] that the error arose within.
+    pub fn render(&self, src: &str, path: Option<&str>) -> String {
+        let mut lines = self.msg.lines().map(str::trim).filter(|l| !l.is_empty());
+        let head = lines.next().unwrap_or_default();
+        let (notes, label): (Vec<_>, Vec<_>) = lines.partition(|l| l.starts_with(WHILE));
+        let label = label.join(" ");
+        let mut out = match path {
+            Some(path) => format!(
+                "{}:{}:{}: {}: {}\n",
+                path, self.line, self.col, self.severity, head
+            ),
+            None => format!("{}\n", head),
+        };
+        let num = self.line.to_string();
+        let pad = " ".repeat(num.len());
+        let text = src
+            .lines()
+            .nth(self.line.saturating_sub(1))
+            .unwrap_or_default();
+        let marks = " ".repeat(self.col.saturating_sub(1)) + &"^".repeat(self.width);
+        out.push_str(&format!("{} |\n", pad));
+        out.push_str(&format!("{} | {}\n", num, text.trim_end()));
+        out.push_str(format!("{} | {} {}", pad, marks, label).trim_end());
+        out.push('\n');
+        for note in notes {
+            out.push_str(&format!(
+                "{} = note: {}\n",
+                pad,
+                note.trim_start_matches("↯ ")
+            ));
+        }
+        out
+    }
+}
+
+/// Render the diagnostic on a single line, as `LINE:COL: error: MESSAGE` (or `warning:` for a
+/// warning), with the lines of the message joined by spaces, so that tools may pick it apart (once
+/// prefixed with the path of the file).
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let msg = (self.msg.lines().map(str::trim))
+            .filter(|line| !line.is_empty())
+            .intersperse(" ")
+            .collect::<String>();
+        write!(f, "{}:{}: {}: {}", self.line, self.col, self.severity, msg)
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Self::Error => write!(f, "error"),
+            Self::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+impl Lint {
+    /// Fetch the expression that this warning is about, as it may be found in the source text
+    /// of the command it concerns, unless it is about the command as a whole.
+    pub fn culprit(&self) -> Option<&Exp> {
+        match self {
+            Self::Unused(_, exp) | Self::Shadowed(_, exp) => Some(exp),
+            Self::Unreachable(..) => None,
+        }
+    }
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Self::Unused(var, exp) => {
+                writeln!(f, ":type {}", exp)?;
+                writeln!(
+                    f,
+                    "    ↯ {} is bound but never used (name it _{} if this is meant)",
+                    var, var
+                )
+            }
+            Self::Shadowed(var, exp) => {
+                writeln!(f, ":type {}", exp)?;
+                writeln!(f, "    ↯ {} shadows a variable of the same name", var)
+            }
+            Self::Unreachable(var, name) => {
+                writeln!(f, ":env {}", var)?;
+                writeln!(
+                    f,
+                    "    ↯ no longer reachable as {}, which refers to another variable",
+                    name
+                )
+            }
+        }
+    }
+}
+
+impl Display for SyntaxErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(f, ":parse {}", self.msg)?;
         Ok(())
     }
 }
 
 impl Error for TypingErr {}
 
+impl TypingErr {
+    /// Fetch the expression that this error is about, as it may be found in the source text that
+    /// the command raising the error was parsed from, unless the error is about no expression in
+    /// particular (or only about those that the type checker derives, such as types it unifies).
+    pub fn culprit(&self) -> Option<Exp> {
+        match self {
+            Self::TypeCompatErr(e) => Some(e.exp.clone()),
+            Self::TypeUndefErr(e) => Some(e.exp.clone()),
+            Self::TypeUnknownErr(e) => Some(Exp::new_var(e.var.clone())),
+            Self::TypeSortErr(e) => Some(e.exp.clone()),
+            Self::TypeFieldErr(e) => Some(e.exp.clone()),
+            Self::TypeDupFieldErr(e) => Some(e.val.clone()),
+            Self::ReductionBudgetErr(e) => Some(e.exp.clone()),
+            Self::ReductionTimeoutErr(e) => Some(e.exp.clone()),
+            Self::ReductionSizeErr(e) => Some(e.exp.clone()),
+            Self::ReductionOverflowErr(e) => Some(e.exp.clone()),
+            Self::InterruptErr(e) => Some(e.exp.clone()),
+            Self::TrailErr(e) => e.err.culprit(),
+            _ => None,
+        }
+    }
+
+    /// Record that this error arose within the given step of type-checking (or reducing) the
+    /// given expression, such as `checking the argument` of an application, extending its trail.
+    pub fn within(self, step: &'static str, exp: &Exp) -> Self {
+        let crumb = Crumb {
+            step,
+            exp: exp.clone(),
+        };
+        match self {
+            Self::TrailErr(mut e) => {
+                e.trail.push(crumb);
+                Self::TrailErr(e)
+            }
+            err => Self::from(TrailErr {
+                err: Box::new(err),
+                trail: vec![crumb],
+            }),
+        }
+    }
+
+    /// Fetch the error that arose, without the trail of steps that it arose within (if any).
+    pub fn cause(&self) -> &TypingErr {
+        match self {
+            Self::TrailErr(e) => &e.err,
+            err => err,
+        }
+    }
+
+    /// Take the error that arose, without the trail of steps that it arose within (if any).
+    pub fn into_cause(self) -> TypingErr {
+        match self {
+            Self::TrailErr(e) => *e.err,
+            err => err,
+        }
+    }
+
+    /// Fetch the trail of steps that this error arose within, innermost first.
+    pub fn trail(&self) -> &[Crumb] {
+        match self {
+            Self::TrailErr(e) => &e.trail,
+            _ => &[],
+        }
+    }
+}
+
 impl Default for TypingErr {
     fn default() -> Self {
         Self::Generic("generic typing error".to_string())
@@ -177,6 +1034,21 @@ impl Display for TypingErr {
             Self::TypeUndefErr(e) => write!(f, "{}", e),
             Self::TypeUnknownErr(e) => write!(f, "{}", e),
             Self::TypeRedeclErr(e) => write!(f, "{}", e),
+            Self::TypeHoleErr(e) => write!(f, "{}", e),
+            Self::TypeSortErr(e) => write!(f, "{}", e),
+            Self::TypePosErr(e) => write!(f, "{}", e),
+            Self::TypeDupDataErr(e) => write!(f, "{}", e),
+            Self::TypeUnifyErr(e) => write!(f, "{}", e),
+            Self::TypeFieldErr(e) => write!(f, "{}", e),
+            Self::TypeDupFieldErr(e) => write!(f, "{}", e),
+            Self::TypeOccursErr(e) => write!(f, "{}", e),
+            Self::ReductionBudgetErr(e) => write!(f, "{}", e),
+            Self::ReductionTimeoutErr(e) => write!(f, "{}", e),
+            Self::ReductionSizeErr(e) => write!(f, "{}", e),
+            Self::ReductionOverflowErr(e) => write!(f, "{}", e),
+            Self::InterruptErr(e) => write!(f, "{}", e),
+            Self::SyntaxErr(e) => write!(f, "{}", e),
+            Self::TrailErr(e) => write!(f, "{}", e),
         }
     }
 }
@@ -0,0 +1,149 @@
+//! Command history and related utilities.
+
+use crate::dir::xdg_dir;
+use std::fs::{self, OpenOptions};
+use std::io::{Result, Write};
+use std::path::{Path, PathBuf};
+
+/// History of the lines submitted to the [editor][crate::edt::Editor], which is kept in a file so
+/// that it persists across sessions, and which may be navigated one entry at a time.
+#[derive(Debug, Default)]
+pub struct History {
+    /// Lines submitted so far, from the earliest to the latest.
+    pub lines: Vec<String>,
+    /// Index of the entry currently being navigated to, if navigation is under way.
+    pub pos: Option<usize>,
+    /// Path of the file that each submitted line is appended to, if there is one.
+    pub path: Option<PathBuf>,
+}
+
+impl History {
+    /// Create a new instance of an empty history, which is not kept in any file.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the history kept in the file at the given path (if it exists), to which each line
+    /// submitted later is appended.
+    pub fn load(path: &Path) -> Result<Self> {
+        let lines = match fs::read_to_string(path) {
+            Ok(src) => src
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(String::from)
+                .collect(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => vec![],
+            Err(e) => return Err(e),
+        };
+        Ok(Self {
+            lines,
+            pos: None,
+            path: Some(path.to_path_buf()),
+        })
+    }
+
+    /// Fetch the path of the file that the history is kept in by default, namely
+    /// `$XDG_DATA_HOME/fluxo/history` (where `$XDG_DATA_HOME` defaults to `~/.local/share`).
+    pub fn default_path() -> Option<PathBuf> {
+        Some(xdg_dir("XDG_DATA_HOME", ".local/share")?.join("history"))
+    }
+
+    /// Record the given line as the latest entry (unless it is blank or repeats the latest entry),
+    /// appending it to the file that the history is kept in, and end any navigation under way.
+    ///
+    /// Input of several lines is recorded as a single entry, so that it may be recalled as a whole,
+    /// but only for the rest of the session, since the file holds one entry per line.
+    pub fn push(&mut self, line: &str) -> Result<()> {
+        self.pos = None;
+        if line.trim().is_empty() || self.lines.last().is_some_and(|l| l == line) {
+            return Ok(());
+        }
+        self.lines.push(line.to_string());
+        match &self.path {
+            Some(path) if !line.contains('\n') => {
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir)?;
+                }
+                let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+                writeln!(file, "{}", line)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Navigate to the entry before the current one (or else to the latest entry, if navigation is
+    /// not under way), returning it, unless there is no such entry.
+    pub fn back(&mut self) -> Option<&str> {
+        let pos = match self.pos {
+            Some(0) => return None,
+            Some(pos) => pos - 1,
+            None => self.lines.len().checked_sub(1)?,
+        };
+        self.pos = Some(pos);
+        Some(&self.lines[pos])
+    }
+
+    /// Navigate to the entry after the current one, returning it, or else end navigation (past the
+    /// latest entry) and return an empty line, unless navigation is not under way.
+    pub fn forward(&mut self) -> Option<&str> {
+        let pos = self.pos? + 1;
+        if pos < self.lines.len() {
+            self.pos = Some(pos);
+            Some(&self.lines[pos])
+        } else {
+            self.pos = None;
+            Some("")
+        }
+    }
+
+    /// Fetch the entry currently navigated to, unless navigation is not under way.
+    pub fn current(&self) -> Option<&str> {
+        self.pos.map(|pos| self.lines[pos].as_str())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_hst_0001() {
+        let mut hst = History::new();
+        assert_eq!(hst.back(), None);
+        assert_eq!(hst.forward(), None);
+        for line in ["show 1n", "", "type 1n", "type 1n", "show 2n"] {
+            hst.push(line).unwrap();
+        }
+        assert_eq!(hst.lines, ["show 1n", "type 1n", "show 2n"]);
+        assert_eq!(hst.back(), Some("show 2n"));
+        assert_eq!(hst.back(), Some("type 1n"));
+        assert_eq!(hst.back(), Some("show 1n"));
+        assert_eq!(hst.back(), None);
+        assert_eq!(hst.forward(), Some("type 1n"));
+        assert_eq!(hst.forward(), Some("show 2n"));
+        assert_eq!(hst.forward(), Some(""));
+        assert_eq!(hst.current(), None);
+        hst.back();
+        assert_eq!(hst.current(), Some("show 2n"));
+        hst.push("show 3n").unwrap();
+        assert_eq!(hst.current(), None);
+        assert_eq!(hst.back(), Some("show 3n"));
+    }
+
+    #[test]
+    fn test_hst_0002() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("fluxo-hst-{}", std::process::id()));
+        let path = dir.join("fluxo").join("history");
+        let mut hst = History::load(&path)?;
+        assert!(hst.lines.is_empty());
+        hst.push("show 1n")?;
+        hst.push("type 1n")?;
+        hst.push("show λx.\r\n  x")?;
+        assert_eq!(hst.back(), Some("show λx.\r\n  x"));
+        // the lines submitted in one session are there at the start of the next
+        let mut hst = History::load(&path)?;
+        assert_eq!(hst.back(), Some("type 1n"));
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
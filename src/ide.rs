@@ -1,14 +1,43 @@
 //! Integrated Development Environment (IDE) and associated structures.
 
-use crate::ast::Ctx;
-use crate::cmd::{Out, Status};
+use crate::ast::{Ctx, Exp};
+use crate::buf::Digraphs;
+#[cfg(feature = "serde")]
+use crate::cmd::Report;
+use crate::cmd::{self, Cmd, Format, Out, Settings, Status};
 use crate::edt::Editor;
+use crate::err::{Diagnostic, Severity, TypingErr};
+use crate::hst::History;
+use crate::key::{self, Action};
+use crate::lay::{Details, Scrollback, StatusBar};
+use crate::par;
 use crate::pmt::Prompt;
-use crossterm::style::{Color, Stylize};
-use crossterm::{execute, queue, terminal};
-use std::io::{stdout, Result, Write};
+use crate::rcv::Recovery;
+use crate::thm::{Paint, Theme};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::{cursor, execute, queue, terminal};
+use std::io::{stdout, BufRead, Result, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Interval at which the status bar is redrawn while a command is evaluated, which is also how
+/// long a command runs before the spinner first appears.
+const SPIN_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Longest that the keyboard is waited on at a time while a command is evaluated, which bounds
+/// the delay between the end of the evaluation and its output.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Size of the stack of the thread that each command is evaluated on, which is as large as that of
+/// the main thread usually is, since nested expressions are checked and reduced recursively.
+const EVAL_STACK: usize = 8 * 1024 * 1024;
 
 /// Integrated Development Environment (IDE) that provides a text-based user interface.
+///
+/// The screen is [laid out][crate::lay::Layout] as a main pane, which holds the transcript of the
+/// session along with the editor, and a side panel, which shows the goals and the context.
 #[derive(Default)]
 pub struct IDE {
     /// Indicates whether or not the IDE has been initialized (and may hence require cleanup).
@@ -21,11 +50,12 @@ impl IDE {
         IDE { init: false }
     }
 
-    /// Run the IDE and return a result when the user session ends.
-    pub fn run() -> Result<()> {
+    /// Run the IDE, starting from the given context and settings, and return a result when the
+    /// user session ends.
+    pub fn run(ctx: Ctx, set: Settings) -> Result<()> {
         let mut ide = Self::new();
         ide.init()?;
-        ide.repl()
+        ide.repl(ctx, set)
     }
 
     /// Perform any initialization operations.
@@ -36,8 +66,7 @@ impl IDE {
             stdout(),
             terminal::EnterAlternateScreen,
             terminal::DisableLineWrap
-        )?;
-        self.show_banner()
+        )
     }
 
     /// Perform any cleanup operations such as resetting terminal state or restoring buffers.
@@ -46,55 +75,795 @@ impl IDE {
         terminal::disable_raw_mode()
     }
 
-    /// Show a banner with basic information about the application and brief help on navigation.
-    fn show_banner(&self) -> Result<()> {
-        let mut stdout = stdout();
-        write!(
-            stdout,
-            "{}",
-            Prompt::show_content(&format!(
+    /// Show a banner with basic information about the application and brief help on navigation,
+    /// in the colors of the theme of the given settings (and in ASCII, if they are so set),
+    /// recording it in the given scrollback.
+    fn show_banner(&self, scr: &mut Scrollback, set: &Settings) -> Result<()> {
+        let (thm, ascii) = (set.thm, set.asc);
+        let key = |cmd: &str| match ascii {
+            true => cmd.to_string().paint(thm.command),
+            false => format!("{} ↩", cmd).paint(thm.command),
+        };
+        let banner = Prompt::show_content(
+            &format!(
                 "{} {}\ntype {} for assistance, {} to exit",
                 env!("CARGO_PKG_NAME"),
                 env!("CARGO_PKG_VERSION"),
-                "help ↩".with(Color::Red),
-                "quit ↩".with(Color::Red),
-            ))
-        )
+                key("help"),
+                key("quit"),
+            ),
+            &thm,
+            ascii,
+        );
+        scr.push(&banner);
+        write!(stdout(), "{}", banner)
     }
 
-    /// Execute a read-eval-print-loop to accept and process user input.
-    fn repl(&self) -> Result<()> {
-        let ctx = Ctx::new();
-        let edt = Editor::new();
+    /// Execute a read-eval-print-loop to accept and process user input, as a [session][Session].
+    ///
+    /// While a command is evaluated, the status bar shows how long it has run, and Ctrl+C
+    /// interrupts it (as it would a term that does not terminate), leaving the session running.
+    ///
+    /// The context is checkpointed to the [recovery][Recovery] directory after each command that
+    /// changes it (as is the input, whenever typing pauses), and the checkpoint is cleared once
+    /// the session is exited. If the previous session ended unexpectedly instead, its input is
+    /// restored to the editor, and its context is kept for [recover][cmd::Session::Recover] to
+    /// restore.
+    fn repl(&self, mut ctx: Ctx, mut set: Settings) -> Result<()> {
+        // the defaults are kept if the history or the digraphs cannot be read, with a warning
+        let mut warn = None;
+        let mut edt = match History::default_path() {
+            Some(path) => Editor::with_history(History::load(&path).unwrap_or_else(|e| {
+                warn = Some(format!("history could not be read: {}", e));
+                History::default()
+            })),
+            None => Editor::new(),
+        };
+        if let Some(path) = Digraphs::default_path() {
+            edt = edt.with_digraphs(Digraphs::load(&path).unwrap_or_else(|e| {
+                warn = Some(format!("digraphs could not be read: {}", e));
+                Digraphs::default()
+            }));
+        }
+        if let Some(path) = Theme::default_path() {
+            set.thm = Theme::load(&path).unwrap_or_else(|e| {
+                warn = Some(format!("theme could not be read: {}", e));
+                Theme::default()
+            });
+        }
+        let mut ses = Session::new(&ctx).with_settings(set);
+        edt.rcv = Recovery::default_path().map(|path| Recovery::new(&path));
+        if let Some(rcv) = &edt.rcv {
+            let chk = rcv.load().unwrap_or_else(|e| {
+                warn = Some(format!("previous session could not be recovered: {}", e));
+                None
+            });
+            if let Some(chk) = chk {
+                ses.rcv = chk.ctx;
+                edt.draft = chk.input;
+            }
+        }
+        edt.bar = StatusBar::new(&ctx, &ses.set);
+        if let Some(warn) = &warn {
+            tracing::warn!("{}", warn);
+        }
+        edt.bar.warn = warn;
+        self.show_banner(&mut edt.scr, &ses.set)?;
+        if ses.rcv.is_some() {
+            let msg = "the previous session ended unexpectedly\ntype session recover to restore it";
+            let out = Out::with_msg(msg, &Status::Content);
+            self.emit(&out, &mut edt, &ctx, &ses.set)?;
+        }
         loop {
-            let cmd = edt.read()?;
-            let out = cmd.eval(&ctx);
-            self.emit(&out)?;
+            let cmd = edt.read(&ctx, &ses.set)?;
+            let (irq, thm) = (Arc::clone(&ses.set.irq), ses.set.thm);
+            let out = Self::busy(irq, &edt.bar, thm, || ses.eval(&cmd, &mut ctx))?;
+            self.emit(&out, &mut edt, &ctx, &ses.set)?;
+            edt.bar.update(&ctx, &ses.set);
             if out.trm {
+                // at worst, the next session offers to recover this one
+                let _ = edt.rcv.as_ref().map(Recovery::clear);
                 return Ok(());
             }
+            if cmd.is_change() || matches!(cmd, Cmd::Undo | Cmd::Reset) {
+                if let Some(Err(e)) = edt.rcv.as_ref().map(|rcv| rcv.save_context(&ctx)) {
+                    edt.bar.warn = Some(format!("session could not be saved: {}", e));
+                }
+            }
         }
     }
 
-    /// Emit the message generated by the evaluated command, if there is one.
-    fn emit(&self, out: &Out) -> Result<()> {
-        if let Some(msg) = &out.msg {
-            match out.status {
-                Status::Success => write!(stdout(), "{}", Prompt::show_success(msg))?,
-                Status::Failure => write!(stdout(), "{}", Prompt::show_failure(msg))?,
-                Status::Content => write!(stdout(), "{}", Prompt::show_content(msg))?,
+    /// Evaluate a command with the given function on a thread of its own, while this thread shows
+    /// a spinner and the time taken in (a copy of) the given status bar, and raises the given flag
+    /// to interrupt the evaluation if Ctrl+C is pressed, lowering it again once the evaluation
+    /// ends.
+    ///
+    /// The evaluation is kept off this thread, so that the terminal stays responsive however long
+    /// it runs. Other keys pressed meanwhile are discarded.
+    fn busy<T: Send>(
+        irq: Arc<AtomicBool>,
+        bar: &StatusBar,
+        thm: Theme,
+        f: impl FnOnce() -> T + Send,
+    ) -> Result<T> {
+        thread::scope(|scope| {
+            let eval = (thread::Builder::new().stack_size(EVAL_STACK)).spawn_scoped(scope, f)?;
+            // the spinner is merely left out if the terminal cannot be written
+            let _ = Self::watch(|| eval.is_finished(), &irq, bar.clone(), &thm);
+            let res = eval.join().unwrap_or_else(|e| std::panic::resume_unwind(e));
+            irq.store(false, Ordering::Relaxed);
+            Ok(res)
+        })
+    }
+
+    /// Redraw the given status bar on the bottom row of the screen with a spinner and the time
+    /// taken, raising the given flag if Ctrl+C is pressed, until the evaluation is done.
+    fn watch(
+        done: impl Fn() -> bool,
+        irq: &AtomicBool,
+        mut bar: StatusBar,
+        thm: &Theme,
+    ) -> Result<()> {
+        let beg = Instant::now();
+        let mut next = beg + SPIN_INTERVAL;
+        let mut stdout = stdout();
+        while !done() {
+            if event::poll(POLL_INTERVAL)? {
+                if let Event::Key(KeyEvent {
+                    code: KeyCode::Char('c'),
+                    modifiers: KeyModifiers::CONTROL,
+                    ..
+                }) = event::read()?
+                {
+                    irq.store(true, Ordering::Relaxed);
+                }
+                continue;
+            }
+            if Instant::now() < next {
+                continue;
             }
+            next += SPIN_INTERVAL;
+            bar.busy = Some(beg.elapsed());
+            let (cols, rows) = terminal::size()?;
+            queue!(
+                stdout,
+                cursor::SavePosition,
+                cursor::MoveTo(0, rows.saturating_sub(1))
+            )?;
+            write!(stdout, "{}", bar.render(usize::from(cols), false, thm))?;
+            execute!(stdout, cursor::RestorePosition)?;
         }
         Ok(())
     }
+
+    /// Emit the message generated by the evaluated command, if there is one, in the colors of the
+    /// theme of the given settings (and in ASCII, if they are so set), recording it in the scrollback
+    /// of the given editor, so that it may be scrolled back to once it has scrolled off the screen.
+    ///
+    /// Only the first line of a failure is emitted, with a hint on how to expand the rest of it,
+    /// which is kept as the [details][Details] of the failure in the editor. The warnings about
+    /// the command (if any) are emitted after its message, dimly.
+    fn emit(&self, out: &Out, edt: &mut Editor, ctx: &Ctx, set: &Settings) -> Result<()> {
+        if let Some(msg) = &out.msg {
+            let (thm, ascii) = (&set.thm, set.asc);
+            let msg = match out.status {
+                Status::Success => Prompt::show_success(msg, thm, ascii),
+                Status::Failure => {
+                    // the details underline the part of the input that the failure is about
+                    let (src, toks) = (&edt.input, par::tokenize(&edt.input));
+                    let pos = (out.err.as_ref()).and_then(|e| par::locate_err(src, &toks, ctx, e));
+                    let diag = pos.map(|pos| Diagnostic::spanning(src, pos, msg).render(src, None));
+                    edt.details = Details::new(diag.as_deref().unwrap_or(msg));
+                    let maps = set.key.keymaps();
+                    let key = (key::listing(&edt.layers.stack(&maps)).into_iter())
+                        .find(|(_, act)| *act == Action::Details)
+                        .map(|(key, _)| key);
+                    let summary = edt.details.summary(key.as_deref());
+                    Prompt::show_failure(&summary, thm, ascii)
+                }
+                Status::Content => Prompt::show_content(msg, thm, ascii),
+                Status::Diagnostics => Prompt::show_diagnostics(msg, thm, ascii),
+                Status::Warning => Prompt::show_warning(msg, thm, ascii),
+            };
+            edt.scr.push(&msg);
+            write!(stdout(), "{}", msg)?;
+        }
+        for lint in &out.lints {
+            self.emit(
+                &Out::with_msg(&lint.to_string(), &Status::Warning),
+                edt,
+                ctx,
+                set,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// State of an interactive session that is kept from one command to the next, besides the
+/// context itself.
+///
+/// Before each command that changes the context succeeds, the context is kept as a checkpoint,
+/// so that the change may be retracted with [undo][Cmd::Undo], or every change may be retracted
+/// with [reset][Cmd::Reset]. Since the context is persistent, each checkpoint shares most of its
+/// structure with the context that follows it.
+///
+/// The result of each [step][Cmd::Step] is kept as well, as the target of the next step when
+/// no expression is given, and the result of each [show][Cmd::Show] or [type][Cmd::Type] is
+/// [kept][Cmd::keep] in the context as `%1`, `%2` and so on, the latest of which is also `it`.
+///
+/// The context of the session that ended unexpectedly before this one (if any) is kept as well,
+/// for [recover][cmd::Session::Recover] to restore, which may be undone like any other change.
+#[derive(Debug)]
+pub struct Session {
+    /// Context that the session started with, which [reset][Cmd::Reset] returns to.
+    pub init: Ctx,
+    /// Checkpoints of the context before each change, from the earliest to the latest.
+    pub chk: Vec<Ctx>,
+    /// Result of the latest step, which the next step continues from, if there is one.
+    pub tgt: Option<Exp>,
+    /// Number of the results kept so far.
+    pub num: usize,
+    /// Context of the session that ended unexpectedly before this one, if there is one.
+    pub rcv: Option<Ctx>,
+    /// Format that the output of commands evaluated outside the IDE is written in.
+    pub fmt: Format,
+    /// Settings that the commands are evaluated with, which are kept apart from the context.
+    pub set: Settings,
+}
+
+impl Session {
+    /// Create a new instance of a session that starts with the given context.
+    pub fn new(ctx: &Ctx) -> Self {
+        Self {
+            init: ctx.clone(),
+            chk: Vec::new(),
+            tgt: None,
+            num: 0,
+            rcv: None,
+            fmt: Format::default(),
+            set: Settings::default(),
+        }
+    }
+
+    /// Evaluate the commands of this session with the given settings.
+    pub fn with_settings(mut self, set: Settings) -> Self {
+        self.set = set;
+        self
+    }
+
+    /// Write the output of the commands evaluated outside the IDE in the given format.
+    pub fn with_format(mut self, fmt: Format) -> Self {
+        self.fmt = fmt;
+        self
+    }
+
+    /// Evaluate the given command in the given context (with the settings of this session, which
+    /// it may change), keeping what the session needs for the
+    /// commands that follow, and return its output, along with the [warnings][Cmd::lint] about
+    /// the command if it succeeds (unless they are turned off).
+    pub fn eval<'c>(&mut self, cmd: &'c Cmd, ctx: &mut Ctx) -> Out<'c> {
+        tracing::debug!(?cmd, "evaluating command");
+        ctx.set_limits(self.set.limits());
+        let pre = ctx.clone();
+        let mut out = match cmd {
+            Cmd::Undo => match self.chk.pop() {
+                Some(pre) => {
+                    *ctx = pre;
+                    Out::with_msg("undo", &Status::Success)
+                }
+                None => Out::with_msg("undo\n    ↯ there is nothing to undo\n", &Status::Failure),
+            },
+            Cmd::Step(exp) => match exp.clone().or_else(|| self.tgt.take()) {
+                Some(exp) => {
+                    let (out, res) = Cmd::step(&exp, ctx, &self.set);
+                    self.tgt = res;
+                    out
+                }
+                None => Out::with_msg(
+                    "step\n    ↯ there is no previous step to continue from\n",
+                    &Status::Failure,
+                ),
+            },
+            Cmd::Show(exp) => self.keep(Cmd::show(exp, ctx, &self.set), ctx),
+            Cmd::Type(exp) => self.keep(Cmd::type_of(exp, ctx, &self.set), ctx),
+            Cmd::Session(cmd::Session::Recover) => match &self.rcv {
+                Some(rcv) => {
+                    self.chk.push(std::mem::replace(ctx, rcv.clone()));
+                    Out::with_msg("session recover", &Status::Success)
+                }
+                None => cmd.eval(ctx, &mut self.set), // which fails, since there is nothing to recover
+            },
+            Cmd::Reset => {
+                self.chk.clear();
+                *ctx = self.init.clone();
+                Out::with_msg("reset", &Status::Success)
+            }
+            _ => {
+                let out = cmd.eval(ctx, &mut self.set);
+                // a file (or library) that is loaded may fail part-way through, after changing the context
+                let failed = matches!(out.status, Status::Failure)
+                    && !matches!(cmd, Cmd::Load(_) | Cmd::Import(_));
+                if cmd.is_change() && !failed {
+                    self.chk.push(pre.clone());
+                }
+                out
+            }
+        };
+        if self.set.lnt && !matches!(out.status, Status::Failure) {
+            out.lints = cmd.lint(&pre, ctx);
+        }
+        out
+    }
+
+    /// Run a plain, line-oriented read-eval-print loop in the given context, which reads each
+    /// command from a line of the given input and writes its output (without any styling) to the
+    /// given output, until the input ends or the session is exited.
+    ///
+    /// Unlike the IDE, this neither takes over the terminal nor depends on its capabilities, so
+    /// it works just as well in the shell of an editor, in a log or on a dumb terminal.
+    pub fn run(&mut self, mut ctx: Ctx, mut inp: impl BufRead, mut out: impl Write) -> Result<()> {
+        let banner = format!(
+            "{} {}\ntype help for assistance, quit to exit",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+        );
+        let banner = Prompt::show_plain(&banner, &Status::Content, self.set.asc);
+        write!(out, "{}", banner)?;
+        let mut line = String::new();
+        loop {
+            write!(out, "{} ", Prompt::Ready.glyph(self.set.asc))?;
+            out.flush()?;
+            line.clear();
+            if inp.read_line(&mut line)? == 0 {
+                return writeln!(out); // the input has ended
+            }
+            let src = line.trim_end();
+            let cmd = par::parse_cmd(src, &par::tokenize(src), &ctx, &self.set)
+                .unwrap_or_else(|err| Cmd::Fail(TypingErr::from(err)));
+            let res = self.eval(&cmd, &mut ctx);
+            if let Some(msg) = &res.msg {
+                let msg = Prompt::show_plain(msg, res.status, self.set.asc);
+                write!(out, "{}", msg)?;
+            }
+            for lint in &res.lints {
+                let msg = Prompt::show_plain(&lint.to_string(), &Status::Warning, self.set.asc);
+                write!(out, "{}", msg)?;
+            }
+            if res.trm {
+                return writeln!(out); // the session has been exited
+            }
+        }
+    }
+
+    /// Evaluate the command on each line of the given input (such as a pipe) in the given context,
+    /// in a batch without a banner or prompts, writing the output of each in the
+    /// [format][Format] of this session to the given output, or (in text) to the given error
+    /// output if it fails, until the input ends or the session is exited. Returns whether every
+    /// command succeeded.
+    ///
+    /// Unlike a [script][Session::script], each command is evaluated as soon as its line is read,
+    /// and the commands after one that fails are still evaluated.
+    pub fn batch(
+        &mut self,
+        ctx: &mut Ctx,
+        mut inp: impl BufRead,
+        mut out: impl Write,
+        mut err: impl Write,
+    ) -> Result<bool> {
+        let mut ok = true;
+        let mut line = String::new();
+        for num in 1.. {
+            line.clear();
+            if inp.read_line(&mut line)? == 0 {
+                break; // the input has ended
+            }
+            let src = line.trim_end();
+            if src.trim_start().is_empty() {
+                continue;
+            }
+            let toks = par::tokenize(src);
+            let (done, trm) = self.report(ctx, (src, &toks, num), None, &mut out, &mut err)?;
+            ok &= done;
+            if trm {
+                break; // the session has been exited
+            }
+        }
+        Ok(ok)
+    }
+
+    /// Evaluate the commands in the given source text (such as the contents of the file at the
+    /// given path) in order, in the given context, writing the output of each in the
+    /// [format][Format] of this session to the given output, until one of them fails, whose
+    /// output is written (in text) to the given error output instead, prefixed with the path and
+    /// the line it starts on. Returns whether every command succeeded (or the session was exited
+    /// before any failed).
+    ///
+    /// As with [load][Cmd::Load], each command starts at the beginning of a line, and continues
+    /// onto the lines that are indented after it.
+    pub fn script(
+        &mut self,
+        ctx: &mut Ctx,
+        src: &str,
+        path: &str,
+        mut out: impl Write,
+        mut err: impl Write,
+    ) -> Result<bool> {
+        let toks = par::tokenize(src);
+        for (_, toks) in par::split_commands(src, &toks) {
+            let end = toks.last().map_or(0, |tok| tok.end);
+            let cmd = (&src[..end], toks, 1);
+            match self.report(ctx, cmd, Some(path), &mut out, &mut err)? {
+                (false, _) => return Ok(false),
+                (true, true) => break,
+                _ => {}
+            }
+        }
+        Ok(true)
+    }
+
+    /// Evaluate the single command in the given source text (such as one given on the command
+    /// line) in the given context, writing its output in the [format][Format] of this session to
+    /// the given output, or (in text) to the given error output if it fails. Returns whether the
+    /// command succeeded.
+    pub fn once(
+        &mut self,
+        ctx: &mut Ctx,
+        src: &str,
+        mut out: impl Write,
+        mut err: impl Write,
+    ) -> Result<bool> {
+        let toks = par::tokenize(src);
+        let (ok, _) = self.report(ctx, (src, &toks, 1), None, &mut out, &mut err)?;
+        Ok(ok)
+    }
+
+    /// Evaluate the command made up of the given tokens of the given source text (which starts
+    /// on the given line of its input, such as the file at the given path) in the given context,
+    /// and write its output in the [format][Format] of this session to the given output, or (in
+    /// text) to the given error output if it fails, prefixed with the path (if any) and the line
+    /// that the failure is located on, followed by any warnings about the command (reported the
+    /// same way). Returns whether the command succeeded, and whether it exited the session.
+    fn report(
+        &mut self,
+        ctx: &mut Ctx,
+        (src, toks, num): (&str, &[par::Tok], usize),
+        path: Option<&str>,
+        out: &mut impl Write,
+        err: &mut impl Write,
+    ) -> Result<(bool, bool)> {
+        let beg = toks.first().map_or(0, |tok| tok.beg);
+        let cmd = par::parse_cmd(src, toks, ctx, &self.set)
+            .unwrap_or_else(|e| Cmd::Fail(TypingErr::from(e)));
+        let res = self.eval(&cmd, ctx);
+        let ok = !matches!(res.status, Status::Failure);
+        let msg = res.msg.unwrap_or_default();
+        let end = toks.last().map_or(beg, |tok| tok.end);
+        let pos = (res.err.as_ref()).and_then(|e| par::locate_err(src, toks, ctx, e));
+        let mut error = Diagnostic::spanning(src, pos.unwrap_or((beg, end)), &msg);
+        error.line += num - 1;
+        match self.fmt {
+            Format::Text if ok => write!(
+                out,
+                "{}",
+                Prompt::show_plain(&msg, res.status, self.set.asc)
+            )?,
+            Format::Text => {
+                let msg = match path {
+                    Some(path) => format!("{}:{}:{}: {}", path, error.line, error.col, msg),
+                    None => msg,
+                };
+                write!(
+                    err,
+                    "{}",
+                    Prompt::show_plain(&msg, res.status, self.set.asc)
+                )?
+            }
+            #[cfg(feature = "serde")]
+            Format::Json => {
+                let rep = Report {
+                    file: path,
+                    command: Some(src[beg..].trim_end()),
+                    status: res.status,
+                    text: &msg,
+                    error: (!ok).then_some(error),
+                };
+                rep.to_writer(&mut *out)?
+            }
+        }
+        for lint in &res.lints {
+            let msg = lint.to_string();
+            let pos = par::locate_lint(src, toks, ctx, lint).unwrap_or((beg, end));
+            let mut warning = Diagnostic::spanning(src, pos, &msg);
+            warning.severity = Severity::Warning;
+            warning.line += num - 1;
+            match self.fmt {
+                Format::Text => {
+                    let msg = match path {
+                        Some(path) => format!("{}:{}:{}: {}", path, warning.line, warning.col, msg),
+                        None => msg,
+                    };
+                    write!(
+                        err,
+                        "{}",
+                        Prompt::show_plain(&msg, &Status::Warning, self.set.asc)
+                    )?
+                }
+                #[cfg(feature = "serde")]
+                Format::Json => {
+                    let rep = Report {
+                        file: path,
+                        command: Some(src[beg..].trim_end()),
+                        status: &Status::Warning,
+                        text: &msg,
+                        error: Some(warning),
+                    };
+                    rep.to_writer(&mut *out)?
+                }
+            }
+        }
+        out.flush()?;
+        Ok((ok, res.trm))
+    }
+
+    /// Keep the result of a command (if there is one) under the next number, and prefix the
+    /// [output][Out] of the command with the variable that it is kept as.
+    fn keep(
+        &mut self,
+        (mut out, res): (Out<'static>, Option<(Exp, Exp)>),
+        ctx: &mut Ctx,
+    ) -> Out<'static> {
+        if let Some((val, typ)) = res {
+            self.num += 1;
+            let var = Cmd::keep(self.num, &val, &typ, ctx);
+            out.msg = out.msg.map(|msg| format!("{} = {}", var, msg));
+        }
+        out
+    }
 }
 
 impl Drop for IDE {
     fn drop(&mut self) {
         if self.init {
             if let Err(e) = IDE::drop(self) {
-                eprint!("{}", Prompt::show_failure(&e.to_string()));
+                eprint!(
+                    "{}",
+                    Prompt::show_failure(&e.to_string(), &Theme::default(), false)
+                );
             }
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::Var;
+
+    #[test]
+    fn test_ide_0001() -> std::io::Result<()> {
+        let inp = "assume A : *\nshow λx : A . x\nundo\nundo\nshow y\nquit\nshow A\n";
+        let mut out = vec![];
+        Session::new(&Ctx::new()).run(Ctx::new(), inp.as_bytes(), &mut out)?;
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert!(lines[0].starts_with("≡ fluxo "));
+        assert_eq!(
+            lines[2..5],
+            ["» ∴ A : *", "» ∴ %1 = λx : A . x", "» ∴ undo"]
+        );
+        assert!(lines[5].starts_with("» ✗ undo") && lines[6].starts_with("≡ "));
+        assert!(lines[7].starts_with("» ✗ "));
+        // the session ends once it is exited, and there is no styling or carriage return
+        assert_eq!(lines.len(), 9);
+        assert!(!out.contains('\r') && !out.contains('\x1b'));
+        let mut out = vec![];
+        Session::new(&Ctx::new()).run(Ctx::new(), "".as_bytes(), &mut out)?;
+        assert!(String::from_utf8(out).unwrap().ends_with("» \n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_ide_0002() {
+        let cmd = |src: &str| {
+            par::parse_cmd(src, &par::tokenize(src), &Ctx::new(), &Settings::default()).unwrap()
+        };
+        let recover = cmd("session recover");
+        let mut ctx = Ctx::new();
+        let mut ses = Session::new(&ctx);
+        let out = ses.eval(&recover, &mut ctx);
+        assert!(matches!(out.status, Status::Failure));
+        // the context of the session that ended unexpectedly replaces this one, undoably
+        let mut rcv = Ctx::new();
+        ses.eval(&cmd("assume A : *"), &mut rcv);
+        ses.rcv = Some(rcv);
+        ses.eval(&cmd("assume B : *"), &mut ctx);
+        let out = ses.eval(&recover, &mut ctx);
+        assert_eq!(out.msg.as_deref(), Some("session recover"));
+        assert!(ctx.get(&Var::new("A")).is_ok() && ctx.get(&Var::new("B")).is_err());
+        ses.eval(&Cmd::Undo, &mut ctx);
+        assert!(ctx.get(&Var::new("A")).is_err() && ctx.get(&Var::new("B")).is_ok());
+    }
+
+    #[test]
+    fn test_ide_0003() -> std::io::Result<()> {
+        let src = "assume A : *\nlet id : A → A =\n  λx : A . x\nshow id\nshow y\nshow A\n";
+        let (mut ctx, mut out, mut err) = (Ctx::new(), vec![], vec![]);
+        let ok = Session::new(&ctx).script(&mut ctx, src, "id.fx", &mut out, &mut err)?;
+        assert!(!ok);
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out, "∴ A : *\n∴ id : A → A\n∴ %1 = λx : A . x\n");
+        // evaluation stops at the first failure, which is reported along with where it is
+        let err = String::from_utf8(err).unwrap();
+        assert_eq!(err.lines().next(), Some("✗ id.fx:5:6: :type y = ?"));
+        assert!(ctx.get(&Var::new("id")).is_ok());
+        let src = "assume A : *\nquit\nshow y\n";
+        let (mut ctx, mut out) = (Ctx::new(), vec![]);
+        assert!(Session::new(&ctx).script(&mut ctx, src, "a.fx", &mut out, vec![])?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ide_0004() -> std::io::Result<()> {
+        let (mut ctx, mut out, mut err) = (Ctx::new(), vec![], vec![]);
+        let src = "type λx : * . x";
+        assert!(Session::new(&ctx).once(&mut ctx, src, &mut out, &mut err)?);
+        assert_eq!(String::from_utf8(out).unwrap(), "∴ %1 = * → *\n");
+        let (mut out, mut err) = (vec![], vec![]);
+        assert!(!Session::new(&ctx).once(&mut ctx, "show y", &mut out, &mut err)?);
+        assert!(out.is_empty());
+        assert_eq!(
+            String::from_utf8(err).unwrap().lines().next(),
+            Some("✗ :type y = ?")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_ide_0005() -> std::io::Result<()> {
+        let inp = "assume A : *\n\nshow y\nlet x : A → A = λa : A . a\nquit\nshow x\n";
+        let (mut ctx, mut out, mut err) = (Ctx::new(), vec![], vec![]);
+        let ok = Session::new(&ctx).batch(&mut ctx, inp.as_bytes(), &mut out, &mut err)?;
+        // the commands after one that fails are still evaluated, until the session is exited
+        assert!(!ok);
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out, "∴ A : *\n∴ x : A → A\n");
+        assert_eq!(
+            String::from_utf8(err).unwrap().lines().next(),
+            Some("✗ :type y = ?")
+        );
+        let (mut out, mut err) = (vec![], vec![]);
+        let ok = Session::new(&ctx).batch(&mut ctx, "show x".as_bytes(), &mut out, &mut err)?;
+        assert!(ok && err.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_ide_0006() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let src = "assume A : *\nlet x : A =\n  (A\nshow A\n";
+        let (mut ctx, mut out) = (Ctx::new(), vec![]);
+        let mut ses = Session::new(&ctx).with_format(Format::Json);
+        assert!(!ses.script(&mut ctx, src, "a.fx", &mut out, vec![])?);
+        let out = String::from_utf8(out)?;
+        let reps = (out.lines().map(serde_json::from_str))
+            .collect::<std::result::Result<Vec<serde_json::Value>, _>>()?;
+        assert_eq!(reps.len(), 2);
+        assert_eq!(reps[0]["command"], "assume A : *");
+        assert_eq!(reps[0]["status"], "success");
+        assert!(reps[0]["error"].is_null());
+        // a syntax error is located where it is found, within the command that fails
+        assert_eq!(reps[1]["file"], "a.fx");
+        assert_eq!(reps[1]["command"], "let x : A =\n  (A");
+        assert_eq!(reps[1]["status"], "failure");
+        assert_eq!(
+            (&reps[1]["error"]["line"], &reps[1]["error"]["col"]),
+            (&3.into(), &5.into())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_ide_0007() -> std::io::Result<()> {
+        use std::time::Duration;
+        let inp = "assume A : *\nlet id : A → A = λa : A . a\nshow id\ntype id\n";
+        let (mut ctx, mut out, mut err) = (Ctx::new(), vec![], vec![]);
+        let mut set = Settings {
+            max: Some(2),
+            ..Settings::default()
+        };
+        let ok = Session::new(&ctx).with_settings(set.clone()).batch(
+            &mut ctx,
+            inp.as_bytes(),
+            &mut out,
+            &mut err,
+        )?;
+        // the command that grows the expression fails, while those that do not still succeed
+        assert!(!ok);
+        let err = String::from_utf8(err).unwrap();
+        assert!(
+            err.contains(":show id") && err.contains("↯ grew to size 3 when reduced (beyond 2)")
+        );
+        assert!(String::from_utf8(out).unwrap().contains("∴ %1 = A → A"));
+        // the time starts anew with each command, so a command that is out of time fails alone
+        let inp = "show id\ntype id\n";
+        let (mut ctx, mut out, mut err) = (ctx.clone(), vec![], vec![]);
+        (set.max, set.tmo) = (None, Some(Duration::ZERO));
+        let ok = Session::new(&ctx).with_settings(set.clone()).batch(
+            &mut ctx,
+            inp.as_bytes(),
+            &mut out,
+            &mut err,
+        )?;
+        assert!(!ok);
+        assert!(String::from_utf8(err)
+            .unwrap()
+            .contains("↯ not reduced within 0ns"));
+        set.tmo = Some(Duration::from_secs(60));
+        assert!(Session::new(&ctx).with_settings(set).once(
+            &mut ctx,
+            "show id",
+            &mut out,
+            vec![]
+        )?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ide_0008() -> std::io::Result<()> {
+        let src = "assume A : *\nlet k : A → A → A = λx : A . λy : A . x\nshow k\n";
+        let (mut ctx, mut out, mut err) = (Ctx::new(), vec![], vec![]);
+        let ok = Session::new(&ctx).script(&mut ctx, src, "k.fx", &mut out, &mut err)?;
+        // warnings go to the error output, but do not fail the script
+        assert!(ok);
+        let err = String::from_utf8(err).unwrap();
+        assert!(err.starts_with("⚠ k.fx:2:30: :type λy : A . x\n"));
+        assert!(!String::from_utf8(out).unwrap().contains('⚠'));
+        Ok(())
+    }
+
+    #[test]
+    fn test_ide_0009() {
+        let cmd = |src: &str| {
+            par::parse_cmd(src, &par::tokenize(src), &Ctx::new(), &Settings::default()).unwrap()
+        };
+        let has = |ctx: &Ctx, var: &str| ctx.get(&Var::new(var)).is_ok();
+        let mut ctx = Ctx::new();
+        let mut ses = Session::new(&ctx);
+        let out = ses.eval(&Cmd::Undo, &mut ctx);
+        assert!(matches!(out.status, Status::Failure));
+        ses.eval(&cmd("assume A : *"), &mut ctx);
+        ses.eval(&cmd("assume a : A"), &mut ctx);
+        // a change that fails leaves no checkpoint behind, nor does a command that changes nothing
+        assert!(matches!(
+            ses.eval(&cmd("assume b : B"), &mut ctx).status,
+            Status::Failure
+        ));
+        ses.eval(&cmd("show a"), &mut ctx);
+        assert_eq!(ses.chk.len(), 2);
+        // each undo retracts the latest change, while reset retracts them all
+        assert_eq!(ses.eval(&Cmd::Undo, &mut ctx).msg.as_deref(), Some("undo"));
+        assert!(has(&ctx, "A") && !has(&ctx, "a"));
+        ses.eval(&cmd("assume c : A"), &mut ctx);
+        assert_eq!(
+            ses.eval(&Cmd::Reset, &mut ctx).msg.as_deref(),
+            Some("reset")
+        );
+        assert!(!has(&ctx, "A") && !has(&ctx, "c") && ses.chk.is_empty());
+        assert!(matches!(
+            ses.eval(&Cmd::Undo, &mut ctx).status,
+            Status::Failure
+        ));
+    }
+
+    #[test]
+    fn test_ide_0010() -> std::io::Result<()> {
+        // a syntax error in a command that begins with an alias is located in the command as written
+        let src = "alias t = type\nt (Type\n";
+        let (mut ctx, mut out, mut err) = (Ctx::new(), vec![], vec![]);
+        assert!(!Session::new(&ctx).script(&mut ctx, src, "a.fx", &mut out, &mut err)?);
+        let err = String::from_utf8(err).unwrap();
+        assert!(err.starts_with("✗ a.fx:2:8: :parse expected ‘)’, found end of input"));
+        Ok(())
+    }
+}
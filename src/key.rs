@@ -0,0 +1,582 @@
+//! Key bindings and related utilities.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::fmt::Display;
+use std::ops::Deref;
+
+/// Key pressed, along with the modifiers held down while it was.
+#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq)]
+pub struct Key {
+    /// Code of the key pressed.
+    pub code: KeyCode,
+    /// Modifiers held down (Ctrl, Alt and so on).
+    pub mods: KeyModifiers,
+}
+
+/// Sequence of keys pressed one after another (such as Ctrl+X followed by U), every one but the
+/// last of which is a prefix that the editor waits for the rest of.
+#[derive(Debug, Clone, Eq, Hash, PartialEq)]
+pub struct Chord(pub Vec<Key>);
+
+/// Result of looking up the keys pressed so far in a [keymap][KeyMap].
+#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq)]
+pub enum Lookup {
+    /// The keys are bound to the given action.
+    Bound(Action),
+    /// The keys are a prefix of one or more chords, so the editor waits for the next key.
+    Prefix,
+    /// The keys are not bound to anything.
+    Unbound,
+}
+
+/// Action that the [editor][crate::edt::Editor] takes when a key bound to it is pressed.
+#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq)]
+pub enum Action {
+    /// Insert the given character at the cursor.
+    Insert(char),
+    /// Submit the input (or accept the completion selected, if there is one).
+    Submit,
+    /// Discard the input, leaving it unsubmitted.
+    Cancel,
+    /// Exit if the input is empty, or else delete the grapheme at the cursor.
+    Eof,
+    /// Move the cursor left past one grapheme.
+    Left,
+    /// Move the cursor right past one grapheme.
+    Right,
+    /// Move the cursor up a line (or else recall the previous input).
+    Up,
+    /// Move the cursor down a line (or else recall the next input).
+    Down,
+    /// Move the cursor left to the start of the word.
+    WordLeft,
+    /// Move the cursor right to the end of the word.
+    WordRight,
+    /// Move the cursor to the start of the line.
+    Home,
+    /// Move the cursor to the end of the line.
+    End,
+    /// Delete the grapheme before the cursor.
+    Backspace,
+    /// Delete the grapheme at the cursor.
+    Delete,
+    /// Swap the graphemes around the cursor.
+    Transpose,
+    /// Kill (cut) the text from the cursor to the end of the line.
+    KillToEnd,
+    /// Kill (cut) the text from the start of the line to the cursor.
+    KillToStart,
+    /// Yank (paste) the text killed latest.
+    Yank,
+    /// Replace the text just yanked with the text killed before it.
+    YankPop,
+    /// Undo the latest group of edits.
+    Undo,
+    /// Redo the latest group of edits undone.
+    Redo,
+    /// Show the completions of the word at the cursor (or select the next one).
+    Complete,
+    /// Select the previous completion.
+    CompletePrev,
+    /// Close the menu of completions.
+    Dismiss,
+    /// Widen the side panel of the IDE (showing it if it is hidden).
+    GrowPanel,
+    /// Narrow the side panel of the IDE (hiding it once it is too narrow).
+    ShrinkPanel,
+    /// Scroll back through the transcript of the session by a page.
+    PageUp,
+    /// Scroll forward through the transcript of the session by a page.
+    PageDown,
+    /// Show the type of the sub-expression under the cursor, without submitting the input.
+    Inspect,
+    /// Move the cursor to the next hole in the input.
+    NextHole,
+    /// Move the cursor to the previous hole in the input.
+    PrevHole,
+    /// Show the terms that the hole at the cursor may be refined with.
+    Refine,
+    /// Expand (or collapse) the details of the latest failure.
+    Details,
+}
+
+/// Set of key bindings that the [editor][crate::edt::Editor] may be configured with, each of
+/// which is layered on top of the [standard keymap][DefKeyMap].
+#[derive(Debug, Clone, Copy, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KeySet {
+    /// Only the bindings of the [standard keymap][DefKeyMap].
+    #[default]
+    Standard,
+    /// The bindings of the [Emacs keymap][EmacsKeyMap], ahead of the standard ones.
+    Emacs,
+}
+
+/// Mapping from keys (and [chords][Chord]) to the [actions][Action] that they are bound to.
+pub trait KeyMap {
+    /// Fetch the key bindings of this keymap, in the order that they are listed to the user.
+    fn bindings(&self) -> Vec<(Chord, Action)>;
+
+    /// Look up the keys pressed so far in this keymap, which may be bound to an action, or else
+    /// begin a chord that is bound to one.
+    fn lookup(&self, keys: &[Key]) -> Lookup {
+        find(&self.bindings(), keys)
+    }
+}
+
+/// Look up the keys pressed so far among the given key bindings.
+fn find(binds: &[(Chord, Action)], keys: &[Key]) -> Lookup {
+    match binds.iter().find(|(chord, _)| chord.0 == keys) {
+        Some((_, act)) => Lookup::Bound(*act),
+        None if binds.iter().any(|(chord, _)| chord.extends(keys)) => Lookup::Prefix,
+        None => Lookup::Unbound,
+    }
+}
+
+/// Look up the keys pressed so far in the given keymaps, the first of which to bind them (or
+/// to begin a chord with them) takes precedence over the rest.
+pub fn lookup<'a, M: Deref<Target = dyn KeyMap + 'a>>(maps: &[M], keys: &[Key]) -> Lookup {
+    (maps.iter())
+        .map(|map| map.lookup(keys))
+        .find(|res| *res != Lookup::Unbound)
+        .unwrap_or(Lookup::Unbound)
+}
+
+/// Fetch the key bindings in effect in the given keymaps, the first of which takes precedence
+/// over the rest, leaving out those of a keymap that are bound (or begin a chord) in a keymap
+/// that takes precedence over it, but including the insertion of the characters typed (if a
+/// keymap inserts them), which is listed under “any character”.
+pub fn listing<'a, M: Deref<Target = dyn KeyMap + 'a>>(maps: &[M]) -> Vec<(String, Action)> {
+    let mut rows = vec![];
+    for (i, map) in maps.iter().enumerate() {
+        let chr = Key::plain(KeyCode::Char('a'));
+        if map.lookup(&[chr]) == Lookup::Bound(Action::Insert('a')) {
+            rows.push(("any character".to_string(), Action::Insert('a')));
+        }
+        for (chord, act) in map.bindings() {
+            if maps[..i]
+                .iter()
+                .all(|pre| pre.lookup(&chord.0) == Lookup::Unbound)
+            {
+                rows.push((chord.to_string(), act));
+            }
+        }
+    }
+    rows
+}
+
+/// Priority of the keymaps of the [set of key bindings][KeySet] chosen in the settings, relative
+/// to which each of the [layers][Layers] registered with the editor either takes precedence (if
+/// its priority is higher) or serves as a fallback (if not).
+pub const BUILTIN: i32 = 0;
+
+/// Keymaps registered as layers of the editor (by an application that embeds it, say), each with
+/// a priority, which are stacked along with the keymaps of the [set of key bindings][KeySet]
+/// chosen, such that a layer of higher priority takes precedence over one of lower priority.
+#[derive(Default)]
+pub struct Layers {
+    /// Keymaps along with their priorities, from the highest priority to the lowest (and in the
+    /// order in which they were registered, among those of the same priority).
+    ents: Vec<(i32, Box<dyn KeyMap>)>,
+}
+
+impl Layers {
+    /// Create a new instance of an empty stack of layers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the given keymap as a layer with the given priority, beneath the layers of the
+    /// same priority registered before it.
+    pub fn register(&mut self, prio: i32, map: Box<dyn KeyMap>) {
+        let idx = self.ents.partition_point(|(p, _)| *p >= prio);
+        self.ents.insert(idx, (prio, map));
+    }
+
+    /// Fetch the stack of keymaps formed by these layers along with the given built-in keymaps
+    /// (of [priority][BUILTIN] zero), the one that takes precedence first.
+    pub fn stack<'a>(&'a self, builtin: &'a [Box<dyn KeyMap>]) -> Vec<&'a dyn KeyMap> {
+        let above = self.ents.iter().filter(|(prio, _)| *prio > BUILTIN);
+        let below = self.ents.iter().filter(|(prio, _)| *prio <= BUILTIN);
+        (above.map(|(_, map)| map.as_ref()))
+            .chain(builtin.iter().map(|map| map.as_ref()))
+            .chain(below.map(|(_, map)| map.as_ref()))
+            .collect()
+    }
+}
+
+/// Standard keymap, which inserts each character typed (without Ctrl or Alt), and binds the
+/// usual keys for moving the cursor, along with Ctrl and Alt shortcuts for editing.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefKeyMap;
+
+/// Keymap that binds the shortcuts of Emacs (Ctrl+A, Ctrl+E, Alt+F and so on), which take
+/// precedence over the [standard ones][DefKeyMap] that they are layered on top of.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EmacsKeyMap;
+
+impl Key {
+    /// Create a new instance of a key pressed with the given modifiers held down.
+    pub fn new(code: KeyCode, mods: KeyModifiers) -> Self {
+        Self { code, mods }
+    }
+
+    /// Create a new instance of a key pressed without any modifiers.
+    pub fn plain(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::NONE)
+    }
+
+    /// Create a new instance of a character key pressed with Ctrl held down.
+    pub fn ctrl(chr: char) -> Self {
+        Self::new(KeyCode::Char(chr), KeyModifiers::CONTROL)
+    }
+
+    /// Create a new instance of a character key pressed with Alt held down.
+    pub fn alt(chr: char) -> Self {
+        Self::new(KeyCode::Char(chr), KeyModifiers::ALT)
+    }
+}
+
+impl Chord {
+    /// Check whether this chord begins with the given keys, and goes on beyond them.
+    pub fn extends(&self, keys: &[Key]) -> bool {
+        self.0.len() > keys.len() && self.0.starts_with(keys)
+    }
+}
+
+impl Action {
+    /// Fetch a short description of this action, as it is listed to the user.
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Self::Insert(_) => "Insert the character typed",
+            Self::Submit => "Submit the input, or accept the completion selected",
+            Self::Cancel => "Discard the input",
+            Self::Eof => "Exit if the input is empty, or else delete the character at the cursor",
+            Self::Left => "Move the cursor left",
+            Self::Right => "Move the cursor right",
+            Self::Up => "Move the cursor up, or recall the previous input",
+            Self::Down => "Move the cursor down, or recall the next input",
+            Self::WordLeft => "Move the cursor to the start of the word",
+            Self::WordRight => "Move the cursor to the end of the word",
+            Self::Home => "Move the cursor to the start of the line",
+            Self::End => "Move the cursor to the end of the line",
+            Self::Backspace => "Delete the character before the cursor",
+            Self::Delete => "Delete the character at the cursor",
+            Self::Transpose => "Swap the characters around the cursor",
+            Self::KillToEnd => "Cut the text from the cursor to the end of the line",
+            Self::KillToStart => "Cut the text from the start of the line to the cursor",
+            Self::Yank => "Paste the text cut latest",
+            Self::YankPop => "Replace the text just pasted with the text cut before it",
+            Self::Undo => "Undo the latest edits",
+            Self::Redo => "Redo the latest edits undone",
+            Self::Complete => "Show the completions of the word, or select the next one",
+            Self::CompletePrev => "Select the previous completion",
+            Self::Dismiss => "Close the menu of completions",
+            Self::GrowPanel => "Widen the side panel",
+            Self::ShrinkPanel => "Narrow the side panel, or hide it",
+            Self::PageUp => "Scroll back through earlier output",
+            Self::PageDown => "Scroll forward through later output",
+            Self::Inspect => "Show the type of the expression under the cursor",
+            Self::NextHole => "Move to the next hole",
+            Self::PrevHole => "Move to the previous hole",
+            Self::Refine => "Show the terms that the hole may be filled with",
+            Self::Details => "Expand or collapse the details of the latest error",
+        }
+    }
+}
+
+impl From<Key> for Chord {
+    fn from(key: Key) -> Self {
+        Self(vec![key])
+    }
+}
+
+impl From<KeyEvent> for Key {
+    fn from(evt: KeyEvent) -> Self {
+        match evt.code {
+            // the character typed is already in upper case
+            KeyCode::Char(_) if evt.modifiers == KeyModifiers::SHIFT => Self::plain(evt.code),
+            // as is the back tab
+            KeyCode::BackTab => Self::plain(evt.code),
+            code => Self::new(code, evt.modifiers),
+        }
+    }
+}
+
+impl KeySet {
+    /// Every set of key bindings, in the order they are listed to the user.
+    pub const ALL: [KeySet; 2] = [KeySet::Standard, KeySet::Emacs];
+
+    /// Fetch the keymaps of this set, the one that takes precedence first.
+    pub fn keymaps(&self) -> Vec<Box<dyn KeyMap>> {
+        match self {
+            Self::Standard => vec![Box::new(DefKeyMap)],
+            Self::Emacs => vec![Box::new(EmacsKeyMap), Box::new(DefKeyMap)],
+        }
+    }
+}
+
+impl KeyMap for DefKeyMap {
+    fn bindings(&self) -> Vec<(Chord, Action)> {
+        vec![
+            (Key::plain(KeyCode::Enter).into(), Action::Submit),
+            (Key::ctrl('c').into(), Action::Cancel),
+            (Key::ctrl('d').into(), Action::Eof),
+            (Key::plain(KeyCode::Left).into(), Action::Left),
+            (Key::plain(KeyCode::Right).into(), Action::Right),
+            (Key::plain(KeyCode::Up).into(), Action::Up),
+            (Key::plain(KeyCode::Down).into(), Action::Down),
+            (
+                Key::new(KeyCode::Left, KeyModifiers::CONTROL).into(),
+                Action::WordLeft,
+            ),
+            (
+                Key::new(KeyCode::Right, KeyModifiers::CONTROL).into(),
+                Action::WordRight,
+            ),
+            (Key::plain(KeyCode::Home).into(), Action::Home),
+            (Key::plain(KeyCode::End).into(), Action::End),
+            (Key::plain(KeyCode::PageUp).into(), Action::PageUp),
+            (Key::plain(KeyCode::PageDown).into(), Action::PageDown),
+            (Key::plain(KeyCode::Backspace).into(), Action::Backspace),
+            (Key::plain(KeyCode::Delete).into(), Action::Delete),
+            (Key::ctrl('k').into(), Action::KillToEnd),
+            (Key::ctrl('u').into(), Action::KillToStart),
+            (Key::ctrl('v').into(), Action::Yank),
+            (Key::alt('v').into(), Action::YankPop),
+            (Key::ctrl('z').into(), Action::Undo),
+            (Key::ctrl('y').into(), Action::Redo),
+            (Key::plain(KeyCode::Tab).into(), Action::Complete),
+            (Key::plain(KeyCode::BackTab).into(), Action::CompletePrev),
+            (Key::plain(KeyCode::Esc).into(), Action::Dismiss),
+            (
+                Key::new(KeyCode::Left, KeyModifiers::ALT).into(),
+                Action::GrowPanel,
+            ),
+            (
+                Key::new(KeyCode::Right, KeyModifiers::ALT).into(),
+                Action::ShrinkPanel,
+            ),
+            (Key::alt('t').into(), Action::Inspect),
+            (Key::alt('n').into(), Action::NextHole),
+            (Key::alt('p').into(), Action::PrevHole),
+            (Key::alt('r').into(), Action::Refine),
+            (Key::alt('e').into(), Action::Details),
+        ]
+    }
+
+    fn lookup(&self, keys: &[Key]) -> Lookup {
+        match keys {
+            [Key {
+                code: KeyCode::Char(chr),
+                mods,
+            }] if mods.difference(KeyModifiers::SHIFT).is_empty() => {
+                Lookup::Bound(Action::Insert(*chr))
+            }
+            _ => find(&self.bindings(), keys),
+        }
+    }
+}
+
+impl KeyMap for EmacsKeyMap {
+    fn bindings(&self) -> Vec<(Chord, Action)> {
+        vec![
+            (Key::ctrl('a').into(), Action::Home),
+            (Key::ctrl('e').into(), Action::End),
+            (Key::ctrl('f').into(), Action::Right),
+            (Key::ctrl('b').into(), Action::Left),
+            (Key::ctrl('n').into(), Action::Down),
+            (Key::ctrl('p').into(), Action::Up),
+            (Key::alt('f').into(), Action::WordRight),
+            (Key::alt('b').into(), Action::WordLeft),
+            (Key::ctrl('d').into(), Action::Eof),
+            (Key::ctrl('h').into(), Action::Backspace),
+            (Key::ctrl('t').into(), Action::Transpose),
+            (Key::ctrl('k').into(), Action::KillToEnd),
+            (Key::ctrl('u').into(), Action::KillToStart),
+            (Key::ctrl('y').into(), Action::Yank),
+            (Key::alt('y').into(), Action::YankPop),
+            (Key::ctrl('_').into(), Action::Undo),
+            (
+                Chord(vec![Key::ctrl('x'), Key::plain(KeyCode::Char('u'))]),
+                Action::Undo,
+            ),
+            (
+                Chord(vec![Key::ctrl('x'), Key::plain(KeyCode::Char('r'))]),
+                Action::Redo,
+            ),
+            (Key::ctrl('g').into(), Action::Dismiss),
+        ]
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.mods.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.mods.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        if self.mods.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(chr) if self.mods.is_empty() => write!(f, "{}", chr),
+            KeyCode::Char(chr) => write!(f, "{}", chr.to_uppercase()),
+            KeyCode::BackTab => write!(f, "Shift+Tab"),
+            KeyCode::PageUp => write!(f, "PgUp"),
+            KeyCode::PageDown => write!(f, "PgDn"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Insert(_) => "insert",
+            Self::Submit => "submit",
+            Self::Cancel => "cancel",
+            Self::Eof => "eof",
+            Self::Left => "left",
+            Self::Right => "right",
+            Self::Up => "up",
+            Self::Down => "down",
+            Self::WordLeft => "word-left",
+            Self::WordRight => "word-right",
+            Self::Home => "home",
+            Self::End => "end",
+            Self::Backspace => "backspace",
+            Self::Delete => "delete",
+            Self::Transpose => "transpose",
+            Self::KillToEnd => "kill-to-end",
+            Self::KillToStart => "kill-to-start",
+            Self::Yank => "yank",
+            Self::YankPop => "yank-pop",
+            Self::Undo => "undo",
+            Self::Redo => "redo",
+            Self::Complete => "complete",
+            Self::CompletePrev => "complete-prev",
+            Self::Dismiss => "dismiss",
+            Self::GrowPanel => "grow-panel",
+            Self::ShrinkPanel => "shrink-panel",
+            Self::PageUp => "page-up",
+            Self::PageDown => "page-down",
+            Self::Inspect => "inspect",
+            Self::NextHole => "next-hole",
+            Self::PrevHole => "prev-hole",
+            Self::Refine => "refine",
+            Self::Details => "details",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Display for Chord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let keys: Vec<String> = self.0.iter().map(Key::to_string).collect();
+        write!(f, "{}", keys.join(" "))
+    }
+}
+
+impl Display for KeySet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Standard => write!(f, "standard"),
+            Self::Emacs => write!(f, "emacs"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_key_0001() {
+        let action = |set: KeySet, key: Key| match lookup(&set.keymaps(), &[key]) {
+            Lookup::Bound(act) => Some(act),
+            _ => None,
+        };
+        let shift = Key::new(KeyCode::Char('A'), KeyModifiers::SHIFT);
+        assert_eq!(action(KeySet::Standard, shift), Some(Action::Insert('A')));
+        assert_eq!(action(KeySet::Standard, Key::ctrl('y')), Some(Action::Redo));
+        assert_eq!(action(KeySet::Standard, Key::ctrl('a')), None);
+        // a character typed with Ctrl is not inserted, even if it is not bound
+        assert_eq!(
+            action(KeySet::Standard, Key::ctrl('c')),
+            Some(Action::Cancel)
+        );
+        assert_eq!(action(KeySet::Standard, Key::ctrl('d')), Some(Action::Eof));
+        assert_eq!(action(KeySet::Standard, Key::ctrl('q')), None);
+        assert_eq!(action(KeySet::Standard, Key::alt('q')), None);
+        // the Emacs bindings take precedence over the standard ones they are layered on
+        assert_eq!(action(KeySet::Emacs, Key::ctrl('y')), Some(Action::Yank));
+        assert_eq!(action(KeySet::Emacs, Key::ctrl('a')), Some(Action::Home));
+        assert_eq!(action(KeySet::Emacs, Key::ctrl('z')), Some(Action::Undo));
+        assert_eq!(action(KeySet::Emacs, Key::ctrl('c')), Some(Action::Cancel));
+        assert_eq!(action(KeySet::Emacs, Key::ctrl('d')), Some(Action::Eof));
+        assert_eq!(action(KeySet::Emacs, Key::alt('x')), None);
+        assert_eq!(Key::ctrl('a').to_string(), "Ctrl+A");
+        assert_eq!(Key::alt('f').to_string(), "Alt+F");
+        assert_eq!(Key::plain(KeyCode::BackTab).to_string(), "Shift+Tab");
+        assert_eq!(Key::plain(KeyCode::Char('x')).to_string(), "x");
+    }
+
+    #[test]
+    fn test_key_0002() {
+        let maps = KeySet::Emacs.keymaps();
+        let (x, u) = (Key::ctrl('x'), Key::plain(KeyCode::Char('u')));
+        assert_eq!(lookup(&maps, &[x]), Lookup::Prefix);
+        assert_eq!(lookup(&maps, &[x, u]), Lookup::Bound(Action::Undo));
+        assert_eq!(lookup(&maps, &[x, Key::ctrl('u')]), Lookup::Unbound);
+        assert_eq!(lookup(&maps, &[u, u]), Lookup::Unbound);
+        assert_eq!(Chord(vec![x, u]).to_string(), "Ctrl+X u");
+        assert_eq!(lookup(&KeySet::Standard.keymaps(), &[x]), Lookup::Unbound);
+    }
+
+    #[test]
+    fn test_key_0003() {
+        struct Fixed(Vec<(Chord, Action)>);
+        impl KeyMap for Fixed {
+            fn bindings(&self) -> Vec<(Chord, Action)> {
+                self.0.clone()
+            }
+        }
+        let mut layers = Layers::new();
+        layers.register(
+            -1,
+            Box::new(Fixed(vec![(Key::ctrl('q').into(), Action::Cancel)])),
+        );
+        layers.register(
+            1,
+            Box::new(Fixed(vec![(Key::ctrl('a').into(), Action::End)])),
+        );
+        layers.register(
+            1,
+            Box::new(Fixed(vec![(Key::ctrl('a').into(), Action::Undo)])),
+        );
+        let builtin = KeySet::Emacs.keymaps();
+        let maps = layers.stack(&builtin);
+        assert_eq!(maps.len(), 5);
+        // a layer of higher priority comes first, as does one registered earlier
+        assert_eq!(lookup(&maps, &[Key::ctrl('a')]), Lookup::Bound(Action::End));
+        assert_eq!(lookup(&maps, &[Key::ctrl('e')]), Lookup::Bound(Action::End));
+        // while a layer of lower priority only binds the keys left unbound
+        assert_eq!(
+            lookup(&maps, &[Key::ctrl('q')]),
+            Lookup::Bound(Action::Cancel)
+        );
+        assert_eq!(
+            lookup(&maps, &[Key::ctrl('z')]),
+            Lookup::Bound(Action::Undo)
+        );
+        let rows = listing(&maps);
+        assert_eq!(rows[0], ("Ctrl+A".to_string(), Action::End));
+        assert_eq!(rows.iter().filter(|(key, _)| key == "Ctrl+A").count(), 1);
+        assert_eq!(rows.last(), Some(&("Ctrl+Q".to_string(), Action::Cancel)));
+    }
+}
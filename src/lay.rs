@@ -0,0 +1,469 @@
+//! Layout of the screen of the IDE, which is split into panes.
+
+use crate::ast::{Ctx, Strategy};
+use crate::buf::Goal;
+use crate::cmd::Settings;
+use crate::key::KeySet;
+use crate::thm::{Paint, Theme};
+use std::time::Duration;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Width of the side panel (including the border that separates it from the main pane) when the
+/// IDE starts, in columns.
+pub const PANEL_WIDTH: u16 = 32;
+
+/// Narrowest that the side panel may be (before it is hidden altogether), in columns.
+pub const PANEL_MIN: u16 = 16;
+
+/// Number of columns by which the side panel is widened or narrowed at a time.
+pub const PANEL_STEP: u16 = 4;
+
+/// Narrowest that the main pane may be, in columns, below which the side panel is not shown.
+pub const MAIN_MIN: u16 = 24;
+
+/// Most lines that the [scrollback][Scrollback] keeps, beyond which the earliest are dropped.
+pub const SCROLLBACK_MAX: usize = 10_000;
+
+/// Most rows that the [details][Details] of a failure take up once they are expanded, including
+/// their heading.
+pub const DETAILS_MAX: usize = 12;
+
+/// Frames of the spinner shown in the [status bar][StatusBar] while a command is evaluated, each
+/// of which is shown for a tenth of a second.
+pub const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// Layout of the screen, which is split into the main pane on the left, which holds the
+/// transcript of the session and the editor, and the side [panel][Panel] on the right.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Layout {
+    /// Width of the side panel (including its border) in columns, or zero if it is hidden.
+    pub side: u16,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self { side: PANEL_WIDTH }
+    }
+}
+
+impl Layout {
+    /// Create a new instance of the layout, with the side panel of its initial width.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Split a screen of the given width into the widths of the main pane and the side panel,
+    /// which is left out (with a width of zero) if there is no room for it.
+    pub fn split(&self, width: u16) -> (u16, u16) {
+        match self.side {
+            side if side > 0 && width >= MAIN_MIN + side => (width - side, side),
+            _ => (width, 0),
+        }
+    }
+
+    /// Widen the side panel by a step (showing it if it is hidden), narrowing the main pane.
+    pub fn grow(&mut self) {
+        self.side = (self.side + PANEL_STEP).max(PANEL_MIN);
+    }
+
+    /// Narrow the side panel by a step (hiding it once it is too narrow), widening the main pane.
+    pub fn shrink(&mut self) {
+        self.side = match self.side.saturating_sub(PANEL_STEP) {
+            side if side < PANEL_MIN => 0,
+            side => side,
+        };
+    }
+}
+
+/// Side panel of the IDE, which shows the goals (the metavariables yet to be solved) and the
+/// variables bound in the context, along with their types, as of the latest command, and above
+/// them the goal of the hole that the cursor is on (if it is on one).
+#[derive(Debug, Clone, Default)]
+pub struct Panel {
+    /// Goals along with their types, in order of creation.
+    pub goals: Vec<String>,
+    /// Variables bound in the context along with their types, from the latest to the earliest.
+    pub vars: Vec<String>,
+    /// Goal of the hole in the input that the cursor is on, if it is on one.
+    pub goal: Option<Goal>,
+}
+
+impl Panel {
+    /// Create a new instance of the panel, showing the goals and variables of the given context.
+    pub fn new(ctx: &Ctx) -> Self {
+        let goals = (ctx.get_unsolved().into_iter())
+            .filter_map(|id| Some(format!("{} : {}", id, ctx.get_meta_type(id)?)))
+            .collect();
+        let vars = (ctx.iter())
+            .map(|(var, typ)| format!("{} : {}", var, typ))
+            .collect::<Vec<_>>();
+        Self {
+            goals,
+            vars: vars.into_iter().rev().collect(),
+            goal: None,
+        }
+    }
+
+    /// Render the panel as (at most) the given number of lines, none of which is wider than the
+    /// given number of columns, under a heading for the goals and another for the context.
+    ///
+    /// The goals are listed first, since there are usually few of them, and as many of the latest
+    /// variables as fit are listed after them. The hole at the cursor comes before both, with the
+    /// variables bound around it above the line that separates them from its type.
+    pub fn render(&self, cols: usize, rows: usize, thm: &Theme) -> Vec<String> {
+        let head = |text: &str| clip(text, cols).paint(thm.muted).to_string();
+        let none = || clip("  none", cols).paint(thm.muted).to_string();
+        let item = |text: &String| clip(&format!("  {}", text), cols);
+        let mut out = vec![];
+        if let Some(goal) = &self.goal {
+            out.push(head("hole"));
+            out.extend(goal.vars.iter().rev().map(item));
+            out.push(head(&format!("  {}", "─".repeat(cols.saturating_sub(2)))));
+            out.push(item(&goal.typ));
+        }
+        out.push(head("goals"));
+        match self.goals.is_empty() {
+            true => out.push(none()),
+            false => out.extend(self.goals.iter().map(item)),
+        }
+        out.push(head("context"));
+        match self.vars.is_empty() {
+            true => out.push(none()),
+            false => out.extend(self.vars.iter().map(item)),
+        }
+        out.truncate(rows);
+        out
+    }
+}
+
+/// Transcript of the session (the input submitted and the output that follows it), as rendered to
+/// the main pane, which is kept so that it may be scrolled back through once it has scrolled off
+/// the screen.
+#[derive(Debug, Clone, Default)]
+pub struct Scrollback {
+    /// Lines of the transcript, from the earliest to the latest.
+    pub lines: Vec<String>,
+    /// Number of lines by which the transcript is scrolled back from the latest line.
+    pub back: usize,
+}
+
+impl Scrollback {
+    /// Create a new instance of an empty scrollback.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append the lines of the given (rendered) text to the transcript, dropping the earliest
+    /// lines if it has grown too long.
+    pub fn push(&mut self, text: &str) {
+        self.lines.extend(text.lines().map(String::from));
+        let over = self.lines.len().saturating_sub(SCROLLBACK_MAX);
+        self.lines.drain(..over);
+    }
+
+    /// Scroll back by a page of the given number of rows (but not past the earliest line),
+    /// returning whether the transcript was scrolled at all.
+    pub fn page_up(&mut self, rows: usize) -> bool {
+        let max = self.lines.len().saturating_sub(rows);
+        let back = (self.back + rows.max(1)).min(max);
+        std::mem::replace(&mut self.back, back) != back
+    }
+
+    /// Scroll forward by a page of the given number of rows (but not past the latest line),
+    /// returning whether the transcript was scrolled at all.
+    pub fn page_down(&mut self, rows: usize) -> bool {
+        let back = self.back.saturating_sub(rows.max(1));
+        std::mem::replace(&mut self.back, back) != back
+    }
+
+    /// Fetch the window of the given number of rows onto the transcript, as scrolled back, which
+    /// is padded with empty lines at the top if the transcript is shorter than the window.
+    pub fn window(&self, rows: usize) -> Vec<&str> {
+        let end = self.lines.len() - self.back.min(self.lines.len());
+        let beg = end.saturating_sub(rows);
+        let pad = rows - (end - beg);
+        std::iter::repeat_n("", pad)
+            .chain(self.lines[beg..end].iter().map(String::as_str))
+            .collect()
+    }
+}
+
+/// Details of the latest failure, of which the transcript holds only the first line (along with
+/// a hint on how to see the rest), and which may be expanded into a panel between the transcript
+/// and the input, and collapsed again.
+#[derive(Debug, Clone, Default)]
+pub struct Details {
+    /// Lines of the message of the failure, in full.
+    pub lines: Vec<String>,
+    /// Whether the panel is expanded.
+    pub open: bool,
+}
+
+impl Details {
+    /// Create a new instance of the (collapsed) details of a failure with the given message.
+    pub fn new(msg: &str) -> Self {
+        Self {
+            lines: msg
+                .lines()
+                .map(|line| line.trim_end().to_string())
+                .collect(),
+            open: false,
+        }
+    }
+
+    /// Fetch the summary of the failure that is shown in the transcript, namely the first line of
+    /// its message, followed by a hint that names the given key (if there is one) if there are
+    /// more lines to see.
+    pub fn summary(&self, key: Option<&str>) -> String {
+        let first = self.lines.first().map_or("", String::as_str);
+        match (self.lines.len(), key) {
+            (0 | 1, _) | (_, None) => first.to_string(),
+            (len, Some(key)) => format!("{} (+{} more, {} for details)", first, len - 1, key),
+        }
+    }
+
+    /// Expand the panel if it is collapsed, or else collapse it, returning whether there are any
+    /// details to show.
+    pub fn toggle(&mut self) -> bool {
+        self.open = !self.open && self.lines.len() > 1;
+        self.open
+    }
+
+    /// Fetch the number of rows that the panel takes up, out of at most the given number, which
+    /// is zero unless it is expanded.
+    pub fn height(&self, rows: usize) -> usize {
+        match self.open {
+            true => (self.lines.len() + 1).min(DETAILS_MAX).min(rows),
+            false => 0,
+        }
+    }
+
+    /// Render the panel as (at most) the given number of lines, none of which is wider than the
+    /// given number of columns, under a heading that separates it from the transcript.
+    pub fn render(&self, cols: usize, rows: usize, thm: &Theme) -> Vec<String> {
+        let head = format!("─ details {}", "─".repeat(cols.saturating_sub(10)));
+        let mut out = vec![clip(&head, cols).paint(thm.muted).to_string()];
+        out.extend(self.lines.iter().map(|line| clip(line, cols)));
+        out.truncate(self.height(rows));
+        out
+    }
+}
+
+/// Status bar shown on the bottom row of the screen, with information about the session as of
+/// the latest command.
+#[derive(Debug, Clone, Default)]
+pub struct StatusBar {
+    /// Set of key bindings chosen in the settings of the session.
+    pub keyset: KeySet,
+    /// Number of variables bound in the context (including those that are shadowed).
+    pub defs: usize,
+    /// Reduction strategy chosen in the context.
+    pub stg: Strategy,
+    /// Warning pending until the next input is submitted, if there is one.
+    pub warn: Option<String>,
+    /// Time for which the command under evaluation has run so far, if one is under way.
+    pub busy: Option<Duration>,
+}
+
+impl StatusBar {
+    /// Create a new instance of the status bar, with the information of the given context and
+    /// settings.
+    pub fn new(ctx: &Ctx, set: &Settings) -> Self {
+        let mut bar = Self::default();
+        bar.update(ctx, set);
+        bar
+    }
+
+    /// Update the information of the given context and settings shown in the status bar, keeping
+    /// any warning.
+    pub fn update(&mut self, ctx: &Ctx, set: &Settings) {
+        self.keyset = set.key;
+        self.defs = ctx.iter().count();
+        self.stg = ctx.get_strategy();
+    }
+
+    /// Render the status bar as a line of the given number of columns, showing whether or not the
+    /// input is in escape mode (or else a spinner and the time taken, while a command is under
+    /// evaluation), with the warning pending (if any) on the right.
+    pub fn render(&self, cols: usize, esc: bool, thm: &Theme) -> String {
+        let mode = match self.busy {
+            Some(time) => {
+                let frame = SPINNER[(time.as_millis() / 100) as usize % SPINNER.len()];
+                format!("{} {:.1}s (Ctrl+C interrupts)", frame, time.as_secs_f64())
+            }
+            None if esc => "escape".to_string(),
+            None => "insert".to_string(),
+        };
+        let info = format!(
+            " {} │ {} keymap │ {} definition{} │ {} strategy ",
+            mode,
+            self.keyset,
+            self.defs,
+            if self.defs == 1 { "" } else { "s" },
+            self.stg
+        );
+        let info = clip(&info, cols);
+        let room = cols - info.width();
+        let warn = match &self.warn {
+            Some(warn) if room > 2 => clip(&format!("⚠ {} ", warn), room - 1),
+            _ => String::new(),
+        };
+        let pad = " ".repeat(room - warn.width());
+        format!(
+            "{}{}",
+            format!("{}{}", info, pad).paint_on(thm.muted),
+            warn.paint(thm.warning).paint_on(thm.muted)
+        )
+    }
+}
+
+/// Clip the given line of text so that it is no wider than the given number of columns, marking
+/// the text left out with an ellipsis.
+fn clip(text: &str, cols: usize) -> String {
+    let text = text.replace('\n', " ");
+    if text.width() <= cols {
+        return text;
+    }
+    let mut out = String::new();
+    for grapheme in text.graphemes(true) {
+        if out.width() + grapheme.width() + 1 > cols {
+            break;
+        }
+        out.push_str(grapheme);
+    }
+    if cols > 0 {
+        out.push('…');
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::par::{parse_cmd, tokenize};
+
+    #[test]
+    fn test_lay_0001() {
+        let mut lay = Layout::new();
+        assert_eq!(lay.split(120), (120 - PANEL_WIDTH, PANEL_WIDTH));
+        assert_eq!(
+            lay.split(MAIN_MIN + PANEL_WIDTH - 1),
+            (MAIN_MIN + PANEL_WIDTH - 1, 0)
+        );
+        lay.grow();
+        assert_eq!(lay.side, PANEL_WIDTH + PANEL_STEP);
+        while lay.side > 0 {
+            lay.shrink();
+        }
+        assert_eq!(lay.split(120), (120, 0));
+        lay.grow();
+        assert_eq!(lay.side, PANEL_MIN);
+    }
+
+    #[test]
+    fn test_lay_0002() -> Result<(), Box<dyn std::error::Error>> {
+        let mut ctx = Ctx::new();
+        for src in ["assume A : *", "assume a : A"] {
+            parse_cmd(src, &tokenize(src), &ctx, &Settings::default())?
+                .eval(&mut ctx, &mut Settings::default());
+        }
+        let pnl = Panel::new(&ctx);
+        assert!(pnl.goals.is_empty());
+        assert_eq!(pnl.vars.len(), 2);
+        assert!(pnl.vars[0].starts_with("a : "));
+        let out = pnl.render(12, 10, &Theme::default());
+        assert_eq!(out.len(), 5);
+        assert!(out[1].contains("none"));
+        assert_eq!(out[3], "  a : A");
+        assert!(out.iter().all(|line| line.width() <= 12 + 10)); // allowing for styling
+        assert_eq!(pnl.render(12, 3, &Theme::default()).len(), 3);
+        assert_eq!(clip("  Nat : Type 0", 8), "  Nat :…");
+        // the hole at the cursor comes first, with its type under the variables around it
+        let pnl = Panel {
+            goal: Some(Goal {
+                typ: "A".to_string(),
+                vars: vec!["y : A".to_string(), "x : A".to_string()],
+                fits: vec![],
+            }),
+            ..pnl
+        };
+        let out = pnl.render(12, 10, &Theme::default());
+        assert_eq!(out[1..3], ["  x : A", "  y : A"]);
+        assert!(out[3].contains("──────────"));
+        assert_eq!(out[4], "  A");
+        assert!(out[5].contains("goals"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_lay_0003() {
+        let mut scr = Scrollback::new();
+        assert_eq!(scr.window(2), ["", ""]);
+        assert!(!scr.page_up(2));
+        scr.push("∴ one\r\n≡ two\r\n");
+        scr.push("» three\r\n∴ four\r\n≡ five\r\n");
+        assert_eq!(scr.lines.len(), 5);
+        assert_eq!(scr.window(2), ["∴ four", "≡ five"]);
+        assert!(scr.page_up(2));
+        assert_eq!(scr.window(2), ["≡ two", "» three"]);
+        // scrolling back stops at the earliest line
+        assert!(scr.page_up(2));
+        assert_eq!(scr.window(2), ["∴ one", "≡ two"]);
+        assert!(!scr.page_up(2));
+        assert!(scr.page_down(2));
+        assert!(scr.page_down(2));
+        assert_eq!(scr.back, 0);
+        assert!(!scr.page_down(2));
+        assert_eq!(scr.window(6)[..2], ["", "∴ one"]);
+    }
+
+    #[test]
+    fn test_lay_0004() -> Result<(), Box<dyn std::error::Error>> {
+        let (mut ctx, mut set) = (Ctx::new(), Settings::default());
+        for src in ["assume A : *", "set keymap emacs"] {
+            parse_cmd(src, &tokenize(src), &ctx, &set)?.eval(&mut ctx, &mut set);
+        }
+        let mut bar = StatusBar::new(&ctx, &set);
+        assert_eq!((bar.keyset, bar.defs), (KeySet::Emacs, 1));
+        let out = bar.render(80, true, &Theme::default());
+        assert!(out.contains(" escape │ emacs keymap │ 1 definition │ normal strategy "));
+        bar.warn = Some("history could not be written".to_string());
+        bar.update(&ctx, &set);
+        assert!(bar
+            .render(120, false, &Theme::default())
+            .contains("⚠ history could not be written"));
+        // the warning gives way to the rest when there is no room for it
+        assert!(!bar.render(40, false, &Theme::default()).contains("⚠"));
+        // a spinner and the time taken take the place of the mode while a command is evaluated
+        bar.busy = Some(Duration::from_millis(1300));
+        assert!(bar
+            .render(120, true, &Theme::default())
+            .contains(" ⠸ 1.3s (Ctrl+C interrupts) │ emacs keymap "));
+        Ok(())
+    }
+
+    #[test]
+    fn test_lay_0005() {
+        let mut det = Details::new(":type f a\r\n    = A → A\r\n    ∉ {*, □, Type 2, …}\r\n");
+        assert_eq!(
+            det.summary(Some("Alt+E")),
+            ":type f a (+2 more, Alt+E for details)"
+        );
+        assert_eq!(det.summary(None), ":type f a");
+        assert_eq!(det.height(20), 0);
+        assert!(det.render(20, 20, &Theme::default()).is_empty());
+        // once expanded, the panel shows every line under its heading, as far as there is room
+        assert!(det.toggle());
+        let out = det.render(12, 20, &Theme::default());
+        assert_eq!(out.len(), 4);
+        assert!(out[0].contains("─ details ─"));
+        assert_eq!(out[1..], [":type f a", "    = A → A", "    ∉ {*, □…"]);
+        assert_eq!(det.render(12, 2, &Theme::default()).len(), 2);
+        assert!(!det.toggle());
+        // there is nothing more to show of a message of one line
+        let mut det = Details::new("undo");
+        assert_eq!(det.summary(Some("Alt+E")), "undo");
+        assert!(!det.toggle());
+    }
+}
@@ -1,10 +1,21 @@
 #![feature(iter_intersperse)]
+#![allow(clippy::result_large_err)]
 
 pub mod app;
 pub mod ast;
 pub mod buf;
 pub mod cmd;
+pub mod dir;
 pub mod edt;
 pub mod err;
+pub mod hst;
 pub mod ide;
+pub mod key;
+pub mod lay;
+pub mod log;
+pub mod par;
 pub mod pmt;
+pub mod rcv;
+pub mod thm;
+pub mod wch;
+pub mod xpt;
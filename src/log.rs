@@ -0,0 +1,93 @@
+//! Logging of the decisions made by the parser, the type checker and the reducer, which users may
+//! capture (with increasing detail) to report bugs.
+
+use crate::dir::xdg_dir;
+use std::fs;
+use std::io::{Error, Result};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::level_filters::LevelFilter;
+use tracing::Subscriber;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+
+/// Fetch the most detailed level of the events logged at the given verbosity (the number of times
+/// `-v` is given), namely warnings alone by default, each command parsed and evaluated at one,
+/// and each step of the type checker and each reduction as well at two.
+pub fn level(verbosity: u8) -> LevelFilter {
+    match verbosity {
+        0 => LevelFilter::WARN,
+        1 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    }
+}
+
+/// Fetch the path of the file that the IDE logs to by default (so that logs do not corrupt the
+/// user interface), namely `$XDG_STATE_HOME/fluxo/fluxo.log` (where `$XDG_STATE_HOME` defaults to
+/// `~/.local/state`).
+pub fn default_path() -> Option<PathBuf> {
+    let name = format!("{}.log", env!("CARGO_PKG_NAME"));
+    Some(xdg_dir("XDG_STATE_HOME", ".local/state")?.join(name))
+}
+
+/// Create a subscriber that writes the events logged at the given verbosity to the given writer,
+/// one line each.
+pub fn subscriber(verbosity: u8, writer: BoxMakeWriter) -> impl Subscriber + Send + Sync {
+    tracing_subscriber::fmt()
+        .with_max_level(level(verbosity))
+        .with_writer(writer)
+        .finish()
+}
+
+/// Log the events at the given verbosity for the rest of the process, appending them to the file
+/// at the given path (which is created if it does not exist), or else writing them to standard
+/// error.
+pub fn init(verbosity: u8, path: Option<&Path>) -> Result<()> {
+    let writer = match path {
+        Some(path) => {
+            if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+                fs::create_dir_all(dir)?;
+            }
+            let file = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?;
+            BoxMakeWriter::new(Mutex::new(file))
+        }
+        None => BoxMakeWriter::new(std::io::stderr),
+    };
+    tracing::subscriber::set_global_default(subscriber(verbosity, writer)).map_err(Error::other)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::Ctx;
+    use crate::cmd::Settings;
+    use crate::ide::Session;
+    use crate::par::{parse_cmd, tokenize};
+
+    #[test]
+    fn test_log_0001() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("fluxo-log-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let path = dir.join("fluxo.log");
+        let log = |verbosity| -> Result<String> {
+            let file = fs::File::create(&path)?;
+            let sub = subscriber(verbosity, BoxMakeWriter::new(Mutex::new(file)));
+            tracing::subscriber::with_default(sub, || {
+                let mut ctx = Ctx::new();
+                let src = "type λx : * . x";
+                let cmd = parse_cmd(src, &tokenize(src), &ctx, &Settings::default()).unwrap();
+                Session::new(&ctx).eval(&cmd, &mut ctx);
+            });
+            fs::read_to_string(&path)
+        };
+        assert!(log(0)?.is_empty());
+        let out = log(1)?;
+        assert!(out.contains("parsed command") && out.contains("evaluating command"));
+        assert!(!out.contains("inferring type"));
+        assert!(log(2)?.contains("inferring type"));
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,3061 @@
+//! Parser that converts raw text into [expressions][Exp] in the core fluxo language.
+//!
+//! Parsing happens in two phases: the source text is first split into [tokens][Tok], which are
+//! then assembled into an expression by a recursive-descent parser using the following grammar:
+//!
+//! ```text
+//! exp := bnd | opr ('→' exp)?
+//! bnd := ('λ' | 'Π' | 'Σ') sym ':' exp '.' exp
+//!        | ('λ' | 'Π') '{' sym ':' exp '}' '.' exp
+//!        | 'λ' sym '.' exp
+//!        | 'let' sym ':' exp '=' exp 'in' exp
+//! opr := app (op (opr | bnd))*
+//! app := atm atm* bnd?
+//! atm := sym | num | '*' | '□' | 'Type' num? | '_' | '(' exp (',' exp ':' exp)? ')' | ('fst' | 'snd') atm
+//!        | '⊤' | '⊥' | '(' ')' | ('Sum' | 'inl' | 'inr' | 'absurd') atm atm | 'case' atm atm atm atm
+//!        | 'ℕ' | lit | 'suc' atm | ('add' | 'mul') atm atm | 'Nat_rec' atm atm atm atm
+//!        | '{' sym ':' exp (',' sym ':' exp)* '}' | '{' sym '=' exp (',' sym '=' exp)* '}'
+//!        | atm '.' sym
+//! ```
+//!
+//! A dependent pair `(a, b : Σx : A . B)` carries its type, since the type of the second component
+//! may depend on the first, which cannot be determined from the components alone.
+//!
+//! The type of a variable bound by a λ abstraction may be left out, as in `λx . x`, in which case
+//! it is taken from the type that the abstraction is [checked][Exp::check] against.
+//!
+//! A variable bound in braces, as in `Π{A : *} . A → A`, is bound [implicitly][Exp::Imp], so the
+//! corresponding argument is left out wherever the function is applied.
+//!
+//! Infix operators (`op`) are those declared as [notation][Notation] in the context that the
+//! text is parsed in, which also determines how a chain of operators is grouped. Operators bind
+//! looser than application, but tighter than arrows.
+//!
+//! Every Unicode glyph also has an ASCII spelling, for users who cannot easily type the former:
+//! `\` for `λ`, `forall` or `pi` for `Π`, `exists` or `sigma` for `Σ`, `->` for `→`, `Type` for `*`,
+//! `Kind` for `□`, `Unit` for `⊤`, `Void` for `⊥` and `Nat` for `ℕ`. The two spellings may be freely
+//! mixed within the same expression.
+//!
+//! A `%` followed immediately by digits, as in `%2`, is a symbolic name (rather than an operator
+//! applied to a number), which refers to the result of an earlier command in the interactive
+//! environment.
+//!
+//! Universes form a cumulative hierarchy `Type 0 : Type 1 : Type 2 : …`, where `*` (or `Type`
+//! without a level) is sugar for `Type 0` and `□` (or `Kind`) is sugar for `Type 1`.
+//!
+//! Besides Π and Σ types, the core language has a [unit type][Exp::Unit], an [empty type]
+//! [Exp::Void] and binary [sum types][Exp::Sum], whose keywords take a fixed number of operands.
+//!
+//! A record `{x = a, y = b}` has the record type `{x : A, y : B}`, and its fields are projected
+//! out with `r.x`, where the `.` must not be surrounded by whitespace (so as to tell it apart from
+//! the `.` of a binder, which is written `λx : A . b`).
+//!
+//! Numeric literals such as `42` denote the corresponding [Church numerals][Exp::new_num], whereas
+//! literals with the suffix `n` (`lit`), such as `42n`, denote the [natural numbers][Exp::Nat]
+//! that are built into the language, on which arithmetic is computed using machine integers. A `let`
+//! binding is also syntactic sugar: `let x : A = e in b` stands for `(λx : A . b) e`.
+//!
+//! Comments are permitted wherever whitespace is, either extending to the end of the line
+//! (`-- comment`) or delimited (`{- comment -}`), in which case they may be nested.
+//!
+//! While the user is editing, the contents of the editor buffer are tracked by a token [stream]
+//! [Stream], which re-tokenizes only the region affected by each edit and marks the location of
+//! the cursor with a dedicated [cursor token][Cat::Cur].
+
+use crate::ast::{Ctor, Ctx, Data, Exp, Fixity, Notation, Pos, Strategy, Var, MAX_PREC};
+use crate::cmd::{Cmd, Session, Setting, Settings};
+use crate::err::{Lint, SyntaxErr, TypingErr};
+use crate::key::KeySet;
+use crate::pmt::Gutter;
+use crate::thm::Preset;
+use crate::xpt::Markup;
+use std::iter::Peekable;
+use std::path::PathBuf;
+use std::str::CharIndices;
+
+/// Category of a lexical [token][Tok].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cat {
+    /// Binder for a λ abstraction, `λ`.
+    Abs,
+    /// Binder for a Π type, `Π`.
+    For,
+    /// Binder for a Σ type, `Σ`.
+    Sig,
+    /// Separator between the components of a dependent pair, `,`.
+    Com,
+    /// Keyword for the first projection of a dependent pair, `fst`.
+    Fst,
+    /// Keyword for the second projection of a dependent pair, `snd`.
+    Snd,
+    /// Separator between a bound variable (or a field) and its type, `:`.
+    Col,
+    /// Separator between the head of a binder and its body, or between a record and the field
+    /// projected out of it, `.`.
+    Dot,
+    /// Arrow between the domain and codomain of a non-dependent Π type, `→`.
+    Arr,
+    /// Keyword that introduces a local definition, `let`.
+    Let,
+    /// Separator between the head of a local definition (or a field) and its value, `=`.
+    Eq,
+    /// Keyword that introduces the body of a local definition, `in`.
+    In,
+    /// Opening parenthesis, `(`.
+    Opn,
+    /// Closing parenthesis, `)`.
+    Cls,
+    /// Opening brace around a variable bound implicitly or the fields of a record, `{`.
+    Lbr,
+    /// Closing brace around a variable bound implicitly or the fields of a record, `}`.
+    Rbr,
+    /// The unit type, `⊤`.
+    Unit,
+    /// The empty type, `⊥`.
+    Void,
+    /// Keyword for a sum type, `Sum`.
+    Sum,
+    /// Keyword for the left injection into a sum type, `inl`.
+    Inl,
+    /// Keyword for the right injection into a sum type, `inr`.
+    Inr,
+    /// Keyword for a case analysis on a sum type, `case`.
+    Case,
+    /// Keyword for the elimination of the empty type, `absurd`.
+    Absurd,
+    /// The type of natural numbers, `ℕ`.
+    Nat,
+    /// Literal natural number, such as `42n`, unless it is too large to be represented.
+    Lit(Option<usize>),
+    /// Keyword for the successor of a natural number, `suc`.
+    Suc,
+    /// Keyword for the sum of two natural numbers, `add`.
+    Add,
+    /// Keyword for the product of two natural numbers, `mul`.
+    Mul,
+    /// Keyword for induction on a natural number, `Nat_rec`.
+    NatRec,
+    /// The type of all types, `*`.
+    TypeMeta,
+    /// The type of all kinds, `□`.
+    KindMeta,
+    /// Keyword that denotes a universe, optionally followed by its level, `Type`.
+    Univ,
+    /// Symbolic name, such as a variable.
+    Sym(String),
+    /// Numeric literal.
+    Num(usize),
+    /// Operator, made up of symbolic characters such as `+` or `⊕`.
+    Opr(String),
+    /// String literal, along with whether it is properly terminated.
+    Str(String, bool),
+    /// Placeholder for an expression that is yet to be written, `_`.
+    Hole,
+    /// Character that does not begin any valid token.
+    Unk(char),
+    /// Zero-width marker for the location of the cursor, which is ignored by the parser.
+    Cur,
+    /// Comment, along with whether it is properly terminated, which is ignored by the parser.
+    Rem(bool),
+}
+
+/// Lexical token, along with the range of bytes that it occupies in the source text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tok {
+    /// Category of the token.
+    pub cat: Cat,
+    /// Byte offset of the start of the token (inclusive).
+    pub beg: usize,
+    /// Byte offset of the end of the token (exclusive).
+    pub end: usize,
+}
+
+/// Largest numeric literal permitted, since a Church numeral nests an application for every unit of
+/// its value, each of which takes a level of recursion to type-check and reduce (so the limit is
+/// kept well within the stack of a thread, even in a debug build). Larger numbers are written as
+/// literals of the built-in natural numbers instead, such as `1000n`.
+const MAX_NUM: usize = 100;
+
+/// Region of source text that an [expression][Exp] was parsed from, along with the regions for
+/// each of its sub-expressions.
+///
+/// The sub-expressions are listed in the order in which they are held by the expression (for
+/// instance, the type and then the body of a binder), which is not necessarily the order in
+/// which they appear in the source text. Expressions that are desugared from literals (such as
+/// numerals) have no spans for their sub-expressions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Span {
+    /// Byte offset of the start of the expression (inclusive).
+    pub beg: usize,
+    /// Byte offset of the end of the expression (exclusive).
+    pub end: usize,
+    /// Spans of the sub-expressions.
+    pub subs: Vec<Span>,
+}
+
+/// Expression parsed from source text, along with its [span][Span].
+type Node = (Exp, Span);
+
+/// Outcome of parsing source text that may not be well-formed, such as input that is still
+/// being edited.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseResult {
+    /// Expression recovered from the source text, if any.
+    pub exp: Option<Exp>,
+    /// Span of the expression recovered from the source text, if any.
+    pub span: Option<Span>,
+    /// Syntax errors encountered while parsing, in the order in which they were encountered.
+    pub errs: Vec<SyntaxErr>,
+}
+
+/// Stream of [tokens][Tok] for text that is being edited, such as the contents of an editor
+/// buffer, which is re-tokenized incrementally as the text changes.
+#[derive(Debug, Default, Clone)]
+pub struct Stream {
+    /// Source text that the tokens were produced from.
+    src: String,
+    /// Tokens produced from the source text (excluding the cursor token).
+    toks: Vec<Tok>,
+    /// Byte offset of the cursor within the source text.
+    cur: usize,
+}
+
+/// Lexer that produces [tokens][Tok] from source text, starting at a given byte offset.
+struct Lexer<'a> {
+    /// Remaining characters to be lexed, indexed relative to the starting offset.
+    chars: Peekable<CharIndices<'a>>,
+    /// Byte offset at which lexing started.
+    off: usize,
+    /// Length of the source text.
+    len: usize,
+}
+
+/// Recursive-descent parser over a sequence of [tokens][Tok].
+struct Parser<'a> {
+    /// Tokens being parsed.
+    toks: Vec<Tok>,
+    /// Index of the next token to be consumed.
+    pos: usize,
+    /// Length of the source text, used to locate errors at the end of input.
+    len: usize,
+    /// Syntax errors encountered so far.
+    errs: Vec<SyntaxErr>,
+    /// Context that holds the notation declared for infix operators.
+    ctx: &'a Ctx,
+}
+
+/// Parse the given source text into an [expression][Exp], failing on the first syntax error.
+pub fn parse(src: &str) -> Result<Exp, SyntaxErr> {
+    parse_partial(src).into_result()
+}
+
+/// Parse the given source text into an [expression][Exp] using the notation declared in the
+/// given context, failing on the first syntax error.
+pub fn parse_in(src: &str, ctx: &Ctx) -> Result<Exp, SyntaxErr> {
+    parse_tokens(&tokenize(src), src.len(), ctx).into_result()
+}
+
+/// Parse the given source text into an [expression][Exp] along with its [span][Span], using the
+/// notation declared in the given context and failing on the first syntax error.
+pub fn parse_spanned(src: &str, ctx: &Ctx) -> Result<(Exp, Span), SyntaxErr> {
+    parse_tokens(&tokenize(src), src.len(), ctx).into_spanned()
+}
+
+/// Parse the given source text into an [expression][Exp], recovering from syntax errors.
+///
+/// Where the intent of the input is unambiguous, such as a missing closing parenthesis or
+/// separator, or a stray character, the error is recorded and parsing continues as though the
+/// input had been repaired. Where a required sub-expression is missing altogether, the enclosing
+/// expression cannot be recovered and is omitted from the result.
+pub fn parse_partial(src: &str) -> ParseResult {
+    parse_tokens(&tokenize(src), src.len(), &Ctx::new())
+}
+
+/// Parse a sequence of [tokens][Tok] produced from source text of the given length, using the
+/// notation declared in the given context and recovering from syntax errors as described for
+/// [parse_partial].
+pub fn parse_tokens(toks: &[Tok], len: usize, ctx: &Ctx) -> ParseResult {
+    let mut par = Parser::new(toks, len, ctx);
+    let exp = par.exp();
+    if let Some(tok) = par.peek().cloned() {
+        par.unexpected::<()>(&tok, "end of input");
+    }
+    let (exp, span) = exp.unzip();
+    ParseResult {
+        exp,
+        span,
+        errs: par.errs,
+    }
+}
+
+/// Parse the given source text (along with the tokens produced from it) into a [command][Cmd]
+/// using the notation declared in the given context and the aliases declared in the given
+/// settings, failing on the first syntax error, which
+/// reports its location within the source text (unlike [`parse_cmd_unlocated`], for callers that
+/// report it themselves).
+///
+/// A command consists of a keyword, optionally followed by arguments:
+///
+/// ```text
+/// cmd := ('exit' | 'quit')
+///      | 'help' key?
+///      | ('show' | 'type' | 'whnf' | 'time' | 'exec') exp
+///      | 'step' exp?
+///      | 'trace' exp
+///      | 'eq' atm exp
+///      | 'export' ('latex' | 'html') exp
+///      | ('infixl' | 'infixr' | 'infix') num str ':' '=' sym
+///      | 'data' sym ':' '=' (cns ('|' cns)*)?
+///      | 'set' 'strategy' ('normal' | 'cbv' | 'whnf')
+///      | 'set' 'fuel' num
+///      | 'set' 'gutter' ('prompts' | 'numbers')
+///      | 'set' 'keymap' ('standard' | 'emacs')
+///      | 'set' 'theme' ('dark' | 'light' | 'high-contrast')
+///      | 'set' 'ascii' ('on' | 'off')
+///      | 'set' 'warnings' ('on' | 'off')
+///      | 'session' ('save' | 'load') str
+///      | 'session' 'recover'
+///      | 'load' str
+///      | 'import' sym
+///      | 'alias' sym '=' tok+
+///      | 'env' num?
+///      | 'search' exp
+///      | 'undo' | 'reset'
+///      | 'bindings'
+///      | 'open' sym ('.' sym)*
+///      | 'let' sym ('.' sym)* ':' exp '=' exp
+///      | 'assume' sym ('.' sym)* ':' exp
+/// cns := sym atm*
+/// ```
+pub fn parse_cmd(src: &str, toks: &[Tok], ctx: &Ctx, set: &Settings) -> Result<Cmd, SyntaxErr> {
+    parse_cmd_unlocated(src, toks, ctx, set).map_err(|err| {
+        let (row, col) = locate(src, err.beg);
+        SyntaxErr {
+            msg: format!("{} (line {}, column {})", err.msg, row, col),
+            ..err
+        }
+    })
+}
+
+/// Parse the given source text (along with the tokens produced from it) into a [command][Cmd]
+/// like [`parse_cmd`], but without the location of the syntax error it fails on in its message,
+/// for callers (such as [diagnostics][crate::err::Diagnostic]) that point at it on their own.
+pub fn parse_cmd_unlocated(
+    src: &str,
+    toks: &[Tok],
+    ctx: &Ctx,
+    set: &Settings,
+) -> Result<Cmd, SyntaxErr> {
+    let toks: Vec<Tok> = toks
+        .iter()
+        .filter(|tok| !matches!(tok.cat, Cat::Cur | Cat::Rem(true)))
+        .cloned()
+        .collect();
+    if let Some((cmd, key, len)) = expand_alias(src, &toks, set) {
+        // the error is located within the source text, on the alias if it lies in what it stands for
+        let pos = |pos: usize| pos.saturating_sub(len) + key.end;
+        return parse_cmd_unlocated(&cmd, &tokenize(&cmd), ctx, set).map_err(|err| SyntaxErr {
+            beg: if err.beg < len { key.beg } else { pos(err.beg) },
+            end: pos(err.end),
+            ..err
+        });
+    }
+    let exp = |args: &[Tok]| parse_tokens(args, src.len(), ctx).into_result();
+    let res = match toks.split_first() {
+        None => Ok(Cmd::Noop),
+        Some((key, args)) => match cmd_for_key(key) {
+            Ok(Cmd::Exit) => parse_tokens_end(args).map(|_| Cmd::Exit),
+            Ok(Cmd::Undo) => parse_tokens_end(args).map(|_| Cmd::Undo),
+            Ok(Cmd::Reset) => parse_tokens_end(args).map(|_| Cmd::Reset),
+            Ok(Cmd::Bindings) => parse_tokens_end(args).map(|_| Cmd::Bindings),
+            Ok(Cmd::Help(_)) => match args.split_first() {
+                None => Ok(Cmd::Help(None)),
+                Some((key, args)) => cmd_for_key(key)
+                    .and_then(|cmd| parse_tokens_end(args).map(|_| Cmd::Help(Some(Box::new(cmd))))),
+            },
+            Ok(Cmd::Show(_)) => exp(args).map(Cmd::Show),
+            Ok(Cmd::Type(_)) => exp(args).map(Cmd::Type),
+            Ok(Cmd::Whnf(_)) => exp(args).map(Cmd::Whnf),
+            Ok(Cmd::Time(_)) => exp(args).map(Cmd::Time),
+            Ok(Cmd::Search(_)) => exp(args).map(Cmd::Search),
+            Ok(Cmd::Step(_)) if args.is_empty() => Ok(Cmd::Step(None)),
+            Ok(Cmd::Step(_)) => exp(args).map(|exp| Cmd::Step(Some(exp))),
+            Ok(Cmd::Trace(_)) => exp(args).map(Cmd::Trace),
+            Ok(Cmd::Eq(..)) => {
+                parse_eq(args, src.len(), ctx).map(|((fst, _), (snd, _))| Cmd::Eq(fst, snd))
+            }
+            Ok(Cmd::Export(..)) => {
+                parse_export(args, src.len(), ctx).map(|(mrk, (exp, _))| Cmd::Export(mrk, exp))
+            }
+            Ok(Cmd::Exec(_)) => exp(args).map(Cmd::Exec),
+            Ok(Cmd::Note(ntn)) => parse_notation(ntn.fix, args, src.len()).map(Cmd::Note),
+            Ok(Cmd::Data(_)) => parse_data(args, src.len(), ctx).map(Cmd::Data),
+            Ok(Cmd::Set(_)) => parse_setting(args, src).map(Cmd::Set),
+            Ok(Cmd::Let(..)) => parse_let(args, src.len(), ctx)
+                .map(|(var, (typ, _), (val, _))| Cmd::Let(var, typ, val)),
+            Ok(Cmd::Assume(..)) => {
+                parse_assume(args, src.len(), ctx).map(|(var, (typ, _))| Cmd::Assume(var, typ))
+            }
+            Ok(Cmd::Env(_)) => match args.split_first() {
+                None => Ok(Cmd::Env(1)),
+                Some((
+                    Tok {
+                        cat: Cat::Num(num), ..
+                    },
+                    rest,
+                )) if *num > 0 => parse_tokens_end(rest).map(|_| Cmd::Env(*num)),
+                Some((tok, _)) => Err(expected("a page number", Some(tok), src.len())),
+            },
+            Ok(Cmd::Load(_)) => parse_path(args, src.len()).map(Cmd::Load),
+            Ok(Cmd::Import(_)) => {
+                parse_name(args, src.len(), "a library").and_then(|(lib, rest)| {
+                    parse_tokens_end(rest)?;
+                    Ok(Cmd::Import(lib))
+                })
+            }
+            Ok(Cmd::Open(_)) => parse_namespace(args, src.len()).map(Cmd::Open),
+            Ok(Cmd::Alias(..)) => parse_alias(src, args).map(|(name, cmd)| Cmd::Alias(name, cmd)),
+            Ok(Cmd::Session(_)) => parse_session(args, src.len()).map(Cmd::Session),
+            Ok(_) | Err(_) => Err(SyntaxErr::new(
+                key.beg,
+                key.end,
+                &format!("unknown command {}", key.cat),
+            )),
+        },
+    };
+    match &res {
+        Ok(cmd) => tracing::debug!(src, ?cmd, "parsed command"),
+        Err(err) => tracing::debug!(src, %err, "rejected command"),
+    }
+    res
+}
+
+/// Parse the expression that the command in the given source text (tokenized as given) takes,
+/// along with the [span][Span] of source text that it was parsed from, unless the command does
+/// not take a single expression.
+pub fn parse_cmd_exp(src: &str, toks: &[Tok], ctx: &Ctx) -> Option<Result<(Exp, Span), SyntaxErr>> {
+    let toks: Vec<Tok> = toks
+        .iter()
+        .filter(|tok| !matches!(tok.cat, Cat::Cur | Cat::Rem(true)))
+        .cloned()
+        .collect();
+    let (key, args) = toks.split_first()?;
+    match cmd_for_key(key).ok()? {
+        Cmd::Show(_)
+        | Cmd::Type(_)
+        | Cmd::Whnf(_)
+        | Cmd::Time(_)
+        | Cmd::Search(_)
+        | Cmd::Step(_)
+        | Cmd::Trace(_)
+        | Cmd::Exec(_)
+            if !args.is_empty() =>
+        {
+            Some(parse_tokens(args, src.len(), ctx).into_spanned())
+        }
+        _ => None,
+    }
+}
+
+/// Find the range of bytes of the given source text (tokenized as given) that the given error,
+/// raised by the command in the source text, is about, namely the [culprit][TypingErr::culprit]
+/// of the error within the expressions that the command takes, unless it is not found there.
+///
+/// The culprit is [blamed][Span::blame] on the expressions that fail to type-check on their own
+/// (such as the value of a definition whose type is well-formed) before the others.
+pub fn locate_err(src: &str, toks: &[Tok], ctx: &Ctx, err: &TypingErr) -> Option<(usize, usize)> {
+    if let TypingErr::SyntaxErr(e) = err {
+        return Some((e.beg, e.end));
+    }
+    locate_exp(src, toks, ctx, &err.culprit()?)
+}
+
+/// Find the range of bytes of the given source text (tokenized as given) that the given warning
+/// about the command in the source text is about, namely the [culprit][Lint::culprit] of the
+/// warning within the expressions that the command takes, unless it is about the command as a
+/// whole (or is not found there).
+pub fn locate_lint(src: &str, toks: &[Tok], ctx: &Ctx, lint: &Lint) -> Option<(usize, usize)> {
+    locate_exp(src, toks, ctx, lint.culprit()?)
+}
+
+/// Find the range of bytes of the given source text (tokenized as given) that the given target
+/// is [blamed][Span::blame] on, within the expressions that the command in the source text takes.
+fn locate_exp(src: &str, toks: &[Tok], ctx: &Ctx, tgt: &Exp) -> Option<(usize, usize)> {
+    let ctx = ctx.isolated(); // so that elaborating the expressions again leaves no metavariables
+    let exps = parse_cmd_exps(src, toks, &ctx);
+    let span = Span::blame_any(exps.iter().map(|(exp, span)| (exp, span, ctx.clone())), tgt)?;
+    Some((span.beg, span.end))
+}
+
+/// Parse the expressions that the command in the given source text (tokenized as given) takes,
+/// along with the [spans][Span] of source text that they were parsed from, in the order in which
+/// they appear, none of which are parsed if the command takes none (or is malformed).
+pub fn parse_cmd_exps(src: &str, toks: &[Tok], ctx: &Ctx) -> Vec<(Exp, Span)> {
+    if let Some(res) = parse_cmd_exp(src, toks, ctx) {
+        return res.into_iter().collect();
+    }
+    let toks: Vec<Tok> = toks
+        .iter()
+        .filter(|tok| !matches!(tok.cat, Cat::Cur | Cat::Rem(true)))
+        .cloned()
+        .collect();
+    let Some((key, args)) = toks.split_first() else {
+        return vec![];
+    };
+    let res = match cmd_for_key(key) {
+        Ok(Cmd::Eq(..)) => parse_eq(args, src.len(), ctx).map(|(fst, snd)| vec![fst, snd]),
+        Ok(Cmd::Export(..)) => parse_export(args, src.len(), ctx).map(|(_, exp)| vec![exp]),
+        Ok(Cmd::Let(..)) => parse_let(args, src.len(), ctx).map(|(_, typ, val)| vec![typ, val]),
+        Ok(Cmd::Assume(..)) => parse_assume(args, src.len(), ctx).map(|(_, typ)| vec![typ]),
+        _ => Ok(vec![]),
+    };
+    res.unwrap_or_default()
+}
+
+/// Keywords that begin each command, which are offered as completions at the start of the input.
+pub const COMMANDS: [&str; 29] = [
+    "alias", "assume", "bindings", "data", "env", "eq", "exec", "exit", "export", "help", "import",
+    "infix", "infixl", "infixr", "let", "load", "open", "quit", "reset", "search", "session",
+    "set", "show", "step", "time", "trace", "type", "undo", "whnf",
+];
+
+/// Keywords of expressions that are spelled with letters, which are offered as completions within
+/// an expression.
+pub const KEYWORDS: [&str; 22] = [
+    "Kind", "Nat", "Nat_rec", "Sum", "Type", "Unit", "Void", "absurd", "add", "case", "exists",
+    "forall", "fst", "in", "inl", "inr", "let", "mul", "pi", "sigma", "snd", "suc",
+];
+
+/// Fetch the command (with placeholder arguments) denoted by the given keyword token.
+fn cmd_for_key(key: &Tok) -> Result<Cmd, SyntaxErr> {
+    match &key.cat {
+        Cat::Sym(sym) => match sym.as_str() {
+            "exit" | "quit" => Ok(Cmd::Exit),
+            "help" => Ok(Cmd::Help(None)),
+            "show" => Ok(Cmd::Show(Default::default())),
+            "type" => Ok(Cmd::Type(Default::default())),
+            "whnf" => Ok(Cmd::Whnf(Default::default())),
+            "time" => Ok(Cmd::Time(Default::default())),
+            "step" => Ok(Cmd::Step(None)),
+            "trace" => Ok(Cmd::Trace(Default::default())),
+            "eq" => Ok(Cmd::Eq(Default::default(), Default::default())),
+            "export" => Ok(Cmd::Export(Default::default(), Default::default())),
+            "exec" => Ok(Cmd::Exec(Default::default())),
+            "data" => Ok(Cmd::Data(Default::default())),
+            "set" => Ok(Cmd::Set(Default::default())),
+            "session" => Ok(Cmd::Session(Default::default())),
+            "open" => Ok(Cmd::Open(Default::default())),
+            "alias" => Ok(Cmd::Alias(Default::default(), Default::default())),
+            "load" => Ok(Cmd::Load(Default::default())),
+            "import" => Ok(Cmd::Import(Default::default())),
+            "env" => Ok(Cmd::Env(1)),
+            "search" => Ok(Cmd::Search(Default::default())),
+            "undo" => Ok(Cmd::Undo),
+            "reset" => Ok(Cmd::Reset),
+            "bindings" => Ok(Cmd::Bindings),
+            "assume" => Ok(Cmd::Assume(Default::default(), Default::default())),
+            "infixl" => Ok(Cmd::Note(Notation::new(
+                Fixity::Infixl,
+                0,
+                "",
+                Var::default(),
+            ))),
+            "infixr" => Ok(Cmd::Note(Notation::new(
+                Fixity::Infixr,
+                0,
+                "",
+                Var::default(),
+            ))),
+            "infix" => Ok(Cmd::Note(Notation::new(
+                Fixity::Infix,
+                0,
+                "",
+                Var::default(),
+            ))),
+            _ => Err(SyntaxErr::new(
+                key.beg,
+                key.end,
+                &format!("unknown command {}", key.cat),
+            )),
+        },
+        Cat::Let => Ok(Cmd::Let(
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        )),
+        _ => Err(SyntaxErr::new(
+            key.beg,
+            key.end,
+            &format!("expected a command, found {}", key.cat),
+        )),
+    }
+}
+
+/// Parse the arguments of a declaration of [notation][Notation] with the given associativity.
+fn parse_notation(fix: Fixity, args: &[Tok], len: usize) -> Result<Notation, SyntaxErr> {
+    let mut toks = args.iter();
+    let mut next = |what: &str| {
+        toks.next().cloned().ok_or_else(|| {
+            let msg = format!("expected {}, found end of input", what);
+            SyntaxErr::new(len, len, &msg)
+        })
+    };
+    let unexpected = |tok: &Tok, what: &str| {
+        let msg = format!("expected {}, found {}", what, tok.cat);
+        SyntaxErr::new(tok.beg, tok.end, &msg)
+    };
+    let tok = next("a precedence")?;
+    let prec = match tok.cat {
+        Cat::Num(num) if num <= MAX_PREC => num,
+        _ => {
+            return Err(unexpected(
+                &tok,
+                &format!("a precedence from 0 to {}", MAX_PREC),
+            ))
+        }
+    };
+    let tok = next("an operator")?;
+    let opr = match &tok.cat {
+        Cat::Str(opr, true) => match tokenize(opr).as_slice() {
+            [Tok {
+                cat: Cat::Opr(sym), ..
+            }] if sym == opr => opr.clone(),
+            _ => {
+                let msg = format!("expected a symbolic operator, found “{}”", opr);
+                return Err(SyntaxErr::new(tok.beg, tok.end, &msg));
+            }
+        },
+        _ => return Err(unexpected(&tok, "an operator in quotes")),
+    };
+    for cat in [Cat::Col, Cat::Eq] {
+        let tok = next("‘:=’")?;
+        if tok.cat != cat {
+            return Err(unexpected(&tok, "‘:=’"));
+        }
+    }
+    let tok = next("a variable")?;
+    let var = match tok.cat {
+        Cat::Sym(sym) => Var(sym),
+        _ => return Err(unexpected(&tok, "a variable")),
+    };
+    parse_tokens_end(toks.as_slice())?;
+    Ok(Notation::new(fix, prec, &opr, var))
+}
+
+/// Parse the arguments of a declaration of an inductive [data type][Data], whose constructors are
+/// separated by `|` and each consist of a name followed by the (atomic) types of its arguments.
+fn parse_data(args: &[Tok], len: usize, ctx: &Ctx) -> Result<Data, SyntaxErr> {
+    let unexpected = |tok: Option<&Tok>, what: &str| expected(what, tok, len);
+    let var = match args.first().map(|tok| &tok.cat) {
+        Some(Cat::Sym(sym)) => Var::new(sym),
+        _ => return Err(unexpected(args.first(), "a variable")),
+    };
+    for (i, cat) in [Cat::Col, Cat::Eq].iter().enumerate() {
+        if args.get(i + 1).map(|tok| &tok.cat) != Some(cat) {
+            return Err(unexpected(args.get(i + 1), "‘:=’"));
+        }
+    }
+    let mut cns = vec![];
+    let mut toks = &args[3..];
+    while !toks.is_empty() || !cns.is_empty() {
+        let (tok, rest) = match toks.split_first() {
+            Some((
+                Tok {
+                    cat: Cat::Sym(sym), ..
+                },
+                rest,
+            )) => (Var::new(sym), rest),
+            _ => return Err(unexpected(toks.first(), "a constructor")),
+        };
+        let end = rest
+            .iter()
+            .position(|tok| tok.cat == Cat::Opr("|".to_string()))
+            .unwrap_or(rest.len());
+        let mut par = Parser::new(&rest[..end], len, ctx);
+        let mut typ = vec![];
+        while let Some(tok) = par.peek().cloned() {
+            match par.atm() {
+                Some((exp, _)) => typ.push(exp),
+                None if par.errs.is_empty() => {
+                    par.unexpected::<()>(&tok, "a type");
+                }
+                None => {}
+            }
+            if !par.errs.is_empty() {
+                return Err(par.errs.remove(0));
+            }
+        }
+        cns.push(Ctor::new(tok, typ));
+        toks = match rest.get(end..) {
+            Some([_, rest @ ..]) => rest, // skip the separator
+            _ => break,
+        };
+    }
+    Ok(Data::new(var, cns))
+}
+
+/// Parse the arguments of a command that changes a [setting][Setting], namely the name of the
+/// setting followed by its value.
+fn parse_setting(args: &[Tok], src: &str) -> Result<Setting, SyntaxErr> {
+    let len = src.len();
+    let unexpected = |tok: Option<&Tok>, what: &str| expected(what, tok, len);
+    match args.first().map(|tok| &tok.cat) {
+        Some(Cat::Sym(sym)) if sym == "strategy" => {
+            let names = Strategy::ALL
+                .iter()
+                .map(Strategy::to_string)
+                .intersperse(", ".to_string())
+                .collect::<String>();
+            let stg = match args.get(1).map(|tok| &tok.cat) {
+                Some(Cat::Sym(sym)) => Strategy::ALL
+                    .into_iter()
+                    .find(|stg| &stg.to_string() == sym),
+                _ => None,
+            };
+            let stg = stg.ok_or_else(|| unexpected(args.get(1), &format!("one of {}", names)))?;
+            parse_tokens_end(&args[2..])?;
+            Ok(Setting::Strategy(stg))
+        }
+        Some(Cat::Sym(sym)) if sym == "fuel" => match args.get(1).map(|tok| &tok.cat) {
+            Some(Cat::Num(num)) => {
+                parse_tokens_end(&args[2..])?;
+                Ok(Setting::Fuel(*num))
+            }
+            _ => Err(unexpected(args.get(1), "a number of steps")),
+        },
+        Some(Cat::Sym(sym)) if sym == "gutter" => {
+            let names = Gutter::ALL
+                .iter()
+                .map(Gutter::to_string)
+                .intersperse(", ".to_string())
+                .collect::<String>();
+            let gut = match args.get(1).map(|tok| &tok.cat) {
+                Some(Cat::Sym(sym)) => Gutter::ALL.into_iter().find(|gut| &gut.to_string() == sym),
+                _ => None,
+            };
+            let gut = gut.ok_or_else(|| unexpected(args.get(1), &format!("one of {}", names)))?;
+            parse_tokens_end(&args[2..])?;
+            Ok(Setting::Gutter(gut))
+        }
+        Some(Cat::Sym(sym)) if sym == "keymap" => {
+            let names = KeySet::ALL
+                .iter()
+                .map(KeySet::to_string)
+                .intersperse(", ".to_string())
+                .collect::<String>();
+            let key = match args.get(1).map(|tok| &tok.cat) {
+                Some(Cat::Sym(sym)) => KeySet::ALL.into_iter().find(|key| &key.to_string() == sym),
+                _ => None,
+            };
+            let key = key.ok_or_else(|| unexpected(args.get(1), &format!("one of {}", names)))?;
+            parse_tokens_end(&args[2..])?;
+            Ok(Setting::KeySet(key))
+        }
+        Some(Cat::Sym(sym)) if sym == "theme" => {
+            let names = Preset::ALL
+                .iter()
+                .map(Preset::to_string)
+                .intersperse(", ".to_string())
+                .collect::<String>();
+            // a name such as high-contrast is split into several tokens, so it is read whole
+            let name = match (args.get(1), args.last()) {
+                (Some(beg), Some(end)) => &src[beg.beg..end.end],
+                _ => "",
+            };
+            let pre = Preset::ALL.into_iter().find(|pre| pre.to_string() == name);
+            let pre = pre.ok_or_else(|| unexpected(args.get(1), &format!("one of {}", names)))?;
+            Ok(Setting::Theme(pre))
+        }
+        Some(Cat::Sym(sym)) if sym == "ascii" || sym == "warnings" => {
+            let on = match args.get(1).map(|tok| &tok.cat) {
+                Some(Cat::Sym(sym)) if sym == "on" => true,
+                Some(Cat::Sym(sym)) if sym == "off" => false,
+                _ => return Err(unexpected(args.get(1), "one of on, off")),
+            };
+            parse_tokens_end(&args[2..])?;
+            match sym.as_str() {
+                "ascii" => Ok(Setting::Ascii(on)),
+                _ => Ok(Setting::Warnings(on)),
+            }
+        }
+        _ => Err(unexpected(args.first(), "a setting")),
+    }
+}
+
+/// Expand the alias (declared in the given settings) that begins the command in the given tokens,
+/// returning the source text of the command with the alias replaced by what it stands for, along
+/// with the token of the alias and the length of what it stands for (which begins the source text
+/// returned), unless the command does not begin with an alias.
+///
+/// Since an alias may only stand for a command that begins with a keyword, which may not itself be
+/// declared as an alias, the command expanded never begins with an alias in turn.
+fn expand_alias<'a>(
+    src: &str,
+    toks: &'a [Tok],
+    set: &Settings,
+) -> Option<(String, &'a Tok, usize)> {
+    let (key, rest) = toks.split_first()?;
+    let Cat::Sym(sym) = &key.cat else {
+        return None;
+    };
+    let cmd = set.get_alias(&Var::new(sym))?;
+    let end = rest.last().map_or(key.end, |tok| tok.end);
+    Some((format!("{}{}", cmd, &src[key.end..end]), key, cmd.len()))
+}
+
+/// Parse the arguments of a command that declares an alias, namely the name of the alias, which
+/// must not be the keyword of a command, followed by `=` and the command that it stands for, which
+/// must begin with the keyword of a command.
+fn parse_alias(src: &str, args: &[Tok]) -> Result<(Var, String), SyntaxErr> {
+    match args {
+        [Tok {
+            cat: Cat::Sym(sym), ..
+        }, eq, cmd @ ..]
+            if eq.cat == Cat::Eq =>
+        {
+            if cmd_for_key(&args[0]).is_ok() {
+                return Err(SyntaxErr::new(
+                    args[0].beg,
+                    args[0].end,
+                    &format!("the command {} cannot be an alias", args[0].cat),
+                ));
+            }
+            match cmd.first() {
+                Some(key) if cmd_for_key(key).is_ok() => {
+                    let end = cmd.last().map_or(key.end, |tok| tok.end);
+                    Ok((Var::new(sym), src[key.beg..end].to_string()))
+                }
+                tok => Err(expected("a command", tok, src.len())),
+            }
+        }
+        [Tok {
+            cat: Cat::Sym(_), ..
+        }, rest @ ..] => Err(expected("‘=’", rest.first(), src.len())),
+        _ => Err(expected("an alias", args.first(), src.len())),
+    }
+}
+
+/// Parse the argument of a command that opens a namespace, namely its (possibly qualified) name.
+fn parse_namespace(args: &[Tok], len: usize) -> Result<Var, SyntaxErr> {
+    let (ns, rest) = parse_name(args, len, "a namespace")?;
+    parse_tokens_end(rest)?;
+    Ok(ns)
+}
+
+/// Parse a (possibly qualified) name at the start of the given tokens, with no whitespace around
+/// each `.` that separates its segments, returning the name along with the remaining tokens.
+fn parse_name<'t>(args: &'t [Tok], len: usize, what: &str) -> Result<(Var, &'t [Tok]), SyntaxErr> {
+    let mut var: Option<(Var, usize)> = None;
+    let mut toks = args;
+    loop {
+        match (toks, &var) {
+            (
+                [Tok {
+                    cat: Cat::Sym(sym),
+                    end,
+                    ..
+                }, rest @ ..],
+                None,
+            ) => {
+                var = Some((Var::new(sym), *end));
+                toks = rest;
+            }
+            (
+                [dot, Tok {
+                    cat: Cat::Sym(sym),
+                    beg,
+                    end,
+                    ..
+                }, rest @ ..],
+                Some((ns, last)),
+            ) if dot.cat == Cat::Dot && dot.beg == *last && dot.end == *beg => {
+                var = Some((Var::new(sym).qualify(ns), *end));
+                toks = rest;
+            }
+            (_, None) => return Err(expected(what, toks.first(), len)),
+            (_, Some((var, _))) => return Ok((var.clone(), toks)),
+        }
+    }
+}
+
+/// Parse the arguments of a command that defines a variable, namely its (possibly qualified) name,
+/// followed by its type and its value.
+fn parse_let(args: &[Tok], len: usize, ctx: &Ctx) -> Result<(Var, Node, Node), SyntaxErr> {
+    let (var, rest) = parse_name(args, len, "a variable")?;
+    match rest.split_first() {
+        Some((tok, rest)) if tok.cat == Cat::Col => {
+            let mut par = Parser::new(rest, len, ctx);
+            let typ = par.exp();
+            if !par.errs.is_empty() {
+                return Err(par.errs.remove(0));
+            }
+            match (typ, par.toks.get(par.pos..).unwrap_or_default()) {
+                (Some(typ), [tok, rest @ ..]) if tok.cat == Cat::Eq => {
+                    let val = parse_tokens(rest, len, ctx).into_spanned()?;
+                    Ok((var, typ, val))
+                }
+                (_, rest) => Err(expected("‘=’", rest.first(), len)),
+            }
+        }
+        _ => Err(expected("‘:’", rest.first(), len)),
+    }
+}
+
+/// Parse the arguments of a command that compares two expressions, the first of which is an atom
+/// (so that it must be parenthesized unless it is a variable, a literal or the like), followed by
+/// the second.
+fn parse_eq(args: &[Tok], len: usize, ctx: &Ctx) -> Result<(Node, Node), SyntaxErr> {
+    let mut par = Parser::new(args, len, ctx);
+    let fst = par.atm();
+    if !par.errs.is_empty() {
+        return Err(par.errs.remove(0));
+    }
+    match (fst, par.toks.get(par.pos..).unwrap_or_default()) {
+        (Some(fst), rest) if !rest.is_empty() => {
+            let snd = parse_tokens(rest, len, ctx).into_spanned()?;
+            Ok((fst, snd))
+        }
+        (_, rest) => Err(expected("an expression", rest.first(), len)),
+    }
+}
+
+/// Parse the arguments of a command that exports an expression, namely the markup to export it
+/// as, followed by the expression.
+fn parse_export(args: &[Tok], len: usize, ctx: &Ctx) -> Result<(Markup, Node), SyntaxErr> {
+    let mrk = match args.first().map(|tok| &tok.cat) {
+        Some(Cat::Sym(sym)) if sym == "latex" => Markup::Latex,
+        Some(Cat::Sym(sym)) if sym == "html" => Markup::Html,
+        _ => return Err(expected("one of latex, html", args.first(), len)),
+    };
+    let exp = parse_tokens(&args[1..], len, ctx).into_spanned()?;
+    Ok((mrk, exp))
+}
+
+/// Parse the arguments of a command that assumes a variable, namely its (possibly qualified) name,
+/// followed by its type.
+fn parse_assume(args: &[Tok], len: usize, ctx: &Ctx) -> Result<(Var, Node), SyntaxErr> {
+    let (var, rest) = parse_name(args, len, "a variable")?;
+    match rest.split_first() {
+        Some((tok, rest)) if tok.cat == Cat::Col => {
+            let typ = parse_tokens(rest, len, ctx).into_spanned()?;
+            Ok((var, typ))
+        }
+        _ => Err(expected("‘:’", rest.first(), len)),
+    }
+}
+
+/// Parse the arguments of a command that saves or restores the [session][Session], namely the
+/// operation followed by the path of the file (as a string literal), which recovering the session
+/// takes none of.
+fn parse_session(args: &[Tok], len: usize) -> Result<Session, SyntaxErr> {
+    let unexpected = |tok: Option<&Tok>, what: &str| expected(what, tok, len);
+    let op: fn(PathBuf) -> Session = match args.first().map(|tok| &tok.cat) {
+        Some(Cat::Sym(sym)) if sym == "save" => Session::Save,
+        Some(Cat::Sym(sym)) if sym == "load" => Session::Load,
+        Some(Cat::Sym(sym)) if sym == "recover" => {
+            return parse_tokens_end(&args[1..]).map(|_| Session::Recover);
+        }
+        _ => return Err(unexpected(args.first(), "one of save, load, recover")),
+    };
+    parse_path(&args[1..], len).map(op)
+}
+
+/// Parse the argument of a command that refers to a file, namely its path (as a string literal).
+fn parse_path(args: &[Tok], len: usize) -> Result<PathBuf, SyntaxErr> {
+    match args.first().map(|tok| &tok.cat) {
+        Some(Cat::Str(path, true)) if !path.is_empty() => {
+            parse_tokens_end(&args[1..])?;
+            Ok(PathBuf::from(path))
+        }
+        _ => Err(expected("the path of a file", args.first(), len)),
+    }
+}
+
+/// Split the [tokens][Tok] produced from the given source text (such as the contents of a file)
+/// into the commands they make up, along with the (1-based) line on which each command starts.
+///
+/// Each command starts at the beginning of a line, and continues onto the lines that follow it
+/// as long as they are indented (or blank), so that a long command may be split across lines.
+/// Comments that start at the beginning of a line are skipped.
+pub fn split_commands<'t>(src: &str, toks: &'t [Tok]) -> Vec<(usize, &'t [Tok])> {
+    let mut cmds = vec![];
+    let mut beg: Option<usize> = None;
+    for (i, tok) in toks.iter().enumerate() {
+        if tok.beg == 0 || src[..tok.beg].ends_with('\n') {
+            if let Some(beg) = beg.take() {
+                cmds.push((locate(src, toks[beg].beg).0, &toks[beg..i]));
+            }
+            if !matches!(tok.cat, Cat::Rem(_)) {
+                beg = Some(i);
+            }
+        }
+    }
+    if let Some(beg) = beg {
+        cmds.push((locate(src, toks[beg].beg).0, &toks[beg..]));
+    }
+    cmds
+}
+
+/// Create an error that reports the given token (or the end of input, located at the given
+/// offset) where something else was expected.
+fn expected(what: &str, tok: Option<&Tok>, len: usize) -> SyntaxErr {
+    match tok {
+        Some(tok) => {
+            let msg = format!("expected {}, found {}", what, tok.cat);
+            SyntaxErr::new(tok.beg, tok.end, &msg)
+        }
+        None => {
+            let msg = format!("expected {}, found end of input", what);
+            SyntaxErr::new(len, len, &msg)
+        }
+    }
+}
+
+/// Check that there are no remaining tokens.
+fn parse_tokens_end(toks: &[Tok]) -> Result<(), SyntaxErr> {
+    match toks.first() {
+        None => Ok(()),
+        Some(tok) => Err(SyntaxErr::new(
+            tok.beg,
+            tok.end,
+            &format!("expected end of input, found {}", tok.cat),
+        )),
+    }
+}
+
+/// Convert a byte offset within the source text into a (1-based) line and column.
+pub fn locate(src: &str, idx: usize) -> (usize, usize) {
+    let pre = &src[..idx];
+    let row = pre.matches('\n').count() + 1;
+    let col = pre.rsplit('\n').next().unwrap_or_default().chars().count() + 1;
+    (row, col)
+}
+
+/// Split the given source text into a sequence of [tokens][Tok], skipping whitespace.
+pub fn tokenize(src: &str) -> Vec<Tok> {
+    Lexer::new(src, 0).collect()
+}
+
+/// Check whether the character may begin a symbolic name.
+fn is_sym_start(chr: char) -> bool {
+    chr == '_' || (chr.is_alphabetic() && !"λΠΣ".contains(chr))
+}
+
+/// Check whether the character may continue a symbolic name.
+fn is_sym_continue(chr: char) -> bool {
+    chr == '\'' || chr.is_ascii_digit() || is_sym_start(chr)
+}
+
+/// Check whether the character may be part of an operator, which is any ASCII symbol not
+/// otherwise reserved, or any non-ASCII symbol other than the glyphs of the core language.
+fn is_opr(chr: char) -> bool {
+    if chr.is_ascii() {
+        "!#$%&*+-/<=>?@^|~".contains(chr)
+    } else {
+        !(chr.is_alphanumeric() || chr.is_whitespace() || "→□⊤⊥".contains(chr))
+    }
+}
+
+impl ParseResult {
+    /// Convert this outcome into a result, failing with the first syntax error (if any).
+    pub fn into_result(self) -> Result<Exp, SyntaxErr> {
+        self.into_spanned().map(|(exp, _)| exp)
+    }
+
+    /// Convert this outcome into a result that carries the span of the expression, failing with
+    /// the first syntax error (if any).
+    pub fn into_spanned(self) -> Result<(Exp, Span), SyntaxErr> {
+        match self.errs.into_iter().next() {
+            Some(err) => Err(err),
+            None => self
+                .exp
+                .zip(self.span)
+                .ok_or_else(|| SyntaxErr::new(0, 0, "expected an expression")),
+        }
+    }
+}
+
+impl Span {
+    /// Create a new instance of span over the given range, with the given spans of sub-expressions.
+    pub fn new(beg: usize, end: usize, subs: Vec<Span>) -> Self {
+        Span { beg, end, subs }
+    }
+
+    /// Check whether the given byte offset lies within this span, or at its end (as the cursor
+    /// does just after the last character of an expression).
+    pub fn contains(&self, idx: usize) -> bool {
+        self.beg <= idx && idx <= self.end
+    }
+
+    /// Find the span of the occurrence of the given target (such as the culprit of a typing
+    /// error) within the given expression (whose span this is, and which is typed in the given
+    /// context) that is to blame for an error, unless it does not occur (or occurs only within
+    /// parts that were desugared).
+    ///
+    /// Since the target may occur more than once, the occurrence is sought in the sub-expressions
+    /// that fail to type-check before the others (binding the variable of each binder whose body
+    /// it descends into, if its type is given), and otherwise in the order of the source text,
+    /// outermost first.
+    pub fn blame<'s>(&'s self, exp: &'s Exp, tgt: &Exp, ctx: &Ctx) -> Option<&'s Span> {
+        // a projection may spell out a qualified name, which it is elaborated into
+        if exp == tgt || matches!(exp, Exp::Proj(..)) && exp.elaborate(ctx) == *tgt {
+            return Some(self);
+        }
+        let subs = exp.fold(vec![], |mut subs, sub, pos| {
+            subs.push((sub, pos));
+            subs
+        });
+        if subs.len() != self.subs.len() {
+            return None; // desugared, so its parts cannot be told apart in the source text
+        }
+        let subs = subs.iter().zip(&self.subs).map(|((sub, pos), span)| {
+            let typ = match pos {
+                Pos::Body(var) => subs.iter().find(|(_, p)| *p == Pos::Type(var)),
+                _ => None,
+            };
+            let ctx = match (pos, typ) {
+                (Pos::Body(var), Some((typ, _))) if **typ != Exp::Hole => {
+                    ctx.extend(var, &typ.elaborate(ctx))
+                }
+                _ => ctx.clone(),
+            };
+            (*sub, span, ctx)
+        });
+        Self::blame_any(subs, tgt)
+    }
+
+    /// Find the span of the occurrence of the given target that is to [blame][Span::blame] for an
+    /// error within any of the given expressions (each along with its span, and the context that
+    /// it is typed in), seeking it in those that fail to type-check before the others, and
+    /// otherwise in the order of the source text.
+    fn blame_any<'s>(
+        exps: impl Iterator<Item = (&'s Exp, &'s Span, Ctx)>,
+        tgt: &Exp,
+    ) -> Option<&'s Span> {
+        let mut exps = exps
+            .map(|(exp, span, ctx)| (exp.elaborate(&ctx).infer(&ctx).is_ok(), exp, span, ctx))
+            .collect::<Vec<_>>();
+        exps.sort_by_key(|(ok, _, span, _)| (*ok, span.beg));
+        (exps.into_iter()).find_map(|(_, exp, span, ctx)| span.blame(exp, tgt, &ctx))
+    }
+}
+
+impl Stream {
+    /// Create a new, empty token stream.
+    pub fn new() -> Self {
+        Self {
+            src: String::new(),
+            toks: Vec::new(),
+            cur: 0,
+        }
+    }
+
+    /// Synchronize this stream with the given source text and cursor location, returning the
+    /// number of tokens that had to be lexed anew.
+    ///
+    /// Tokens that lie entirely before the edited region are kept as they are. Lexing resumes
+    /// after the last of them, and stops as soon as it produces a token beyond the edited region
+    /// that matches (once shifted by the change in length) a previously lexed token, at which
+    /// point the remaining tokens are reused, since the lexer carries no state between tokens.
+    pub fn sync(&mut self, src: &str, cur: usize) -> usize {
+        self.cur = cur;
+        if self.src == src {
+            return 0;
+        }
+
+        // Locate the edited region, by trimming the longest common prefix and suffix.
+        let pre = common_len(self.src.chars(), src.chars(), char::len_utf8);
+        let max = self.src.len().min(src.len()) - pre;
+        let suf = common_len(self.src.chars().rev(), src.chars().rev(), char::len_utf8).min(max);
+        let end = src.len() - suf; // end of the edited region in the new source text
+
+        // Keep the tokens that end strictly before the edited region, since any token that ends
+        // at its boundary may be extended by the edit.
+        let keep = self.toks.partition_point(|tok| tok.end < pre);
+        let old = self.toks.split_off(keep);
+        let beg = self.toks.last().map_or(0, |tok| tok.end);
+
+        let mut count = 0;
+        for tok in Lexer::new(src, beg) {
+            if tok.beg >= end {
+                let pos = tok.beg + self.src.len() - src.len(); // location in the old text
+                if let Ok(i) = old.binary_search_by_key(&pos, |tok| tok.beg) {
+                    if old[i].cat == tok.cat && old[i].end + src.len() == tok.end + self.src.len() {
+                        let shift = |tok: &Tok| Tok {
+                            cat: tok.cat.clone(),
+                            beg: tok.beg + src.len() - self.src.len(),
+                            end: tok.end + src.len() - self.src.len(),
+                        };
+                        self.toks.extend(old[i..].iter().map(shift));
+                        break;
+                    }
+                }
+            }
+            self.toks.push(tok);
+            count += 1;
+        }
+        self.src = src.to_string();
+        count
+    }
+
+    /// Fetch the tokens in this stream, including a [cursor token][Cat::Cur], which is placed
+    /// immediately before the first token that begins at or after the cursor.
+    pub fn toks(&self) -> Vec<Tok> {
+        let mut toks = self.toks.clone();
+        let idx = toks.partition_point(|tok| tok.beg < self.cur);
+        toks.insert(
+            idx,
+            Tok {
+                cat: Cat::Cur,
+                beg: self.cur,
+                end: self.cur,
+            },
+        );
+        toks
+    }
+
+    /// Parse the tokens in this stream using the notation declared in the given context,
+    /// recovering from syntax errors.
+    pub fn parse(&self, ctx: &Ctx) -> ParseResult {
+        parse_tokens(&self.toks, self.src.len(), ctx)
+    }
+}
+
+/// Compute the number of bytes in the longest common prefix of two sequences of characters.
+fn common_len<I: Iterator<Item = char>>(a: I, b: I, len: fn(char) -> usize) -> usize {
+    a.zip(b)
+        .take_while(|(x, y)| x == y)
+        .map(|(x, _)| len(x))
+        .sum()
+}
+
+impl<'a> Lexer<'a> {
+    /// Create a new lexer over the given source text, starting at the given byte offset.
+    fn new(src: &'a str, off: usize) -> Self {
+        Lexer {
+            chars: src[off..].char_indices().peekable(),
+            off,
+            len: src.len(),
+        }
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = Tok;
+
+    fn next(&mut self) -> Option<Tok> {
+        let chars = &mut self.chars;
+        while let Some((idx, chr)) = chars.next() {
+            let cat = match chr {
+                _ if chr.is_whitespace() => continue,
+                'λ' | '\\' => Cat::Abs,
+                'Π' => Cat::For,
+                'Σ' => Cat::Sig,
+                ',' => Cat::Com,
+                ':' => Cat::Col,
+                '.' => Cat::Dot,
+                '→' => Cat::Arr,
+                '-' if chars.next_if(|(_, c)| *c == '>').is_some() => Cat::Arr,
+                '-' if chars.next_if(|(_, c)| *c == '-').is_some() => {
+                    while chars.next_if(|(_, c)| *c != '\n' && *c != '\r').is_some() {}
+                    Cat::Rem(true)
+                }
+                '{' if chars.next_if(|(_, c)| *c == '-').is_some() => {
+                    let mut depth = 1;
+                    while depth > 0 {
+                        match chars.next() {
+                            Some((_, '{')) if chars.next_if(|(_, c)| *c == '-').is_some() => {
+                                depth += 1
+                            }
+                            Some((_, '-')) if chars.next_if(|(_, c)| *c == '}').is_some() => {
+                                depth -= 1
+                            }
+                            Some(_) => continue,
+                            None => break,
+                        }
+                    }
+                    Cat::Rem(depth == 0)
+                }
+                '{' => Cat::Lbr,
+                '}' => Cat::Rbr,
+                '(' => Cat::Opn,
+                ')' => Cat::Cls,
+                '□' => Cat::KindMeta,
+                '⊤' => Cat::Unit,
+                '⊥' => Cat::Void,
+                'ℕ' => Cat::Nat,
+                '"' => {
+                    let mut str = String::new();
+                    while let Some((_, nxt)) = chars.next_if(|(_, c)| !matches!(c, '"' | '\n')) {
+                        str.push(nxt);
+                    }
+                    Cat::Str(str, chars.next_if(|(_, c)| *c == '"').is_some())
+                }
+                '%' if chars.peek().is_some_and(|(_, c)| c.is_ascii_digit()) => {
+                    let mut sym = String::from(chr);
+                    while let Some((_, nxt)) = chars.next_if(|(_, c)| c.is_ascii_digit()) {
+                        sym.push(nxt);
+                    }
+                    Cat::Sym(sym) // the result of an earlier command, as kept by the environment
+                }
+                _ if is_sym_start(chr) => {
+                    let mut sym = String::from(chr);
+                    while let Some((_, nxt)) = chars.next_if(|(_, c)| is_sym_continue(*c)) {
+                        sym.push(nxt);
+                    }
+                    match sym.as_str() {
+                        "forall" | "pi" => Cat::For,
+                        "exists" | "sigma" => Cat::Sig,
+                        "fst" => Cat::Fst,
+                        "snd" => Cat::Snd,
+                        "let" => Cat::Let,
+                        "in" => Cat::In,
+                        "Type" => Cat::Univ,
+                        "Kind" => Cat::KindMeta,
+                        "Unit" => Cat::Unit,
+                        "Void" => Cat::Void,
+                        "Sum" => Cat::Sum,
+                        "inl" => Cat::Inl,
+                        "inr" => Cat::Inr,
+                        "case" => Cat::Case,
+                        "absurd" => Cat::Absurd,
+                        "Nat" => Cat::Nat,
+                        "suc" => Cat::Suc,
+                        "add" => Cat::Add,
+                        "mul" => Cat::Mul,
+                        "Nat_rec" => Cat::NatRec,
+                        "_" => Cat::Hole,
+                        _ => Cat::Sym(sym),
+                    }
+                }
+                _ if chr.is_ascii_digit() => {
+                    let mut num = String::from(chr);
+                    while let Some((_, nxt)) = chars.next_if(|(_, c)| c.is_ascii_digit()) {
+                        num.push(nxt);
+                    }
+                    let mut ahead = chars.clone();
+                    if ahead.next_if(|(_, c)| *c == 'n').is_some()
+                        && !ahead.peek().is_some_and(|(_, c)| is_sym_continue(*c))
+                    {
+                        chars.next(); // the suffix of a literal natural number
+                        Cat::Lit(num.parse().ok())
+                    } else {
+                        Cat::Num(num.parse().unwrap_or(usize::MAX))
+                    }
+                }
+                _ if is_opr(chr) => {
+                    let mut opr = String::from(chr);
+                    while let Some((_, nxt)) = chars.next_if(|(_, c)| is_opr(*c)) {
+                        opr.push(nxt);
+                    }
+                    match opr.as_str() {
+                        "*" => Cat::TypeMeta,
+                        "=" => Cat::Eq,
+                        _ => Cat::Opr(opr),
+                    }
+                }
+                _ => Cat::Unk(chr),
+            };
+            let end = chars.peek().map_or(self.len, |(i, _)| self.off + *i);
+            return Some(Tok {
+                cat,
+                beg: self.off + idx,
+                end,
+            });
+        }
+        None
+    }
+}
+
+impl<'a> Parser<'a> {
+    /// Create a new parser over the given tokens, ignoring any cursor tokens and comments (but
+    /// reporting comments that are not properly terminated), which uses the notation declared in
+    /// the given context.
+    fn new(toks: &[Tok], len: usize, ctx: &'a Ctx) -> Self {
+        let mut par = Parser {
+            toks: Vec::new(),
+            pos: 0,
+            len,
+            errs: Vec::new(),
+            ctx,
+        };
+        for tok in toks {
+            match tok.cat {
+                Cat::Cur | Cat::Rem(true) => continue,
+                Cat::Rem(false) => {
+                    let err = SyntaxErr::new(tok.beg, tok.end, "unterminated comment");
+                    par.fail::<()>(err);
+                }
+                Cat::Str(_, false) => {
+                    let err = SyntaxErr::new(tok.beg, tok.end, "unterminated string");
+                    par.fail::<()>(err);
+                    par.toks.push(tok.clone());
+                }
+                _ => par.toks.push(tok.clone()),
+            }
+        }
+        par
+    }
+
+    /// Parse an expression, which is either a binder or an application of operators (possibly as
+    /// the domain of a right-associative arrow).
+    fn exp(&mut self) -> Option<Node> {
+        match self.peek().map(|tok| &tok.cat) {
+            Some(Cat::Abs) | Some(Cat::For) | Some(Cat::Sig) | Some(Cat::Let) => self.bnd(),
+            _ => {
+                let exp = self.opr(0, None);
+                if self.accept(&Cat::Arr) {
+                    let cod = self.exp();
+                    let ((dom, dsp), (cod, csp)) = (exp?, cod?);
+                    let span = Span::new(dsp.beg, csp.end, vec![dsp, csp]);
+                    Some((Exp::new_arr(dom, cod), span))
+                } else {
+                    exp
+                }
+            }
+        }
+    }
+
+    /// Parse a binder (λ abstraction, Π type, Σ type or local definition), whose body extends as far
+    /// right as possible.
+    fn bnd(&mut self) -> Option<Node> {
+        let tok = self.toks[self.pos].clone(); // only called when the next token is a binder
+        self.pos += 1;
+        let imp = match self.peek().cloned() {
+            Some(brc) if brc.cat == Cat::Lbr => {
+                self.pos += 1;
+                if !matches!(tok.cat, Cat::Abs | Cat::For) {
+                    let msg = format!("{} cannot bind a variable implicitly", tok.cat);
+                    self.fail::<()>(SyntaxErr::new(brc.beg, brc.end, &msg));
+                }
+                true
+            }
+            _ => false,
+        };
+        let var = self.sym();
+        let typ = match self.peek().cloned() {
+            Some(dot) if dot.cat == Cat::Dot && tok.cat == Cat::Abs && !imp => {
+                Some((Exp::get_hole(), Span::new(dot.beg, dot.beg, vec![]))) // not annotated
+            }
+            _ => {
+                self.expect(&Cat::Col, "‘:’");
+                self.exp()
+            }
+        };
+        if imp {
+            self.expect(&Cat::Rbr, "‘}’");
+        }
+        let typ = match typ {
+            Some((typ, tsp)) if imp => Some((Exp::Imp(Box::new(typ)), tsp)),
+            typ => typ,
+        };
+        if tok.cat == Cat::Let {
+            self.expect(&Cat::Eq, "‘=’");
+            let val = self.exp();
+            self.expect(&Cat::In, "‘in’");
+            let exp = self.exp();
+            let (var, (typ, tsp), (val, vsp), (exp, esp)) = (var?, typ?, val?, exp?);
+            let end = esp.end;
+            let abs = (
+                Exp::new_abs(var, typ, exp),
+                Span::new(tok.beg, end, vec![tsp, esp]),
+            );
+            let span = Span::new(tok.beg, end, vec![abs.1, vsp]);
+            return Some((Exp::new_app(abs.0, val), span)); // desugar
+        }
+        self.expect(&Cat::Dot, "‘.’");
+        let exp = self.exp();
+        let (var, (typ, tsp), (exp, esp)) = (var?, typ?, exp?);
+        let span = Span::new(tok.beg, esp.end, vec![tsp, esp]);
+        match tok.cat {
+            Cat::Abs => Some((Exp::new_abs(var, typ, exp), span)),
+            Cat::Sig => Some((Exp::new_sig(var, typ, exp), span)),
+            _ => Some((Exp::new_for(var, typ, exp), span)),
+        }
+    }
+
+    /// Parse a sequence of applications separated by infix operators whose precedence is at least
+    /// the given minimum, grouped according to the precedence and associativity of each operator.
+    /// The last operand may be a binder, whose body extends as far right as possible.
+    ///
+    /// The operator (if any) that the sequence is the right operand of is given, so that chains
+    /// of operators of equal precedence that cannot be grouped unambiguously are reported.
+    fn opr(&mut self, min: usize, mut prv: Option<Notation>) -> Option<Node> {
+        let mut exp = self.app();
+        while let Some(tok) = self.peek().cloned() {
+            let ntn = match &tok.cat {
+                Cat::Opr(opr) => self.ctx.get_notation(opr).cloned(),
+                _ => break,
+            };
+            let ntn = match ntn {
+                Some(ntn) if ntn.prec < min => break,
+                Some(ntn) => ntn,
+                None => {
+                    self.pos += 1;
+                    self.unexpected::<()>(&tok, "a declared operator");
+                    exp = match (exp, self.app()) {
+                        (Some(fst), Some(snd)) => Some(Parser::join(fst, snd)),
+                        (fst, None) => fst,
+                        (None, snd) => snd,
+                    }; // skip the unknown operator, as though it were a stray character
+                    continue;
+                }
+            };
+            if let Some(prv) = prv.filter(|prv| prv.prec == ntn.prec) {
+                if prv.fix != ntn.fix || ntn.fix == Fixity::Infix {
+                    let msg = format!(
+                        "cannot chain ‘{}’ and ‘{}’ without parentheses",
+                        prv.opr, ntn.opr
+                    );
+                    self.fail::<()>(SyntaxErr::new(tok.beg, tok.end, &msg));
+                }
+            }
+            self.pos += 1;
+            let rhs = match (self.peek().map(|tok| &tok.cat), ntn.fix) {
+                (Some(Cat::Abs) | Some(Cat::For) | Some(Cat::Sig) | Some(Cat::Let), _) => {
+                    self.bnd()
+                }
+                (_, Fixity::Infixr) => self.opr(ntn.prec, Some(ntn.clone())),
+                _ => self.opr(ntn.prec + 1, None),
+            };
+            exp = match (exp, rhs) {
+                (Some((lhs, lsp)), Some((rhs, rsp))) => {
+                    let (beg, end) = (lsp.beg, rsp.end);
+                    let opr = Span::new(tok.beg, tok.end, vec![]);
+                    let fst = Span::new(beg, tok.end, vec![opr, lsp]);
+                    Some((ntn.apply(lhs, rhs), Span::new(beg, end, vec![fst, rsp])))
+                }
+                _ => None,
+            };
+            prv = Some(ntn);
+        }
+        exp
+    }
+
+    /// Parse a left-associative sequence of applications, optionally ending with a binder.
+    ///
+    /// Operands that cannot be recovered are dropped from the sequence.
+    fn app(&mut self) -> Option<Node> {
+        let mut exp = self.atm();
+        loop {
+            let arg = match self.peek().map(|tok| &tok.cat) {
+                Some(Cat::Abs) | Some(Cat::For) | Some(Cat::Sig) | Some(Cat::Let) => self.bnd(),
+                Some(Cat::Sym(_)) | Some(Cat::Num(_)) | Some(Cat::TypeMeta)
+                | Some(Cat::KindMeta) | Some(Cat::Univ) | Some(Cat::Hole) | Some(Cat::Opn)
+                | Some(Cat::Fst) | Some(Cat::Snd) | Some(Cat::Unk(_)) | Some(Cat::Unit)
+                | Some(Cat::Void) | Some(Cat::Sum) | Some(Cat::Inl) | Some(Cat::Inr)
+                | Some(Cat::Case) | Some(Cat::Absurd) | Some(Cat::Nat) | Some(Cat::Lit(_))
+                | Some(Cat::Suc) | Some(Cat::Add) | Some(Cat::Mul) | Some(Cat::NatRec)
+                | Some(Cat::Lbr) => self.atm(),
+                _ => return exp,
+            };
+            exp = match (exp, arg) {
+                (Some(fst), Some(snd)) => Some(Parser::join(fst, snd)),
+                (fst, None) => fst,
+                (None, snd) => snd,
+            };
+        }
+    }
+
+    /// Parse an atomic expression, followed by any number of projections of fields, each written
+    /// as a `.` and the label of the field with no whitespace around the `.` (which is otherwise
+    /// the separator between the head and body of a binder).
+    fn atm(&mut self) -> Option<Node> {
+        let mut exp = self.prim();
+        while let (Some((_, sp)), Some(dot), Some(fld)) = (
+            exp.as_ref(),
+            self.toks.get(self.pos),
+            self.toks.get(self.pos + 1),
+        ) {
+            match &fld.cat {
+                Cat::Sym(sym) if dot.cat == Cat::Dot && sp.end == dot.beg && dot.end == fld.beg => {
+                    let var = Var::new(sym);
+                    let end = fld.end;
+                    self.pos += 2;
+                    exp = exp.map(|(exp, sp)| {
+                        let span = Span::new(sp.beg, end, vec![sp]);
+                        (Exp::new_proj(exp, var), span)
+                    });
+                }
+                _ => break,
+            }
+        }
+        exp
+    }
+
+    /// Parse a primary expression (variable, numeric literal, sort, hole, parenthesized
+    /// expression, dependent pair, record, record type, projection of a dependent pair, or a
+    /// keyword applied to its operands).
+    ///
+    /// Unknown characters are skipped, but any other unexpected token is left for an enclosing
+    /// expression to consume.
+    fn prim(&mut self) -> Option<Node> {
+        let tok = match self.peek().cloned() {
+            Some(tok) => tok,
+            None => return self.missing("an expression"),
+        };
+        let span = Span::new(tok.beg, tok.end, vec![]);
+        match tok.cat {
+            Cat::Sym(sym) => {
+                self.pos += 1;
+                Some((Exp::new_var(Var(sym)), span))
+            }
+            Cat::Num(num) => {
+                self.pos += 1;
+                if num > MAX_NUM {
+                    let msg = format!("numeric literal exceeds the maximum of {}", MAX_NUM);
+                    self.fail(SyntaxErr::new(tok.beg, tok.end, &msg))
+                } else {
+                    Some((Exp::new_num(num), span)) // desugared, so no spans for sub-expressions
+                }
+            }
+            Cat::TypeMeta => {
+                self.pos += 1;
+                Some((Exp::get_type_meta(), span))
+            }
+            Cat::KindMeta => {
+                self.pos += 1;
+                Some((Exp::get_kind_meta(), span))
+            }
+            Cat::Univ => {
+                self.pos += 1;
+                match self.peek().map(|tok| (tok.cat.clone(), tok.end)) {
+                    Some((Cat::Num(lvl), end)) => {
+                        self.pos += 1;
+                        Some((Exp::new_sort(lvl), Span::new(tok.beg, end, vec![])))
+                    }
+                    _ => Some((Exp::get_type_meta(), span)),
+                }
+            }
+            Cat::Hole => {
+                self.pos += 1;
+                Some((Exp::get_hole(), span))
+            }
+            Cat::Opn => {
+                self.pos += 1;
+                if self.accept(&Cat::Cls) {
+                    let end = self.toks[self.pos - 1].end;
+                    return Some((Exp::Triv, Span::new(tok.beg, end, vec![])));
+                }
+                let exp = self.exp();
+                if self.accept(&Cat::Com) {
+                    let snd = self.exp();
+                    self.expect(&Cat::Col, "‘:’");
+                    let typ = self.exp();
+                    self.expect(&Cat::Cls, "‘)’");
+                    let end = self.toks[self.pos - 1].end; // include the closing parenthesis, if any
+                    let ((fst, fsp), (snd, ssp), (typ, tsp)) = (exp?, snd?, typ?);
+                    let span = Span::new(tok.beg, end, vec![fsp, ssp, tsp]);
+                    return Some((Exp::new_pair(fst, snd, typ), span));
+                }
+                self.expect(&Cat::Cls, "‘)’");
+                let end = self.toks[self.pos - 1].end; // include the closing parenthesis, if any
+                exp.map(|(exp, sp)| (exp, Span::new(tok.beg, end, sp.subs)))
+            }
+            Cat::Lbr => self.rec(),
+            Cat::Fst | Cat::Snd => {
+                self.pos += 1;
+                let (exp, sp) = self.atm()?;
+                let span = Span::new(tok.beg, sp.end, vec![sp]);
+                match tok.cat {
+                    Cat::Fst => Some((Exp::new_fst(exp), span)),
+                    _ => Some((Exp::new_snd(exp), span)),
+                }
+            }
+            Cat::Unit => {
+                self.pos += 1;
+                Some((Exp::Unit, span))
+            }
+            Cat::Void => {
+                self.pos += 1;
+                Some((Exp::Void, span))
+            }
+            Cat::Nat => {
+                self.pos += 1;
+                Some((Exp::Nat, span))
+            }
+            Cat::Lit(num) => {
+                self.pos += 1;
+                match num {
+                    Some(num) => Some((Exp::Lit(num), span)),
+                    None => self.fail(SyntaxErr::new(tok.beg, tok.end, "literal is too large")),
+                }
+            }
+            Cat::Sum
+            | Cat::Inl
+            | Cat::Inr
+            | Cat::Case
+            | Cat::Absurd
+            | Cat::Suc
+            | Cat::Add
+            | Cat::Mul
+            | Cat::NatRec => {
+                self.pos += 1;
+                let len = match tok.cat {
+                    Cat::Suc => 1,
+                    Cat::Case | Cat::NatRec => 4,
+                    _ => 2,
+                };
+                let args = (0..len).map(|_| self.atm()).collect::<Option<Vec<_>>>()?;
+                let end = args.last().map_or(tok.end, |(_, sp)| sp.end);
+                let (mut args, sps): (Vec<_>, Vec<_>) = args.into_iter().unzip();
+                let span = Span::new(tok.beg, end, sps);
+                let mut arg = || args.remove(0);
+                match tok.cat {
+                    Cat::Sum => Some((Exp::new_sum(arg(), arg()), span)),
+                    Cat::Inl => Some((Exp::new_inl(arg(), arg()), span)),
+                    Cat::Inr => Some((Exp::new_inr(arg(), arg()), span)),
+                    Cat::Case => Some((Exp::new_case(arg(), arg(), arg(), arg()), span)),
+                    Cat::Absurd => Some((Exp::new_absurd(arg(), arg()), span)),
+                    Cat::Suc => Some((Exp::new_suc(arg()), span)),
+                    Cat::Add => Some((Exp::new_add(arg(), arg()), span)),
+                    Cat::Mul => Some((Exp::new_mul(arg(), arg()), span)),
+                    _ => Some((Exp::new_nat_rec(arg(), arg(), arg(), arg()), span)),
+                }
+            }
+            Cat::Unk(_) => {
+                self.pos += 1;
+                self.unexpected(&tok, "an expression")
+            }
+            _ => self.unexpected(&tok, "an expression"),
+        }
+    }
+
+    /// Parse a record type (`{x : A, y : B}`) or a record (`{x = M, y = N}`), which must have at
+    /// least one field (fields that are labelled alike are left for the type checker to reject).
+    fn rec(&mut self) -> Option<Node> {
+        let beg = self.toks[self.pos].beg; // only called when the next token is a brace
+        self.pos += 1;
+        let sep = match self.toks.get(self.pos + 1).map(|tok| &tok.cat) {
+            Some(Cat::Eq) => Cat::Eq,
+            _ => Cat::Col,
+        };
+        let mut fields: Vec<(Option<Var>, Option<Node>)> = vec![];
+        loop {
+            let tok = self.peek().cloned();
+            let var = match tok.as_ref().map(|tok| &tok.cat) {
+                Some(Cat::Sym(_)) => self.sym(),
+                Some(_) => self.unexpected(tok.as_ref().unwrap(), "a field"),
+                None => self.missing("a field"),
+            };
+            self.expect(&sep, if sep == Cat::Eq { "‘=’" } else { "‘:’" });
+            fields.push((var, self.exp()));
+            if !self.accept(&Cat::Com) {
+                break;
+            }
+        }
+        self.expect(&Cat::Rbr, "‘}’");
+        let end = self.toks[self.pos - 1].end; // include the closing brace, if any
+        let (fields, sps): (Vec<_>, Vec<_>) = fields
+            .into_iter()
+            .map(|(var, exp)| var.zip(exp).map(|(var, (exp, sp))| ((var, exp), sp)))
+            .collect::<Option<Vec<_>>>()?
+            .into_iter()
+            .unzip();
+        let span = Span::new(beg, end, sps);
+        match sep {
+            Cat::Eq => Some((Exp::new_struct(fields), span)),
+            _ => Some((Exp::new_rec(fields), span)),
+        }
+    }
+
+    /// Combine two parsed expressions into an application of the first to the second.
+    fn join((fst, fsp): Node, (snd, ssp): Node) -> Node {
+        let span = Span::new(fsp.beg, ssp.end, vec![fsp, ssp]);
+        (Exp::new_app(fst, snd), span)
+    }
+
+    /// Parse a symbolic name, to be used as a bound variable.
+    fn sym(&mut self) -> Option<Var> {
+        match self.peek().cloned() {
+            Some(Tok {
+                cat: Cat::Sym(sym), ..
+            }) => {
+                self.pos += 1;
+                Some(Var(sym))
+            }
+            Some(tok) => self.unexpected(&tok, "a variable"),
+            None => self.missing("a variable"),
+        }
+    }
+
+    /// Consume the next token if it belongs to the given category, or else record an error and
+    /// carry on as though it had been present.
+    fn expect(&mut self, cat: &Cat, what: &str) {
+        if !self.accept(cat) {
+            match self.peek().cloned() {
+                Some(tok) => self.unexpected::<()>(&tok, what),
+                None => self.missing::<()>(what),
+            };
+        }
+    }
+
+    /// Consume the next token if (and only if) it belongs to the given category.
+    fn accept(&mut self, cat: &Cat) -> bool {
+        if self.peek().map(|tok| &tok.cat) == Some(cat) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Look at the next token without consuming it.
+    fn peek(&self) -> Option<&Tok> {
+        self.toks.get(self.pos)
+    }
+
+    /// Record an error that reports an unexpected token.
+    fn unexpected<T>(&mut self, tok: &Tok, what: &str) -> Option<T> {
+        let msg = format!("expected {}, found {}", what, tok.cat);
+        self.fail(SyntaxErr::new(tok.beg, tok.end, &msg))
+    }
+
+    /// Record an error that reports an unexpected end of input.
+    fn missing<T>(&mut self, what: &str) -> Option<T> {
+        let msg = format!("expected {}, found end of input", what);
+        self.fail(SyntaxErr::new(self.len, self.len, &msg))
+    }
+
+    /// Record an error, unless one has already been recorded at the same location (in which case
+    /// the new error is most likely a consequence of the earlier one).
+    fn fail<T>(&mut self, err: SyntaxErr) -> Option<T> {
+        if self.errs.last().map(|e| e.beg) != Some(err.beg) {
+            self.errs.push(err);
+        }
+        None
+    }
+}
+
+impl Cat {
+    /// Fetch the name of the part of the user interface (as named in a [theme][crate::thm::Theme])
+    /// that tokens of this category are highlighted as, unless they are left as they are (as
+    /// punctuation is).
+    pub fn part(&self) -> Option<&'static str> {
+        match self {
+            Cat::Abs | Cat::For | Cat::Sig | Cat::Let | Cat::In => Some("keyword"),
+            Cat::TypeMeta | Cat::KindMeta | Cat::Univ => Some("sort"),
+            Cat::Unit | Cat::Void | Cat::Nat | Cat::Sum => Some("builtin"),
+            Cat::Fst | Cat::Snd | Cat::Inl | Cat::Inr | Cat::Case | Cat::Absurd => Some("elim"),
+            Cat::Suc | Cat::Add | Cat::Mul | Cat::NatRec => Some("elim"),
+            Cat::Sym(_) => Some("symbol"),
+            Cat::Num(_) | Cat::Lit(Some(_)) => Some("literal"),
+            Cat::Str(_, true) => Some("string"),
+            Cat::Rem(true) => Some("comment"),
+            Cat::Hole => Some("hole"),
+            Cat::Unk(_) | Cat::Lit(None) | Cat::Str(_, false) | Cat::Rem(false) => Some("error"),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Cat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Abs => write!(f, "‘λ’"),
+            Self::For => write!(f, "‘Π’"),
+            Self::Sig => write!(f, "‘Σ’"),
+            Self::Com => write!(f, "‘,’"),
+            Self::Fst => write!(f, "‘fst’"),
+            Self::Snd => write!(f, "‘snd’"),
+            Self::Col => write!(f, "‘:’"),
+            Self::Dot => write!(f, "‘.’"),
+            Self::Arr => write!(f, "‘→’"),
+            Self::Let => write!(f, "‘let’"),
+            Self::Eq => write!(f, "‘=’"),
+            Self::In => write!(f, "‘in’"),
+            Self::Opn => write!(f, "‘(’"),
+            Self::Cls => write!(f, "‘)’"),
+            Self::Lbr => write!(f, "‘{{’"),
+            Self::Rbr => write!(f, "‘}}’"),
+            Self::Unit => write!(f, "‘⊤’"),
+            Self::Void => write!(f, "‘⊥’"),
+            Self::Sum => write!(f, "‘Sum’"),
+            Self::Inl => write!(f, "‘inl’"),
+            Self::Inr => write!(f, "‘inr’"),
+            Self::Case => write!(f, "‘case’"),
+            Self::Absurd => write!(f, "‘absurd’"),
+            Self::Nat => write!(f, "‘ℕ’"),
+            Self::Lit(Some(num)) => write!(f, "‘{}n’", num),
+            Self::Lit(None) => write!(f, "a literal"),
+            Self::Suc => write!(f, "‘suc’"),
+            Self::Add => write!(f, "‘add’"),
+            Self::Mul => write!(f, "‘mul’"),
+            Self::NatRec => write!(f, "‘Nat_rec’"),
+            Self::TypeMeta => write!(f, "‘*’"),
+            Self::KindMeta => write!(f, "‘□’"),
+            Self::Univ => write!(f, "‘Type’"),
+            Self::Sym(sym) => write!(f, "‘{}’", sym),
+            Self::Num(num) => write!(f, "‘{}’", num),
+            Self::Opr(opr) => write!(f, "‘{}’", opr),
+            Self::Str(str, _) => write!(f, "“{}”", str),
+            Self::Hole => write!(f, "‘_’"),
+            Self::Unk(chr) => write!(f, "‘{}’", chr),
+            Self::Cur => write!(f, "cursor"),
+            Self::Rem(_) => write!(f, "comment"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::cmd::Settings;
+
+    #[test]
+    fn test_par_tok_0001() {
+        let cats: Vec<Cat> = tokenize("λx' : □ . (x y_1)*")
+            .into_iter()
+            .map(|tok| tok.cat)
+            .collect();
+        assert_eq!(
+            cats,
+            vec![
+                Cat::Abs,
+                Cat::Sym("x'".to_string()),
+                Cat::Col,
+                Cat::KindMeta,
+                Cat::Dot,
+                Cat::Opn,
+                Cat::Sym("x".to_string()),
+                Cat::Sym("y_1".to_string()),
+                Cat::Cls,
+                Cat::TypeMeta,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_par_tok_0002() {
+        let mut stream = Stream::new();
+        let mut src = String::new();
+        for (idx, chr) in "λx : * -> * . (f x) → y".char_indices() {
+            src.insert(idx, chr);
+            stream.sync(&src, src.len());
+            assert_eq!(stream.toks, tokenize(&src));
+        }
+        for src in [
+            "λx : * -> * . (f x) → yz",
+            "g λx : * -> * . (f x) → yz",
+            "g λx : * - * . (f x) → yz",
+            "g λx : * -> * . (fx) → yz",
+            "g λx : * -> * . ?? (fx) → yz",
+            "λx : * -> * . ?? (fx) → yz",
+            "",
+        ] {
+            stream.sync(src, 0);
+            assert_eq!(stream.toks, tokenize(src));
+        }
+    }
+
+    #[test]
+    fn test_par_tok_0003() {
+        let mut stream = Stream::new();
+        let src = "f x ".repeat(1000);
+        assert_eq!(stream.sync(&src, 0), 2000);
+        assert_eq!(stream.sync(&format!("{}y", src), src.len() + 1), 1);
+        assert_eq!(stream.sync(&format!("g {}y", src), 1), 1);
+        assert_eq!(stream.sync(&format!("g {}y", src), 0), 0);
+        assert_eq!(stream.sync(&format!("g{}y", src), 1), 1);
+        assert_eq!(stream.toks.len(), 2001);
+    }
+
+    #[test]
+    fn test_par_tok_0004() {
+        let mut stream = Stream::new();
+        stream.sync("ab cd", 1);
+        let cats: Vec<Cat> = stream.toks().into_iter().map(|tok| tok.cat).collect();
+        assert_eq!(
+            cats,
+            vec![
+                Cat::Sym("ab".to_string()),
+                Cat::Cur,
+                Cat::Sym("cd".to_string())
+            ]
+        );
+        stream.sync("ab cd", 3);
+        assert_eq!(stream.toks()[1].cat, Cat::Cur);
+        stream.sync("ab cd", 5);
+        assert_eq!(stream.toks()[2].cat, Cat::Cur);
+        assert_eq!(stream.parse(&Ctx::new()), parse_partial("ab cd"));
+    }
+
+    #[test]
+    fn test_par_tok_0005() {
+        let src = "f -- apply f\r\n  {- to the {- nested -} argument -} x -->";
+        let toks = tokenize(src);
+        assert_eq!(toks.len(), 5);
+        assert_eq!(&src[toks[1].beg..toks[1].end], "-- apply f");
+        assert_eq!(toks[2].cat, Cat::Rem(true));
+        assert_eq!(toks[3].cat, Cat::Sym("x".to_string()));
+        assert_eq!(toks[4].cat, Cat::Rem(true));
+        assert_eq!(parse(src), parse("f x"));
+    }
+
+    #[test]
+    fn test_par_tok_0006() {
+        let mut stream = Stream::new();
+        for src in [
+            "f x y",
+            "f {- x y",
+            "f {- x -} y",
+            "f {- x {- -} y",
+            "f { x y",
+        ] {
+            stream.sync(src, 0);
+            assert_eq!(stream.toks, tokenize(src));
+        }
+    }
+
+    #[test]
+    fn test_par_err_0001() {
+        let res = parse_partial("f {- x {- -} y");
+        assert_eq!(res.exp, Some(Exp::new_var(Var::new("f"))));
+        assert_eq!(
+            res.errs,
+            vec![SyntaxErr::new(2, 14, "unterminated comment")]
+        );
+    }
+
+    #[test]
+    fn test_par_cmd_0001() {
+        let cmd = |src: &str| parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default());
+        assert_eq!(cmd("  "), Ok(Cmd::Noop));
+        assert_eq!(cmd("quit -- bye"), Ok(Cmd::Exit));
+        assert_eq!(cmd("exit"), Ok(Cmd::Exit));
+        assert_eq!(cmd("undo"), Ok(Cmd::Undo));
+        assert_eq!(cmd("reset"), Ok(Cmd::Reset));
+        assert!(cmd("undo 2").is_err());
+        assert_eq!(cmd("help"), Ok(Cmd::Help(None)));
+        assert_eq!(
+            cmd("help type"),
+            Ok(Cmd::Help(Some(Box::new(Cmd::Type(Default::default())))))
+        );
+        assert_eq!(cmd("show λx : * . x"), parse("λx : * . x").map(Cmd::Show));
+        assert_eq!(cmd("type f x"), parse("f x").map(Cmd::Type));
+        assert_eq!(cmd("exec 1"), parse("1").map(Cmd::Exec));
+    }
+
+    #[test]
+    fn test_par_cmd_0002() {
+        let cmd = |src: &str| parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default());
+        assert_eq!(
+            cmd("show λx : * x"),
+            Err(SyntaxErr::new(
+                14,
+                14,
+                "expected ‘.’, found end of input (line 1, column 14)"
+            ))
+        );
+        assert_eq!(
+            cmd("type\n  (x"),
+            Err(SyntaxErr::new(
+                9,
+                9,
+                "expected ‘)’, found end of input (line 2, column 5)"
+            ))
+        );
+        assert!(cmd("exit now").is_err());
+        assert!(cmd("help me").is_err());
+        assert!(cmd("help show x").is_err());
+        assert_eq!(
+            cmd("frobnicate x"),
+            Err(SyntaxErr::new(
+                0,
+                10,
+                "unknown command ‘frobnicate’ (line 1, column 1)"
+            ))
+        );
+        assert!(cmd("(show x)").is_err());
+        assert!(cmd("show").is_err());
+    }
+
+    #[test]
+    fn test_par_cmd_0003() {
+        let cmd = |src: &str| parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default());
+        assert_eq!(
+            cmd("infixr 5 \"⊕\" := plus"),
+            Ok(Cmd::Note(Notation::new(
+                Fixity::Infixr,
+                5,
+                "⊕",
+                Var::new("plus")
+            )))
+        );
+        assert_eq!(
+            cmd("infix 4 \"==\" := eq"),
+            Ok(Cmd::Note(Notation::new(
+                Fixity::Infix,
+                4,
+                "==",
+                Var::new("eq")
+            )))
+        );
+        assert!(cmd("infixl 10 \"+\" := plus").is_err());
+        assert!(cmd("infixl 6 \"→\" := arrow").is_err());
+        assert!(cmd("infixl 6 \"a\" := plus").is_err());
+        assert!(cmd("infixl 6 \"+\" = plus").is_err());
+        assert!(cmd("infixl 6 \"+\" := plus minus").is_err());
+        assert!(cmd("infixl 6 \"+ := plus").is_err());
+    }
+
+    #[test]
+    fn test_par_cmd_0004() {
+        let cmd = |src: &str| parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default());
+        let typ = |src: &str| parse(src).unwrap();
+        assert_eq!(
+            cmd("data Peano := zero | succ Peano"),
+            Ok(Cmd::Data(Data::new(
+                Var::new("Peano"),
+                vec![
+                    Ctor::new(Var::new("zero"), vec![]),
+                    Ctor::new(Var::new("succ"), vec![typ("Peano")]),
+                ]
+            )))
+        );
+        assert_eq!(
+            cmd("data T := leaf | node (Peano → T) (T) Bool"),
+            Ok(Cmd::Data(Data::new(
+                Var::new("T"),
+                vec![
+                    Ctor::new(Var::new("leaf"), vec![]),
+                    Ctor::new(
+                        Var::new("node"),
+                        vec![typ("Peano → T"), typ("T"), typ("Bool")]
+                    ),
+                ]
+            )))
+        );
+        assert_eq!(
+            cmd("data Empty :="),
+            Ok(Cmd::Data(Data::new(Var::new("Empty"), vec![])))
+        );
+        assert_eq!(
+            cmd("data T := leaf | node (Peano → T) T").map(|cmd| match cmd {
+                Cmd::Data(dat) => dat.to_string(),
+                _ => String::new(),
+            }),
+            Ok("data T := leaf | node (Peano → T) T".to_string())
+        );
+        assert!(cmd("data Peano := zero |").is_err());
+        assert!(cmd("data Peano := | zero").is_err());
+        assert!(cmd("data Peano = zero").is_err());
+        assert!(cmd("data := zero").is_err());
+        assert!(cmd("data Peano := zero succ → Peano").is_err());
+        assert!(cmd("data Peano := zero | succ (Peano").is_err());
+    }
+
+    #[test]
+    fn test_par_cmd_0005() {
+        let cmd = |src: &str| parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default());
+        for stg in Strategy::ALL {
+            let src = format!("set strategy {}", stg);
+            assert_eq!(cmd(&src), Ok(Cmd::Set(Setting::Strategy(stg))));
+            assert_eq!(Setting::Strategy(stg).to_string(), src);
+        }
+        assert_eq!(
+            cmd("set strategy lazy"),
+            Err(SyntaxErr::new(
+                13,
+                17,
+                "expected one of normal, cbv, whnf, found ‘lazy’ (line 1, column 14)"
+            ))
+        );
+        assert!(cmd("set strategy").is_err());
+        assert!(cmd("set strategy cbv whnf").is_err());
+        assert_eq!(cmd("set fuel 10"), Ok(Cmd::Set(Setting::Fuel(10))));
+        assert_eq!(Setting::Fuel(10).to_string(), "set fuel 10");
+        assert!(cmd("set fuel ten").is_err());
+        assert!(cmd("set fuel").is_err());
+        assert!(cmd("set").is_err());
+    }
+
+    #[test]
+    fn test_par_cmd_0006() {
+        let cmd = |src: &str| parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default());
+        let save = Session::Save(PathBuf::from("work/main.fx"));
+        assert_eq!(
+            cmd(r#"session save "work/main.fx""#),
+            Ok(Cmd::Session(save.clone()))
+        );
+        assert_eq!(save.to_string(), r#"session save "work/main.fx""#);
+        let load = Session::Load(PathBuf::from("main.fx"));
+        assert_eq!(cmd(r#"session load "main.fx""#), Ok(Cmd::Session(load)));
+        assert_eq!(
+            cmd("session load main.fx"),
+            Err(SyntaxErr::new(
+                13,
+                17,
+                "expected the path of a file, found ‘main’ (line 1, column 14)"
+            ))
+        );
+        assert!(cmd(r#"session load """#).is_err());
+        assert!(cmd(r#"session load "main.fx"#).is_err());
+        assert!(cmd(r#"session open "main.fx""#).is_err());
+        assert!(cmd(r#"session save "a" "b""#).is_err());
+        assert!(cmd("session").is_err());
+        assert_eq!(cmd("session recover"), Ok(Cmd::Session(Session::Recover)));
+        assert_eq!(Session::Recover.to_string(), "session recover");
+        assert!(cmd(r#"session recover "main.fx""#).is_err());
+    }
+
+    #[test]
+    fn test_par_cmd_0007() {
+        let cmd = |src: &str| parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default());
+        assert_eq!(cmd("open nat"), Ok(Cmd::Open(Var::new("nat"))));
+        assert_eq!(cmd("open data.list"), Ok(Cmd::Open(Var::new("data.list"))));
+        assert!(cmd("open data. list").is_err());
+        assert!(cmd("open data.").is_err());
+        assert!(cmd("open nat int").is_err());
+        assert!(cmd("open").is_err());
+    }
+
+    #[test]
+    fn test_par_cmd_0008() -> Result<(), Box<dyn std::error::Error>> {
+        let cmd = |src: &str, ctx: &Ctx| parse_cmd(src, &tokenize(src), ctx, &Settings::default());
+        assert_eq!(
+            cmd(
+                "let id : Π{A : *} . A → A = λ{A : *} . λx : A . x",
+                &Ctx::new()
+            ),
+            Ok(Cmd::Let(
+                Var::new("id"),
+                parse("Π{A : *} . A → A")?,
+                parse("λ{A : *} . λx : A . x")?
+            ))
+        );
+        assert_eq!(
+            cmd("let nat.one : {n : ℕ} = {n = 1}", &Ctx::new()),
+            Ok(Cmd::Let(
+                Var::new("nat.one"),
+                parse("{n : ℕ}")?,
+                parse("{n = 1}")?
+            ))
+        );
+        assert!(cmd("let id = λx . x", &Ctx::new()).is_err());
+        assert!(cmd("let id : * → *", &Ctx::new()).is_err());
+        assert!(cmd("let id : * → * =", &Ctx::new()).is_err());
+        assert!(cmd("let : * = ℕ", &Ctx::new()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_par_cmd_0009() -> Result<(), Box<dyn std::error::Error>> {
+        let cmd = |src: &str| parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default());
+        assert_eq!(
+            cmd("assume lem : Π{P : *} . Sum P (P → ⊥)"),
+            Ok(Cmd::Assume(
+                Var::new("lem"),
+                parse("Π{P : *} . Sum P (P → ⊥)")?
+            ))
+        );
+        assert_eq!(
+            cmd("assume set.U : *"),
+            Ok(Cmd::Assume(Var::new("set.U"), Exp::Sort(0)))
+        );
+        assert!(cmd("assume lem").is_err());
+        assert!(cmd("assume lem :").is_err());
+        assert!(cmd("assume : *").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_par_cmd_0010() {
+        let cmd = |src: &str| parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default());
+        assert_eq!(
+            cmd(r#"load "lib/nat.fx""#),
+            Ok(Cmd::Load(PathBuf::from("lib/nat.fx")))
+        );
+        assert!(cmd("load").is_err());
+        assert!(cmd("load lib").is_err());
+        let src = "-- naturals\nassume A : *\n\nlet id : A → A =\n  λx . x\n{- done -}\n  show id";
+        let toks = tokenize(src);
+        let cmds: Vec<_> = split_commands(src, &toks)
+            .into_iter()
+            .map(|(line, toks)| (line, toks.len()))
+            .collect();
+        assert_eq!(cmds, [(2, 4), (4, 11)]);
+    }
+
+    #[test]
+    fn test_par_cmd_0011() {
+        let cmd = |src: &str| parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default());
+        assert_eq!(cmd("env"), Ok(Cmd::Env(1)));
+        assert_eq!(cmd("env 3"), Ok(Cmd::Env(3)));
+        assert!(cmd("env 0").is_err());
+        assert!(cmd("env all").is_err());
+        assert!(cmd("env 1 2").is_err());
+    }
+
+    #[test]
+    fn test_par_cmd_0012() -> Result<(), Box<dyn std::error::Error>> {
+        let cmd = |src: &str| parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default());
+        assert_eq!(cmd("step"), Ok(Cmd::Step(None)));
+        assert_eq!(
+            cmd("step (λx : * . x) ⊤"),
+            Ok(Cmd::Step(Some(parse("(λx : * . x) ⊤")?)))
+        );
+        assert!(cmd("step (").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_par_cmd_0013() {
+        let cmd = |src: &str| parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default());
+        assert_eq!(cmd("trace 1n"), Ok(Cmd::Trace(Exp::Lit(1))));
+        assert!(cmd("trace").is_err());
+    }
+
+    #[test]
+    fn test_par_cmd_0014() {
+        let cmd = |src: &str| parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default());
+        assert_eq!(cmd("whnf fst x"), parse("fst x").map(Cmd::Whnf));
+        assert!(cmd("whnf").is_err());
+    }
+
+    #[test]
+    fn test_par_cmd_0015() -> Result<(), Box<dyn std::error::Error>> {
+        let cmd = |src: &str| parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default());
+        assert_eq!(
+            cmd("eq (λx : * . x) f a"),
+            Ok(Cmd::Eq(parse("λx : * . x")?, parse("f a")?))
+        );
+        assert_eq!(cmd("eq 2n 2n"), Ok(Cmd::Eq(Exp::Lit(2), Exp::Lit(2))));
+        assert!(cmd("eq 2n").is_err());
+        assert!(cmd("eq").is_err());
+        assert!(cmd("eq (2n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_par_cmd_0016() {
+        let cmd = |src: &str| parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default());
+        assert_eq!(cmd("time add 1n 1n"), parse("add 1n 1n").map(Cmd::Time));
+        assert!(cmd("time").is_err());
+    }
+
+    #[test]
+    fn test_par_cmd_0017() {
+        let cmd = |src: &str| parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default());
+        assert_eq!(cmd("search _ → ℕ"), parse("_ → ℕ").map(Cmd::Search));
+        assert!(cmd("search").is_err());
+    }
+
+    #[test]
+    fn test_par_cmd_0018() {
+        let cmd = |src: &str| parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default());
+        assert_eq!(cmd("import prelude"), Ok(Cmd::Import(Var::new("prelude"))));
+        assert!(cmd("import").is_err());
+        assert!(cmd("import \"prelude.fx\"").is_err());
+    }
+
+    #[test]
+    fn test_par_cmd_0019() {
+        let cats = |src: &str| {
+            tokenize(src)
+                .into_iter()
+                .map(|tok| tok.cat)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(cats("%12"), [Cat::Sym("%12".into())]);
+        assert_eq!(cats("% 12"), [Cat::Opr("%".into()), Cat::Num(12)]);
+        assert_eq!(cats("%%"), [Cat::Opr("%%".into())]);
+    }
+
+    #[test]
+    fn test_par_cmd_0020() {
+        let cmd = |src: &str| parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default());
+        assert!(
+            matches!(cmd("help let"), Ok(Cmd::Help(Some(tgt))) if matches!(*tgt, Cmd::Let(..)))
+        );
+        assert!(cmd("help frobnicate").is_err());
+        assert!(cmd("help show type").is_err());
+    }
+
+    #[test]
+    fn test_par_cmd_0021() {
+        let mut ctx = Ctx::new();
+        let mut set = Settings::default();
+        let cmd = |src: &str, set: &Settings| parse_cmd(src, &tokenize(src), &Ctx::new(), set);
+        let alias = |name: &str, src: &str| Cmd::Alias(Var::new(name), src.into());
+        let none = Settings::default();
+        assert_eq!(cmd("alias t = type", &none), Ok(alias("t", "type")));
+        assert_eq!(
+            cmd("alias nf = set  strategy normal", &none),
+            Ok(alias("nf", "set  strategy normal"))
+        );
+        assert!(cmd("alias", &none).is_err());
+        assert!(cmd("alias t type", &none).is_err());
+        assert!(cmd("alias t = 1n", &none).is_err());
+        assert!(cmd("alias t =", &none).is_err());
+        assert!(cmd("alias show = type", &none).is_err());
+        // an alias stands for the command only where it begins a command, and cannot stand for another
+        alias("t", "type").eval(&mut ctx, &mut set);
+        alias("nf", "set strategy normal").eval(&mut ctx, &mut set);
+        assert_eq!(
+            cmd("t λx : ℕ . x", &set),
+            parse("λx : ℕ . x").map(Cmd::Type)
+        );
+        assert_eq!(cmd("nf", &set), cmd("set strategy normal", &none));
+        assert!(cmd("nf now", &set).is_err());
+        assert_eq!(cmd("show t", &set), parse("t").map(Cmd::Show));
+        assert!(cmd("alias u = t", &set).is_err());
+        assert_eq!(cmd("alias t = show", &set), Ok(alias("t", "show")));
+        alias("t", "show").eval(&mut ctx, &mut set);
+        assert_eq!(cmd("t 1n", &set), parse("1n").map(Cmd::Show));
+        // aliases are settings of the session, rather than part of its context
+        assert_eq!(set.get_alias(&Var::new("t")), Some("show"));
+        assert_eq!(set.get_alias(&Var::new("u")), None);
+        assert_eq!(cmd("t 1n", &none).map_err(|e| (e.beg, e.end)), Err((0, 1)));
+        // syntax errors are located in the command as written, rather than as expanded
+        let pos = |src: &str| cmd(src, &set).map_err(|e| (e.beg, e.end, e.msg));
+        assert_eq!(
+            pos("t (Type"),
+            Err((
+                7,
+                7,
+                "expected ‘)’, found end of input (line 1, column 8)".into()
+            ))
+        );
+        assert_eq!(
+            pos("nf  now"),
+            Err((
+                4,
+                7,
+                "expected end of input, found ‘now’ (line 1, column 5)".into()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_par_cmd_0022() {
+        let src = "set gutter numbers";
+        let cmd = parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default());
+        assert_eq!(cmd, Ok(Cmd::Set(Setting::Gutter(Gutter::Numbers))));
+        assert_eq!(Setting::Gutter(Gutter::Numbers).to_string(), src);
+        let src = "set gutter lines";
+        assert!(parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default()).is_err());
+    }
+
+    #[test]
+    fn test_par_cmd_0023() {
+        let src = "set keymap emacs";
+        let cmd = parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default());
+        assert_eq!(cmd, Ok(Cmd::Set(Setting::KeySet(KeySet::Emacs))));
+        assert_eq!(Setting::KeySet(KeySet::Emacs).to_string(), src);
+        let src = "set keymap vi";
+        assert!(parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default()).is_err());
+    }
+
+    #[test]
+    fn test_par_cmd_0024() {
+        let src = "set theme high-contrast";
+        let cmd = parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default());
+        assert_eq!(cmd, Ok(Cmd::Set(Setting::Theme(Preset::HighContrast))));
+        assert_eq!(Setting::Theme(Preset::HighContrast).to_string(), src);
+        for src in ["set theme sepia", "set theme high - contrast", "set theme"] {
+            assert!(parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_par_cmd_0025() {
+        let src = "set ascii on";
+        let cmd = parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default());
+        assert_eq!(cmd, Ok(Cmd::Set(Setting::Ascii(true))));
+        assert_eq!(Setting::Ascii(false).to_string(), "set ascii off");
+        for src in ["set ascii yes", "set ascii", "set ascii on off"] {
+            assert!(parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_par_cmd_0026() -> Result<(), Box<dyn std::error::Error>> {
+        let cmd = |src: &str| parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default());
+        assert_eq!(
+            cmd("export html λx : ℕ . x"),
+            Ok(Cmd::Export(Markup::Html, parse("λx : ℕ . x")?))
+        );
+        assert!(cmd("export pdf ℕ").is_err());
+        assert!(cmd("export latex").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_par_cmd_0027() {
+        let src = "set warnings off";
+        let cmd = parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default());
+        assert_eq!(cmd, Ok(Cmd::Set(Setting::Warnings(false))));
+        assert_eq!(Setting::Warnings(true).to_string(), "set warnings on");
+        for src in ["set warnings no", "set warnings", "set warnings on off"] {
+            assert!(parse_cmd(src, &tokenize(src), &Ctx::new(), &Settings::default()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_par_opr_0001() {
+        let mut ctx = Ctx::new();
+        ctx.put_notation(Notation::new(Fixity::Infixl, 6, "+", Var::new("plus")));
+        ctx.put_notation(Notation::new(Fixity::Infixl, 7, "×", Var::new("times")));
+        ctx.put_notation(Notation::new(Fixity::Infixr, 8, "^", Var::new("pow")));
+        let exp = |src: &str| parse_in(src, &ctx);
+        assert_eq!(exp("a + b"), parse("plus a b"));
+        assert_eq!(exp("a + b + c"), parse("plus (plus a b) c"));
+        assert_eq!(exp("a ^ b ^ c"), parse("pow a (pow b c)"));
+        assert_eq!(exp("a + b × c"), parse("plus a (times b c)"));
+        assert_eq!(exp("f a × g b + c"), parse("plus (times (f a) (g b)) c"));
+        assert_eq!(exp("(a + b) × c"), parse("times (plus a b) c"));
+        assert_eq!(exp("a + b → c"), parse("plus a b → c"));
+        assert_eq!(
+            exp("a + λx : * . x + b"),
+            parse("plus a (λx : * . plus x b)")
+        );
+    }
+
+    #[test]
+    fn test_par_opr_0002() {
+        let mut ctx = Ctx::new();
+        ctx.put_notation(Notation::new(Fixity::Infixl, 6, "+", Var::new("plus")));
+        ctx.put_notation(Notation::new(Fixity::Infixr, 6, "++", Var::new("cat")));
+        ctx.put_notation(Notation::new(Fixity::Infix, 4, "==", Var::new("eq")));
+        let exp = |src: &str| parse_in(src, &ctx);
+        assert_eq!(exp("a == b"), parse("eq a b"));
+        assert_eq!(
+            exp("a == b == c"),
+            Err(SyntaxErr::new(
+                7,
+                9,
+                "cannot chain ‘==’ and ‘==’ without parentheses"
+            ))
+        );
+        assert!(exp("a + b ++ c").is_err());
+        assert!(exp("a ++ b + c").is_err());
+        assert_eq!(
+            exp("a - b"),
+            Err(SyntaxErr::new(
+                2,
+                3,
+                "expected a declared operator, found ‘-’"
+            ))
+        );
+        assert!(parse("a + b").is_err());
+    }
+
+    #[test]
+    fn test_par_opr_0003() {
+        let mut ctx = Ctx::new();
+        ctx.put_notation(Notation::new(Fixity::Infixl, 6, "+", Var::new("plus")));
+        ctx.put_notation(Notation::new(Fixity::Infixl, 7, "×", Var::new("times")));
+        ctx.put_notation(Notation::new(Fixity::Infixr, 8, "^", Var::new("pow")));
+        for src in [
+            "a + b × c",
+            "(a + b) × c",
+            "a × b + c × d",
+            "a ^ b ^ c",
+            "(a ^ b) ^ c",
+            "f a + g (b + c)",
+            "a + b → c × d",
+            "(a → b) + c",
+            "(λx : * . x) + a",
+            "a + λx : * . x + b",
+            "a + (λx : * . x) + b",
+        ] {
+            assert_eq!(
+                parse_in(src, &ctx).map(|exp| exp.display(&ctx).to_string()),
+                Ok(src.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_par_spn_0001() {
+        let (exp, span) = Exp::parse_spanned("λx : (a b) . f x 3").unwrap();
+        assert_eq!(Ok(exp), "λx : (a b) . f x 3".parse::<Exp>());
+        let leaf = |beg, end| Span::new(beg, end, vec![]);
+        assert_eq!(
+            span,
+            Span::new(
+                0,
+                19,
+                vec![
+                    Span::new(6, 11, vec![leaf(7, 8), leaf(9, 10)]),
+                    Span::new(
+                        14,
+                        19,
+                        vec![
+                            Span::new(14, 17, vec![leaf(14, 15), leaf(16, 17)]),
+                            leaf(18, 19)
+                        ]
+                    ),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn test_par_spn_0002() {
+        let mut ctx = Ctx::new();
+        ctx.put_notation(Notation::new(Fixity::Infixl, 6, "+", Var::new("plus")));
+        let (_, span) = parse_spanned("a + b → c", &ctx).unwrap();
+        let leaf = |beg, end| Span::new(beg, end, vec![]);
+        assert_eq!(
+            span,
+            Span::new(
+                0,
+                11,
+                vec![
+                    Span::new(
+                        0,
+                        5,
+                        vec![Span::new(0, 3, vec![leaf(2, 3), leaf(0, 1)]), leaf(4, 5)]
+                    ),
+                    leaf(10, 11),
+                ]
+            )
+        );
+        assert!("λx : * .".parse::<Exp>().is_err());
+    }
+
+    #[test]
+    fn test_par_spn_0003() {
+        let mut ctx = Ctx::new();
+        let mut set = Settings::default();
+        let mut run = |src: &str| {
+            let toks = tokenize(src);
+            let cmd = parse_cmd(src, &toks, &ctx, &set).unwrap();
+            let err = cmd.eval(&mut ctx, &mut set).err;
+            let pos = err.and_then(|e| locate_err(src, &toks, &ctx, &e));
+            pos.map(|(beg, end)| src[beg..end].to_string())
+        };
+        assert_eq!(run("assume A : *"), None);
+        assert_eq!(run("let f : A → A = λx : A . x"), None);
+        // the error is blamed on the argument, not on the types that are spelled the same
+        let src = "let g : A → A = λx : A . f (f A)";
+        assert_eq!(run(src).as_deref(), Some("A"));
+        assert_eq!(run("eq A (f y)").as_deref(), Some("y"));
+        assert_eq!(run("show (λx : A . x) (f A)").as_deref(), Some("A"));
+        // a field labelled again is blamed on its value
+        assert_eq!(run("show {x = f, x = 1n}").as_deref(), Some("1n"));
+    }
+
+    #[test]
+    fn test_par_exp_0001() {
+        for src in [
+            "Σx : A . P x",
+            "(a, b : Σx : A . B)",
+            "fst p",
+            "f (snd (fst p))",
+            "(λx : * . x, fst q : Σx : A . B)",
+        ] {
+            assert_eq!(parse(src).map(|exp| exp.to_string()), Ok(src.to_string()));
+        }
+        assert_eq!(
+            parse("exists x : Type . sigma y : x . x").map(|exp| format!("{:#}", exp)),
+            Ok("exists x : Type . exists y : x . x".to_string())
+        );
+        assert!(parse("(a, b)").is_err());
+        assert!(parse("fst").is_err());
+    }
+
+    #[test]
+    fn test_par_exp_0002() {
+        for src in ["Type 2", "λx : Type 3 . x", "f (Type 2) *", "□ → Type 5"] {
+            assert_eq!(parse(src).map(|exp| exp.to_string()), Ok(src.to_string()));
+        }
+        assert_eq!(parse("Type 0"), Ok(Exp::get_type_meta()));
+        assert_eq!(parse("Type"), Ok(Exp::get_type_meta()));
+        assert_eq!(parse("Type 1"), Ok(Exp::get_kind_meta()));
+        assert_eq!(
+            parse("Type 7 → Type 1").map(|exp| format!("{:#}", exp)),
+            Ok("Type 7 -> Kind".to_string())
+        );
+    }
+
+    #[test]
+    fn test_par_exp_0003() {
+        for src in [
+            "Π{A : *} . A → A",
+            "λ{A : *} . λx : A . x",
+            "(λ{A : *} . A) B",
+        ] {
+            assert_eq!(parse(src).map(|exp| exp.to_string()), Ok(src.to_string()));
+        }
+        assert_eq!(
+            parse("forall {A : Type} . A").map(|exp| format!("{:#}", exp)),
+            Ok("forall {A : Type} . A".to_string())
+        );
+        assert_eq!(
+            parse("Π{A : *} . A"),
+            Ok(Exp::new_for(
+                Var::new("A"),
+                Exp::Imp(Box::new(Exp::get_type_meta())),
+                Exp::new_var(Var::new("A"))
+            ))
+        );
+        assert_eq!(parse("{- note -} x"), parse("x"));
+        assert!(parse("Σ{A : *} . A").is_err());
+        assert!(parse("λ{x : * . x").is_err());
+    }
+
+    #[test]
+    fn test_par_exp_0004() {
+        assert_eq!(
+            parse("λx . x"),
+            Ok(Exp::new_abs(
+                Var::new("x"),
+                Exp::get_hole(),
+                Exp::new_var(Var::new("x"))
+            ))
+        );
+        assert_eq!(parse("λx . λy : * . x"), parse("λx : _ . λy : * . x"));
+        assert!(parse("Πx . x").is_err());
+        assert!(parse("λ{x} . x").is_err());
+    }
+
+    #[test]
+    fn test_par_exp_0005() {
+        for src in [
+            "⊤ → ⊥",
+            "()",
+            "Sum A (Sum ⊤ ⊥)",
+            "inl B a",
+            "case P f g (inr A b)",
+        ] {
+            assert_eq!(parse(src).map(|exp| exp.to_string()), Ok(src.to_string()));
+        }
+        assert_eq!(
+            parse("absurd (Unit -> Void) v").map(|exp| format!("{:#}", exp)),
+            Ok("absurd (Unit -> Void) v".to_string())
+        );
+        assert_eq!(
+            parse("inl B a b"),
+            Ok(Exp::new_app(
+                Exp::new_inl(Exp::new_var(Var::new("B")), Exp::new_var(Var::new("a"))),
+                Exp::new_var(Var::new("b"))
+            ))
+        );
+        assert_eq!(parse("( )"), Ok(Exp::Triv));
+        assert!(parse("Sum A").is_err());
+        assert!(parse("case P f g").is_err());
+    }
+
+    #[test]
+    fn test_par_exp_0006() {
+        for src in [
+            "ℕ → ℕ",
+            "42n",
+            "suc (add 1n (mul x 2n))",
+            "Nat_rec P 0n s n",
+            "f 3 3n",
+        ] {
+            assert_eq!(parse(src).map(|exp| exp.to_string()), Ok(src.to_string()));
+        }
+        assert_eq!(
+            parse("Nat").map(|exp| format!("{:#}", exp)),
+            Ok("Nat".to_string())
+        );
+        assert_eq!(parse("7n"), Ok(Exp::Lit(7)));
+        assert_eq!(
+            parse("3nat"),
+            Ok(Exp::new_app(Exp::new_num(3), Exp::new_var(Var::new("nat"))))
+        );
+        assert!(parse("99999999999999999999999n").is_err());
+        assert!(parse("suc").is_err());
+    }
+
+    #[test]
+    fn test_par_exp_0007() {
+        for src in [
+            "{x : ℕ, y : ℕ → ℕ}",
+            "{x = 1n, y = λn : ℕ . n}",
+            "r.x",
+            "f r.x.y",
+            "(f r).x",
+            "{p = {q = a}}.p.q",
+            "λr : {x : *} . r.x",
+        ] {
+            assert_eq!(parse(src).map(|exp| exp.to_string()), Ok(src.to_string()));
+        }
+        assert_eq!(
+            parse("(r).x"),
+            Ok(Exp::new_proj(Exp::new_var(Var::new("r")), Var::new("x")))
+        );
+        assert_eq!(
+            parse("λx : A . x").map(|exp| exp.to_string()),
+            Ok("λx : A . x".to_string())
+        );
+        assert!(parse("r. x").is_err());
+        assert!(parse("{}").is_err());
+        assert!(parse("{x : A, y = a}").is_err());
+        assert_eq!(
+            parse("{x = a, x = b}").map(|exp| exp.to_string()),
+            Ok("{x = a, x = b}".to_string())
+        );
+        assert_eq!(
+            parse_partial("{x : A, y : B").errs,
+            vec![SyntaxErr::new(13, 13, "expected ‘}’, found end of input")]
+        );
+    }
+
+    #[test]
+    fn test_par_exp_0008() -> Result<(), Box<dyn std::error::Error>> {
+        // the largest literal permitted is type-checked and reduced within the stack of a thread
+        let ctx = Ctx::new();
+        let exp = parse(&MAX_NUM.to_string())?;
+        assert_eq!(
+            exp.infer(&ctx)?.to_string(),
+            "Πnat : * . (nat → nat) → nat → nat"
+        );
+        assert_eq!(exp.reduce(&ctx)?.to_string(), MAX_NUM.to_string());
+        assert!(parse(&(MAX_NUM + 1).to_string()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_par_exp_0009() {
+        assert_eq!(
+            parse("x y z"),
+            Ok(Exp::new_app(
+                Exp::new_app(Exp::new_var(Var::new("x")), Exp::new_var(Var::new("y"))),
+                Exp::new_var(Var::new("z")),
+            ))
+        );
+    }
+
+    #[test]
+    fn test_par_exp_0010() {
+        assert_eq!(
+            parse("λx : * . Πy : x . y"),
+            Ok(Exp::new_abs(
+                Var::new("x"),
+                Exp::get_type_meta(),
+                Exp::new_for(
+                    Var::new("y"),
+                    Exp::new_var(Var::new("x")),
+                    Exp::new_var(Var::new("y")),
+                ),
+            ))
+        );
+    }
+
+    #[test]
+    fn test_par_exp_0011() {
+        for src in [
+            "x (y z)",
+            "x λy : * . λw : * . w m",
+            "x (λy : * . λw : * . w) m",
+            "(λy : (λy : b . y) t . y r) t",
+            "(□ → λm : * . w) k",
+            "λx : * → * . x",
+        ] {
+            assert_eq!(parse(src).map(|exp| exp.to_string()), Ok(src.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_par_exp_0012() {
+        assert_eq!(
+            parse("\\x : Type . forall y : x . pi z : Kind . y"),
+            parse("λx : * . Πy : x . Πz : □ . y")
+        );
+    }
+
+    #[test]
+    fn test_par_exp_0013() {
+        for src in [
+            "\\x : Type . forall y : x . y",
+            "x (\\y : Type . \\w : Type . w) m",
+            "(Kind -> \\m : Type . w) k",
+        ] {
+            assert_eq!(
+                parse(src).map(|exp| format!("{:#}", exp)),
+                Ok(src.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_par_exp_0014() {
+        assert_eq!(
+            parse("* → *"),
+            Ok(Exp::For(
+                Var::new("_"),
+                Box::new(Exp::get_type_meta()),
+                Box::new(Exp::get_type_meta())
+            ))
+        );
+        assert_eq!(parse("a -> b → c"), parse("a → (b → c)"));
+        assert_ne!(parse("a → b → c"), parse("(a → b) → c"));
+    }
+
+    #[test]
+    fn test_par_exp_0015() {
+        for src in [
+            "a → b → c",
+            "(a → b) → c",
+            "x y → z",
+            "x (a → b) c",
+            "x (y (a → b))",
+            "x (λy : * . y) → z",
+            "Πx : * . x → x",
+            "Πx : * . Πy : x . * → y",
+            "(Πx : * . x) → *",
+            "λf : * → * . f",
+        ] {
+            assert_eq!(parse(src).map(|exp| exp.to_string()), Ok(src.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_par_exp_0016() {
+        assert_eq!(
+            parse("let id : * → * = λx : * . x in f (id y)"),
+            parse("(λid : * → * . f (id y)) (λx : * . x)")
+        );
+        assert_eq!(
+            parse("f let x : * = y in let z : * = x in z"),
+            parse("f ((λx : * . (λz : * . z) x) y)")
+        );
+    }
+
+    #[test]
+    fn test_par_exp_0017() {
+        assert_eq!(
+            parse("2"),
+            parse("λnat : * . λs : nat → nat . λz : nat . s (s z)")
+        );
+        for src in ["0", "f 1 (g 12) x2", "λn : * . 3"] {
+            assert_eq!(parse(src).map(|exp| exp.to_string()), Ok(src.to_string()));
+        }
+        assert_eq!(
+            parse("λa : * . λb : a → a . λc : a . b (b c)").map(|exp| exp.to_string()),
+            Ok("2".to_string())
+        );
+        assert_eq!(
+            parse("λa : * . λb : a → a . λc : a . c (b c)").map(|exp| exp.to_string()),
+            Ok("λa : * . λb : a → a . λc : a . c (b c)".to_string())
+        );
+        assert!(parse("101").is_err());
+        assert!(parse("65537").is_err());
+        assert!(parse("99999999999999999999999").is_err());
+    }
+
+    #[test]
+    fn test_par_exp_0018() {
+        assert_eq!(
+            parse("f _ _x"),
+            Ok(Exp::new_app(
+                Exp::new_app(Exp::new_var(Var::new("f")), Exp::get_hole()),
+                Exp::new_var(Var::new("_x"))
+            ))
+        );
+        assert_eq!(
+            parse("λx : _ . _").map(|exp| exp.to_string()),
+            Ok("λx : _ . _".to_string())
+        );
+    }
+
+    #[test]
+    fn test_par_exp_0019() {
+        assert_eq!(parse("((x))  ( y )"), parse("x y"));
+    }
+
+    #[test]
+    fn test_par_err_0002() {
+        let err = parse("λx x").unwrap_err();
+        assert_eq!((err.beg, err.end), (4, 5));
+    }
+
+    #[test]
+    fn test_par_err_0003() {
+        let err = parse("(x y").unwrap_err();
+        assert_eq!((err.beg, err.end), (4, 4));
+    }
+
+    #[test]
+    fn test_par_err_0004() {
+        let res = parse_partial("λx * . (f x");
+        assert_eq!(
+            res.exp.map(|exp| exp.to_string()),
+            Some("λx : * . f x".to_string())
+        );
+        assert_eq!(
+            res.errs
+                .iter()
+                .map(|err| (err.beg, err.end))
+                .collect::<Vec<_>>(),
+            vec![(4, 5), (12, 12)]
+        );
+    }
+
+    #[test]
+    fn test_par_err_0005() {
+        let res = parse_partial("f ? x ) y");
+        assert_eq!(res.exp.map(|exp| exp.to_string()), Some("f x".to_string()));
+        assert_eq!(res.errs.len(), 2);
+    }
+
+    #[test]
+    fn test_par_err_0006() {
+        let res = parse_partial("λx : * . ");
+        assert_eq!(res.exp, None);
+        assert_eq!(res.errs.len(), 1);
+        assert_eq!(parse("λx : * . ").unwrap_err(), res.errs[0]);
+    }
+
+    #[test]
+    fn test_par_err_0007() {
+        let res = parse_partial("let x * = y in x");
+        assert_eq!(res.exp, parse("let x : * = y in x").ok());
+        assert_eq!(
+            res.errs,
+            vec![SyntaxErr::new(6, 7, "expected ‘:’, found ‘*’")]
+        );
+    }
+
+    #[test]
+    fn test_par_err_0008() {
+        assert!(parse("").is_err());
+        assert!(parse("x )").is_err());
+        assert!(parse("λ* : * . x").is_err());
+        assert!(parse("x ? y").is_err());
+    }
+
+    #[test]
+    fn test_par_err_0009() {
+        use crate::err::Diagnostic;
+        let src = "assume A : *\nlet g : A =\n  f (g A)\nshow (g";
+        let diag = Diagnostic::spanning(src, (30, 33), ":type g A\n    = ?\n    ∉ {A}\n");
+        assert_eq!((diag.line, diag.col, diag.width), (3, 6, 3));
+        // the offending part is underlined, and labeled with all but the first line of the message
+        assert_eq!(
+            diag.render(src, Some("a.fx")),
+            "a.fx:3:6: error: :type g A\n  |\n3 |   f (g A)\n  |      ^^^ = ? ∉ {A}\n"
+        );
+        // a range that runs past its line is underlined as far as the end of the line
+        let diag = Diagnostic::spanning(src, (13, src.len()), ":parse oops");
+        assert_eq!((diag.line, diag.col, diag.width), (2, 1, 11));
+        assert_eq!(
+            diag.render(src, None),
+            ":parse oops\n  |\n2 | let g : A =\n  | ^^^^^^^^^^^\n"
+        );
+    }
+
+    #[test]
+    fn test_par_err_0010() {
+        let src = "assume A : *\nassume f : A → A\ntype λx : A . f (f x x)\n";
+        let diags = Cmd::check_source(src, &mut Ctx::new(), &mut Settings::default());
+        assert_eq!(diags.len(), 1);
+        // the steps that the error arose within are noted after the offending line, innermost first
+        let out = diags[0].render(src, None);
+        let notes = out.lines().skip(4).collect::<Vec<_>>();
+        assert_eq!(
+            notes,
+            [
+                "  = note: while checking the argument of f (f x x)",
+                "  = note: while inferring the body of λx : A . f (f x x)",
+            ]
+        );
+    }
+}
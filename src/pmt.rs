@@ -1,9 +1,11 @@
 //! Styled prompts for various input and output scenarios.
 
-use crossterm::style::{Color, StyledContent, Stylize};
+use crossterm::style::StyledContent;
 use std::fmt::Display;
+use unicode_width::UnicodeWidthStr;
 
 use crate::cmd::Status;
+use crate::thm::{Paint, Theme};
 
 /// Types of prompts that may be rendered to the user under various circumstances.
 pub enum Prompt {
@@ -17,17 +19,48 @@ pub enum Prompt {
     Failure,
     /// System is providing long-form content (diagnostics, user guide, etc.)
     Content,
+    /// System is reporting statistics of the evaluation of a command (time taken, etc.)
+    Diagnostics,
+    /// System has generated the warning that follows the prompt.
+    Warning,
+}
+
+/// Content of the gutter that precedes each line of the input, as chosen by a setting.
+#[derive(Debug, Clone, Copy, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Gutter {
+    /// Prompt of each line, or else a marker on the line that contains a syntax error.
+    #[default]
+    Prompts,
+    /// Number of each line (if there is more than one), followed by the prompt or marker.
+    Numbers,
+}
+
+impl Gutter {
+    /// Every kind of gutter, in the order they are listed to the user.
+    pub const ALL: [Gutter; 2] = [Gutter::Prompts, Gutter::Numbers];
+}
+
+impl Display for Gutter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Prompts => write!(f, "prompts"),
+            Self::Numbers => write!(f, "numbers"),
+        }
+    }
 }
 
 impl Prompt {
-    /// Render success output prefixed with an appropriate prompt.
-    pub fn show_success(msg: &str) -> String {
-        Self::show_output(msg, |_| &Prompt::Success)
+    /// Render success output prefixed with an appropriate prompt, in the colors of the given theme
+    /// (and in ASCII, if so specified).
+    pub fn show_success(msg: &str, thm: &Theme, ascii: bool) -> String {
+        Self::show_output(msg, thm, ascii, |_| &Prompt::Success)
     }
 
-    /// Render failure output prefixed with an appropriate prompt.
-    pub fn show_failure(msg: &str) -> String {
-        Self::show_output(msg, |i| {
+    /// Render failure output prefixed with an appropriate prompt, in the colors of the given theme
+    /// (and in ASCII, if so specified).
+    pub fn show_failure(msg: &str, thm: &Theme, ascii: bool) -> String {
+        Self::show_output(msg, thm, ascii, |i| {
             if i == 0 {
                 &Prompt::Failure
             } else {
@@ -36,29 +69,116 @@ impl Prompt {
         })
     }
 
-    /// Render content output prefixed with an appropriate prompt.
-    pub fn show_content(msg: &str) -> String {
-        Self::show_output(msg, |_| &Prompt::Content)
+    /// Render content output prefixed with an appropriate prompt, in the colors of the given theme
+    /// (and in ASCII, if so specified).
+    pub fn show_content(msg: &str, thm: &Theme, ascii: bool) -> String {
+        Self::show_output(msg, thm, ascii, |_| &Prompt::Content)
+    }
+
+    /// Render diagnostics output prefixed with an appropriate prompt, in the colors of the given
+    /// theme (and in ASCII, if so specified).
+    pub fn show_diagnostics(msg: &str, thm: &Theme, ascii: bool) -> String {
+        Self::show_output(msg, thm, ascii, |_| &Prompt::Diagnostics)
+    }
+
+    /// Render warning output prefixed with an appropriate prompt, in the colors of the given theme
+    /// (and in ASCII, if so specified), where the warning itself is shown dimly (in the color of
+    /// secondary text), so as not to draw attention away from the output around it.
+    pub fn show_warning(msg: &str, thm: &Theme, ascii: bool) -> String {
+        msg.lines()
+            .map(|s| {
+                let pmt = Prompt::Warning.styled(thm, ascii);
+                format!("{} {}\r\n", pmt, s.trim_end().paint(thm.muted))
+            })
+            .collect::<String>()
+    }
+
+    /// Render output prefixed with the prompt appropriate to the given status, without any styling
+    /// and with each line ended by a bare newline, as suits a terminal that is not under the
+    /// control of the IDE (or a log), in ASCII if so specified.
+    pub fn show_plain(msg: &str, status: &Status, ascii: bool) -> String {
+        msg.lines()
+            .enumerate()
+            .map(|(i, s)| {
+                let pmt = match status {
+                    Status::Failure if i > 0 => &Prompt::Content,
+                    status => status.into(),
+                };
+                format!("{} {}\n", pmt.glyph(ascii), s.trim_end())
+            })
+            .collect::<String>()
     }
 
     /// Render output prefixed with the prompt generated by the given closure.
-    fn show_output<'a>(msg: &str, gen: fn(usize) -> &'a Prompt) -> String {
+    fn show_output<'a>(
+        msg: &str,
+        thm: &Theme,
+        ascii: bool,
+        gen: fn(usize) -> &'a Prompt,
+    ) -> String {
         msg.lines()
             .enumerate()
-            .map(|(i, s)| format!("{} {}\r\n", gen(i), s.trim_end()))
+            .map(|(i, s)| format!("{} {}\r\n", gen(i).styled(thm, ascii), s.trim_end()))
             .collect::<String>()
     }
 
-    /// Render the prompt as styled content (such as a colored string).
-    fn as_styled_content(&self) -> StyledContent<&'static str> {
-        match self {
-            Prompt::Ready => "»".with(Color::Cyan),
-            Prompt::Continue => "↳".with(Color::Cyan),
-            Prompt::Success => "∴".with(Color::DarkGreen),
-            Prompt::Failure => "✗".with(Color::Red),
-            Prompt::Content => "≡".with(Color::DarkGrey),
+    /// Render the prompt as styled content (such as a colored string), in the colors of the given
+    /// theme (and in ASCII, if so specified).
+    pub fn styled(&self, thm: &Theme, ascii: bool) -> StyledContent<&'static str> {
+        self.glyph(ascii).paint(match self {
+            Prompt::Ready => thm.ready,
+            Prompt::Continue => thm.cont,
+            Prompt::Success => thm.success,
+            Prompt::Failure => thm.failure,
+            Prompt::Content => thm.content,
+            Prompt::Diagnostics => thm.diagnostics,
+            Prompt::Warning => thm.warning,
+        })
+    }
+
+    /// Fetch the glyph that the prompt is rendered as, or its ASCII equivalent if so specified (for
+    /// terminals whose fonts lack the glyph).
+    pub fn glyph(&self, ascii: bool) -> &'static str {
+        match (self, ascii) {
+            (Prompt::Ready, false) => "»",
+            (Prompt::Ready, true) => ">",
+            (Prompt::Continue, false) => "↳",
+            (Prompt::Continue, true) => "..",
+            (Prompt::Success, false) => "∴",
+            (Prompt::Success, true) => "=>",
+            (Prompt::Failure, false) => "✗",
+            (Prompt::Failure, true) => "x",
+            (Prompt::Content, false) => "≡",
+            (Prompt::Content, true) => "#",
+            (Prompt::Diagnostics, false) => "⧗",
+            (Prompt::Diagnostics, true) => "%",
+            (Prompt::Warning, false) => "⚠",
+            (Prompt::Warning, true) => "!",
         }
     }
+
+    /// Fetch the width of the widest of the prompts that precede the lines of the input (in ASCII,
+    /// if so specified), which the others are padded to.
+    pub fn input_width(ascii: bool) -> usize {
+        [Prompt::Ready, Prompt::Continue, Prompt::Failure]
+            .iter()
+            .map(|pmt| pmt.glyph(ascii).width())
+            .max()
+            .unwrap_or(1)
+    }
+
+    /// Check whether the terminal is likely to render Unicode glyphs, judging by the character
+    /// encoding of the locale (as given by the first of `LC_ALL`, `LC_CTYPE` and `LANG` that is
+    /// set), which is assumed to be UTF-8 unless it is named otherwise (such as the `C` locale).
+    pub fn probe_unicode() -> bool {
+        ["LC_ALL", "LC_CTYPE", "LANG"]
+            .into_iter()
+            .find_map(|key| std::env::var(key).ok().filter(|val| !val.is_empty()))
+            .is_none_or(|val| {
+                let val = val.to_ascii_lowercase();
+                val.contains("utf-8") || val.contains("utf8")
+            })
+    }
 }
 
 impl Default for &Prompt {
@@ -69,7 +189,7 @@ impl Default for &Prompt {
 
 impl Display for Prompt {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.as_styled_content())
+        write!(f, "{}", self.styled(&Theme::default(), false))
     }
 }
 
@@ -79,6 +199,8 @@ impl From<&Status> for &Prompt {
             Status::Success => &Prompt::Success,
             Status::Failure => &Prompt::Failure,
             Status::Content => &Prompt::Content,
+            Status::Diagnostics => &Prompt::Diagnostics,
+            Status::Warning => &Prompt::Warning,
         }
     }
 }
@@ -0,0 +1,35 @@
+-- The prelude bundled with fluxo, which `import prelude` brings into the session.
+
+-- Combinators
+let id : Π{A : *} . A → A = λ{A : *} . λx : A . x
+let const : Π{A : *} . Π{B : *} . A → B → A =
+  λ{A : *} . λ{B : *} . λx : A . λy : B . x
+let flip : Π{A : *} . Π{B : *} . Π{C : *} . (A → B → C) → B → A → C =
+  λ{A : *} . λ{B : *} . λ{C : *} . λf : A → B → C . λy : B . λx : A . f x y
+let compose : Π{A : *} . Π{B : *} . Π{C : *} . (B → C) → (A → B) → A → C =
+  λ{A : *} . λ{B : *} . λ{C : *} . λg : B → C . λf : A → B . λx : A . g (f x)
+
+-- Booleans
+data Bool := true | false
+let not : Bool → Bool = λb . Bool_rec (λb : Bool . Bool) false true b
+let and : Bool → Bool → Bool = λa . λb . Bool_rec (λa : Bool . Bool) b false a
+let or : Bool → Bool → Bool = λa . λb . Bool_rec (λa : Bool . Bool) true b a
+let if : Π{A : *} . Bool → A → A → A =
+  λ{A : *} . λb : Bool . λx : A . λy : A . Bool_rec (λb : Bool . A) x y b
+
+-- Pairs
+let Pair : * → * → * = λA : * . λB : * . Σx : A . B
+let pair : Π{A : *} . Π{B : *} . A → B → Pair A B =
+  λ{A : *} . λ{B : *} . λx : A . λy : B . (x, y : Σz : A . B)
+let swap : Π{A : *} . Π{B : *} . Pair A B → Pair B A =
+  λ{A : *} . λ{B : *} . λp : Pair A B . (snd p, fst p : Σz : B . A)
+
+-- Church naturals
+let Church : □ = ΠA : * . (A → A) → A → A
+let csuc : Church → Church =
+  λn : Church . λA : * . λf : A → A . λx : A . f (n A f x)
+let cadd : Church → Church → Church =
+  λm : Church . λn : Church . λA : * . λf : A → A . λx : A . m A f (n A f x)
+let cmul : Church → Church → Church =
+  λm : Church . λn : Church . λA : * . λf : A → A . m A (n A f)
+let cnat : Church → ℕ = λn : Church . n ℕ (λk : ℕ . suc k) 0n
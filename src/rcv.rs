@@ -0,0 +1,168 @@
+//! Autosave of the session and recovery of a session that ended unexpectedly.
+
+use crate::ast::Ctx;
+use crate::dir::xdg_dir;
+use std::fs;
+use std::io::Result;
+use std::path::{Path, PathBuf};
+
+/// Name of the file that the [context][Ctx] of the session is checkpointed to.
+#[cfg(feature = "serde")]
+const CONTEXT: &str = "context.json";
+
+/// Name of the file that the input not yet submitted is checkpointed to.
+const INPUT: &str = "input";
+
+/// Checkpoint of a session, as it was last saved by [Recovery].
+#[derive(Debug, Default)]
+pub struct Checkpoint {
+    /// Context of the session, unless it was never saved (or cannot be, without the `serde`
+    /// feature enabled).
+    pub ctx: Option<Ctx>,
+    /// Input that had not yet been submitted, which is empty if there was none.
+    pub input: String,
+}
+
+/// Directory that the state of the session is checkpointed to as the session goes on, and
+/// cleared once the session ends as it should, so that a session that ends unexpectedly (such as
+/// when the process panics or the terminal is closed) may be recovered when the next one starts.
+///
+/// Each file is written in full to a temporary file that then replaces it, so that a checkpoint
+/// is never left half-written.
+#[derive(Debug, Clone)]
+pub struct Recovery {
+    /// Path of the directory that checkpoints are kept in.
+    pub dir: PathBuf,
+}
+
+impl Recovery {
+    /// Create a new instance that keeps checkpoints in the directory at the given path.
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+        }
+    }
+
+    /// Fetch the path of the directory that checkpoints are kept in by default, namely
+    /// `$XDG_DATA_HOME/fluxo/recovery` (where `$XDG_DATA_HOME` defaults to `~/.local/share`).
+    pub fn default_path() -> Option<PathBuf> {
+        Some(xdg_dir("XDG_DATA_HOME", ".local/share")?.join("recovery"))
+    }
+
+    /// Load the checkpoint left behind by a session that ended unexpectedly, unless there is none.
+    pub fn load(&self) -> Result<Option<Checkpoint>> {
+        let input = match fs::read_to_string(self.dir.join(INPUT)) {
+            Ok(input) => Some(input),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e),
+        };
+        let ctx = self.load_context()?;
+        if ctx.is_none() && input.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(Checkpoint {
+            ctx,
+            input: input.unwrap_or_default(),
+        }))
+    }
+
+    /// Checkpoint the given context.
+    #[cfg(feature = "serde")]
+    pub fn save_context(&self, ctx: &Ctx) -> Result<()> {
+        let mut buf = Vec::new();
+        ctx.to_writer(&mut buf)?;
+        self.write(CONTEXT, &buf)
+    }
+
+    /// Checkpoint the given context, which does nothing, since contexts can only be saved when the
+    /// `serde` feature is enabled.
+    #[cfg(not(feature = "serde"))]
+    pub fn save_context(&self, _: &Ctx) -> Result<()> {
+        Ok(())
+    }
+
+    /// Checkpoint the given input, which has not yet been submitted (clearing the checkpoint if
+    /// the input is empty).
+    pub fn save_input(&self, input: &str) -> Result<()> {
+        if input.is_empty() {
+            return self.remove(INPUT);
+        }
+        self.write(INPUT, input.as_bytes())
+    }
+
+    /// Clear every checkpoint, once the session has ended as it should.
+    pub fn clear(&self) -> Result<()> {
+        match fs::remove_dir_all(&self.dir) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// Load the context checkpointed, unless there is none.
+    #[cfg(feature = "serde")]
+    fn load_context(&self) -> Result<Option<Ctx>> {
+        match fs::File::open(self.dir.join(CONTEXT)) {
+            Ok(file) => Ok(Some(Ctx::from_reader(std::io::BufReader::new(file))?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Load the context checkpointed, of which there is never one, since contexts can only be
+    /// saved when the `serde` feature is enabled.
+    #[cfg(not(feature = "serde"))]
+    fn load_context(&self) -> Result<Option<Ctx>> {
+        Ok(None)
+    }
+
+    /// Write the given contents to the file with the given name, by way of a temporary file that
+    /// replaces it once it is written in full.
+    fn write(&self, name: &str, contents: &[u8]) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let tmp = self.dir.join(format!(".{}.tmp", name));
+        fs::write(&tmp, contents)?;
+        fs::rename(&tmp, self.dir.join(name))
+    }
+
+    /// Remove the file with the given name, if it exists.
+    fn remove(&self, name: &str) -> Result<()> {
+        match fs::remove_file(self.dir.join(name)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::{Exp, Var};
+
+    #[test]
+    fn test_rcv_0001() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let dir = std::env::temp_dir().join(format!("fluxo-rcv-{}", std::process::id()));
+        let rcv = Recovery::new(&dir);
+        assert!(rcv.load()?.is_none());
+        rcv.save_input("let x := 1n\n  ")?;
+        let mut ctx = Ctx::new();
+        ctx.put(&Var::new("A"), &Exp::get_type_meta())?;
+        rcv.save_context(&ctx)?;
+        // a session that ends unexpectedly leaves its checkpoint for the next one to recover,
+        // definitions and all
+        let chk = Recovery::new(&dir).load()?.unwrap();
+        assert_eq!(chk.input, "let x := 1n\n  ");
+        assert_eq!(chk.ctx.is_some(), cfg!(feature = "serde"));
+        if let Some(ctx) = chk.ctx {
+            assert!(ctx.get(&Var::new("A")).is_ok());
+        }
+        rcv.save_input("")?;
+        assert_eq!(
+            rcv.load()?.map(|chk| chk.input),
+            cfg!(feature = "serde").then(String::new)
+        );
+        rcv.clear()?;
+        assert!(rcv.load()?.is_none());
+        rcv.clear()?;
+        Ok(())
+    }
+}
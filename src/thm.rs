@@ -0,0 +1,375 @@
+//! Color themes that the user interface is rendered with.
+
+use crate::dir::xdg_dir;
+use crossterm::style::{Color, Stylize};
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Built-in [theme][Theme], which may be chosen by name with a setting.
+#[derive(Debug, Clone, Copy, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Preset {
+    /// Bright colors that stand out on a dark background.
+    #[default]
+    Dark,
+    /// Dark colors that stand out on a light background.
+    Light,
+    /// The brightest colors, with few shades of each, for the greatest legibility.
+    HighContrast,
+}
+
+impl Preset {
+    /// Every built-in theme, in the order they are listed to the user.
+    pub const ALL: [Preset; 3] = [Preset::Dark, Preset::Light, Preset::HighContrast];
+
+    /// Fetch the colors of this built-in theme.
+    pub fn theme(&self) -> Theme {
+        let base = *self;
+        match self {
+            Self::Dark => Theme {
+                base,
+                ready: Color::Cyan,
+                cont: Color::Cyan,
+                success: Color::DarkGreen,
+                failure: Color::Red,
+                content: Color::DarkGrey,
+                diagnostics: Color::DarkYellow,
+                keyword: Color::Magenta,
+                sort: Color::Yellow,
+                builtin: Color::DarkYellow,
+                elim: Color::Blue,
+                symbol: Color::Cyan,
+                literal: Color::DarkCyan,
+                string: Color::DarkGreen,
+                comment: Color::DarkGrey,
+                hole: Color::DarkMagenta,
+                error: Color::Red,
+                muted: Color::DarkGrey,
+                command: Color::Red,
+                warning: Color::Yellow,
+            },
+            Self::Light => Theme {
+                base,
+                ready: Color::DarkCyan,
+                cont: Color::DarkCyan,
+                success: Color::DarkGreen,
+                failure: Color::DarkRed,
+                content: Color::DarkGrey,
+                diagnostics: Color::DarkYellow,
+                keyword: Color::DarkMagenta,
+                sort: Color::DarkYellow,
+                builtin: Color::DarkYellow,
+                elim: Color::DarkBlue,
+                symbol: Color::DarkBlue,
+                literal: Color::DarkCyan,
+                string: Color::DarkGreen,
+                comment: Color::Grey,
+                hole: Color::Magenta,
+                error: Color::DarkRed,
+                muted: Color::Grey,
+                command: Color::DarkRed,
+                warning: Color::DarkYellow,
+            },
+            Self::HighContrast => Theme {
+                base,
+                ready: Color::White,
+                cont: Color::White,
+                success: Color::Green,
+                failure: Color::Red,
+                content: Color::White,
+                diagnostics: Color::Yellow,
+                keyword: Color::Magenta,
+                sort: Color::Yellow,
+                builtin: Color::Yellow,
+                elim: Color::Cyan,
+                symbol: Color::White,
+                literal: Color::Green,
+                string: Color::Green,
+                comment: Color::Grey,
+                hole: Color::Magenta,
+                error: Color::Red,
+                muted: Color::Grey,
+                command: Color::Yellow,
+                warning: Color::Yellow,
+            },
+        }
+    }
+}
+
+impl Display for Preset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Dark => write!(f, "dark"),
+            Self::Light => write!(f, "light"),
+            Self::HighContrast => write!(f, "high-contrast"),
+        }
+    }
+}
+
+/// Colors that each part of the user interface is rendered with, which start out as those of a
+/// [built-in theme][Preset], any of which may be changed in the configuration file.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Theme {
+    /// Built-in theme that these colors start out as.
+    pub base: Preset,
+    /// Prompt of the first line of the input.
+    pub ready: Color,
+    /// Prompt of each line of the input after the first.
+    pub cont: Color,
+    /// Prompt of a message that reports success.
+    pub success: Color,
+    /// Prompt of a message that reports failure, or marks the line of a syntax error.
+    pub failure: Color,
+    /// Prompt of long-form content (such as help).
+    pub content: Color,
+    /// Prompt of the statistics of an evaluation.
+    pub diagnostics: Color,
+    /// Keywords that bind variables (such as `λ` and `let`).
+    pub keyword: Color,
+    /// Sorts and the metavariables that stand for them (such as `*`).
+    pub sort: Color,
+    /// Built-in types (such as `ℕ`).
+    pub builtin: Color,
+    /// Constructors and eliminators of the built-in types (such as `inl` and `case`).
+    pub elim: Color,
+    /// Variables.
+    pub symbol: Color,
+    /// Numeric and other literals.
+    pub literal: Color,
+    /// String literals.
+    pub string: Color,
+    /// Comments.
+    pub comment: Color,
+    /// Holes.
+    pub hole: Color,
+    /// Tokens that are malformed or unbalanced.
+    pub error: Color,
+    /// Secondary text (such as line numbers, headings and the background of the status bar).
+    pub muted: Color,
+    /// Commands named in help.
+    pub command: Color,
+    /// Warnings.
+    pub warning: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Preset::default().theme()
+    }
+}
+
+impl Theme {
+    /// Load the theme in the file at the given path (or the default theme, unless it exists),
+    /// which starts out as the built-in theme named on a line such as `theme light` (or else the
+    /// default), and in which each other line names a part of the user interface followed by its
+    /// color (as in `keyword dark_magenta`), where blank lines and those starting with `#` are
+    /// ignored.
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let mut thm = Self::default();
+        let src = match fs::read_to_string(path) {
+            Ok(src) => src,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(thm),
+            Err(e) => return Err(e),
+        };
+        let invalid = |msg: String| std::io::Error::new(std::io::ErrorKind::InvalidData, msg);
+        for line in src.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match line.split_whitespace().collect::<Vec<_>>()[..] {
+                ["theme", name] => match Preset::ALL.into_iter().find(|p| p.to_string() == name) {
+                    Some(base) => thm = base.theme(),
+                    None => return Err(invalid(format!("unknown theme {}", name))),
+                },
+                [part, color] => {
+                    let color = Color::try_from(color)
+                        .map_err(|_| invalid(format!("unknown color {}", color)))?;
+                    match thm.part(part) {
+                        Some(slot) => *slot = color,
+                        None => return Err(invalid(format!("unknown part {}", part))),
+                    }
+                }
+                _ => {
+                    let msg = format!("expected a part and its color, found {}", line);
+                    return Err(invalid(msg));
+                }
+            }
+        }
+        Ok(thm)
+    }
+
+    /// Fetch the path of the file that users keep their theme in by default, namely
+    /// `$XDG_CONFIG_HOME/fluxo/theme` (where `$XDG_CONFIG_HOME` defaults to `~/.config`).
+    pub fn default_path() -> Option<PathBuf> {
+        Some(xdg_dir("XDG_CONFIG_HOME", ".config")?.join("theme"))
+    }
+
+    /// Fetch the color of the part of the user interface with the given name, unless there is no
+    /// such part.
+    pub fn color(&self, name: &str) -> Option<Color> {
+        let mut thm = *self;
+        thm.part(name).copied()
+    }
+
+    /// Fetch the color of the part of the user interface with the given name, for it to be
+    /// changed, unless there is no such part.
+    fn part(&mut self, name: &str) -> Option<&mut Color> {
+        Some(match name {
+            "ready" => &mut self.ready,
+            "cont" => &mut self.cont,
+            "success" => &mut self.success,
+            "failure" => &mut self.failure,
+            "content" => &mut self.content,
+            "diagnostics" => &mut self.diagnostics,
+            "keyword" => &mut self.keyword,
+            "sort" => &mut self.sort,
+            "builtin" => &mut self.builtin,
+            "elim" => &mut self.elim,
+            "symbol" => &mut self.symbol,
+            "literal" => &mut self.literal,
+            "string" => &mut self.string,
+            "comment" => &mut self.comment,
+            "hole" => &mut self.hole,
+            "error" => &mut self.error,
+            "muted" => &mut self.muted,
+            "command" => &mut self.command,
+            "warning" => &mut self.warning,
+            _ => return None,
+        })
+    }
+}
+
+/// Whether output is styled with colors, as decided once for the whole process by the [policy]
+/// [Coloring] chosen on the command line (and colored unless it is applied).
+static COLORED: AtomicBool = AtomicBool::new(true);
+
+/// Policy that decides whether output is styled with colors, as chosen on the command line.
+#[derive(Debug, Clone, Copy, Default, Eq, Hash, PartialEq, clap::ValueEnum)]
+pub enum Coloring {
+    /// Color output written to a terminal, unless `NO_COLOR` is set (to anything but nothing).
+    #[default]
+    Auto,
+    /// Color output wherever it is written.
+    Always,
+    /// Never color output.
+    Never,
+}
+
+impl Coloring {
+    /// Decide whether output is colored under this policy, given whether it is written to a
+    /// terminal.
+    pub fn decide(&self, terminal: bool) -> bool {
+        match self {
+            Self::Auto => terminal && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()),
+            Self::Always => true,
+            Self::Never => false,
+        }
+    }
+
+    /// Apply this policy to all output of the process, given whether it is written to a terminal.
+    pub fn apply(&self, terminal: bool) {
+        COLORED.store(self.decide(terminal), Ordering::Relaxed);
+    }
+}
+
+impl Display for Coloring {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Auto => write!(f, "auto"),
+            Self::Always => write!(f, "always"),
+            Self::Never => write!(f, "never"),
+        }
+    }
+}
+
+/// Check whether output is styled with colors, under the [policy][Coloring] applied to the
+/// process.
+pub fn colored() -> bool {
+    COLORED.load(Ordering::Relaxed)
+}
+
+/// Styling of text (or of text already styled), which the user interface goes through rather
+/// than [Stylize] itself, so that no colors are written unless output is [colored][colored].
+pub trait Paint: Stylize {
+    /// Style this text in the given (foreground) color, if output is colored.
+    fn paint(self, color: Color) -> Self::Styled {
+        let mut styled = self.stylize();
+        if colored() {
+            styled.as_mut().foreground_color = Some(color);
+        }
+        styled
+    }
+
+    /// Style this text on a background of the given color, if output is colored.
+    fn paint_on(self, color: Color) -> Self::Styled {
+        let mut styled = self.stylize();
+        if colored() {
+            styled.as_mut().background_color = Some(color);
+        }
+        styled
+    }
+
+    /// Style this text with its colors reversed, which sets it apart even if output is not
+    /// colored (as `NO_COLOR` only asks for the absence of colors, not of every style).
+    fn invert(self) -> Self::Styled {
+        self.reverse()
+    }
+}
+
+impl<T: Stylize> Paint for T {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ast::Ctx;
+    use crate::cmd::{Cmd, Setting, Settings};
+
+    #[test]
+    fn test_thm_0001() -> std::io::Result<()> {
+        let dir = std::env::temp_dir().join(format!("fluxo-thm-{}", std::process::id()));
+        let path = dir.join("theme");
+        assert_eq!(Theme::load(&path)?, Theme::default());
+        fs::create_dir_all(&dir)?;
+        fs::write(
+            &path,
+            "# mine\ntheme light\n\nkeyword red\nmuted dark_grey\n",
+        )?;
+        let thm = Theme::load(&path)?;
+        assert_eq!(thm.base, Preset::Light);
+        assert_eq!((thm.keyword, thm.muted), (Color::Red, Color::DarkGrey));
+        assert_eq!(thm.ready, Preset::Light.theme().ready);
+        for src in ["keyword", "keyword mauve", "gutter red", "theme sepia"] {
+            fs::write(&path, src)?;
+            let err = Theme::load(&path).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        }
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_thm_0002() {
+        // output that is not written to a terminal is colored only if it is asked for
+        assert!(!Coloring::Auto.decide(false));
+        assert!(Coloring::Always.decide(false));
+        assert!(!Coloring::Never.decide(true));
+        assert_eq!(Coloring::default().to_string(), "auto");
+        // the policy applies to the whole process, which colors output until it is applied
+        assert!(colored());
+        assert_eq!(
+            "A".paint(Color::Red).to_string(),
+            "A".with(Color::Red).to_string()
+        );
+    }
+
+    #[test]
+    fn test_thm_0003() {
+        let (mut ctx, mut set) = (Ctx::new(), Settings::default());
+        let out = Cmd::Set(Setting::Theme(Preset::HighContrast)).eval(&mut ctx, &mut set);
+        assert_eq!(out.msg.as_deref(), Some("set theme high-contrast"));
+        assert_eq!(set.thm, Preset::HighContrast.theme());
+    }
+}
@@ -0,0 +1,77 @@
+//! Watching of files for changes, so that they may be checked again whenever they are saved.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Period between successive polls of the files being watched.
+pub const POLL: Duration = Duration::from_millis(250);
+
+/// Set of files being watched, along with the time that each was last modified (if it exists).
+///
+/// The files are polled rather than watched through the notifications of the operating system,
+/// which works the same on every platform, and for editors that save by replacing the file.
+#[derive(Debug, Clone)]
+pub struct Watch {
+    /// Paths of the files being watched, along with the time each was last seen to be modified.
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl Watch {
+    /// Create a new instance that watches the files at the given paths, as they are now.
+    pub fn new(paths: &[PathBuf]) -> Self {
+        let files = paths.iter().map(|path| (path.clone(), modified(path)));
+        Self {
+            files: files.collect(),
+        }
+    }
+
+    /// Check whether any of the files has been modified (or created, or removed) since the last
+    /// time this was checked (or since this instance was created).
+    pub fn changed(&mut self) -> bool {
+        let mut changed = false;
+        for (path, time) in &mut self.files {
+            let now = modified(path);
+            changed |= now != *time;
+            *time = now;
+        }
+        changed
+    }
+
+    /// Wait until any of the files is modified (or created, or removed).
+    pub fn wait(&mut self) {
+        while !self.changed() {
+            thread::sleep(POLL);
+        }
+    }
+}
+
+/// Fetch the time that the file at the given path was last modified, unless it does not exist
+/// (or cannot be read).
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_wch_0001() -> std::io::Result<()> {
+        let dir = std::env::temp_dir().join(format!("fluxo-wch-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        let path = dir.join("a.fx");
+        let mut wch = Watch::new(std::slice::from_ref(&path));
+        assert!(!wch.changed());
+        fs::write(&path, "assume A : *\n")?; // created
+        assert!(wch.changed());
+        assert!(!wch.changed());
+        let file = fs::File::options().write(true).open(&path)?;
+        file.set_modified(SystemTime::now() + Duration::from_secs(60))?; // saved
+        assert!(wch.changed());
+        fs::remove_dir_all(&dir)?;
+        assert!(wch.changed()); // removed
+        Ok(())
+    }
+}
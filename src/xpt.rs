@@ -0,0 +1,282 @@
+//! Export of expressions, and the judgements made about them, to markup for other documents.
+
+use crate::ast::{Ctx, Exp, PrintOptions, Var};
+use crate::cmd::{Cmd, Settings, Status};
+use crate::err::Diagnostic;
+use crate::par::{locate_err, parse_cmd_unlocated, split_commands, tokenize, Cat};
+use std::fmt::Display;
+
+/// Markup that expressions are exported as, for inclusion in other documents.
+#[derive(Debug, Clone, Copy, Default, Eq, Hash, PartialEq, clap::ValueEnum)]
+pub enum Markup {
+    /// LaTeX, in math mode, where each judgement is set as displayed math.
+    #[default]
+    Latex,
+    /// HTML, where each token is wrapped in a `span` whose class names the part of the user
+    /// interface it is highlighted as (such as `fx-keyword`), for a style sheet to color.
+    Html,
+}
+
+impl Markup {
+    /// Render the given expression (using the notation declared in the given context) as markup.
+    pub fn render(&self, exp: &Exp, ctx: &Ctx) -> String {
+        let src = exp.pretty_in(ctx, &PrintOptions::flat(false));
+        let mut out = String::new();
+        let mut end = None;
+        for tok in tokenize(&src) {
+            if let Some(end) = end {
+                out.push_str(self.space(tok.beg > end));
+            }
+            out.push_str(&self.token(&tok.cat, &src[tok.beg..tok.end]));
+            end = Some(tok.end);
+        }
+        out
+    }
+
+    /// Render the judgement that the given expression has the given type, `⊢ e : T`.
+    pub fn typing(&self, exp: &Exp, typ: &Exp, ctx: &Ctx) -> String {
+        let (exp, typ) = (self.render(exp, ctx), self.render(typ, ctx));
+        self.block(&[self.symbol("⊢"), &exp, self.symbol(":"), &typ])
+    }
+
+    /// Render the judgement that the given expression reduces to the given value, `e ⇓ v`.
+    pub fn reduction(&self, exp: &Exp, val: &Exp, ctx: &Ctx) -> String {
+        let (exp, val) = (self.render(exp, ctx), self.render(val, ctx));
+        self.block(&[&exp, self.symbol("⇓"), &val])
+    }
+
+    /// Render the judgement that the given expressions are convertible, `e₁ ≡ e₂`.
+    pub fn conversion(&self, fst: &Exp, snd: &Exp, ctx: &Ctx) -> String {
+        let (fst, snd) = (self.render(fst, ctx), self.render(snd, ctx));
+        self.block(&[&fst, self.symbol("≡"), &snd])
+    }
+
+    /// Render the declaration of the given variable, with the given type and value (if it is
+    /// defined rather than assumed), `x : T ≔ v`.
+    pub fn declaration(&self, var: &Var, typ: &Exp, val: Option<&Exp>, ctx: &Ctx) -> String {
+        let name = self.render(&Exp::new_var(var.clone()), ctx);
+        let typ = self.render(typ, ctx);
+        let val = val.map(|val| self.render(val, ctx));
+        let mut parts = vec![name.as_str(), self.symbol(":"), &typ];
+        if let Some(val) = &val {
+            parts.extend([self.symbol("≔"), val.as_str()]);
+        }
+        self.block(&parts)
+    }
+
+    /// Render a token of the given category, spelled as given.
+    fn token(&self, cat: &Cat, text: &str) -> String {
+        match self {
+            Self::Latex => match cat {
+                Cat::Abs => r"\lambda".to_string(),
+                Cat::For => r"\Pi".to_string(),
+                Cat::Sig => r"\Sigma".to_string(),
+                Cat::Arr => r"\to".to_string(),
+                Cat::Dot => r".\,".to_string(),
+                Cat::Lbr => r"\{".to_string(),
+                Cat::Rbr => r"\}".to_string(),
+                Cat::Unit => r"\top".to_string(),
+                Cat::Void => r"\bot".to_string(),
+                Cat::Nat => r"\mathbb{N}".to_string(),
+                Cat::TypeMeta => r"\star".to_string(),
+                Cat::KindMeta => r"\Box".to_string(),
+                Cat::Hole => r"\_".to_string(),
+                Cat::Sym(_) if text.chars().count() == 1 => latex(text),
+                Cat::Sym(_) => format!(r"\mathit{{{}}}", latex(text)),
+                Cat::Opr(_) => format!(r"\mathbin{{{}}}", latex(text)),
+                Cat::Str(..) => format!(r"\texttt{{{}}}", latex(text)),
+                Cat::Num(_) | Cat::Lit(_) => format!(r"\mathrm{{{}}}", latex(text)),
+                _ if text.chars().all(char::is_alphabetic) => {
+                    format!(r"\mathsf{{{}}}", latex(text))
+                }
+                _ => latex(text),
+            },
+            Self::Html => match cat.part() {
+                Some(part) => format!(r#"<span class="fx-{}">{}</span>"#, part, html(text)),
+                None => html(text),
+            },
+        }
+    }
+
+    /// Render the symbol of a judgement spelled as given.
+    fn symbol(&self, text: &'static str) -> &'static str {
+        match (self, text) {
+            (Self::Latex, "⊢") => r"\vdash",
+            (Self::Latex, "⇓") => r"\Downarrow",
+            (Self::Latex, "≡") => r"\equiv",
+            (Self::Latex, "≔") => r"\mathrel{:=}",
+            _ => text,
+        }
+    }
+
+    /// Fetch the space between two tokens, given whether they are apart (rather than adjacent,
+    /// which in LaTeX still calls for a space that ends a control word, such as `\lambda x`).
+    fn space(&self, apart: bool) -> &'static str {
+        match (self, apart) {
+            (Self::Latex, true) => r" \; ",
+            (Self::Latex, false) => " ",
+            (Self::Html, true) => " ",
+            (Self::Html, false) => "",
+        }
+    }
+
+    /// Render a judgement made up of the given parts (already rendered), set apart from the text
+    /// around it.
+    fn block(&self, parts: &[&str]) -> String {
+        match self {
+            Self::Latex => format!("\\[ {} \\]\n", parts.join(" ")),
+            Self::Html => format!("<p><code class=\"fluxo\">{}</code></p>\n", parts.join(" ")),
+        }
+    }
+}
+
+impl Display for Markup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Latex => write!(f, "latex"),
+            Self::Html => write!(f, "html"),
+        }
+    }
+}
+
+/// Evaluate the commands in the given source text (such as the contents of a file) in order, in
+/// the given context with the given settings, and render the judgements they make in the given markup: the declaration of
+/// each variable that is [defined][Cmd::Let] or [assumed][Cmd::Assume], the type of each
+/// expression that is [typed][Cmd::Type], the value of each that is [shown][Cmd::Show], and the
+/// convertibility of each pair that is [compared][Cmd::Eq]. The other commands are evaluated as
+/// usual, but leave no trace in the markup.
+///
+/// Fails with a [diagnostic][Diagnostic] about the first command that fails.
+pub fn export(
+    src: &str,
+    ctx: &mut Ctx,
+    set: &mut Settings,
+    mrk: Markup,
+) -> Result<String, Diagnostic> {
+    let toks = tokenize(src);
+    let mut doc = String::new();
+    for (_, toks) in split_commands(src, &toks) {
+        let end = toks.last().map_or(0, |tok| tok.end);
+        let cmd = parse_cmd_unlocated(&src[..end], toks, ctx, set)
+            .map_err(|e| Diagnostic::spanning(src, (e.beg, e.end), &e.to_string()))?;
+        let out = cmd.eval(ctx, set);
+        if let Status::Failure = out.status {
+            let beg = toks.first().map_or(0, |tok| tok.beg);
+            let pos = (out.err.as_ref()).and_then(|e| locate_err(&src[..end], toks, ctx, e));
+            let msg = out.msg.unwrap_or_default();
+            return Err(Diagnostic::spanning(src, pos.unwrap_or((beg, end)), &msg));
+        }
+        doc.push_str(&judgement(&cmd, ctx, mrk).unwrap_or_default());
+        if out.trm {
+            break;
+        }
+    }
+    Ok(doc)
+}
+
+/// Render the judgement made by the given command (once it has been evaluated in the given
+/// context), unless it makes none.
+pub fn judgement(cmd: &Cmd, ctx: &Ctx, mrk: Markup) -> Option<String> {
+    match cmd {
+        Cmd::Let(var, ..) | Cmd::Assume(var, _) => {
+            let typ = ctx.get(var).ok()?;
+            Some(mrk.declaration(var, typ, ctx.get_def(var), ctx))
+        }
+        Cmd::Type(exp) => {
+            let typ = exp.elaborate(ctx).infer(ctx).ok()?.zonk(ctx);
+            Some(mrk.typing(exp, &typ, ctx))
+        }
+        Cmd::Show(exp) => {
+            let (_, res) = Cmd::show(exp, ctx, &Settings::default()); // only the result is kept
+            Some(mrk.reduction(exp, &res?.0, ctx))
+        }
+        Cmd::Eq(fst, snd) => Some(mrk.conversion(fst, snd, ctx)),
+        _ => None,
+    }
+}
+
+/// Escape the characters of the given text that are special to LaTeX.
+fn latex(text: &str) -> String {
+    text.chars().fold(String::new(), |mut out, chr| {
+        match chr {
+            '#' | '$' | '%' | '&' | '_' | '{' | '}' => out.extend(['\\', chr]),
+            '\\' => out.push_str(r"\backslash{}"),
+            '^' => out.push_str(r"\hat{}"),
+            '~' => out.push_str(r"\sim{}"),
+            _ => out.push(chr),
+        }
+        out
+    })
+}
+
+/// Escape the characters of the given text that are special to HTML.
+fn html(text: &str) -> String {
+    text.chars().fold(String::new(), |mut out, chr| {
+        match chr {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(chr),
+        }
+        out
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::par::parse;
+
+    #[test]
+    fn test_xpt_0001() -> Result<(), Diagnostic> {
+        let src = "assume A : *\nlet id : A → A = λx : A . x\ntype id\nshow id\nset fuel 100\n";
+        let doc = export(
+            src,
+            &mut Ctx::new(),
+            &mut Settings::default(),
+            Markup::Latex,
+        )?;
+        let lines = doc.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], r"\[ A : \star \]");
+        assert_eq!(
+            lines[1],
+            r"\[ \mathit{id} : A \; \to \; A \mathrel{:=} \lambda x \; : \; A \; .\, \; x \]"
+        );
+        assert_eq!(lines[2], r"\[ \vdash \mathit{id} : A \; \to \; A \]");
+        let doc = export(src, &mut Ctx::new(), &mut Settings::default(), Markup::Html)?;
+        assert_eq!(
+            doc.lines().next(),
+            Some(
+                r#"<p><code class="fluxo"><span class="fx-symbol">A</span> : <span class="fx-sort">*</span></code></p>"#
+            )
+        );
+        let err = export(
+            "assume A : *\n\nshow (A\n",
+            &mut Ctx::new(),
+            &mut Settings::default(),
+            Markup::Html,
+        )
+        .unwrap_err();
+        assert_eq!((err.line, err.col), (3, 8));
+        Ok(())
+    }
+
+    #[test]
+    fn test_xpt_0002() -> Result<(), Box<dyn std::error::Error>> {
+        let (mut ctx, mut set) = (Ctx::new(), Settings::default());
+        let export = Cmd::Export(Markup::Html, parse("λx : ℕ . x")?);
+        let out = export.eval(&mut ctx, &mut set).msg.unwrap();
+        assert!(out.starts_with(r#"<p><code class="fluxo">⊢ <span class="fx-keyword">λ</span>"#));
+        assert!(out
+            .trim_end()
+            .ends_with(r#"<span class="fx-builtin">ℕ</span></code></p>"#));
+        let export = Cmd::Export(Markup::Latex, parse("y")?);
+        assert!(matches!(
+            export.eval(&mut ctx, &mut set).status,
+            Status::Failure
+        ));
+        Ok(())
+    }
+}